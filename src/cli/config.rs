@@ -0,0 +1,342 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::cli::hooks::get_config_file_path;
+use crate::config::edit::{parse_key, parse_value, ConfigDocument};
+use crate::config::format::ConfigFormat;
+use crate::config::layers::{
+    config_files, file_layers, project_config_paths, system_config_path, user_config_paths,
+    LayeredConfig, LoadOptions, ENV_VARS,
+};
+use crate::config::migrate::{diff, migrate, CONFIG_VERSION};
+use crate::config::validate::{validate_file, validate_str};
+use crate::core::config_schema::config_file_schema;
+use crate::symbols::*;
+
+/// Handle the `config show` command
+///
+/// Without `--effective`, lists the config layers and which of them are
+/// present. With `--effective`, prints the merged configuration as TOML,
+/// and with `--origin` every value annotated with the layers that set it.
+pub fn handle_config_show(options: &LoadOptions, effective: bool, origin: bool) -> Result<()> {
+    if !effective {
+        print_layers(options);
+        return Ok(());
+    }
+
+    let layered = LayeredConfig::load(options)?;
+    if origin {
+        for line in origin_lines(&layered) {
+            println!("{line}");
+        }
+    } else {
+        print!("{}", toml::to_string_pretty(&layered.table)?);
+    }
+    Ok(())
+}
+
+/// Handle the `config validate` command
+///
+/// Checks every config file layer and the files they include strictly and
+/// prints each problem as `file:line:column: message`. Fails if any problem
+/// was found.
+pub fn handle_config_validate(options: &LoadOptions) -> Result<()> {
+    let files = config_files(options.config_path.as_deref());
+    if files.is_empty() {
+        if let Some(path) = &options.config_path {
+            return Err(anyhow::anyhow!("Config file not found: {}", path.display()));
+        }
+        info!("{} No config files found", SYMBOL_INDICATOR_INFO);
+        return Ok(());
+    }
+
+    // Included files are only known once the includes resolve
+    let (paths, include_error): (Vec<PathBuf>, _) =
+        match file_layers(options.config_path.as_deref()) {
+            Ok(layers) => (
+                layers
+                    .iter()
+                    .filter_map(|layer| layer.source.path().map(PathBuf::from))
+                    .collect(),
+                None,
+            ),
+            Err(error) => (
+                files.into_iter().map(|(_, path)| path).collect(),
+                Some(error),
+            ),
+        };
+
+    let mut error_count = 0;
+    for path in paths {
+        let errors = validate_file(&path)?;
+        if errors.is_empty() {
+            println!("{} {} is valid", SYMBOL_INDICATOR_SUCCESS, path.display());
+        }
+        for error in &errors {
+            let separator = if error.line > 0 { ":" } else { ": " };
+            println!("{}{separator}{}", path.display(), error);
+        }
+        error_count += errors.len();
+    }
+    // A file that doesn't parse also fails the includes; it's reported above
+    if let Some(error) = include_error.filter(|_| error_count == 0) {
+        println!("{error:#}");
+        error_count += 1;
+    }
+
+    if error_count > 0 {
+        return Err(anyhow::anyhow!(
+            "Found {} error(s) in the configuration",
+            error_count
+        ));
+    }
+    Ok(())
+}
+
+/// Handle the `config schema` command
+///
+/// Prints the JSON Schema of the configuration file, including every
+/// registered plugin's section, for editors such as taplo.
+pub fn handle_config_schema() -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(&config_file_schema())?);
+    Ok(())
+}
+
+/// Handle the `config get` command
+///
+/// Prints the effective value of a key: strings as-is, everything else as
+/// TOML.
+pub fn handle_config_get(options: &LoadOptions, key: &str) -> Result<()> {
+    let layered = LayeredConfig::load(options)?;
+    let mut value = None;
+    let mut table = Some(&layered.table);
+    for segment in parse_key(key)? {
+        value = table.and_then(|table| table.get(&segment));
+        table = value.and_then(toml::Value::as_table);
+    }
+
+    match value {
+        Some(toml::Value::String(value)) => println!("{value}"),
+        Some(toml::Value::Table(table)) => print!("{}", toml::to_string_pretty(table)?),
+        Some(value) => println!("{value}"),
+        None => return Err(anyhow::anyhow!("Config key '{key}' is not set")),
+    }
+    Ok(())
+}
+
+/// Handle the `config set` command
+///
+/// Edits only the given key of the config file, so comments and formatting
+/// of the rest of the file survive. Rejects values that would make the file
+/// invalid.
+pub async fn handle_config_set(options: &LoadOptions, key: &str, value: &str) -> Result<()> {
+    let mut document = ConfigDocument::open(&config_file(options)).await?;
+    let before = validate_str(&document.to_string());
+    document.set(&parse_key(key)?, &parse_value(value))?;
+
+    let new_errors: Vec<String> = validate_str(&document.to_string())
+        .iter()
+        .map(|error| error.message.clone())
+        .filter(|message| !before.iter().any(|error| &error.message == message))
+        .collect();
+    if !new_errors.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Invalid value for '{key}': {}",
+            new_errors.join("; ")
+        ));
+    }
+
+    document.save().await?;
+    info!(
+        "{} Set {key} in {}",
+        SYMBOL_INDICATOR_SUCCESS,
+        document.path().display()
+    );
+    Ok(())
+}
+
+/// Handle the `config unset` command
+pub async fn handle_config_unset(options: &LoadOptions, key: &str) -> Result<()> {
+    let path = config_file(options);
+    let mut document = ConfigDocument::open(&path).await?;
+    if !document.unset(&parse_key(key)?) {
+        info!("Config key '{key}' is not set in {}", path.display());
+        return Ok(());
+    }
+
+    document.save().await?;
+    info!(
+        "{} Removed {key} from {}",
+        SYMBOL_INDICATOR_SUCCESS,
+        path.display()
+    );
+    Ok(())
+}
+
+/// Handle the `config migrate` command
+///
+/// Upgrades every config file layer and the files they include to the
+/// current format, printing a diff of each change. The original file is
+/// kept next to it as `<name>.v<version>.bak`.
+pub async fn handle_config_migrate(options: &LoadOptions, dry_run: bool) -> Result<()> {
+    let layers = file_layers(options.config_path.as_deref())?;
+    if layers.is_empty() {
+        if let Some(path) = &options.config_path {
+            return Err(anyhow::anyhow!("Config file not found: {}", path.display()));
+        }
+        info!("{} No config files found", SYMBOL_INDICATOR_INFO);
+        return Ok(());
+    }
+
+    for path in layers.iter().filter_map(|layer| layer.source.path()) {
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        let format = ConfigFormat::from_path(path);
+        let toml_content = format
+            .to_toml_string(&content)
+            .with_context(|| format!("{}", path.display()))?;
+        let migrated = migrate(&toml_content).with_context(|| format!("{}", path.display()))?;
+        if migrated.content == toml_content {
+            println!(
+                "{} {} is up to date (version {CONFIG_VERSION})",
+                SYMBOL_INDICATOR_SUCCESS,
+                path.display()
+            );
+            continue;
+        }
+
+        println!(
+            "{} {}: version {} -> {CONFIG_VERSION}",
+            SYMBOL_TOOL_CONFIG,
+            path.display(),
+            migrated.from
+        );
+        for change in &migrated.changes {
+            println!("  - {change}");
+        }
+        let migrated_content = format.render(&migrated.content)?;
+        print!("{}", diff(&content, &migrated_content));
+        if dry_run {
+            continue;
+        }
+
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(format!(".v{}.bak", migrated.from));
+        let backup = PathBuf::from(backup);
+        tokio::fs::copy(path, &backup)
+            .await
+            .with_context(|| format!("Failed to back up config file: {}", path.display()))?;
+        tokio::fs::write(path, &migrated_content)
+            .await
+            .with_context(|| format!("Failed to write config file: {}", path.display()))?;
+        println!(
+            "{} Migrated {} (backup: {})",
+            SYMBOL_INDICATOR_SUCCESS,
+            path.display(),
+            backup.display()
+        );
+    }
+    Ok(())
+}
+
+/// File edited by `config set` and `config unset`
+fn config_file(options: &LoadOptions) -> PathBuf {
+    get_config_file_path(options.config_path.clone())
+}
+
+/// Config layers in the order they are merged
+fn print_layers(options: &LoadOptions) {
+    let status = |path: &PathBuf| {
+        if path.is_file() {
+            format!("{}", path.display())
+        } else {
+            format!("{} (not found)", path.display())
+        }
+    };
+
+    // Candidates in other formats than TOML are only listed when present
+    let listed =
+        |path: &PathBuf| path.is_file() || ConfigFormat::from_path(path) == ConfigFormat::Toml;
+
+    println!("Config layers (lowest precedence first):");
+    if let Some(path) = &options.config_path {
+        println!("  file:    {}", status(path));
+    } else {
+        if let Some(path) = system_config_path() {
+            println!("  system:  {}", status(&path));
+        }
+        for path in user_config_paths().iter().filter(|path| listed(path)) {
+            println!("  user:    {}", status(path));
+        }
+        for path in project_config_paths().iter().filter(|path| listed(path)) {
+            println!("  project: {}", status(path));
+        }
+    }
+    if let Some(profile) = &options.profile {
+        println!("  profile: [profiles.{profile}]");
+    }
+    for (var, key) in ENV_VARS {
+        if std::env::var_os(var).is_some() {
+            println!("  env:     {var} -> {key}");
+        }
+    }
+    for layer in &options.cli {
+        println!("  {}", layer.source);
+    }
+}
+
+/// `key = value  # source` lines for every value of the merged config
+fn origin_lines(layered: &LayeredConfig) -> Vec<String> {
+    layered
+        .values_with_origins()
+        .into_iter()
+        .map(|(key, value, origins)| {
+            let origins: Vec<String> = origins.iter().map(ToString::to_string).collect();
+            if origins.is_empty() {
+                format!("{key} = {value}")
+            } else {
+                format!("{key} = {value}  # {}", origins.join(", "))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::layers::{ConfigLayer, ConfigSource};
+
+    /// Test annotating merged values with their origins
+    /// Verifies each value lists the layers that set it
+    #[test]
+    fn test_origin_lines() {
+        let mut layered = LayeredConfig::default();
+        layered.merge(
+            ConfigLayer {
+                source: ConfigSource::File(PathBuf::from("config.toml")),
+                table: toml::from_str("output_dir = \"/file\"\n[plugins.static]\nfiles = [\"a\"]")
+                    .unwrap(),
+            },
+            true,
+        );
+        layered.merge(
+            ConfigLayer {
+                source: ConfigSource::Env("DOTSNAPSHOT_OUTPUT_DIR".to_string()),
+                table: toml::Table::new(),
+            }
+            .with_value("output_dir", toml::Value::String("/env".to_string())),
+            false,
+        );
+
+        let lines = origin_lines(&layered);
+        assert_eq!(
+            lines,
+            vec![
+                "output_dir = \"/env\"  # env: DOTSNAPSHOT_OUTPUT_DIR".to_string(),
+                "plugins.static.files = [\"a\"]  # file: config.toml".to_string(),
+            ]
+        );
+    }
+}
@@ -3,8 +3,12 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use tracing::{error, info, warn};
 
+use crate::config::edit::ConfigDocument;
+use crate::config::format::{candidates, to_plugin_value};
+use crate::config::layers::file_layers;
 use crate::config::{Config, GlobalConfig, GlobalHooks, PluginConfig, PluginHooks};
 use crate::core::hooks::{HookAction, HookContext, HookManager, HooksConfig};
+use crate::core::paths::expand;
 use crate::symbols::*;
 use crate::{HookActionArgs, HookTarget, HooksCommands};
 
@@ -68,11 +72,15 @@ pub(crate) async fn handle_add_hook(
     action: HookActionArgs,
     config_path: Option<PathBuf>,
 ) -> Result<()> {
-    let mut config = load_or_create_config(config_path.clone()).await?;
     let hook_action = convert_action_args_to_hook_action(action)?;
 
     // Determine target type and plugin
     let (hook_type, plugin_name) = determine_hook_target(&target)?;
+    let config_path = Some(hooks_file(
+        config_path,
+        &hook_key(&hook_type, plugin_name.as_deref()),
+    )?);
+    let mut config = load_or_create_config(config_path.clone()).await?;
 
     // Add hook to appropriate configuration section
     match hook_type.as_str() {
@@ -128,8 +136,7 @@ pub(crate) async fn handle_add_hook(
     }
 
     // Save updated configuration
-    let config_file_path = get_config_file_path(config_path);
-    config.save_to_file(&config_file_path).await?;
+    save_hooks(&config, config_path, &hook_type, plugin_name.as_deref()).await?;
 
     // Show success message
     let plugin_context = if let Some(plugin) = &plugin_name {
@@ -148,7 +155,7 @@ pub(crate) async fn handle_add_hook(
     if let HookAction::Script { command, .. } = &hook_action {
         let hooks_config = config.get_hooks_config();
         let script_path = hooks_config.resolve_script_path(command);
-        let expanded_path = HooksConfig::expand_tilde(&script_path);
+        let expanded_path = expand(&script_path)?;
 
         if !expanded_path.exists() {
             warn!(
@@ -174,8 +181,12 @@ pub(crate) async fn handle_remove_hook(
     script: Option<String>,
     config_path: Option<PathBuf>,
 ) -> Result<()> {
-    let mut config = load_or_create_config(config_path.clone()).await?;
     let (hook_type, plugin_name) = determine_hook_target(&target)?;
+    let config_path = Some(hooks_file(
+        config_path,
+        &hook_key(&hook_type, plugin_name.as_deref()),
+    )?);
+    let mut config = load_or_create_config(config_path.clone()).await?;
 
     // Get mutable reference to the appropriate hook list
     let hooks = match hook_type.as_str() {
@@ -301,8 +312,7 @@ pub(crate) async fn handle_remove_hook(
     }
 
     // Save updated configuration
-    let config_file_path = get_config_file_path(config_path);
-    config.save_to_file(&config_file_path).await?;
+    save_hooks(&config, config_path, &hook_type, None).await?;
 
     Ok(())
 }
@@ -316,7 +326,7 @@ pub(crate) async fn handle_list_hooks(
     verbose: bool,
     config_path: Option<PathBuf>,
 ) -> Result<()> {
-    let config = load_or_create_config(config_path).await?;
+    let config = Config::load_with_profile(config_path.as_deref(), None).await?;
     let hooks_config = config.get_hooks_config();
 
     info!("{} Plugin Hooks Configuration:", SYMBOL_ACTION_HOOK);
@@ -364,7 +374,7 @@ pub(crate) async fn handle_validate_hooks(
     post_snapshot: bool,
     config_path: Option<PathBuf>,
 ) -> Result<()> {
-    let config = load_or_create_config(config_path).await?;
+    let config = Config::load_with_profile(config_path.as_deref(), None).await?;
     let hooks_config = config.get_hooks_config();
     let hook_context = HookContext::new(
         "validation".to_string(),
@@ -486,11 +496,12 @@ pub(crate) async fn handle_scripts_dir(
     create: bool,
     config_path: Option<PathBuf>,
 ) -> Result<()> {
+    let config_path = Some(hooks_file(config_path, &["hooks", "scripts_dir"])?);
     let mut config = load_or_create_config(config_path.clone()).await?;
 
     if let Some(new_path) = set {
         // Set new scripts directory
-        let expanded_path = HooksConfig::expand_tilde(&new_path);
+        let expanded_path = expand(&new_path)?;
 
         if config.hooks.is_none() {
             config.hooks = Some(HooksConfig::default());
@@ -498,8 +509,7 @@ pub(crate) async fn handle_scripts_dir(
         config.hooks.as_mut().unwrap().scripts_dir = expanded_path.clone();
 
         // Save configuration
-        let config_file_path = get_config_file_path(config_path);
-        config.save_to_file(&config_file_path).await?;
+        save_config_value(&config, config_path, &["hooks", "scripts_dir"]).await?;
 
         info!(
             "{} Scripts directory updated: {}",
@@ -529,7 +539,7 @@ pub(crate) async fn handle_scripts_dir(
         // Show current scripts directory
         let hooks_config = config.get_hooks_config();
         let scripts_dir = &hooks_config.scripts_dir;
-        let expanded_dir = HooksConfig::expand_tilde(scripts_dir);
+        let expanded_dir = expand(scripts_dir)?;
 
         info!(
             "{} Current scripts directory: {}",
@@ -646,24 +656,113 @@ pub(crate) fn determine_hook_target(target: &HookTarget) -> Result<(String, Opti
     }
 }
 
+/// Load the file the hooks commands write to
+///
+/// Only that file is read, not the merged config layers, so saving it back
+/// doesn't copy settings from other layers into it.
 pub(crate) async fn load_or_create_config(config_path: Option<PathBuf>) -> Result<Config> {
-    if let Some(path) = config_path {
-        if path.exists() {
-            Config::load_from_file(&path).await
-        } else {
-            Ok(Config::default())
-        }
+    let path = get_config_file_path(config_path);
+    if path.exists() {
+        Config::load_from_file(&path).await
     } else {
-        Config::load().await
+        Ok(Config::default())
     }
 }
 
+/// Write one hook list back to the config file
+async fn save_hooks(
+    config: &Config,
+    config_path: Option<PathBuf>,
+    hook_type: &str,
+    plugin_name: Option<&str>,
+) -> Result<()> {
+    save_config_value(config, config_path, &hook_key(hook_type, plugin_name)).await
+}
+
+/// Config key of a hook list
+fn hook_key<'a>(hook_type: &'a str, plugin_name: Option<&'a str>) -> Vec<&'a str> {
+    match plugin_name {
+        Some(plugin_name) => vec!["plugins", plugin_name, "hooks", hook_type],
+        None => vec!["global", "hooks", hook_type],
+    }
+}
+
+/// The file a hooks command edits for a key
+///
+/// That is the last file among the config file and its includes that sets
+/// the key, since its value is the one in effect. Keys no file sets go to
+/// `local_config` if configured, otherwise to the config file itself.
+pub(crate) fn hooks_file(config_path: Option<PathBuf>, key: &[&str]) -> Result<PathBuf> {
+    let path = get_config_file_path(config_path);
+    if !path.exists() {
+        return Ok(path);
+    }
+
+    let layers = file_layers(Some(&path))?;
+    let owner = layers
+        .iter()
+        .rev()
+        .find(|layer| table_has_key(&layer.table, key))
+        .and_then(|layer| layer.source.path());
+    let local = layers
+        .iter()
+        .rev()
+        .find_map(|layer| layer.table.get("local_config")?.as_str());
+    Ok(match (owner, local) {
+        (Some(owner), _) => owner.to_path_buf(),
+        (None, Some(local)) => PathBuf::from(local),
+        (None, None) => path,
+    })
+}
+
+fn table_has_key(table: &toml::Table, key: &[&str]) -> bool {
+    match key {
+        [] => true,
+        [last] => table.contains_key(*last),
+        [first, rest @ ..] => table
+            .get(*first)
+            .and_then(toml::Value::as_table)
+            .is_some_and(|table| table_has_key(table, rest)),
+    }
+}
+
+/// Write the value of one key back to the config file
+///
+/// Only that key is edited, so comments and formatting of the rest of the
+/// file are preserved. A key the config no longer has is removed.
+pub(crate) async fn save_config_value(
+    config: &Config,
+    config_path: Option<PathBuf>,
+    key: &[&str],
+) -> Result<()> {
+    let values = toml::Value::try_from(config).context("Failed to serialize config")?;
+    let value = key
+        .iter()
+        .try_fold(&values, |value, segment| value.get(segment));
+
+    let mut document = ConfigDocument::open(&get_config_file_path(config_path)).await?;
+    match value {
+        Some(value) => document.set(key, value)?,
+        None => {
+            document.unset(key);
+        }
+    }
+    document.save().await
+}
+
+/// The `--config` file, or the user's `dotsnapshot/config.*` file
+///
+/// Without an existing user config file, a new one is `config.toml`.
 pub(crate) fn get_config_file_path(config_path: Option<PathBuf>) -> PathBuf {
     config_path.unwrap_or_else(|| {
-        dirs::config_dir()
+        let base = dirs::config_dir()
             .unwrap_or_else(|| PathBuf::from("."))
             .join("dotsnapshot")
-            .join("config.toml")
+            .join("config");
+        candidates(&base)
+            .into_iter()
+            .find(|path| path.is_file())
+            .unwrap_or_else(|| base.with_extension("toml"))
     })
 }
 
@@ -699,7 +798,7 @@ pub(crate) fn ensure_plugin_config(config: &mut Config, plugin_name: &str) {
                 post_plugin: Vec::new(),
             }),
         };
-        if let Ok(value) = toml::Value::try_from(plugin_config) {
+        if let Ok(value) = to_plugin_value(&plugin_config) {
             plugins.plugins.insert(plugin_name.to_string(), value);
         } else {
             warn!(
@@ -710,13 +809,15 @@ pub(crate) fn ensure_plugin_config(config: &mut Config, plugin_name: &str) {
     } else {
         // Ensure hooks exist
         if let Some(plugin_value) = plugins.plugins.get_mut(plugin_name) {
-            if let Ok(mut plugin_config) = plugin_value.clone().try_into::<PluginConfig>() {
+            if let Ok(mut plugin_config) =
+                serde_json::from_value::<PluginConfig>(plugin_value.clone())
+            {
                 if plugin_config.hooks.is_none() {
                     plugin_config.hooks = Some(PluginHooks {
                         pre_plugin: Vec::new(),
                         post_plugin: Vec::new(),
                     });
-                    if let Ok(value) = toml::Value::try_from(plugin_config) {
+                    if let Ok(value) = to_plugin_value(&plugin_config) {
                         *plugin_value = value;
                     } else {
                         warn!(
@@ -742,9 +843,9 @@ where
     let plugins = config.plugins.as_mut()?;
     let plugin_value = plugins.plugins.get_mut(plugin_name)?;
 
-    if let Ok(mut plugin_config) = plugin_value.clone().try_into::<PluginConfig>() {
+    if let Ok(mut plugin_config) = serde_json::from_value::<PluginConfig>(plugin_value.clone()) {
         let result = modifier(&mut plugin_config);
-        match toml::Value::try_from(plugin_config) {
+        match to_plugin_value(&plugin_config) {
             Ok(value) => {
                 *plugin_value = value;
                 Some(result)
@@ -783,7 +884,7 @@ pub(crate) async fn handle_plugin_hook_removal(
     // Get the current plugin config or create a new one
     let current_value = plugins.plugins.get(plugin_name).cloned();
     let mut plugin_config = if let Some(value) = current_value {
-        value.try_into::<PluginConfig>().unwrap_or(PluginConfig {
+        serde_json::from_value::<PluginConfig>(value).unwrap_or(PluginConfig {
             target_path: None,
             output_file: None,
             hooks: None,
@@ -877,19 +978,13 @@ pub(crate) async fn handle_plugin_hook_removal(
     // Save the modified config back to the HashMap
     plugins.plugins.insert(
         plugin_name.to_string(),
-        toml::Value::try_from(plugin_config).with_context(|| {
+        to_plugin_value(&plugin_config).with_context(|| {
             format!("Failed to serialize PluginConfig for plugin '{plugin_name}'")
         })?,
     );
 
     // Save the config
-    let save_path = config_path.unwrap_or_else(|| {
-        Config::get_config_paths()
-            .into_iter()
-            .next()
-            .unwrap_or_else(|| PathBuf::from("dotsnapshot.toml"))
-    });
-    config.save_to_file(&save_path).await?;
+    save_hooks(config, config_path, hook_type, Some(plugin_name)).await?;
 
     info!("{} Configuration updated", SYMBOL_INDICATOR_SUCCESS);
 
@@ -1005,7 +1100,8 @@ pub(crate) fn show_hook_list(
     for (index, hook) in hooks.iter().enumerate() {
         if let HookAction::Script { command, .. } = hook {
             let script_path = hooks_config.resolve_script_path(command);
-            let expanded_path = HooksConfig::expand_tilde(&script_path);
+            // Listing shows unexpandable paths as missing instead of failing
+            let expanded_path = expand(&script_path).unwrap_or(script_path);
             let exists = if expanded_path.exists() {
                 SYMBOL_INDICATOR_SUCCESS
             } else {
@@ -86,7 +86,7 @@ mod tests {
         let plugin_config = plugins.plugins.get(plugin_name).unwrap();
         // plugin_config is a toml::Value, not a PluginConfig struct
         if let Some(hooks_val) = plugin_config.get("hooks") {
-            assert!(hooks_val.is_table());
+            assert!(hooks_val.is_object());
         } else {
             panic!("hooks should be present");
         }
@@ -0,0 +1,61 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::config::Config;
+use crate::core::index::SnapshotIndex;
+use crate::core::lock::SnapshotLock;
+use crate::core::snapshot::SnapshotManager;
+use crate::symbols::*;
+
+/// Handle the `index rebuild` command
+///
+/// Rebuilds the checksum index at the root of the output directory from the
+/// metadata of every snapshot, e.g. after snapshots were copied in or the
+/// index file was lost.
+pub async fn handle_index_rebuild(
+    output: Option<PathBuf>,
+    config_path: Option<PathBuf>,
+    profile: Option<String>,
+) -> Result<()> {
+    let config = Config::load_with_profile(config_path.as_deref(), profile.as_deref()).await?;
+    let output_dir = match output {
+        Some(output) => output,
+        None => config.get_output_dir()?,
+    };
+
+    if !output_dir.is_dir() {
+        return Err(anyhow::anyhow!(
+            "Snapshot directory does not exist: {}",
+            output_dir.display()
+        ));
+    }
+
+    let Some(_lock) = SnapshotLock::try_acquire(&output_dir)? else {
+        return Err(anyhow::anyhow!(
+            "Another snapshot run is using {}",
+            output_dir.display()
+        ));
+    };
+
+    let manager = SnapshotManager::new(output_dir.clone());
+    let index = SnapshotIndex::rebuild(&manager).await?;
+    index.save(&output_dir).await?;
+
+    if index.is_empty() {
+        info!(
+            "{} No snapshot output to index in {}",
+            SYMBOL_INDICATOR_WARNING,
+            output_dir.display()
+        );
+    }
+    info!(
+        "{} Indexed {} files from {} snapshots in {}",
+        SYMBOL_INDICATOR_SUCCESS,
+        index.len(),
+        manager.list_snapshots()?.len(),
+        SnapshotIndex::path(&output_dir).display()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,400 @@
+use anyhow::{Context, Result};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tracing::info;
+
+use crate::cli::hooks::get_config_file_path;
+use crate::config::format::ConfigFormat;
+use crate::config::migrate::CONFIG_VERSION;
+use crate::config::Config;
+use crate::core::plugin::PluginDescriptor;
+use crate::symbols::*;
+
+/// Dotfiles suggested for the static plugin when they exist in the home directory
+const DOTFILE_CANDIDATES: &[&str] = &[
+    ".bashrc",
+    ".bash_profile",
+    ".zshrc",
+    ".zprofile",
+    ".profile",
+    ".gitconfig",
+    ".gitignore_global",
+    ".vimrc",
+    ".tmux.conf",
+    ".inputrc",
+    ".editorconfig",
+    ".ssh/config",
+    ".config/fish/config.fish",
+    ".config/nvim/init.lua",
+    ".config/nvim/init.vim",
+    ".config/starship.toml",
+    ".config/alacritty/alacritty.toml",
+    ".config/kitty/kitty.conf",
+    ".config/wezterm/wezterm.lua",
+];
+
+/// Options of the `init` command
+pub struct InitOptions {
+    pub config_path: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub non_interactive: bool,
+    pub force: bool,
+}
+
+/// A plugin found in the registry
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectedPlugin {
+    pub name: String,
+    /// Whether its tool was detected and, when asked, the user kept it
+    pub enabled: bool,
+    /// Where the plugin restores to on this platform
+    pub restore_target_dir: Option<PathBuf>,
+}
+
+/// Choices the generated config is written from
+#[derive(Debug, Clone, Default)]
+pub struct InitPlan {
+    pub output_dir: PathBuf,
+    pub scripts_dir: PathBuf,
+    pub plugins: Vec<DetectedPlugin>,
+    pub dotfiles: Vec<PathBuf>,
+}
+
+/// Handle the `init` command
+///
+/// Detects installed tools by validating every registered plugin, suggests
+/// dotfiles for the static plugin, and writes a commented config file.
+/// Without `--non-interactive`, each detected plugin and dotfile is
+/// confirmed first.
+pub async fn handle_init_command(options: InitOptions) -> Result<()> {
+    let config_path = std::path::absolute(get_config_file_path(options.config_path))
+        .context("Failed to resolve config file path")?;
+    if config_path.exists() && !options.force {
+        return Err(anyhow::anyhow!(
+            "Config file already exists: {} (use --force to overwrite it)",
+            config_path.display()
+        ));
+    }
+    let home_dir = dirs::home_dir().context("Could not determine home directory")?;
+
+    info!("{} Detecting installed tools...", SYMBOL_ACTION_SEARCH);
+    let detected = detect_plugins().await;
+    let dotfiles = suggest_dotfiles(&home_dir);
+
+    let mut plan = InitPlan {
+        output_dir: options.output.unwrap_or_else(default_output_dir),
+        scripts_dir: config_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join("scripts"),
+        plugins: detected,
+        dotfiles,
+    };
+
+    if !options.non_interactive {
+        let mut input = io::stdin().lock();
+        plan = confirm_plan(plan, &mut input, &home_dir)?;
+    }
+
+    for plugin in &plan.plugins {
+        let status = if plugin.enabled {
+            SYMBOL_INDICATOR_SUCCESS
+        } else {
+            SYMBOL_INDICATOR_WARNING
+        };
+        info!("  {} {}", status, plugin.name);
+    }
+
+    // The comments only survive in TOML
+    let content = ConfigFormat::from_path(&config_path).render(&render_config(&plan, &home_dir))?;
+    if let Some(parent) = config_path.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .context("Failed to create config directory")?;
+    }
+    fs::write(&config_path, content)
+        .await
+        .with_context(|| format!("Failed to write config file: {}", config_path.display()))?;
+    fs::create_dir_all(&plan.scripts_dir)
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to create scripts directory: {}",
+                plan.scripts_dir.display()
+            )
+        })?;
+
+    info!(
+        "{} Wrote {} ({} plugin(s), {} dotfile(s))",
+        SYMBOL_INDICATOR_SUCCESS,
+        config_path.display(),
+        plan.plugins.iter().filter(|plugin| plugin.enabled).count(),
+        plan.dotfiles.len()
+    );
+    info!(
+        "{} Created scripts directory {}",
+        SYMBOL_CONTENT_FOLDER,
+        plan.scripts_dir.display()
+    );
+    Ok(())
+}
+
+/// Every registered plugin, with whether its `validate()` passes here
+///
+/// The static plugin is left out; it is enabled by choosing dotfiles.
+pub async fn detect_plugins() -> Vec<DetectedPlugin> {
+    let mut descriptors: Vec<&PluginDescriptor> = inventory::iter::<PluginDescriptor>()
+        .filter(|descriptor| Config::plugin_config_key(descriptor.name) != "static")
+        .collect();
+    descriptors.sort_by_key(|descriptor| descriptor.name);
+
+    // Defaults that are just the current directory depend on where restore
+    // runs, so they aren't written to the config
+    let current_dir = std::env::current_dir().ok();
+    let mut detected = Vec::new();
+    for descriptor in descriptors {
+        let plugin = (descriptor.factory)(None);
+        detected.push(DetectedPlugin {
+            name: descriptor.name.to_string(),
+            enabled: plugin.validate().await.is_ok(),
+            restore_target_dir: plugin
+                .get_default_restore_target_dir()
+                .ok()
+                .filter(|dir| Some(dir) != current_dir.as_ref()),
+        });
+    }
+    detected
+}
+
+/// Common dotfiles that exist under the home directory
+pub fn suggest_dotfiles(home_dir: &Path) -> Vec<PathBuf> {
+    DOTFILE_CANDIDATES
+        .iter()
+        .map(|candidate| home_dir.join(candidate))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Ask about each detected plugin and suggested dotfile
+pub fn confirm_plan(
+    mut plan: InitPlan,
+    input: &mut impl BufRead,
+    home_dir: &Path,
+) -> Result<InitPlan> {
+    for plugin in plan.plugins.iter_mut().filter(|plugin| plugin.enabled) {
+        plugin.enabled = confirm(input, &format!("Snapshot {}?", plugin.name), true)?;
+    }
+
+    let mut dotfiles = Vec::new();
+    for dotfile in plan.dotfiles {
+        let question = format!("Back up {}?", tilde_path(&dotfile, home_dir));
+        if confirm(input, &question, true)? {
+            dotfiles.push(dotfile);
+        }
+    }
+    plan.dotfiles = dotfiles;
+    Ok(plan)
+}
+
+/// Ask a yes/no question; an empty answer takes the default
+fn confirm(input: &mut impl BufRead, question: &str, default: bool) -> Result<bool> {
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    print!("{SYMBOL_EXPERIENCE_QUESTION} {question} {hint}: ");
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    Ok(match answer.trim().to_lowercase().as_str() {
+        "" => default,
+        answer => matches!(answer, "y" | "yes"),
+    })
+}
+
+/// Default snapshot directory in the platform's data directory
+fn default_output_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("dotsnapshot")
+        .join("snapshots")
+}
+
+/// A path with the home directory written as `~`
+fn tilde_path(path: &Path, home_dir: &Path) -> String {
+    match path.strip_prefix(home_dir) {
+        Ok(relative) if relative.as_os_str().is_empty() => "~".to_string(),
+        Ok(relative) => format!("~/{}", relative.display()),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+/// A TOML string literal
+fn quoted(value: &str) -> String {
+    toml::Value::String(value.to_string()).to_string()
+}
+
+/// The commented config file for a plan
+pub fn render_config(plan: &InitPlan, home_dir: &Path) -> String {
+    let enabled: Vec<&DetectedPlugin> = plan
+        .plugins
+        .iter()
+        .filter(|plugin| plugin.enabled)
+        .collect();
+    let mut included: Vec<String> = enabled.iter().map(|plugin| quoted(&plugin.name)).collect();
+    if !plan.dotfiles.is_empty() {
+        included.push(quoted("static_files"));
+    }
+
+    let mut lines = vec![
+        "# dotsnapshot configuration, generated by `dotsnapshot init`".to_string(),
+        "# Check it with `dotsnapshot config validate` after editing".to_string(),
+        format!("version = {CONFIG_VERSION}"),
+        String::new(),
+        "# Directory where snapshots are stored".to_string(),
+        format!(
+            "output_dir = {}",
+            quoted(&tilde_path(&plan.output_dir, home_dir))
+        ),
+        String::new(),
+        "# Plugins to run; these were detected on this machine".to_string(),
+        format!("include_plugins = [{}]", included.join(", ")),
+    ];
+
+    let missing: Vec<&str> = plan
+        .plugins
+        .iter()
+        .filter(|plugin| !plugin.enabled)
+        .map(|plugin| plugin.name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        lines.push(format!("# Not enabled: {}", missing.join(", ")));
+    }
+
+    if !plan.dotfiles.is_empty() {
+        let files: Vec<String> = plan
+            .dotfiles
+            .iter()
+            .map(|path| format!("    {},", quoted(&tilde_path(path, home_dir))))
+            .collect();
+        lines.extend([
+            String::new(),
+            "# Dotfiles copied into each snapshot".to_string(),
+            "[plugins.static]".to_string(),
+            "files = [".to_string(),
+        ]);
+        lines.extend(files);
+        lines.push("]".to_string());
+    }
+
+    for plugin in &enabled {
+        let Some(restore_target_dir) = &plugin.restore_target_dir else {
+            continue;
+        };
+        lines.extend([
+            String::new(),
+            format!("[plugins.{}]", plugin.name),
+            "# Where `dotsnapshot restore` puts files on this platform".to_string(),
+            format!(
+                "restore_target_dir = {}",
+                quoted(&tilde_path(restore_target_dir, home_dir))
+            ),
+        ]);
+    }
+
+    lines.extend([
+        String::new(),
+        "[hooks]".to_string(),
+        "# Scripts used by hooks are looked up here".to_string(),
+        format!(
+            "scripts_dir = {}",
+            quoted(&tilde_path(&plan.scripts_dir, home_dir))
+        ),
+    ]);
+
+    let mut content = lines.join("\n");
+    content.push('\n');
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::validate::validate_str;
+    use tempfile::TempDir;
+
+    fn plan(home_dir: &Path) -> InitPlan {
+        InitPlan {
+            output_dir: home_dir.join("snapshots"),
+            scripts_dir: home_dir.join(".config/dotsnapshot/scripts"),
+            plugins: vec![
+                DetectedPlugin {
+                    name: "vscode_settings".to_string(),
+                    enabled: true,
+                    restore_target_dir: Some(home_dir.join(".config/Code/User")),
+                },
+                DetectedPlugin {
+                    name: "homebrew_brewfile".to_string(),
+                    enabled: false,
+                    restore_target_dir: Some(home_dir.to_path_buf()),
+                },
+            ],
+            dotfiles: vec![home_dir.join(".zshrc")],
+        }
+    }
+
+    /// Test rendering the generated config file
+    /// Verifies it is commented, uses ~ paths and passes strict validation
+    #[test]
+    fn test_render_config() {
+        let home_dir = Path::new("/home/user");
+        let content = render_config(&plan(home_dir), home_dir);
+
+        assert!(content.starts_with("# dotsnapshot configuration"));
+        assert!(content.contains("output_dir = \"~/snapshots\""));
+        assert!(content.contains("include_plugins = [\"vscode_settings\", \"static_files\"]"));
+        assert!(content.contains("# Not enabled: homebrew_brewfile"));
+        assert!(content.contains("files = [\n    \"~/.zshrc\",\n]"));
+        assert!(content.contains("restore_target_dir = \"~/.config/Code/User\""));
+        assert!(!content.contains("[plugins.homebrew_brewfile]"));
+        assert!(content.contains("scripts_dir = \"~/.config/dotsnapshot/scripts\""));
+
+        assert!(validate_str(&content).is_empty());
+    }
+
+    /// Test the interactive questions
+    /// Verifies empty answers accept the default and "n" drops the item
+    #[test]
+    fn test_confirm_plan() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let home_dir = temp_dir.path();
+        let mut plan = plan(home_dir);
+        plan.dotfiles.push(home_dir.join(".gitconfig"));
+
+        let mut input = "n\n\nno\n".as_bytes();
+        let plan = confirm_plan(plan, &mut input, home_dir)?;
+        assert!(!plan.plugins[0].enabled);
+        assert_eq!(plan.dotfiles, vec![home_dir.join(".zshrc")]);
+        Ok(())
+    }
+
+    /// Test suggesting dotfiles
+    /// Verifies only candidates that exist as files are suggested
+    #[test]
+    fn test_suggest_dotfiles() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let home_dir = temp_dir.path();
+        std::fs::write(home_dir.join(".zshrc"), "")?;
+        std::fs::create_dir_all(home_dir.join(".config/nvim"))?;
+        std::fs::write(home_dir.join(".config/nvim/init.lua"), "")?;
+        std::fs::create_dir(home_dir.join(".vimrc"))?;
+
+        assert_eq!(
+            suggest_dotfiles(home_dir),
+            vec![
+                home_dir.join(".zshrc"),
+                home_dir.join(".config/nvim/init.lua")
+            ]
+        );
+        Ok(())
+    }
+}
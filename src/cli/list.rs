@@ -0,0 +1,109 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::config::Config;
+use crate::core::snapshot::{SnapshotManager, SnapshotMetadata};
+use crate::symbols::*;
+
+/// Handle the `list` command
+///
+/// Prints the snapshots in the output directory, oldest first, with the
+/// machine and profile each was taken with. With `host`, only snapshots
+/// taken on that host are listed.
+pub async fn handle_list_command(
+    output: Option<PathBuf>,
+    host: Option<String>,
+    config_path: Option<PathBuf>,
+    profile: Option<String>,
+) -> Result<()> {
+    let config = Config::load_with_profile(config_path.as_deref(), profile.as_deref()).await?;
+    let output_dir = match output {
+        Some(output) => output,
+        None => config.get_output_dir()?,
+    };
+
+    let snapshots: Vec<_> = SnapshotManager::new(output_dir.clone())
+        .load_snapshots()
+        .await?
+        .into_iter()
+        .filter(|(_, metadata)| match &host {
+            Some(host) => metadata
+                .as_ref()
+                .is_some_and(|metadata| metadata.is_from_host(host)),
+            None => true,
+        })
+        .collect();
+
+    if snapshots.is_empty() {
+        info!(
+            "{} No snapshots found in {}{}",
+            SYMBOL_INDICATOR_INFO,
+            output_dir.display(),
+            host.map(|host| format!(" from host '{host}'"))
+                .unwrap_or_default()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{:<17} {:<24} {:<16} {:<12} PLUGINS",
+        "SNAPSHOT", "HOST", "USER", "PROFILE"
+    );
+    for (path, metadata) in &snapshots {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        println!("{:<17} {}", name, describe(metadata.as_ref()));
+    }
+
+    Ok(())
+}
+
+/// Host, user, profile and plugin count columns of a snapshot
+fn describe(metadata: Option<&SnapshotMetadata>) -> String {
+    let Some(metadata) = metadata else {
+        return format!("{:<24} {:<16} {:<12} -", "-", "-", "-");
+    };
+    let (hostname, username) = metadata
+        .host
+        .as_ref()
+        .map(|host| (host.hostname.as_str(), host.username.as_str()))
+        .unwrap_or(("-", "-"));
+
+    format!(
+        "{:<24} {:<16} {:<12} {}",
+        hostname,
+        username,
+        metadata.profile.as_deref().unwrap_or("-"),
+        metadata.checksums.len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test describing snapshots for the list output
+    /// Verifies placeholders for snapshots without host, profile or metadata
+    #[test]
+    fn test_describe_snapshot() {
+        let mut metadata = SnapshotManager::new(PathBuf::from(".")).create_metadata();
+        metadata.profile = Some("work".to_string());
+        metadata
+            .checksums
+            .insert("static".to_string(), "sum".to_string());
+        let host = metadata.host.as_mut().unwrap();
+        host.hostname = "laptop".to_string();
+        host.username = "me".to_string();
+
+        let columns: Vec<String> = describe(Some(&metadata))
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        assert_eq!(columns, ["laptop", "me", "work", "1"]);
+
+        metadata.host = None;
+        metadata.profile = None;
+        assert!(describe(Some(&metadata)).starts_with("- "));
+        assert_eq!(describe(None).split_whitespace().count(), 4);
+    }
+}
@@ -1,2 +1,8 @@
+pub mod config;
 pub mod hooks;
+pub mod index;
+pub mod init;
+pub mod list;
 pub mod restore;
+pub mod schedule;
+pub mod watch;
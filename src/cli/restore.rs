@@ -1,9 +1,11 @@
 use anyhow::Result;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::{error, info, warn};
 
 use crate::config::Config;
 use crate::core::restore::RestoreManager;
+use crate::core::selector::exclude_selectors;
+use crate::core::snapshot::{HostInfo, SnapshotManager};
 use crate::symbols::*;
 
 /// Handle restore subcommand
@@ -12,27 +14,22 @@ pub async fn handle_restore_command(
     snapshot_path: Option<PathBuf>,
     latest: bool,
     plugins: Option<String>,
+    exclude: Option<String>,
     dry_run: bool,
     backup: bool,
     force: bool,
     target_dir: Option<PathBuf>,
     config_path: Option<PathBuf>,
+    profile: Option<String>,
+    host: Option<String>,
 ) -> Result<()> {
     // Load configuration
-    let config = if let Some(config_path) = config_path {
-        if config_path.exists() {
-            Config::load_from_file(&config_path).await?
-        } else {
-            Config::default()
-        }
-    } else {
-        Config::load().await.unwrap_or_default()
-    };
+    let config = Config::load_with_profile(config_path.as_deref(), profile.as_deref()).await?;
 
     // Determine the actual snapshot path
     let actual_snapshot_path = if latest {
         // Find the latest snapshot in the default output directory
-        find_latest_snapshot(&config).await?
+        find_latest_snapshot(&config, host.as_deref()).await?
     } else if let Some(path) = snapshot_path {
         path
     } else {
@@ -66,12 +63,19 @@ pub async fn handle_restore_command(
         ));
     }
 
-    // Parse plugins filter
-    let selected_plugins = plugins.map(|p| {
+    warn_on_origin_mismatch(&actual_snapshot_path, config.active_profile.as_deref()).await;
+
+    // Parse plugins filter, with --exclude entries as negated selectors
+    let mut selected_plugins = plugins.map(|p| {
         p.split(',')
             .map(|s| s.trim().to_string())
             .collect::<Vec<_>>()
     });
+    if let Some(exclude) = exclude.as_deref() {
+        selected_plugins
+            .get_or_insert_with(Vec::new)
+            .extend(exclude_selectors(exclude));
+    }
 
     // Determine target directory - this is the global override if provided
     let global_target_override = target_dir;
@@ -184,9 +188,52 @@ pub async fn handle_restore_command(
     Ok(())
 }
 
+/// Warn when the snapshot was taken on another machine or with a different profile
+async fn warn_on_origin_mismatch(snapshot_path: &Path, active_profile: Option<&str>) {
+    let manager = SnapshotManager::new(snapshot_path.to_path_buf());
+    let Ok(metadata) = manager.load_metadata(snapshot_path).await else {
+        return;
+    };
+
+    if let Some(host) = &metadata.host {
+        let current = HostInfo::current();
+        if !host.matches(&current.hostname) {
+            warn!(
+                "{} Snapshot was taken on host '{}' ({}/{}), restoring on '{}'",
+                SYMBOL_INDICATOR_WARNING, host.hostname, host.os, host.arch, current.hostname
+            );
+        }
+    }
+    if let Some(message) = profile_mismatch(metadata.profile.as_deref(), active_profile) {
+        warn!("{} {}", SYMBOL_INDICATOR_WARNING, message);
+    }
+}
+
+/// Describe a mismatch between the snapshot's profile and the active profile
+fn profile_mismatch(
+    snapshot_profile: Option<&str>,
+    active_profile: Option<&str>,
+) -> Option<String> {
+    if snapshot_profile == active_profile {
+        return None;
+    }
+
+    let describe = |profile: Option<&str>| match profile {
+        Some(profile) => format!("profile '{profile}'"),
+        None => "no profile".to_string(),
+    };
+    Some(format!(
+        "Snapshot was taken with {}, but restoring with {}",
+        describe(snapshot_profile),
+        describe(active_profile)
+    ))
+}
+
 /// Find the latest snapshot directory in the default snapshot directory
-async fn find_latest_snapshot(config: &Config) -> Result<PathBuf> {
-    let snapshot_base_dir = config.get_output_dir();
+///
+/// With `host`, only snapshots taken on that host are considered.
+async fn find_latest_snapshot(config: &Config, host: Option<&str>) -> Result<PathBuf> {
+    let snapshot_base_dir = config.get_output_dir()?;
 
     if !snapshot_base_dir.exists() {
         return Err(anyhow::anyhow!(
@@ -220,7 +267,27 @@ async fn find_latest_snapshot(config: &Config) -> Result<PathBuf> {
     // Sort by directory name (which is timestamp-based) in descending order
     snapshot_dirs.sort_by(|a, b| b.0.cmp(&a.0));
 
-    let latest_snapshot_path = snapshot_dirs[0].1.clone();
+    let latest_snapshot_path = match host {
+        Some(host) => {
+            let manager = SnapshotManager::new(snapshot_base_dir.clone());
+            let mut from_host = None;
+            for (_, path) in snapshot_dirs {
+                let metadata = manager.load_metadata(&path).await;
+                if metadata.is_ok_and(|metadata| metadata.is_from_host(host)) {
+                    from_host = Some(path);
+                    break;
+                }
+            }
+            from_host.ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No snapshots from host '{}' found in: {}",
+                    host,
+                    snapshot_base_dir.display()
+                )
+            })?
+        }
+        None => snapshot_dirs[0].1.clone(),
+    };
     info!(
         "{} Found latest snapshot: {}",
         SYMBOL_EXPERIENCE_IDEA,
@@ -255,6 +322,22 @@ mod tests {
     use tempfile::TempDir;
     use tokio::fs;
 
+    /// Test describing profile mismatches between a snapshot and the active config
+    /// Verifies that matching profiles produce no warning
+    #[test]
+    fn test_profile_mismatch() {
+        assert_eq!(profile_mismatch(None, None), None);
+        assert_eq!(profile_mismatch(Some("work"), Some("work")), None);
+        assert_eq!(
+            profile_mismatch(Some("work"), Some("personal")).unwrap(),
+            "Snapshot was taken with profile 'work', but restoring with profile 'personal'"
+        );
+        assert_eq!(
+            profile_mismatch(None, Some("work")).unwrap(),
+            "Snapshot was taken with no profile, but restoring with profile 'work'"
+        );
+    }
+
     /// Test snapshot directory name validation
     /// Verifies that snapshot directory patterns are correctly identified
     #[test]
@@ -287,7 +370,7 @@ mod tests {
             ..Default::default()
         };
 
-        let result = find_latest_snapshot(&config).await;
+        let result = find_latest_snapshot(&config, None).await;
         assert!(result.is_err());
         assert!(result
             .unwrap_err()
@@ -316,10 +399,51 @@ mod tests {
         fs::create_dir_all(&snapshot3).await.unwrap();
         fs::create_dir_all(&non_snapshot).await.unwrap();
 
-        let result = find_latest_snapshot(&config).await.unwrap();
+        let result = find_latest_snapshot(&config, None).await.unwrap();
         assert_eq!(result, snapshot2); // Should be the latest (20240117_143022)
     }
 
+    /// Test finding the latest snapshot taken on a specific host
+    /// Verifies snapshots from other hosts and without metadata are skipped
+    #[tokio::test]
+    async fn test_find_latest_snapshot_from_host() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = Config {
+            output_dir: Some(temp_dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let manager = SnapshotManager::new(temp_dir.path().to_path_buf());
+
+        let save = |name: &'static str, hostname: &'static str| {
+            let manager = &manager;
+            let snapshot_dir = temp_dir.path().join(name);
+            async move {
+                fs::create_dir_all(&snapshot_dir).await.unwrap();
+                let mut metadata = manager.create_metadata();
+                metadata.host.as_mut().unwrap().hostname = hostname.to_string();
+                manager
+                    .save_metadata(&snapshot_dir, &metadata)
+                    .await
+                    .unwrap();
+                snapshot_dir
+            }
+        };
+        let laptop = save("20240115_120000", "laptop.example.com").await;
+        save("20240116_120000", "desktop").await;
+        fs::create_dir_all(temp_dir.path().join("20240117_120000"))
+            .await
+            .unwrap();
+
+        let result = find_latest_snapshot(&config, Some("laptop")).await.unwrap();
+        assert_eq!(result, laptop);
+
+        let result = find_latest_snapshot(&config, Some("server")).await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("No snapshots from host 'server'"));
+    }
+
     /// Test finding latest snapshot with nonexistent directory
     /// Verifies appropriate error when snapshot directory doesn't exist
     #[tokio::test]
@@ -329,7 +453,7 @@ mod tests {
             ..Default::default()
         };
 
-        let result = find_latest_snapshot(&config).await;
+        let result = find_latest_snapshot(&config, None).await;
         assert!(result.is_err());
         assert!(result
             .unwrap_err()
@@ -345,11 +469,14 @@ mod tests {
             None,  // snapshot_path
             false, // latest
             None,  // plugins
+            None,  // exclude
             false, // dry_run
             true,  // backup
             false, // force
             None,  // target_dir
             None,  // config_path
+            None,  // profile
+            None,  // host
         )
         .await;
 
@@ -369,11 +496,14 @@ mod tests {
             Some(nonexistent_path.clone()),
             false, // latest
             None,  // plugins
+            None,  // exclude
             false, // dry_run
             true,  // backup
             false, // force
             None,  // target_dir
             None,  // config_path
+            None,  // profile
+            None,  // host
         )
         .await;
 
@@ -396,11 +526,14 @@ mod tests {
             Some(file_path),
             false, // latest
             None,  // plugins
+            None,  // exclude
             false, // dry_run
             true,  // backup
             false, // force
             None,  // target_dir
             None,  // config_path
+            None,  // profile
+            None,  // host
         )
         .await;
 
@@ -433,11 +566,14 @@ mod tests {
             Some(snapshot_dir),
             false, // latest
             None,  // plugins
+            None,  // exclude
             true,  // dry_run - use dry run to avoid actual restoration
             true,  // backup
             false, // force
             None,  // target_dir
             Some(config_path),
+            None, // profile
+            None, // host
         )
         .await;
 
@@ -463,11 +599,14 @@ mod tests {
             Some(snapshot_dir),
             false, // latest
             None,  // plugins
+            None,  // exclude
             true,  // dry_run - use dry run to avoid actual restoration
             true,  // backup
             false, // force
             None,  // target_dir
             Some(nonexistent_config),
+            None, // profile
+            None, // host
         )
         .await;
 
@@ -492,11 +631,14 @@ mod tests {
             Some(snapshot_dir),
             false,                               // latest
             Some("vscode,homebrew".to_string()), // plugins filter
+            None,                                // exclude
             true,  // dry_run - use dry run to avoid actual restoration
             true,  // backup
             false, // force
             None,  // target_dir
             None,  // config_path
+            None,  // profile
+            None,  // host
         )
         .await;
 
@@ -524,11 +666,14 @@ mod tests {
             Some(snapshot_dir),
             false, // latest
             None,  // plugins
+            None,  // exclude
             true,  // dry_run - use dry run to avoid actual restoration
             true,  // backup
             false, // force
             Some(target_dir),
             None, // config_path
+            None, // profile
+            None, // host
         )
         .await;
 
@@ -562,11 +707,14 @@ mod tests {
             None,  // snapshot_path
             true,  // latest - find latest snapshot
             None,  // plugins
+            None,  // exclude
             true,  // dry_run - use dry run to avoid actual restoration
             true,  // backup
             false, // force
             None,  // target_dir
             Some(config_path),
+            None, // profile
+            None, // host
         )
         .await;
 
@@ -599,11 +747,14 @@ mod tests {
             Some(snapshot_dir),
             false,                                      // latest
             Some("vscode, homebrew , npm".to_string()), // plugins with spaces
+            None,                                       // exclude
             true,                                       // dry_run
             false,                                      // backup (disabled)
             true,                                       // force
             Some(target_dir),
             Some(config_path),
+            None, // profile
+            None, // host
         )
         .await;
 
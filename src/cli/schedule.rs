@@ -0,0 +1,301 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::{info, warn};
+
+use crate::config::Config;
+use crate::core::schedule::{
+    check_profile_name, crontab_marker, crontab_with_entry, crontab_without_entry,
+    find_crontab_entry, systemd_user_dir, unit_name, ScheduleBackend, ScheduleInterval,
+    ScheduleSpec,
+};
+use crate::symbols::*;
+
+/// Handle the `schedule install` command
+///
+/// Generates a systemd user service and timer running dotsnapshot with the
+/// absolute binary and config paths, falling back to a crontab entry where
+/// systemd user sessions are unavailable. Each profile gets its own schedule.
+pub async fn handle_schedule_install(
+    every: ScheduleInterval,
+    output: Option<PathBuf>,
+    backend: Option<ScheduleBackend>,
+    dry_run: bool,
+    config_path: Option<PathBuf>,
+    profile: Option<String>,
+) -> Result<()> {
+    let config = resolve_config_path(config_path)?;
+    if let Some(profile) = &profile {
+        check_profile_name(profile)?;
+        // Fail now rather than on every scheduled run
+        Config::load_with_profile(config.as_deref(), Some(profile)).await?;
+    }
+
+    let spec = ScheduleSpec {
+        binary: std::env::current_exe()
+            .and_then(|exe| exe.canonicalize())
+            .context("Could not determine the dotsnapshot binary path")?,
+        config,
+        output: output.map(std::path::absolute).transpose()?,
+        profile,
+        every,
+        log_file: log_file_path()?,
+    };
+    if spec.config.is_none() {
+        warn!(
+            "{} No config file found, scheduled snapshots will use the defaults",
+            SYMBOL_INDICATOR_WARNING
+        );
+    }
+
+    let backend = match backend {
+        Some(backend) => backend,
+        None => detect_backend().await,
+    };
+    match backend {
+        ScheduleBackend::Systemd => install_systemd(&spec, dry_run).await,
+        ScheduleBackend::Cron => install_cron(&spec, dry_run).await,
+    }
+}
+
+/// Handle the `schedule status` command
+pub async fn handle_schedule_status(profile: Option<String>) -> Result<()> {
+    let profile = profile.as_deref();
+    let mut installed = false;
+
+    let timer_path = unit_path(profile, "timer")?;
+    if timer_path.exists() {
+        installed = true;
+        println!(
+            "{} systemd timer: {}",
+            SYMBOL_EXPERIENCE_TIME,
+            timer_path.display()
+        );
+        if let Ok(timers) = run(
+            "systemctl",
+            &[
+                "--user",
+                "list-timers",
+                &format!("{}.timer", unit_name(profile)),
+                "--all",
+                "--no-pager",
+            ],
+            None,
+        )
+        .await
+        {
+            println!("{}", timers.trim_end());
+        }
+    }
+
+    let crontab = read_crontab().await;
+    if let Some(entry) = find_crontab_entry(&crontab, &crontab_marker(profile)) {
+        installed = true;
+        println!("{SYMBOL_EXPERIENCE_TIME} crontab entry: {entry}");
+    }
+
+    if !installed {
+        println!("{SYMBOL_INDICATOR_INFO} No scheduled snapshots installed");
+    }
+    Ok(())
+}
+
+/// Handle the `schedule remove` command
+///
+/// Removes both the systemd units and the crontab entry, whichever exist.
+pub async fn handle_schedule_remove(dry_run: bool, profile: Option<String>) -> Result<()> {
+    let profile = profile.as_deref();
+    let mut removed = false;
+
+    let service_path = unit_path(profile, "service")?;
+    let timer_path = unit_path(profile, "timer")?;
+    if timer_path.exists() || service_path.exists() {
+        removed = true;
+        let timer = format!("{}.timer", unit_name(profile));
+        if dry_run {
+            println!("Would run: systemctl --user disable --now {timer}");
+            println!("Would remove: {}", timer_path.display());
+            println!("Would remove: {}", service_path.display());
+        } else {
+            if let Err(e) = run("systemctl", &["--user", "disable", "--now", &timer], None).await {
+                warn!(
+                    "{} Could not disable {}: {}",
+                    SYMBOL_INDICATOR_WARNING, timer, e
+                );
+            }
+            for path in [&timer_path, &service_path] {
+                if path.exists() {
+                    tokio::fs::remove_file(path).await?;
+                }
+            }
+            let _ = run("systemctl", &["--user", "daemon-reload"], None).await;
+            info!(
+                "{} Removed systemd timer {}",
+                SYMBOL_INDICATOR_SUCCESS, timer
+            );
+        }
+    }
+
+    let crontab = read_crontab().await;
+    let marker = crontab_marker(profile);
+    if find_crontab_entry(&crontab, &marker).is_some() {
+        removed = true;
+        if dry_run {
+            println!("Would remove the dotsnapshot crontab entry");
+        } else {
+            write_crontab(&crontab_without_entry(&crontab, &marker)).await?;
+            info!("{} Removed crontab entry", SYMBOL_INDICATOR_SUCCESS);
+        }
+    }
+
+    if !removed {
+        info!("{} No scheduled snapshots installed", SYMBOL_INDICATOR_INFO);
+    }
+    Ok(())
+}
+
+async fn install_systemd(spec: &ScheduleSpec, dry_run: bool) -> Result<()> {
+    let profile = spec.profile.as_deref();
+    let service_path = unit_path(profile, "service")?;
+    let timer_path = unit_path(profile, "timer")?;
+    let timer = format!("{}.timer", spec.unit_name());
+
+    if dry_run {
+        println!(
+            "Would write {}:\n{}",
+            service_path.display(),
+            spec.systemd_service()
+        );
+        println!(
+            "Would write {}:\n{}",
+            timer_path.display(),
+            spec.systemd_timer()
+        );
+        println!("Would run: systemctl --user daemon-reload");
+        println!("Would run: systemctl --user enable --now {timer}");
+        return Ok(());
+    }
+
+    if let Some(unit_dir) = service_path.parent() {
+        tokio::fs::create_dir_all(unit_dir).await?;
+    }
+    tokio::fs::write(&service_path, spec.systemd_service()).await?;
+    tokio::fs::write(&timer_path, spec.systemd_timer()).await?;
+    run("systemctl", &["--user", "daemon-reload"], None).await?;
+    run("systemctl", &["--user", "enable", "--now", &timer], None).await?;
+
+    info!(
+        "{} Installed systemd timer {} running every {}",
+        SYMBOL_INDICATOR_SUCCESS, timer, spec.every
+    );
+    info!(
+        "{} Logs: journalctl --user -u {}.service",
+        SYMBOL_INDICATOR_INFO,
+        spec.unit_name()
+    );
+    Ok(())
+}
+
+async fn install_cron(spec: &ScheduleSpec, dry_run: bool) -> Result<()> {
+    let entry = spec.crontab_entry()?;
+
+    if dry_run {
+        println!("Would add to crontab:\n{entry}");
+        return Ok(());
+    }
+
+    if let Some(log_dir) = spec.log_file.parent() {
+        tokio::fs::create_dir_all(log_dir).await?;
+    }
+    let crontab = read_crontab().await;
+    write_crontab(&crontab_with_entry(&crontab, &entry)).await?;
+
+    info!(
+        "{} Installed crontab entry running every {}",
+        SYMBOL_INDICATOR_SUCCESS, spec.every
+    );
+    info!(
+        "{} Logs: {}",
+        SYMBOL_INDICATOR_INFO,
+        spec.log_file.display()
+    );
+    Ok(())
+}
+
+/// systemd user timers on Linux when a user session is available, cron otherwise
+async fn detect_backend() -> ScheduleBackend {
+    if cfg!(target_os = "linux")
+        && run("systemctl", &["--user", "show-environment"], None)
+            .await
+            .is_ok()
+    {
+        ScheduleBackend::Systemd
+    } else {
+        ScheduleBackend::Cron
+    }
+}
+
+/// Absolute path of the config file scheduled runs should use
+fn resolve_config_path(config_path: Option<PathBuf>) -> Result<Option<PathBuf>> {
+    match config_path {
+        Some(path) => path
+            .canonicalize()
+            .map(Some)
+            .with_context(|| format!("Config file not found: {}", path.display())),
+        None => Ok(Config::get_config_paths()
+            .into_iter()
+            .find(|path| path.exists())
+            .and_then(|path| path.canonicalize().ok())),
+    }
+}
+
+fn unit_path(profile: Option<&str>, extension: &str) -> Result<PathBuf> {
+    let config_dir = dirs::config_dir().context("Could not determine config directory")?;
+    Ok(systemd_user_dir(&config_dir).join(format!("{}.{extension}", unit_name(profile))))
+}
+
+fn log_file_path() -> Result<PathBuf> {
+    let data_dir = dirs::data_local_dir().context("Could not determine data directory")?;
+    Ok(data_dir.join("dotsnapshot").join("schedule.log"))
+}
+
+/// Current user crontab, empty when there is none
+async fn read_crontab() -> String {
+    run("crontab", &["-l"], None).await.unwrap_or_default()
+}
+
+async fn write_crontab(crontab: &str) -> Result<()> {
+    run("crontab", &["-"], Some(crontab)).await.map(|_| ())
+}
+
+/// Run a command, returning its stdout or an error with its stderr
+async fn run(program: &str, args: &[&str], stdin: Option<&str>) -> Result<String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {program}"))?;
+
+    if let (Some(input), Some(mut child_stdin)) = (stdin, child.stdin.take()) {
+        child_stdin.write_all(input.as_bytes()).await?;
+    }
+
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,260 @@
+use anyhow::{Context, Result};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{error, info, warn};
+
+use crate::config::Config;
+use crate::core::executor::SnapshotExecutor;
+use crate::core::lock::SnapshotLock;
+use crate::core::plugin::PluginRegistry;
+use crate::core::selector::exclude_selectors;
+use crate::core::watch::{Debouncer, WatchPlan};
+use crate::symbols::*;
+
+/// Options of the `watch` command; unset values fall back to the config file
+pub struct WatchOptions {
+    pub output: Option<PathBuf>,
+    pub plugins: Option<String>,
+    pub exclude: Option<String>,
+    pub debounce_ms: Option<u64>,
+    pub poll_interval: Option<u64>,
+}
+
+/// Handle the `watch` command
+///
+/// Watches the files captured by settings, keybindings and static files
+/// plugins and runs only the affected plugins once changes settle, carrying
+/// the output of the others forward from the latest snapshot.
+/// Plugins without watchable files are snapshotted on the poll interval.
+/// Runs until interrupted.
+pub async fn handle_watch_command(
+    options: WatchOptions,
+    config_path: Option<PathBuf>,
+    profile: Option<String>,
+) -> Result<()> {
+    let config = Config::load_with_profile(config_path.as_deref(), profile.as_deref()).await?;
+
+    let output_dir = match options.output {
+        Some(output) => output,
+        None => config.get_output_dir()?,
+    };
+    tokio::fs::create_dir_all(&output_dir).await?;
+    // Compare against absolute event paths when ignoring our own output
+    let output_dir = output_dir.canonicalize().unwrap_or(output_dir);
+
+    let debounce = options
+        .debounce_ms
+        .map(Duration::from_millis)
+        .unwrap_or_else(|| config.get_watch_debounce());
+    let poll_interval = match options.poll_interval {
+        Some(0) => None,
+        Some(seconds) => Some(Duration::from_secs(seconds)),
+        None => config.get_watch_poll_interval(),
+    };
+
+    let mut selected_plugins: Vec<String> = if let Some(cli_plugins) = options.plugins.as_deref() {
+        cli_plugins.split(',').map(|s| s.to_string()).collect()
+    } else if let Some(config_plugins) = config.get_include_plugins() {
+        config_plugins
+    } else {
+        vec!["all".to_string()]
+    };
+    if let Some(exclude) = options.exclude.as_deref() {
+        selected_plugins.extend(exclude_selectors(exclude));
+    }
+    let mut registry = PluginRegistry::new();
+    let selected_plugins_refs: Vec<&str> = selected_plugins.iter().map(|s| s.as_str()).collect();
+    registry
+        .register_from_descriptors(Some(&config), &selected_plugins_refs)
+        .await?;
+    let registry = Arc::new(registry);
+
+    let mut plan = WatchPlan::build(&registry).await;
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = RecommendedWatcher::new(
+        move |result: notify::Result<notify::Event>| match result {
+            // Reading files (including our own snapshot runs) is not a change
+            Ok(event) if event.kind.is_access() => {}
+            Ok(event) => {
+                let _ = tx.send(event.paths);
+            }
+            Err(e) => warn!("File watcher error: {}", e),
+        },
+        notify::Config::default(),
+    )
+    .context("Failed to start file watcher")?;
+
+    let mut watched = Vec::new();
+    update_watches(&mut watcher, &mut watched, &plan);
+    if !plan.polled.is_empty() {
+        match poll_interval {
+            Some(interval) => info!(
+                "{} Polling every {}s: {}",
+                SYMBOL_EXPERIENCE_TIME,
+                interval.as_secs(),
+                plan.polled.join(", ")
+            ),
+            None => info!(
+                "{} Polling disabled, not snapshotting: {}",
+                SYMBOL_INDICATOR_INFO,
+                plan.polled.join(", ")
+            ),
+        }
+    }
+
+    let config = Arc::new(config);
+    let mut polled: BTreeSet<String> = plan.polled.iter().cloned().collect();
+    let mut debouncer = Debouncer::new(debounce);
+    let mut poll = poll_interval
+        .filter(|_| !polled.is_empty())
+        .map(|interval| tokio::time::interval_at(Instant::now() + interval, interval));
+
+    loop {
+        let deadline = debouncer.deadline();
+        tokio::select! {
+            Some(paths) = rx.recv() => {
+                debouncer.push(paths, &output_dir, Instant::now());
+                continue;
+            }
+            _ = sleep_until(deadline), if deadline.is_some() => {
+                let Some(changed) = debouncer.take_ready(Instant::now()) else {
+                    continue;
+                };
+                let affected = plan.affected_plugins(&changed);
+                if affected.is_empty() {
+                    continue;
+                }
+                if !snapshot_plugins(&registry, &affected, &output_dir, &config).await {
+                    // Output directory busy: try again after the next quiet period
+                    debouncer.push(changed, &output_dir, Instant::now());
+                    continue;
+                }
+            }
+            _ = tick(&mut poll), if poll.is_some() => {
+                if polled.is_empty() {
+                    continue;
+                }
+                if !snapshot_plugins(&registry, &polled, &output_dir, &config).await {
+                    // Output directory busy: try again once it had time to finish
+                    if let Some(poll) = &mut poll {
+                        poll.reset_after(debounce);
+                    }
+                    continue;
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                info!("{} Stopped watching", SYMBOL_INDICATOR_INFO);
+                return Ok(());
+            }
+        }
+
+        // A snapshot ran: watched files may have appeared or disappeared since,
+        // including new matches of glob patterns
+        plan = WatchPlan::build(&registry).await;
+        update_watches(&mut watcher, &mut watched, &plan);
+        polled = plan.polled.iter().cloned().collect();
+        if poll.is_none() && !polled.is_empty() {
+            poll = poll_interval
+                .map(|interval| tokio::time::interval_at(Instant::now() + interval, interval));
+        }
+    }
+}
+
+/// Watch the roots of `plan`, stopping to watch roots it no longer has
+fn update_watches(
+    watcher: &mut RecommendedWatcher,
+    watched: &mut Vec<(PathBuf, RecursiveMode)>,
+    plan: &WatchPlan,
+) {
+    let roots = plan.watch_roots();
+    for (root, mode) in watched.iter() {
+        if !roots.contains(&(root.clone(), *mode)) {
+            let _ = watcher.unwatch(root);
+        }
+    }
+    for (root, mode) in &roots {
+        if watched.contains(&(root.clone(), *mode)) {
+            continue;
+        }
+        match watcher.watch(root, *mode) {
+            Ok(()) => info!("{} Watching {}", SYMBOL_ACTION_SEARCH, root.display()),
+            Err(e) => warn!(
+                "{} Cannot watch {}: {}",
+                SYMBOL_INDICATOR_WARNING,
+                root.display(),
+                e
+            ),
+        }
+    }
+    *watched = roots;
+}
+
+/// Snapshot the given plugins, skipping the snapshot when their output is unchanged
+///
+/// The output of the other plugins is carried forward from the latest
+/// snapshot, so the new snapshot is complete. Returns false when another run
+/// holds the output directory lock.
+async fn snapshot_plugins(
+    registry: &Arc<PluginRegistry>,
+    plugins: &BTreeSet<String>,
+    output_dir: &Path,
+    config: &Arc<Config>,
+) -> bool {
+    let Ok(Some(_lock)) = SnapshotLock::try_acquire(output_dir) else {
+        warn!(
+            "{} Another snapshot run is using {}, retrying later",
+            SYMBOL_INDICATOR_WARNING,
+            output_dir.display()
+        );
+        return false;
+    };
+
+    info!(
+        "{} Snapshotting {}",
+        SYMBOL_ACTION_LAUNCH,
+        plugins.iter().cloned().collect::<Vec<_>>().join(", ")
+    );
+    let unaffected = registry
+        .names()
+        .into_iter()
+        .filter(|name| !plugins.contains(name))
+        .collect();
+    let executor = SnapshotExecutor::with_config(
+        Arc::clone(registry),
+        output_dir.to_path_buf(),
+        config.clone(),
+    )
+    .with_skip_unchanged(true)
+    .with_carried_forward(unaffected);
+
+    match executor.execute_snapshot_with_report().await {
+        Ok(report) if report.changed => info!(
+            "{} Snapshot created at: {}",
+            SYMBOL_INDICATOR_SUCCESS,
+            report.snapshot_dir.display()
+        ),
+        Ok(_) => info!(
+            "{} Nothing changed, no snapshot created",
+            SYMBOL_INDICATOR_INFO
+        ),
+        Err(e) => error!("{} Snapshot creation failed: {}", SYMBOL_INDICATOR_ERROR, e),
+    }
+    true
+}
+
+async fn sleep_until(deadline: Option<Instant>) {
+    if let Some(deadline) = deadline {
+        tokio::time::sleep_until(deadline).await;
+    }
+}
+
+async fn tick(poll: &mut Option<tokio::time::Interval>) {
+    if let Some(poll) = poll {
+        poll.tick().await;
+    }
+}
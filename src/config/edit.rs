@@ -0,0 +1,448 @@
+//! Format-preserving edits of config files
+//!
+//! Commands that change the configuration (`hooks add`, `config set`, ...)
+//! edit the TOML document in place instead of re-serializing [`Config`], so
+//! comments, key order and formatting of everything they don't touch
+//! survive. YAML and JSON files are edited as TOML and written back in
+//! their own format, which keeps their values but not their comments or
+//! key order.
+//!
+//! [`Config`]: super::Config
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+use super::format::ConfigFormat;
+use toml_edit::{ArrayOfTables, DocumentMut, InlineTable, Item, Table, TableLike, Value};
+
+/// A config file opened for editing
+#[derive(Debug, Clone)]
+pub struct ConfigDocument {
+    path: PathBuf,
+    format: ConfigFormat,
+    document: DocumentMut,
+}
+
+impl ConfigDocument {
+    /// Open a config file for editing; a missing file starts out empty
+    pub async fn open(path: &Path) -> Result<Self> {
+        let content = if path.exists() {
+            fs::read_to_string(path)
+                .await
+                .with_context(|| format!("Failed to read config file: {}", path.display()))?
+        } else {
+            String::new()
+        };
+        let format = ConfigFormat::from_path(path);
+        let document = format
+            .to_toml_string(&content)
+            .and_then(|content| Ok(content.parse::<DocumentMut>()?))
+            .with_context(|| {
+                format!(
+                    "Failed to parse config file as {}: {}",
+                    format.name(),
+                    path.display()
+                )
+            })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            format,
+            document,
+        })
+    }
+
+    /// File this document is saved to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Set a key path to a value, creating parent tables as needed
+    ///
+    /// An existing value keeps its comments; entries of an array of tables
+    /// that are unchanged keep theirs too. Setting an empty array of tables
+    /// removes the key.
+    pub fn set<S: AsRef<str>, T: Serialize + ?Sized>(
+        &mut self,
+        key: &[S],
+        value: &T,
+    ) -> Result<()> {
+        let value = toml::Value::try_from(value).context("Failed to serialize config value")?;
+        let (last, parents) = key.split_last().context("Empty config key")?;
+        let table = parent_table(self.document.as_table_mut(), parents)?;
+        let last = last.as_ref();
+
+        let is_table_list = matches!(&value, toml::Value::Array(items) if items.is_empty())
+            && matches!(table.get(last), Some(Item::ArrayOfTables(_)));
+        if is_table_list {
+            table.remove(last);
+            return Ok(());
+        }
+
+        match table.get_mut(last) {
+            Some(item) => update_item(item, value),
+            None => {
+                table.insert(last, new_item(value));
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove a key path; returns whether it was set
+    pub fn unset<S: AsRef<str>>(&mut self, key: &[S]) -> bool {
+        let Some((last, parents)) = key.split_last() else {
+            return false;
+        };
+        let mut table: &mut dyn TableLike = self.document.as_table_mut();
+        for segment in parents {
+            match table
+                .get_mut(segment.as_ref())
+                .and_then(Item::as_table_like_mut)
+            {
+                Some(child) => table = child,
+                None => return false,
+            }
+        }
+        table.remove(last.as_ref()).is_some()
+    }
+
+    /// Write the document back to its file
+    pub async fn save(&self) -> Result<()> {
+        if let Some(parent) = self
+            .path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)
+                .await
+                .context("Failed to create config directory")?;
+        }
+        let content = self
+            .format
+            .render(&self.document.to_string())
+            .with_context(|| format!("Failed to write config file as {}", self.format.name()))?;
+        fs::write(&self.path, content)
+            .await
+            .context("Failed to write config file")
+    }
+}
+
+/// The document as TOML, whatever the format of its file
+impl std::fmt::Display for ConfigDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.document)
+    }
+}
+
+/// Split a dotted key such as `plugins.vscode_settings.target_path`
+///
+/// Segments containing dots can be quoted: `plugins."my.plugin"`.
+pub fn parse_key(key: &str) -> Result<Vec<String>> {
+    let keys = toml_edit::Key::parse(key).with_context(|| format!("Invalid config key '{key}'"))?;
+    Ok(keys.iter().map(|key| key.get().to_string()).collect())
+}
+
+/// Parse a command-line value as TOML, falling back to a plain string
+///
+/// `true`, `30` and `["a", "b"]` keep their types; `~/snapshots` doesn't
+/// need quoting.
+pub fn parse_value(raw: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {raw}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+/// Walk to the table holding the last key, creating missing tables
+fn parent_table<'a, S: AsRef<str>>(
+    root: &'a mut Table,
+    parents: &[S],
+) -> Result<&'a mut dyn TableLike> {
+    let mut table: &mut dyn TableLike = root;
+    for segment in parents {
+        let segment = segment.as_ref();
+        let item = table.entry(segment).or_insert_with(|| {
+            let mut table = Table::new();
+            table.set_implicit(true);
+            Item::Table(table)
+        });
+        table = item
+            .as_table_like_mut()
+            .with_context(|| format!("'{segment}' is not a table"))?;
+    }
+    Ok(table)
+}
+
+/// Replace an item with a new value, keeping whatever formatting still applies
+fn update_item(item: &mut Item, value: toml::Value) {
+    match (item, value) {
+        (Item::Table(table), toml::Value::Table(values)) => {
+            let stale: Vec<String> = table
+                .iter()
+                .map(|(key, _)| key.to_string())
+                .filter(|key| !values.contains_key(key))
+                .collect();
+            for key in stale {
+                table.remove(&key);
+            }
+            for (key, value) in values {
+                match table.get_mut(&key) {
+                    Some(item) => update_item(item, value),
+                    None => {
+                        table.insert(&key, new_item(value));
+                    }
+                }
+            }
+        }
+        (Item::ArrayOfTables(tables), toml::Value::Array(values)) if all_tables(&values) => {
+            // Keep the formatting of entries that didn't change. Serialized
+            // entries spell out defaults the file may omit, so an entry
+            // matches when all of its own keys are unchanged.
+            let mut previous: Vec<Option<Table>> = tables.iter().cloned().map(Some).collect();
+            let mut updated = ArrayOfTables::new();
+            for value in values {
+                let kept = previous.iter_mut().find(|table| {
+                    table
+                        .as_ref()
+                        .is_some_and(|table| is_unchanged(table, &value))
+                });
+                match kept.and_then(Option::take) {
+                    Some(table) => updated.push(table),
+                    None => updated.push(new_table(value)),
+                }
+            }
+            *tables = updated;
+        }
+        (Item::Value(current), value) => {
+            let decor = current.decor().clone();
+            *current = new_value(value);
+            *current.decor_mut() = decor;
+        }
+        (item, value) => *item = new_item(value),
+    }
+}
+
+/// Whether every key of an existing table has the same value in `value`
+fn is_unchanged(table: &Table, value: &toml::Value) -> bool {
+    table
+        .iter()
+        .all(|(key, item)| to_toml(item).as_ref() == value.get(key))
+}
+
+fn all_tables(values: &[toml::Value]) -> bool {
+    !values.is_empty() && values.iter().all(toml::Value::is_table)
+}
+
+/// Item for a value set at a new key: tables and lists of tables get their own sections
+fn new_item(value: toml::Value) -> Item {
+    match value {
+        toml::Value::Table(_) => Item::Table(new_table(value)),
+        toml::Value::Array(values) if all_tables(&values) => {
+            let mut tables = ArrayOfTables::new();
+            for value in values {
+                tables.push(new_table(value));
+            }
+            Item::ArrayOfTables(tables)
+        }
+        value => Item::Value(new_value(value)),
+    }
+}
+
+fn new_table(value: toml::Value) -> Table {
+    let mut table = Table::new();
+    if let toml::Value::Table(values) = value {
+        for (key, value) in values {
+            table.insert(&key, new_item(value));
+        }
+    }
+    table
+}
+
+fn new_value(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(value) => Value::from(value),
+        toml::Value::Integer(value) => Value::from(value),
+        toml::Value::Float(value) => Value::from(value),
+        toml::Value::Boolean(value) => Value::from(value),
+        toml::Value::Datetime(value) => Value::from(value),
+        toml::Value::Array(values) => Value::Array(values.into_iter().map(new_value).collect()),
+        toml::Value::Table(values) => {
+            let mut table = InlineTable::new();
+            for (key, value) in values {
+                table.insert(&key, new_value(value));
+            }
+            Value::InlineTable(table)
+        }
+    }
+}
+
+/// Plain TOML value of a document item
+fn to_toml(item: &Item) -> Option<toml::Value> {
+    match item {
+        Item::None => None,
+        Item::Value(value) => Some(value_to_toml(value)),
+        Item::Table(table) => Some(toml::Value::Table(
+            table
+                .iter()
+                .filter_map(|(key, item)| Some((key.to_string(), to_toml(item)?)))
+                .collect(),
+        )),
+        Item::ArrayOfTables(tables) => Some(toml::Value::Array(
+            tables
+                .iter()
+                .filter_map(|table| to_toml(&Item::Table(table.clone())))
+                .collect(),
+        )),
+    }
+}
+
+fn value_to_toml(value: &Value) -> toml::Value {
+    match value {
+        Value::String(value) => toml::Value::String(value.value().clone()),
+        Value::Integer(value) => toml::Value::Integer(*value.value()),
+        Value::Float(value) => toml::Value::Float(*value.value()),
+        Value::Boolean(value) => toml::Value::Boolean(*value.value()),
+        Value::Datetime(value) => toml::Value::Datetime(*value.value()),
+        Value::Array(values) => toml::Value::Array(values.iter().map(value_to_toml).collect()),
+        Value::InlineTable(table) => toml::Value::Table(
+            table
+                .iter()
+                .map(|(key, value)| (key.to_string(), value_to_toml(value)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::hooks::HookAction;
+    use tempfile::TempDir;
+
+    const CONFIG: &str = r#"# Where snapshots go
+output_dir = "./snapshots" # relative to the current directory
+
+[plugins.vscode_settings]
+# Custom restore location
+restore_target_dir = "~/code"
+
+# Announce snapshots
+[[global.hooks.pre-snapshot]]
+action = "log"
+message = "starting"
+"#;
+
+    /// Test setting values in an existing config file
+    /// Verifies comments survive and new keys go into their sections
+    #[tokio::test]
+    async fn test_set_preserves_comments() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("config.toml");
+        std::fs::write(&path, CONFIG)?;
+
+        let mut document = ConfigDocument::open(&path).await?;
+        document.set(&["output_dir"], "~/snapshots")?;
+        document.set(&["plugins", "vscode_settings", "target_path"], "vscode")?;
+        document.set(&["hooks", "scripts_dir"], "~/scripts")?;
+        document.save().await?;
+
+        let content = std::fs::read_to_string(&path)?;
+        assert!(content.contains("# Where snapshots go\n"));
+        assert!(
+            content.contains("output_dir = \"~/snapshots\" # relative to the current directory")
+        );
+        assert!(content.contains(
+            "# Custom restore location\nrestore_target_dir = \"~/code\"\ntarget_path = \"vscode\""
+        ));
+        assert!(content.contains("[hooks]\nscripts_dir = \"~/scripts\""));
+        Ok(())
+    }
+
+    /// Test updating a list of hooks
+    /// Verifies unchanged entries keep their comments and an empty list removes the key
+    #[tokio::test]
+    async fn test_set_table_list() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("config.toml");
+        std::fs::write(&path, CONFIG)?;
+
+        let mut document = ConfigDocument::open(&path).await?;
+        let key = ["global", "hooks", "pre-snapshot"];
+        let mut hooks = vec![
+            HookAction::Log {
+                message: "starting".to_string(),
+                level: "info".to_string(),
+            },
+            HookAction::Log {
+                message: "second".to_string(),
+                level: "info".to_string(),
+            },
+        ];
+        document.set(&key, &hooks)?;
+        let content = document.to_string();
+        assert_eq!(content.matches("[[global.hooks.pre-snapshot]]").count(), 2);
+        assert!(content.contains(
+            "# Announce snapshots\n[[global.hooks.pre-snapshot]]\naction = \"log\"\nmessage = \"starting\"\n"
+        ));
+        assert!(content.contains("level = \"info\"\nmessage = \"second\""));
+
+        hooks.remove(0);
+        document.set(&key, &hooks)?;
+        let content = document.to_string();
+        assert!(!content.contains("starting"));
+        assert!(content.contains("message = \"second\""));
+
+        document.set(&key, &Vec::<HookAction>::new())?;
+        assert!(!document.to_string().contains("pre-snapshot"));
+        assert!(document.unset(&["plugins", "vscode_settings", "restore_target_dir"]));
+        assert!(!document.unset(&["plugins", "missing", "key"]));
+        Ok(())
+    }
+
+    /// Test parsing command-line keys and values
+    /// Verifies quoted key segments and typed values with a string fallback
+    #[test]
+    fn test_parse_key_and_value() -> Result<()> {
+        assert_eq!(
+            parse_key("plugins.\"my.plugin\".target_path")?,
+            vec!["plugins", "my.plugin", "target_path"]
+        );
+        assert!(parse_key("plugins..x").is_err());
+
+        assert_eq!(parse_value("true"), toml::Value::Boolean(true));
+        assert_eq!(parse_value("30"), toml::Value::Integer(30));
+        assert_eq!(
+            parse_value("[\"static\"]"),
+            toml::Value::Array(vec![toml::Value::String("static".to_string())])
+        );
+        assert_eq!(
+            parse_value("~/snapshots"),
+            toml::Value::String("~/snapshots".to_string())
+        );
+        Ok(())
+    }
+
+    /// Test editing a YAML config file
+    /// Verifies the file is written back as YAML with its other values intact
+    #[tokio::test]
+    async fn test_edit_yaml_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("config.yaml");
+        std::fs::write(
+            &path,
+            "output_dir: ~/snapshots\nplugins:\n  static:\n    files: [~/.zshrc]\n",
+        )?;
+
+        let mut document = ConfigDocument::open(&path).await?;
+        document.set(&["plugins", "static", "hardlink"], &true)?;
+        document.save().await?;
+
+        let content = std::fs::read_to_string(&path)?;
+        let value: serde_json::Value = serde_yaml::from_str(&content)?;
+        assert_eq!(value["output_dir"], "~/snapshots");
+        assert_eq!(value["plugins"]["static"]["files"][0], "~/.zshrc");
+        assert_eq!(value["plugins"]["static"]["hardlink"], true);
+        Ok(())
+    }
+}
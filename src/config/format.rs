@@ -0,0 +1,174 @@
+//! Config file formats
+//!
+//! Config files can be written in TOML, YAML or JSON; the format is chosen by
+//! the file extension, and files without a known extension are TOML. All
+//! formats are read into the same TOML table for merging, so the layer rules
+//! and validation apply to each of them. `null` values in YAML and JSON mean
+//! the key is not set.
+//!
+//! Plugin sections are handed to plugins as [`PluginConfigValue`]s, which
+//! don't depend on the format they were read from.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use super::PluginConfigValue;
+
+/// Extensions of config files, in the order they are looked for
+pub const EXTENSIONS: &[&str] = &["toml", "yaml", "yml", "json"];
+
+/// Format of a config file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Format of a file by its extension
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("yaml" | "yml") => Self::Yaml,
+            Some("json") => Self::Json,
+            _ => Self::Toml,
+        }
+    }
+
+    /// Name shown in messages
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Toml => "TOML",
+            Self::Yaml => "YAML",
+            Self::Json => "JSON",
+        }
+    }
+
+    /// Parse file content into a table
+    pub fn parse(self, content: &str) -> Result<toml::Table> {
+        let value: serde_json::Value = match self {
+            Self::Toml => return Ok(toml::from_str(content)?),
+            Self::Yaml if content.trim().is_empty() => return Ok(toml::Table::new()),
+            Self::Yaml => serde_yaml::from_str(content)?,
+            Self::Json => serde_json::from_str(content)?,
+        };
+        match to_toml(strip_nulls(value))? {
+            toml::Value::Table(table) => Ok(table),
+            _ => Err(anyhow::anyhow!(
+                "expected a table of settings at the top level"
+            )),
+        }
+    }
+
+    /// Convert file content to TOML, unchanged for TOML files
+    pub fn to_toml_string(self, content: &str) -> Result<String> {
+        match self {
+            Self::Toml => Ok(content.to_string()),
+            _ => Ok(toml::to_string(&self.parse(content)?)?),
+        }
+    }
+
+    /// Write TOML content in this format, unchanged for TOML files
+    pub fn render(self, content: &str) -> Result<String> {
+        let value = || -> Result<serde_json::Value> {
+            Ok(serde_json::to_value(toml::from_str::<toml::Table>(
+                content,
+            )?)?)
+        };
+        Ok(match self {
+            Self::Toml => content.to_string(),
+            Self::Yaml => serde_yaml::to_string(&value()?)?,
+            Self::Json => format!("{}\n", serde_json::to_string_pretty(&value()?)?),
+        })
+    }
+}
+
+/// Config file candidates for a path without extension, one per format
+pub fn candidates(base: &Path) -> Vec<PathBuf> {
+    EXTENSIONS
+        .iter()
+        .map(|extension| base.with_extension(extension))
+        .collect()
+}
+
+/// Serialize a plugin's config for [`super::PluginsConfig`]
+///
+/// Unset optional fields are left out rather than stored as `null`.
+pub fn to_plugin_value<T: Serialize + ?Sized>(value: &T) -> Result<PluginConfigValue> {
+    Ok(strip_nulls(serde_json::to_value(value)?))
+}
+
+/// Remove `null` values, which no config key accepts, from tables
+fn strip_nulls(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, strip_nulls(value)))
+                .collect(),
+        ),
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.into_iter().map(strip_nulls).collect())
+        }
+        value => value,
+    }
+}
+
+fn to_toml(value: serde_json::Value) -> Result<toml::Value> {
+    toml::Value::try_from(value).context("value can't be represented in the configuration")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test parsing each config format
+    /// Verifies YAML and JSON read into the same table as TOML, with nulls dropped
+    #[test]
+    fn test_parse_formats() -> Result<()> {
+        let toml = ConfigFormat::Toml
+            .parse("output_dir = \"~/snapshots\"\n[plugins.static]\nfiles = [\"~/.zshrc\"]\n")?;
+        let yaml = ConfigFormat::Yaml.parse(
+            "output_dir: ~/snapshots\nlogging: null\nplugins:\n  static:\n    files: [\"~/.zshrc\"]\n",
+        )?;
+        let json = ConfigFormat::Json.parse(
+            r#"{"output_dir": "~/snapshots", "plugins": {"static": {"files": ["~/.zshrc"]}}}"#,
+        )?;
+        assert_eq!(yaml, toml);
+        assert_eq!(json, toml);
+        assert!(ConfigFormat::Json.parse("[1, 2]").is_err());
+        assert!(ConfigFormat::Yaml.parse("")?.is_empty());
+        Ok(())
+    }
+
+    /// Test detecting the format and converting back
+    /// Verifies extensions map to formats and TOML content converts to each format
+    #[test]
+    fn test_format_round_trip() -> Result<()> {
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("config.yml")),
+            ConfigFormat::Yaml
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("config.JSON")),
+            ConfigFormat::Json
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("config")),
+            ConfigFormat::Toml
+        );
+
+        let toml = "output_dir = \"/snapshots\"\n";
+        for format in [ConfigFormat::Yaml, ConfigFormat::Json] {
+            let content = format.render(toml)?;
+            assert_eq!(format.to_toml_string(&content)?, toml);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,946 @@
+//! Layered configuration loading
+//!
+//! The effective configuration is merged from, lowest precedence first:
+//! the system config, the user config, the project config in the current
+//! directory, `DOTSNAPSHOT_*` environment variables and command-line flags.
+//! An explicit `--config` file replaces the three file layers. The
+//! `[hosts.<hostname>]` section of this machine and the selected profile are
+//! applied on top of the merged files, below environment variables and flags.
+//!
+//! Merge rules between layers: tables merge key by key, scalars replace, and
+//! lists replace except the accumulating ones in [`APPEND_LISTS`] (static
+//! `files`/`ignore` and hook lists), which are appended without duplicates.
+//! Host and profile overlays always replace lists, so they can drop entries.
+//!
+//! A config file can `include` other files, which are loaded recursively and
+//! merged just below it with the same rules: included files in the order
+//! listed, glob matches in sorted order, and the including file last so its
+//! own values win. A file is loaded only once even if included several
+//! times, and an include cycle is an error. The `local_config` file, if it
+//! exists, is included after the listed files.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::warn;
+
+use super::format::{candidates, ConfigFormat};
+use super::Config;
+use crate::core::paths::expand_path;
+use crate::core::policy::FailurePolicy;
+use crate::core::snapshot::hostname_matches;
+
+/// Lists that accumulate across config layers instead of being replaced
+pub const APPEND_LISTS: &[&str] = &[
+    "files",
+    "ignore",
+    "pre-snapshot",
+    "post-snapshot",
+    "pre-plugin",
+    "post-plugin",
+];
+
+/// Keys a profile or host section may override
+pub const OVERLAY_KEYS: &[&str] = &["output_dir", "include_plugins", "plugins", "global"];
+
+/// Environment variables mapped onto config keys
+pub const ENV_VARS: &[(&str, &str)] = &[
+    ("DOTSNAPSHOT_OUTPUT_DIR", "output_dir"),
+    ("DOTSNAPSHOT_INCLUDE_PLUGINS", "include_plugins"),
+    ("DOTSNAPSHOT_VERBOSE", "logging.verbose"),
+    ("DOTSNAPSHOT_TIME_FORMAT", "logging.time_format"),
+    ("DOTSNAPSHOT_FAIL_ON", "snapshot.fail_on"),
+    ("DOTSNAPSHOT_SKIP_UNCHANGED", "snapshot.skip_unchanged"),
+    ("DOTSNAPSHOT_SCRIPTS_DIR", "hooks.scripts_dir"),
+];
+
+/// Where a configuration value came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// System-wide config file
+    System(PathBuf),
+    /// Config file in the user's config directory
+    User(PathBuf),
+    /// Config file in the current directory
+    Project(PathBuf),
+    /// File given with `--config`
+    File(PathBuf),
+    /// File included by another config file
+    Include(PathBuf),
+    /// `[hosts.<hostname>]` section
+    Host(String),
+    /// `[profiles.<name>]` section
+    Profile(String),
+    /// Environment variable
+    Env(String),
+    /// Command-line flag
+    Cli(String),
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::System(path) => write!(f, "system: {}", path.display()),
+            ConfigSource::User(path) => write!(f, "user: {}", path.display()),
+            ConfigSource::Project(path) => write!(f, "project: {}", path.display()),
+            ConfigSource::File(path) => write!(f, "file: {}", path.display()),
+            ConfigSource::Include(path) => write!(f, "include: {}", path.display()),
+            ConfigSource::Host(name) => write!(f, "host: [hosts.{name}]"),
+            ConfigSource::Profile(name) => write!(f, "profile: [profiles.{name}]"),
+            ConfigSource::Env(var) => write!(f, "env: {var}"),
+            ConfigSource::Cli(flag) => write!(f, "cli: {flag}"),
+        }
+    }
+}
+
+impl ConfigSource {
+    /// The config file of a file layer
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            ConfigSource::System(path)
+            | ConfigSource::User(path)
+            | ConfigSource::Project(path)
+            | ConfigSource::File(path)
+            | ConfigSource::Include(path) => Some(path),
+            _ => None,
+        }
+    }
+}
+
+/// One source of configuration values
+#[derive(Debug, Clone)]
+pub struct ConfigLayer {
+    pub source: ConfigSource,
+    pub table: toml::Table,
+}
+
+impl ConfigLayer {
+    /// Read a config file as a layer, in the format of its extension
+    pub fn from_file(source: ConfigSource, path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        super::migrate::warn_if_outdated(path, &content);
+        let format = ConfigFormat::from_path(path);
+        let table = format.parse(&content).with_context(|| {
+            format!(
+                "Failed to parse config file as {}: {}",
+                format.name(),
+                path.display()
+            )
+        })?;
+        Ok(Self { source, table })
+    }
+
+    /// Build a layer setting a dotted key to a value
+    pub fn with_value(mut self, key: &str, value: toml::Value) -> Self {
+        let mut segments: Vec<&str> = key.split('.').collect();
+        let last = segments.pop().unwrap_or_default();
+        let mut table = &mut self.table;
+        for segment in segments {
+            table = table
+                .entry(segment)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                .as_table_mut()
+                .expect("config layer keys don't overlap");
+        }
+        table.insert(last.to_string(), value);
+        self
+    }
+}
+
+/// What to load: the `--config` file, profile and command-line overrides
+#[derive(Debug, Clone, Default)]
+pub struct LoadOptions {
+    /// Explicit config file replacing the system, user and project files
+    pub config_path: Option<PathBuf>,
+    /// Profile to apply
+    pub profile: Option<String>,
+    /// Hostname selecting the `[hosts.<hostname>]` section
+    pub hostname: Option<String>,
+    /// Command-line flags, one layer per flag
+    pub cli: Vec<ConfigLayer>,
+}
+
+/// Merged configuration table that remembers where each value came from
+#[derive(Debug, Clone, Default)]
+pub struct LayeredConfig {
+    /// Merged TOML table
+    pub table: toml::Table,
+    /// Sources of each leaf value by dotted key; appended lists have several
+    pub origins: BTreeMap<String, Vec<ConfigSource>>,
+    /// Sources that were found and merged, lowest precedence first
+    pub sources: Vec<ConfigSource>,
+    /// Profile that was applied
+    pub active_profile: Option<String>,
+}
+
+impl LayeredConfig {
+    /// Load and merge every layer
+    pub fn load(options: &LoadOptions) -> Result<Self> {
+        let mut layered = Self::default();
+        for layer in file_layers(options.config_path.as_deref())? {
+            layered.merge(layer, true);
+        }
+        if let Some(hostname) = &options.hostname {
+            layered.apply_host(hostname)?;
+        }
+        if let Some(profile) = &options.profile {
+            layered.apply_profile(profile)?;
+        }
+        for layer in env_layers(std::env::vars())? {
+            layered.merge(layer, false);
+        }
+        for layer in options.cli.iter().cloned() {
+            layered.merge(layer, false);
+        }
+        Ok(layered)
+    }
+
+    /// Deserialize the merged table
+    pub fn to_config(&self) -> Result<Config> {
+        let mut config: Config = toml::Value::Table(self.table.clone())
+            .try_into()
+            .context("Invalid configuration")?;
+        config.active_profile = self.active_profile.clone();
+        Ok(config)
+    }
+
+    /// Merge a layer on top; `append` enables accumulating [`APPEND_LISTS`]
+    pub fn merge(&mut self, layer: ConfigLayer, append: bool) {
+        merge_table(
+            &mut self.table,
+            layer.table,
+            "",
+            &layer.source,
+            append,
+            &mut self.origins,
+        );
+        self.sources.push(layer.source);
+    }
+
+    /// Overlay the `[profiles.<name>]` section
+    pub fn apply_profile(&mut self, name: &str) -> Result<()> {
+        let profiles = self.overlays("profiles");
+        let Some(overlay) = profiles.get(name) else {
+            let mut available: Vec<_> = profiles.keys().map(String::as_str).collect();
+            available.sort_unstable();
+            return Err(anyhow::anyhow!(
+                "Profile '{}' not found in config (available: {})",
+                name,
+                if available.is_empty() {
+                    "none".to_string()
+                } else {
+                    available.join(", ")
+                }
+            ));
+        };
+
+        self.apply_overlay(ConfigSource::Profile(name.to_string()), overlay)?;
+        self.active_profile = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Overlay the `[hosts.<hostname>]` section matching the hostname, if any
+    ///
+    /// A section naming the full hostname wins over one naming its short
+    /// name. Only one section applies; a warning lists the others that
+    /// matched. Returns whether a section matched.
+    pub fn apply_host(&mut self, hostname: &str) -> Result<bool> {
+        let hosts = self.overlays("hosts");
+        let mut matching: Vec<(&String, &toml::Table)> = hosts
+            .iter()
+            .filter(|(name, _)| hostname_matches(hostname, name))
+            .collect();
+        // Stable sort keeps alphabetical order among equally good matches
+        matching.sort_by_key(|(name, _)| !hostname.eq_ignore_ascii_case(name));
+        let Some(&(name, overlay)) = matching.first() else {
+            return Ok(false);
+        };
+
+        if matching.len() > 1 {
+            let ignored: Vec<String> = matching[1..]
+                .iter()
+                .map(|(name, _)| format!("[hosts.{name}]"))
+                .collect();
+            warn!(
+                "Several host sections match '{}', applying [hosts.{}] and ignoring {}",
+                hostname,
+                name,
+                ignored.join(", ")
+            );
+        }
+        self.apply_overlay(ConfigSource::Host(name.clone()), overlay)?;
+        Ok(true)
+    }
+
+    /// Leaf values of the merged table with their sources, by dotted key
+    pub fn values_with_origins(&self) -> Vec<(String, toml::Value, Vec<ConfigSource>)> {
+        let mut values = Vec::new();
+        collect_leaves(&self.table, "", &mut |key, value| {
+            let origins = self.origins.get(&key).cloned().unwrap_or_default();
+            values.push((key, value.clone(), origins));
+        });
+        values
+    }
+
+    fn overlays(&self, section: &str) -> BTreeMap<String, toml::Table> {
+        self.table
+            .get(section)
+            .and_then(toml::Value::as_table)
+            .map(|overlays| {
+                overlays
+                    .iter()
+                    .filter_map(|(name, overlay)| Some((name.clone(), overlay.as_table()?.clone())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn apply_overlay(&mut self, source: ConfigSource, overlay: &toml::Table) -> Result<()> {
+        let name = match &source {
+            ConfigSource::Host(name) => format!("Host '{name}'"),
+            ConfigSource::Profile(name) => format!("Profile '{name}'"),
+            other => other.to_string(),
+        };
+        if let Some(key) = overlay
+            .keys()
+            .find(|key| !OVERLAY_KEYS.contains(&key.as_str()))
+        {
+            return Err(anyhow::anyhow!(
+                "{} cannot override '{}' (allowed: {})",
+                name,
+                key,
+                OVERLAY_KEYS.join(", ")
+            ));
+        }
+
+        let mut merged = self.clone();
+        merged.merge(
+            ConfigLayer {
+                source,
+                table: overlay.clone(),
+            },
+            false,
+        );
+        merged
+            .to_config()
+            .with_context(|| format!("Invalid settings in {}", name.to_lowercase()))?;
+        *self = merged;
+        Ok(())
+    }
+}
+
+/// System, user and project config files, or the explicit `--config` file,
+/// each preceded by the files it includes
+pub fn file_layers(config_path: Option<&Path>) -> Result<Vec<ConfigLayer>> {
+    let mut includes = Includes::default();
+    for (source, path) in config_files(config_path) {
+        includes.load(source, &path)?;
+    }
+    Ok(includes.layers)
+}
+
+/// State of resolving `include` directives
+#[derive(Default)]
+struct Includes {
+    /// Loaded layers in merge order
+    layers: Vec<ConfigLayer>,
+    /// Canonical paths of every file loaded so far
+    seen: Vec<PathBuf>,
+    /// Canonical paths of the files currently being loaded, outermost first
+    stack: Vec<PathBuf>,
+}
+
+impl Includes {
+    /// Load a config file after the files it includes
+    fn load(&mut self, source: ConfigSource, path: &Path) -> Result<()> {
+        let canonical = path
+            .canonicalize()
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        if let Some(start) = self.stack.iter().position(|file| *file == canonical) {
+            let chain: Vec<String> = self.stack[start..]
+                .iter()
+                .chain([&canonical])
+                .map(|file| file.display().to_string())
+                .collect();
+            return Err(anyhow::anyhow!(
+                "Config include cycle: {}",
+                chain.join(" -> ")
+            ));
+        }
+        if self.seen.contains(&canonical) {
+            return Ok(());
+        }
+        self.seen.push(canonical.clone());
+
+        let mut layer = ConfigLayer::from_file(source, path)?;
+        let dir = canonical.parent().unwrap_or(Path::new("/")).to_path_buf();
+        let mut files = Vec::new();
+        match layer.table.remove("include") {
+            Some(toml::Value::Array(entries)) => {
+                for entry in entries {
+                    let Some(entry) = entry.as_str() else {
+                        return Err(anyhow::anyhow!(
+                            "Invalid include in {}: expected a path, found {}",
+                            path.display(),
+                            entry
+                        ));
+                    };
+                    files.extend(
+                        resolve_include(entry, &dir)
+                            .with_context(|| format!("Invalid include in {}", path.display()))?,
+                    );
+                }
+            }
+            Some(other) => {
+                return Err(anyhow::anyhow!(
+                    "Invalid include in {}: expected a list of paths, found {}",
+                    path.display(),
+                    other
+                ));
+            }
+            None => {}
+        }
+        if let Some(toml::Value::String(local)) = layer.table.get_mut("local_config") {
+            let local_path = dir.join(
+                expand_path(local)
+                    .with_context(|| format!("Invalid local_config in {}", path.display()))?,
+            );
+            *local = local_path.to_string_lossy().into_owned();
+            if local_path.is_file() {
+                files.push(local_path);
+            }
+        }
+
+        self.stack.push(canonical);
+        for file in files {
+            self.load(ConfigSource::Include(file.clone()), &file)?;
+        }
+        self.stack.pop();
+        self.layers.push(layer);
+        Ok(())
+    }
+}
+
+/// Files an `include` entry refers to, relative to the including file's directory
+///
+/// Entries with glob characters may match nothing; other entries must exist.
+fn resolve_include(entry: &str, dir: &Path) -> Result<Vec<PathBuf>> {
+    let path = dir.join(expand_path(entry)?);
+    if !entry.contains(['*', '?', '[']) {
+        if !path.is_file() {
+            return Err(anyhow::anyhow!(
+                "Included config file not found: {}",
+                path.display()
+            ));
+        }
+        return Ok(vec![path]);
+    }
+
+    let pattern = path.to_string_lossy();
+    let mut paths: Vec<PathBuf> = glob::glob(&pattern)
+        .with_context(|| format!("Invalid include pattern: {entry}"))?
+        .filter_map(Result::ok)
+        .filter(|path| path.is_file())
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Existing config files in merge order, with the layer each one forms
+pub fn config_files(config_path: Option<&Path>) -> Vec<(ConfigSource, PathBuf)> {
+    if let Some(path) = config_path {
+        if !path.exists() {
+            return Vec::new();
+        }
+        return vec![(ConfigSource::File(path.to_path_buf()), path.to_path_buf())];
+    }
+
+    let user = first_existing(user_config_paths());
+    let project = first_existing(project_config_paths())
+        // The project file may be the user file when run from the home directory
+        .filter(|project| !user.as_ref().is_some_and(|user| same_file(user, project)));
+
+    let mut files = Vec::new();
+    let system = system_config_path().and_then(|path| first_existing(candidates(&path)));
+    if let Some(path) = system {
+        files.push((ConfigSource::System(path.clone()), path));
+    }
+    if let Some(path) = user {
+        files.push((ConfigSource::User(path.clone()), path));
+    }
+    if let Some(path) = project {
+        files.push((ConfigSource::Project(path.clone()), path));
+    }
+    files
+}
+
+/// System-wide config file; other extensions than `.toml` are looked for too
+pub fn system_config_path() -> Option<PathBuf> {
+    if cfg!(windows) {
+        std::env::var_os("PROGRAMDATA")
+            .map(|dir| PathBuf::from(dir).join("dotsnapshot").join("config.toml"))
+    } else {
+        Some(PathBuf::from("/etc/dotsnapshot/config.toml"))
+    }
+}
+
+/// User config file candidates; the first existing one is used
+///
+/// Each location is looked for with every extension in
+/// [`super::format::EXTENSIONS`].
+pub fn user_config_paths() -> Vec<PathBuf> {
+    let mut bases = Vec::new();
+    if let Some(config_dir) = dirs::config_dir() {
+        bases.push(config_dir.join("dotsnapshot").join("config"));
+        bases.push(config_dir.join("dotsnapshot"));
+    }
+    if let Some(home_dir) = dirs::home_dir() {
+        bases.push(home_dir.join(".config").join("dotsnapshot").join("config"));
+        bases.push(home_dir.join(".dotsnapshot"));
+    }
+    bases.iter().flat_map(|base| candidates(base)).collect()
+}
+
+/// Project config file candidates in the current directory
+pub fn project_config_paths() -> Vec<PathBuf> {
+    [PathBuf::from("dotsnapshot"), PathBuf::from(".dotsnapshot")]
+        .iter()
+        .flat_map(|base| candidates(base))
+        .collect()
+}
+
+/// Layers for the `DOTSNAPSHOT_*` variables in [`ENV_VARS`] that are set
+pub fn env_layers(vars: impl IntoIterator<Item = (String, String)>) -> Result<Vec<ConfigLayer>> {
+    let vars: BTreeMap<String, String> = vars.into_iter().collect();
+    let mut layers = Vec::new();
+    for (var, key) in ENV_VARS {
+        let Some(raw) = vars.get(*var) else {
+            continue;
+        };
+        let value =
+            env_value(key, raw).with_context(|| format!("Invalid value for {var}: '{raw}'"))?;
+        layers.push(
+            ConfigLayer {
+                source: ConfigSource::Env(var.to_string()),
+                table: toml::Table::new(),
+            }
+            .with_value(key, value),
+        );
+    }
+    Ok(layers)
+}
+
+/// Convert an environment variable to the type of its config key
+fn env_value(key: &str, raw: &str) -> Result<toml::Value> {
+    Ok(match key {
+        "include_plugins" => toml::Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|plugin| !plugin.is_empty())
+                .map(|plugin| toml::Value::String(plugin.to_string()))
+                .collect(),
+        ),
+        "logging.verbose" | "snapshot.skip_unchanged" => {
+            toml::Value::Boolean(match raw.trim().to_lowercase().as_str() {
+                "1" | "true" | "yes" | "on" => true,
+                "0" | "false" | "no" | "off" | "" => false,
+                _ => return Err(anyhow::anyhow!("expected true or false")),
+            })
+        }
+        "snapshot.fail_on" => {
+            toml::Value::try_from(FailurePolicy::from_str(raw).map_err(anyhow::Error::msg)?)?
+        }
+        _ => toml::Value::String(raw.to_string()),
+    })
+}
+
+fn first_existing(paths: Vec<PathBuf>) -> Option<PathBuf> {
+    paths.into_iter().find(|path| path.is_file())
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    matches!((a.canonicalize(), b.canonicalize()), (Ok(a), Ok(b)) if a == b)
+}
+
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+/// Merge `layer` into `base`, recording the source of every value it sets
+fn merge_table(
+    base: &mut toml::Table,
+    layer: toml::Table,
+    prefix: &str,
+    source: &ConfigSource,
+    append: bool,
+    origins: &mut BTreeMap<String, Vec<ConfigSource>>,
+) {
+    for (key, value) in layer {
+        let path = join_key(prefix, &key);
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(layer_table)) => {
+                merge_table(base_table, layer_table, &path, source, append, origins);
+            }
+            (Some(toml::Value::Array(base_list)), toml::Value::Array(layer_list))
+                if append && APPEND_LISTS.contains(&key.as_str()) =>
+            {
+                for item in layer_list {
+                    if !base_list.contains(&item) {
+                        base_list.push(item);
+                    }
+                }
+                origins.entry(path).or_default().push(source.clone());
+            }
+            (_, value) => {
+                let nested = format!("{path}.");
+                origins.retain(|key, _| *key != path && !key.starts_with(&nested));
+                collect_leaves_of(&value, &path, &mut |leaf, _| {
+                    origins.insert(leaf, vec![source.clone()]);
+                });
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Visit every non-table value with its dotted key
+fn collect_leaves(table: &toml::Table, prefix: &str, visit: &mut impl FnMut(String, &toml::Value)) {
+    for (key, value) in table {
+        collect_leaves_of(value, &join_key(prefix, key), visit);
+    }
+}
+
+fn collect_leaves_of(
+    value: &toml::Value,
+    path: &str,
+    visit: &mut impl FnMut(String, &toml::Value),
+) {
+    match value {
+        toml::Value::Table(table) => collect_leaves(table, path, visit),
+        value => visit(path.to_string(), value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(source: ConfigSource, content: &str) -> ConfigLayer {
+        ConfigLayer {
+            source,
+            table: toml::from_str(content).unwrap(),
+        }
+    }
+
+    fn user() -> ConfigSource {
+        ConfigSource::User(PathBuf::from("/home/me/.config/dotsnapshot/config.toml"))
+    }
+
+    fn project() -> ConfigSource {
+        ConfigSource::Project(PathBuf::from("dotsnapshot.toml"))
+    }
+
+    /// Test merging config layers
+    /// Verifies scalars and ordinary lists replace while static files and hooks append
+    #[test]
+    fn test_merge_layers() -> Result<()> {
+        let mut layered = LayeredConfig::default();
+        layered.merge(
+            layer(
+                user(),
+                r#"
+                output_dir = "~/snapshots"
+                include_plugins = ["vscode", "static"]
+
+                [plugins.static]
+                files = ["~/.gitconfig", "~/.zshrc"]
+                hardlink = true
+
+                [[global.hooks.post-snapshot]]
+                action = "log"
+                message = "user"
+                "#,
+            ),
+            true,
+        );
+        layered.merge(
+            layer(
+                project(),
+                r#"
+                include_plugins = ["static"]
+
+                [plugins.static]
+                files = ["~/.zshrc", "./.editorconfig"]
+
+                [[global.hooks.post-snapshot]]
+                action = "log"
+                message = "project"
+                "#,
+            ),
+            true,
+        );
+
+        let config = layered.to_config()?;
+        assert_eq!(
+            config.get_include_plugins(),
+            Some(vec!["static".to_string()])
+        );
+        assert_eq!(config.get_global_post_snapshot_hooks().len(), 2);
+        let files = layered.table["plugins"]["static"]["files"]
+            .as_array()
+            .unwrap();
+        assert_eq!(files.len(), 3);
+
+        assert_eq!(layered.origins["output_dir"], vec![user()]);
+        assert_eq!(layered.origins["include_plugins"], vec![project()]);
+        assert_eq!(
+            layered.origins["plugins.static.files"],
+            vec![user(), project()]
+        );
+        assert_eq!(layered.origins["plugins.static.hardlink"], vec![user()]);
+        Ok(())
+    }
+
+    /// Test choosing between host sections matching the same machine
+    /// Verifies the full hostname wins over the short name, whatever the case
+    #[test]
+    fn test_host_precedence() -> Result<()> {
+        let mut base = LayeredConfig::default();
+        base.merge(
+            layer(
+                user(),
+                r#"
+                [hosts.laptop]
+                output_dir = "/short"
+
+                [hosts."Laptop.Example.com"]
+                output_dir = "/full"
+                "#,
+            ),
+            true,
+        );
+
+        let mut layered = base.clone();
+        assert!(layered.apply_host("laptop.example.com")?);
+        assert_eq!(
+            layered.to_config()?.get_output_dir()?,
+            PathBuf::from("/full")
+        );
+        assert_eq!(
+            layered.origins["output_dir"],
+            vec![ConfigSource::Host("Laptop.Example.com".to_string())]
+        );
+
+        let mut layered = base.clone();
+        assert!(layered.apply_host("laptop.local")?);
+        assert_eq!(
+            layered.to_config()?.get_output_dir()?,
+            PathBuf::from("/short")
+        );
+        Ok(())
+    }
+
+    /// Test host and profile overlays on a layered config
+    /// Verifies overlays replace lists and are recorded as origins
+    #[test]
+    fn test_overlay_origins() -> Result<()> {
+        let mut layered = LayeredConfig::default();
+        layered.merge(
+            layer(
+                user(),
+                r#"
+                [plugins.static]
+                files = ["~/.gitconfig"]
+
+                [hosts.laptop.plugins.static]
+                hardlink = true
+
+                [profiles.work.plugins.static]
+                files = ["~/.ssh/config"]
+                "#,
+            ),
+            true,
+        );
+
+        assert!(layered.apply_host("laptop.local")?);
+        layered.apply_profile("work")?;
+
+        let files = layered.table["plugins"]["static"]["files"]
+            .as_array()
+            .unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            layered.origins["plugins.static.files"],
+            vec![ConfigSource::Profile("work".to_string())]
+        );
+        assert_eq!(
+            layered.origins["plugins.static.hardlink"],
+            vec![ConfigSource::Host("laptop".to_string())]
+        );
+        assert_eq!(layered.to_config()?.active_profile.as_deref(), Some("work"));
+        Ok(())
+    }
+
+    /// Test reading DOTSNAPSHOT_* environment variables
+    /// Verifies values are converted to the config key's type and invalid ones fail
+    #[test]
+    fn test_env_layers() -> Result<()> {
+        let vars = [
+            ("DOTSNAPSHOT_OUTPUT_DIR", "/env/snapshots"),
+            ("DOTSNAPSHOT_INCLUDE_PLUGINS", "static, vscode"),
+            ("DOTSNAPSHOT_SKIP_UNCHANGED", "yes"),
+            ("DOTSNAPSHOT_FAIL_ON", "any-failure"),
+            ("DOTSNAPSHOT_SNAPSHOT_DIR", "/ignored"),
+        ]
+        .map(|(var, value)| (var.to_string(), value.to_string()));
+
+        let mut layered = LayeredConfig::default();
+        for layer in env_layers(vars)? {
+            layered.merge(layer, false);
+        }
+        let config = layered.to_config()?;
+        assert_eq!(
+            config.get_output_dir().unwrap(),
+            PathBuf::from("/env/snapshots")
+        );
+        assert_eq!(
+            config.get_include_plugins(),
+            Some(vec!["static".to_string(), "vscode".to_string()])
+        );
+        assert!(config.is_skip_unchanged());
+        assert_eq!(config.get_fail_on(), FailurePolicy::AnyFailure);
+        assert_eq!(
+            layered.origins["snapshot.skip_unchanged"],
+            vec![ConfigSource::Env("DOTSNAPSHOT_SKIP_UNCHANGED".to_string())]
+        );
+        assert_eq!(layered.sources.len(), 4);
+
+        let invalid = [("DOTSNAPSHOT_VERBOSE".to_string(), "maybe".to_string())];
+        assert!(env_layers(invalid).is_err());
+        Ok(())
+    }
+
+    /// Test loading an explicit config file with command-line overrides
+    /// Verifies the file replaces the standard locations and flags win
+    #[test]
+    fn test_load_explicit_file() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let config_path = temp_dir.path().join("config.toml");
+        std::fs::write(&config_path, "output_dir = \"/file\"\n")?;
+
+        let options = LoadOptions {
+            config_path: Some(config_path.clone()),
+            cli: vec![ConfigLayer {
+                source: ConfigSource::Cli("--output".to_string()),
+                table: toml::Table::new(),
+            }
+            .with_value("output_dir", toml::Value::String("/cli".to_string()))],
+            ..LoadOptions::default()
+        };
+        let layered = LayeredConfig::load(&options)?;
+        assert_eq!(layered.sources[0], ConfigSource::File(config_path));
+        assert_eq!(
+            layered.to_config()?.get_output_dir()?,
+            PathBuf::from("/cli")
+        );
+
+        let missing = LoadOptions {
+            config_path: Some(temp_dir.path().join("missing.toml")),
+            ..LoadOptions::default()
+        };
+        assert!(file_layers(missing.config_path.as_deref())?.is_empty());
+        Ok(())
+    }
+
+    /// Test resolving include directives
+    /// Verifies includes and glob matches merge below the including file in order
+    #[test]
+    fn test_includes() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let dir = temp_dir.path();
+        std::fs::create_dir(dir.join("conf.d"))?;
+        std::fs::write(
+            dir.join("base.toml"),
+            "output_dir = \"/base\"\n[plugins.static]\nfiles = [\"~/.base\"]\n",
+        )?;
+        std::fs::write(
+            dir.join("conf.d/10-a.toml"),
+            "output_dir = \"/a\"\n[plugins.static]\nfiles = [\"~/.a\"]\n",
+        )?;
+        std::fs::write(dir.join("conf.d/20-b.toml"), "output_dir = \"/b\"\n")?;
+        std::fs::write(dir.join("local.toml"), "include_plugins = [\"static\"]\n")?;
+        let config_path = dir.join("config.toml");
+        std::fs::write(
+            &config_path,
+            r#"
+            include = ["base.toml", "conf.d/*.toml", "empty.d/*.toml"]
+            local_config = "local.toml"
+
+            [plugins.static]
+            files = ["~/.main"]
+            "#,
+        )?;
+
+        let layers = file_layers(Some(&config_path))?;
+        let names: Vec<String> = layers
+            .iter()
+            .map(|layer| {
+                let path = layer.source.path().unwrap();
+                path.file_name().unwrap().to_string_lossy().into_owned()
+            })
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "base.toml",
+                "10-a.toml",
+                "20-b.toml",
+                "local.toml",
+                "config.toml"
+            ]
+        );
+        assert!(matches!(layers[0].source, ConfigSource::Include(_)));
+
+        let layered = LayeredConfig::load(&LoadOptions {
+            config_path: Some(config_path),
+            ..LoadOptions::default()
+        })?;
+        let config = layered.to_config()?;
+        assert_eq!(config.get_output_dir()?, PathBuf::from("/b"));
+        assert_eq!(config.include_plugins, Some(vec!["static".to_string()]));
+        assert_eq!(config.include, None);
+        assert_eq!(
+            layered.table["plugins"]["static"]["files"],
+            toml::Value::try_from(["~/.base", "~/.a", "~/.main"])?
+        );
+        Ok(())
+    }
+
+    /// Test include errors
+    /// Verifies cycles show the chain and missing files name the including file
+    #[test]
+    fn test_include_errors() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let dir = temp_dir.path();
+        std::fs::write(dir.join("a.toml"), "include = [\"b.toml\"]\n")?;
+        std::fs::write(dir.join("b.toml"), "include = [\"a.toml\"]\n")?;
+        let error = format!("{:#}", file_layers(Some(&dir.join("a.toml"))).unwrap_err());
+        assert!(error.contains("Config include cycle"));
+        assert!(error.contains("a.toml -> "));
+        assert!(error.contains("b.toml -> "));
+
+        std::fs::write(dir.join("c.toml"), "include = [\"missing.toml\"]\n")?;
+        let error = format!("{:#}", file_layers(Some(&dir.join("c.toml"))).unwrap_err());
+        assert!(error.contains("Invalid include in"));
+        assert!(error.contains("c.toml"));
+        assert!(error.contains("missing.toml"));
+        Ok(())
+    }
+}
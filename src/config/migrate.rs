@@ -0,0 +1,313 @@
+//! Upgrades of config files written for older formats
+//!
+//! Config files carry a format `version`; files without one are version 1.
+//! [`migrate`] applies the steps in [`MIGRATIONS`] from the file's version up
+//! to [`CONFIG_VERSION`], editing the TOML document in place so comments and
+//! formatting survive.
+//!
+//! Snapshots don't need migrating: their metadata is still read from both
+//! `.snapshot/checksum.json` and the older `metadata.json`.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use toml_edit::{DocumentMut, Item, Table};
+use tracing::warn;
+
+use super::format::ConfigFormat;
+
+/// Current config format version
+pub const CONFIG_VERSION: u32 = 2;
+
+/// One upgrade step
+struct Migration {
+    /// Version the step upgrades from
+    from: u32,
+    /// What the step changes, shown by `config migrate`
+    description: &'static str,
+    /// Apply the step; returns whether the document changed
+    apply: fn(&mut DocumentMut) -> Result<bool>,
+}
+
+/// Upgrade steps in order
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: 1,
+    description: "move the top-level [static] section to [plugins.static]",
+    apply: move_static_section,
+}];
+
+/// Result of migrating a config file's content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migrated {
+    /// Version the content was written for
+    pub from: u32,
+    /// Migrated content
+    pub content: String,
+    /// Descriptions of the steps that changed something
+    pub changes: Vec<&'static str>,
+}
+
+/// Upgrade config content to [`CONFIG_VERSION`]
+///
+/// Fails for content written for a newer version.
+pub fn migrate(content: &str) -> Result<Migrated> {
+    let mut document = content
+        .parse::<DocumentMut>()
+        .context("Failed to parse config file as TOML")?;
+    let from = file_version(&document)?;
+    if from > CONFIG_VERSION {
+        return Err(anyhow::anyhow!(
+            "Config format version {from} is newer than this dotsnapshot supports \
+             ({CONFIG_VERSION}); upgrade dotsnapshot"
+        ));
+    }
+
+    let mut changes = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|migration| migration.from >= from) {
+        if (migration.apply)(&mut document)? {
+            changes.push(migration.description);
+        }
+    }
+    if from < CONFIG_VERSION {
+        set_version(&mut document);
+    }
+    Ok(Migrated {
+        from,
+        content: document.to_string(),
+        changes,
+    })
+}
+
+/// Warn when a config file uses a layout `config migrate` would change
+///
+/// Unversioned files that are otherwise current don't warn. Content that
+/// doesn't parse is left to the caller to report.
+pub fn warn_if_outdated(path: &Path, content: &str) {
+    let Ok(content) = ConfigFormat::from_path(path).to_toml_string(content) else {
+        return;
+    };
+    let content = content.as_str();
+    match migrate(content) {
+        Ok(migrated) if !migrated.changes.is_empty() => warn!(
+            "Config file {} uses an outdated layout ({}); run `dotsnapshot config migrate` to upgrade it",
+            path.display(),
+            migrated.changes.join("; ")
+        ),
+        Ok(_) => {}
+        Err(error) if content.parse::<DocumentMut>().is_ok() => {
+            warn!("{}: {error}", path.display())
+        }
+        Err(_) => {}
+    }
+}
+
+/// Line diff of two texts with two lines of context around each change
+///
+/// Removed lines start with `-`, added lines with `+` and context lines with
+/// a space; skipped unchanged lines are marked with `@@`.
+pub fn diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence lengths of the suffixes
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            lines.push(('-', old[i]));
+            i += 1;
+        } else {
+            lines.push(('+', new[j]));
+            j += 1;
+        }
+    }
+
+    const CONTEXT: usize = 2;
+    let near_change = |index: usize| {
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + CONTEXT + 1).min(lines.len());
+        lines[start..end].iter().any(|(kind, _)| *kind != ' ')
+    };
+    let mut output = String::new();
+    let mut skipped = false;
+    for (index, (kind, line)) in lines.iter().enumerate() {
+        if near_change(index) {
+            if skipped {
+                output.push_str("@@\n");
+                skipped = false;
+            }
+            output.push_str(&format!("{kind}{line}\n"));
+        } else {
+            skipped = true;
+        }
+    }
+    output
+}
+
+/// Format version of a document; 1 when it has none
+fn file_version(document: &DocumentMut) -> Result<u32> {
+    match document.get("version") {
+        None => Ok(1),
+        Some(item) => item
+            .as_integer()
+            .and_then(|version| u32::try_from(version).ok())
+            .context("Config 'version' must be a positive integer"),
+    }
+}
+
+/// Set `version` as the first key of the document
+fn set_version(document: &mut DocumentMut) {
+    let root = document.as_table_mut();
+    if let Some(version) = root.get_mut("version").and_then(Item::as_value_mut) {
+        let decor = version.decor().clone();
+        *version = i64::from(CONFIG_VERSION).into();
+        *version.decor_mut() = decor;
+        return;
+    }
+
+    // A comment heading the file stays above the new key
+    let first_key = root
+        .iter()
+        .find(|(_, item)| item.is_value())
+        .map(|(key, _)| key.to_string());
+    let header = first_key.and_then(|key| {
+        let mut key = root.key_mut(&key)?;
+        let prefix = key.leaf_decor().prefix().cloned();
+        key.leaf_decor_mut().set_prefix("");
+        prefix
+    });
+
+    root.insert("version", toml_edit::value(i64::from(CONFIG_VERSION)));
+    if let (Some(header), Some(mut key)) = (header, root.key_mut("version")) {
+        key.leaf_decor_mut().set_prefix(header);
+    }
+    root.sort_values_by(|a, _, b, _| (a.get() != "version").cmp(&(b.get() != "version")));
+}
+
+/// Version 1 → 2: the legacy top-level `[static]` section, which the static
+/// files plugin no longer reads, becomes `[plugins.static]`
+///
+/// Lists are appended to an existing `[plugins.static]` without duplicates;
+/// other keys already set there win.
+fn move_static_section(document: &mut DocumentMut) -> Result<bool> {
+    let Some(legacy) = document.remove("static") else {
+        return Ok(false);
+    };
+    let legacy = legacy
+        .into_table()
+        .map_err(|_| anyhow::anyhow!("Legacy 'static' config must be a table"))?;
+
+    let plugins = document
+        .entry("plugins")
+        .or_insert_with(|| {
+            let mut table = Table::new();
+            table.set_implicit(true);
+            Item::Table(table)
+        })
+        .as_table_mut()
+        .context("Config 'plugins' must be a table")?;
+    let Some(current) = plugins.get_mut("static") else {
+        plugins.insert("static", Item::Table(legacy));
+        return Ok(true);
+    };
+    let current = current
+        .as_table_like_mut()
+        .context("Config 'plugins.static' must be a table")?;
+
+    for (key, item) in legacy {
+        match (current.get_mut(&key).and_then(Item::as_array_mut), &item) {
+            (Some(list), Item::Value(toml_edit::Value::Array(legacy_list))) => {
+                for value in legacy_list.iter() {
+                    let exists = list
+                        .iter()
+                        .any(|existing| existing.to_string().trim() == value.to_string().trim());
+                    if !exists {
+                        list.push(value.clone().decorated("", ""));
+                    }
+                }
+            }
+            _ => {
+                if !current.contains_key(&key) {
+                    current.insert(&key, item);
+                }
+            }
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test migrating a version 1 config
+    /// Verifies [static] moves under [plugins] with its comments and the version is set
+    #[test]
+    fn test_migrate_static_section() -> Result<()> {
+        let content = "# My config\noutput_dir = \"~/snapshots\"\n\n# Files to keep\n[static]\nfiles = [\"~/.zshrc\"] # shell\n";
+        let migrated = migrate(content)?;
+
+        assert_eq!(migrated.from, 1);
+        assert_eq!(migrated.changes.len(), 1);
+        assert_eq!(
+            migrated.content,
+            "# My config\nversion = 2\noutput_dir = \"~/snapshots\"\n\n# Files to keep\n[plugins.static]\nfiles = [\"~/.zshrc\"] # shell\n"
+        );
+        let config: crate::config::Config = toml::from_str(&migrated.content)?;
+        assert!(config.static_files.is_none());
+        assert_eq!(config.version, Some(CONFIG_VERSION));
+
+        let again = migrate(&migrated.content)?;
+        assert!(again.changes.is_empty());
+        assert_eq!(again.content, migrated.content);
+        Ok(())
+    }
+
+    /// Test merging the legacy section into an existing [plugins.static]
+    /// Verifies lists are appended without duplicates and existing keys win
+    #[test]
+    fn test_migrate_merges_static_lists() -> Result<()> {
+        let content = "[static]\nfiles = [\"~/.zshrc\", \"~/.vimrc\"]\nignore = [\"*.log\"]\n\n[plugins.static]\nfiles = [\"~/.zshrc\"]\n";
+        let migrated = migrate(content)?;
+        let config: crate::config::Config = toml::from_str(&migrated.content)?;
+        let static_config: crate::config::StaticPluginConfig = serde_json::from_value(
+            config
+                .get_raw_plugin_config("static_files")
+                .unwrap()
+                .clone(),
+        )?;
+
+        assert_eq!(
+            static_config.files,
+            Some(vec!["~/.zshrc".to_string(), "~/.vimrc".to_string()])
+        );
+        assert_eq!(static_config.ignore, Some(vec!["*.log".to_string()]));
+
+        assert!(migrate("version = 3\n").is_err());
+        Ok(())
+    }
+
+    /// Test the migration diff
+    /// Verifies changed lines are marked and distant unchanged lines are skipped
+    #[test]
+    fn test_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\n";
+        let new = "a\nb\nc\nd\ne\nF\ng\n";
+        assert_eq!(diff(old, new), "@@\n d\n e\n-f\n+F\n g\n");
+        assert_eq!(diff(old, old), "");
+    }
+}
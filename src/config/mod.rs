@@ -1,13 +1,39 @@
 use anyhow::{Context, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::fs;
 
+use crate::core::checksum::HashAlgorithm;
 use crate::core::hooks::{HookAction, HooksConfig};
+use crate::core::paths::expand;
+use crate::core::policy::FailurePolicy;
+use crate::core::snapshot::current_hostname;
+
+pub mod edit;
+pub mod format;
+pub mod layers;
+pub mod migrate;
+pub mod validate;
+
+use format::ConfigFormat;
+use layers::{LayeredConfig, LoadOptions};
+
+/// Default quiet period before watch mode snapshots changed files
+const DEFAULT_WATCH_DEBOUNCE_MS: u64 = 2000;
+
+/// Default interval for polling package-manager plugins in watch mode (one hour)
+const DEFAULT_WATCH_POLL_INTERVAL_SECS: u64 = 3600;
 
 /// Configuration for file-snapshots
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct Config {
+    /// Config format version; files without one are version 1
+    /// (see [`migrate`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+
     /// Output directory for snapshots
     pub output_dir: Option<PathBuf>,
 
@@ -23,11 +49,14 @@ pub struct Config {
     /// Global hooks configuration
     pub global: Option<GlobalConfig>,
 
-    /// Static plugin configuration (legacy)
+    /// Static plugin configuration (legacy, moved to `[plugins.static]` by
+    /// `config migrate`)
     #[serde(rename = "static")]
     pub static_files: Option<StaticFilesConfig>,
 
     /// Plugin-specific configurations
+    #[serde(default)]
+    #[schemars(schema_with = "crate::core::config_schema::plugins_schema")]
     pub plugins: Option<PluginsConfig>,
 
     /// UI configuration
@@ -35,10 +64,39 @@ pub struct Config {
 
     /// Validation configuration
     pub validation: Option<ValidationConfig>,
+
+    /// Snapshot run configuration
+    pub snapshot: Option<SnapshotConfig>,
+
+    /// Watch mode configuration
+    pub watch: Option<WatchConfig>,
+
+    /// Named overlays selected with `--profile` (see [`LayeredConfig::apply_profile`])
+    #[serde(default)]
+    #[schemars(schema_with = "crate::core::config_schema::overlays_schema")]
+    pub profiles: Option<std::collections::HashMap<String, toml::Table>>,
+
+    /// Overlays applied on the machine with that hostname (see [`LayeredConfig::apply_host`])
+    #[serde(default)]
+    #[schemars(schema_with = "crate::core::config_schema::overlays_schema")]
+    pub hosts: Option<std::collections::HashMap<String, toml::Table>>,
+
+    /// Other config files merged below this one; entries may be globs such
+    /// as `conf.d/*.toml` and are relative to this file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include: Option<Vec<String>>,
+
+    /// File that hooks commands write to when no config file sets the edited key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_config: Option<PathBuf>,
+
+    /// Profile applied to this configuration, recorded in snapshot metadata
+    #[serde(skip)]
+    pub active_profile: Option<String>,
 }
 
 /// Logging configuration
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct LoggingConfig {
     /// Enable verbose logging by default
     pub verbose: Option<bool>,
@@ -48,14 +106,14 @@ pub struct LoggingConfig {
 }
 
 /// Global hooks configuration
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct GlobalConfig {
     /// Global hooks that apply to all plugins
     pub hooks: Option<GlobalHooks>,
 }
 
 /// Global hooks that apply to all snapshots
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct GlobalHooks {
     /// Hooks to run before any plugins execute
     #[serde(rename = "pre-snapshot", default)]
@@ -66,15 +124,18 @@ pub struct GlobalHooks {
     pub post_snapshot: Vec<HookAction>,
 }
 
-/// Plugin-specific configurations - raw TOML values for plugin self-discovery
+/// A plugin's config section, independent of the format of the file it came from
+pub type PluginConfigValue = serde_json::Value;
+
+/// Plugin-specific configurations - raw values for plugin self-discovery
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct PluginsConfig {
     #[serde(flatten)]
-    pub plugins: std::collections::HashMap<String, toml::Value>,
+    pub plugins: std::collections::HashMap<String, PluginConfigValue>,
 }
 
 /// Generic plugin configuration
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct PluginConfig {
     /// Custom target path in snapshot (relative to snapshot root)
     pub target_path: Option<String>,
@@ -87,7 +148,7 @@ pub struct PluginConfig {
 }
 
 /// Plugin-specific hooks
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct PluginHooks {
     /// Hooks to run before this plugin executes
     #[serde(rename = "pre-plugin", default)]
@@ -99,7 +160,7 @@ pub struct PluginHooks {
 }
 
 /// Static files plugin configuration with additional options
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct StaticPluginConfig {
     /// Custom target path in snapshot (relative to snapshot root)
     pub target_path: Option<String>,
@@ -109,10 +170,14 @@ pub struct StaticPluginConfig {
     pub files: Option<Vec<String>>,
     /// Glob patterns to ignore when copying files/directories
     pub ignore: Option<Vec<String>>,
+    /// Hardlink files identical to the previous snapshot instead of copying them
+    pub hardlink: Option<bool>,
+    /// Plugin-specific hooks
+    pub hooks: Option<PluginHooks>,
 }
 
 /// Static files plugin configuration (legacy)
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct StaticFilesConfig {
     /// List of file paths to include in snapshots
     pub files: Option<Vec<String>>,
@@ -121,20 +186,45 @@ pub struct StaticFilesConfig {
 }
 
 /// UI configuration for display customization
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct UiConfig {
     /// Custom names for plugin categories/groups
     pub plugin_categories: Option<std::collections::HashMap<String, String>>,
 }
 
 /// Validation configuration
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct ValidationConfig {
     /// Configuration files that are allowed to have no file extension
     /// These are well-known configuration files that traditionally don't use extensions
     pub well_known_no_extension: Option<Vec<String>>,
 }
 
+/// Snapshot run configuration
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct SnapshotConfig {
+    /// Which failures make the run exit with a non-zero code:
+    /// "never", "any-failure", "all-failed" (default) or a list of critical plugins
+    pub fail_on: Option<FailurePolicy>,
+
+    /// Skip creating a snapshot when every plugin's output matches the latest snapshot
+    pub skip_unchanged: Option<bool>,
+
+    /// Hash algorithm for file and directory checksums: "sha256" (default) or "blake3"
+    pub hash_algorithm: Option<HashAlgorithm>,
+}
+
+/// Watch mode configuration
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct WatchConfig {
+    /// Quiet period after the last file change before snapshotting, in milliseconds
+    pub debounce_ms: Option<u64>,
+
+    /// Interval for snapshotting plugins without watchable files (package managers),
+    /// in seconds; 0 disables polling
+    pub poll_interval: Option<u64>,
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -147,6 +237,14 @@ impl Default for Config {
             plugins: None,
             ui: None,
             validation: None,
+            snapshot: None,
+            watch: None,
+            profiles: None,
+            hosts: None,
+            version: None,
+            include: None,
+            local_config: None,
+            active_profile: None,
         }
     }
 }
@@ -157,78 +255,66 @@ impl Config {
         let content = fs::read_to_string(path.as_ref())
             .await
             .context("Failed to read config file")?;
+        migrate::warn_if_outdated(path.as_ref(), &content);
 
-        let config: Config =
-            toml::from_str(&content).context("Failed to parse config file as TOML")?;
+        let format = ConfigFormat::from_path(path.as_ref());
+        let config: Config = format
+            .parse(&content)
+            .and_then(|table| Ok(toml::Value::Table(table).try_into()?))
+            .with_context(|| format!("Failed to parse config file as {}", format.name()))?;
 
         Ok(config)
     }
 
-    /// Try to load configuration from standard locations
+    /// Load the layered configuration from the standard locations
+    ///
+    /// See [`layers`] for the layers and how they merge.
+    // WORKAROUND: only the library API and tests use this, not the binary
+    #[allow(dead_code)]
     pub async fn load() -> Result<Self> {
-        let config_paths = Self::get_config_paths();
+        Self::load_with_profile(None, None).await
+    }
 
-        for path in config_paths {
-            if path.exists() {
-                return Self::load_from_file(&path).await;
-            }
-        }
+    /// Load the layered configuration
+    pub async fn load_with(options: &LoadOptions) -> Result<Self> {
+        LayeredConfig::load(options)?.to_config()
+    }
 
-        // Return default config if no config file found
-        Ok(Self::default())
+    /// Load the layered configuration with `--config` and `--profile`
+    ///
+    /// The `[hosts.<hostname>]` section of this machine is applied first, then
+    /// the selected profile. A `--config` path that doesn't exist yields the
+    /// default configuration.
+    pub async fn load_with_profile(
+        config_path: Option<&Path>,
+        profile: Option<&str>,
+    ) -> Result<Self> {
+        Self::load_with(&LoadOptions {
+            config_path: config_path.map(Path::to_path_buf),
+            profile: profile.map(str::to_string),
+            hostname: Some(current_hostname()),
+            cli: Vec::new(),
+        })
+        .await
     }
 
     /// Get potential configuration file paths in order of preference
+    ///
+    /// The current directory comes first, then the user config locations,
+    /// each with every supported extension.
     pub fn get_config_paths() -> Vec<PathBuf> {
-        let mut paths = Vec::new();
-
-        // 1. Current directory
-        paths.push(PathBuf::from("dotsnapshot.toml"));
-        paths.push(PathBuf::from(".dotsnapshot.toml"));
-
-        // 2. User config directory
-        if let Some(config_dir) = dirs::config_dir() {
-            paths.push(config_dir.join("dotsnapshot").join("config.toml"));
-            paths.push(config_dir.join("dotsnapshot.toml"));
-        }
-
-        // 3. User home directory
-        if let Some(home_dir) = dirs::home_dir() {
-            paths.push(
-                home_dir
-                    .join(".config")
-                    .join("dotsnapshot")
-                    .join("config.toml"),
-            );
-            paths.push(home_dir.join(".dotsnapshot.toml"));
-        }
-
+        let mut paths = layers::project_config_paths();
+        paths.extend(layers::user_config_paths());
         paths
     }
 
     /// Get the output directory, using the configured value or default
-    pub fn get_output_dir(&self) -> PathBuf {
+    pub fn get_output_dir(&self) -> Result<PathBuf> {
         let path = self
             .output_dir
             .clone()
             .unwrap_or_else(|| PathBuf::from("./snapshots"));
-        Self::expand_tilde(&path)
-    }
-
-    /// Expand tilde (~) to home directory if present
-    fn expand_tilde(path: &Path) -> PathBuf {
-        if let Some(path_str) = path.to_str() {
-            if let Some(stripped) = path_str.strip_prefix("~/") {
-                if let Some(home_dir) = dirs::home_dir() {
-                    return home_dir.join(stripped);
-                }
-            } else if path_str == "~" {
-                if let Some(home_dir) = dirs::home_dir() {
-                    return home_dir;
-                }
-            }
-        }
-        path.to_path_buf()
+        expand(&path).context("Invalid output_dir")
     }
 
     /// Get the included plugins list (None means all plugins)
@@ -252,17 +338,65 @@ impl Config {
             .unwrap_or_else(|| "[year]-[month]-[day] [hour]:[minute]:[second]".to_string())
     }
 
+    /// Get the failure policy for snapshot runs
+    pub fn get_fail_on(&self) -> FailurePolicy {
+        self.snapshot
+            .as_ref()
+            .and_then(|s| s.fail_on.clone())
+            .unwrap_or_default()
+    }
+
+    /// Check if snapshots are skipped when nothing changed since the latest one
+    pub fn is_skip_unchanged(&self) -> bool {
+        self.snapshot
+            .as_ref()
+            .and_then(|s| s.skip_unchanged)
+            .unwrap_or(false)
+    }
+
+    /// Get the hash algorithm for file and directory checksums
+    pub fn get_hash_algorithm(&self) -> HashAlgorithm {
+        self.snapshot
+            .as_ref()
+            .and_then(|s| s.hash_algorithm)
+            .unwrap_or_default()
+    }
+
+    /// Get the quiet period watch mode waits for after the last file change
+    pub fn get_watch_debounce(&self) -> Duration {
+        Duration::from_millis(
+            self.watch
+                .as_ref()
+                .and_then(|w| w.debounce_ms)
+                .unwrap_or(DEFAULT_WATCH_DEBOUNCE_MS),
+        )
+    }
+
+    /// Get the interval for polling plugins without watchable files in watch mode
+    ///
+    /// Returns `None` when polling is disabled.
+    pub fn get_watch_poll_interval(&self) -> Option<Duration> {
+        let seconds = self
+            .watch
+            .as_ref()
+            .and_then(|w| w.poll_interval)
+            .unwrap_or(DEFAULT_WATCH_POLL_INTERVAL_SECS);
+        (seconds > 0).then(|| Duration::from_secs(seconds))
+    }
+
     /// Get raw plugin configuration for a specific plugin (plugin self-discovery)
-    pub fn get_raw_plugin_config(&self, plugin_name: &str) -> Option<&toml::Value> {
+    pub fn get_raw_plugin_config(&self, plugin_name: &str) -> Option<&PluginConfigValue> {
         let plugins = self.plugins.as_ref()?;
+        plugins.plugins.get(Self::plugin_config_key(plugin_name))
+    }
 
+    /// Key of a plugin's section under `[plugins]`
+    pub fn plugin_config_key(plugin_name: &str) -> &str {
         // Handle special case: static_files plugin config is stored under "static" key
-        let config_key = match plugin_name {
+        match plugin_name {
             "static_files" => "static",
             _ => plugin_name,
-        };
-
-        plugins.plugins.get(config_key)
+        }
     }
 
     /// Get hooks configuration
@@ -306,13 +440,18 @@ impl Config {
     fn get_plugin_hooks(&self, plugin_name: &str) -> Option<PluginHooks> {
         let raw_config = self.get_raw_plugin_config(plugin_name)?;
         if let Some(hooks_value) = raw_config.get("hooks") {
-            hooks_value.clone().try_into().ok()
+            serde_json::from_value(hooks_value.clone()).ok()
         } else {
             None
         }
     }
 
     /// Save configuration to file
+    ///
+    /// Rewrites the whole file; the CLI edits existing files with
+    /// [`edit::ConfigDocument`] instead so their comments survive.
+    // WORKAROUND: only the library API and tests use this, not the binary
+    #[allow(dead_code)]
     pub async fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         let content = toml::to_string_pretty(self).context("Failed to serialize config to TOML")?;
 
@@ -334,12 +473,28 @@ impl Config {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use layers::{ConfigLayer, ConfigSource};
     use tempfile::TempDir;
 
+    fn layered(content: &str) -> Result<LayeredConfig> {
+        let mut layered = LayeredConfig::default();
+        layered.merge(
+            ConfigLayer {
+                source: ConfigSource::File(PathBuf::from("config.toml")),
+                table: toml::from_str(content)?,
+            },
+            true,
+        );
+        Ok(layered)
+    }
+
     #[tokio::test]
     async fn test_config_default() {
         let config = Config::default();
-        assert_eq!(config.get_output_dir(), PathBuf::from("./snapshots"));
+        assert_eq!(
+            config.get_output_dir().unwrap(),
+            PathBuf::from("./snapshots")
+        );
         assert_eq!(config.get_include_plugins(), None);
         assert!(!config.is_verbose_default());
     }
@@ -365,6 +520,7 @@ mod tests {
             plugins: None,
             ui: None,
             validation: None,
+            ..Config::default()
         };
 
         // Save config
@@ -374,7 +530,7 @@ mod tests {
         let loaded_config = Config::load_from_file(&config_path).await?;
 
         assert_eq!(
-            loaded_config.get_output_dir(),
+            loaded_config.get_output_dir().unwrap(),
             PathBuf::from("/tmp/snapshots")
         );
         assert_eq!(
@@ -433,14 +589,20 @@ mod tests {
                         "target_path".to_string(),
                         toml::Value::String("~/vscode".to_string()),
                     );
-                    plugins_map.insert("vscode".to_string(), toml::Value::Table(vscode_config));
+                    plugins_map.insert(
+                        "vscode".to_string(),
+                        serde_json::to_value(vscode_config).unwrap(),
+                    );
 
                     let mut homebrew_config = toml::value::Table::new();
                     homebrew_config.insert(
                         "output_file".to_string(),
                         toml::Value::String("brewfile.txt".to_string()),
                     );
-                    plugins_map.insert("homebrew".to_string(), toml::Value::Table(homebrew_config));
+                    plugins_map.insert(
+                        "homebrew".to_string(),
+                        serde_json::to_value(homebrew_config).unwrap(),
+                    );
 
                     plugins_map
                 },
@@ -454,6 +616,7 @@ mod tests {
                 }),
             }),
             validation: None,
+            ..Config::default()
         };
 
         // Test hook configuration methods
@@ -514,11 +677,12 @@ mod tests {
             plugins: None,
             ui: None,
             validation: None,
+            ..Config::default()
         };
 
         // Test default behaviors
         assert_eq!(
-            minimal_config.get_output_dir(),
+            minimal_config.get_output_dir().unwrap(),
             PathBuf::from("./snapshots")
         );
         assert_eq!(minimal_config.get_include_plugins(), None);
@@ -569,6 +733,7 @@ mod tests {
             plugins: None,
             ui: None,
             validation: None,
+            ..Config::default()
         };
 
         // Should still return false for verbose when not set
@@ -646,7 +811,10 @@ mod tests {
                         "output_file".to_string(),
                         toml::Value::String("vscode_config.json".to_string()),
                     );
-                    plugins_map.insert("vscode".to_string(), toml::Value::Table(vscode_config));
+                    plugins_map.insert(
+                        "vscode".to_string(),
+                        serde_json::to_value(vscode_config).unwrap(),
+                    );
 
                     let mut homebrew_config = toml::value::Table::new();
                     homebrew_config.insert(
@@ -654,11 +822,15 @@ mod tests {
                         toml::Value::String("Brewfile".to_string()),
                     );
                     homebrew_config.insert("include_casks".to_string(), toml::Value::Boolean(true));
-                    plugins_map.insert("homebrew".to_string(), toml::Value::Table(homebrew_config));
+                    plugins_map.insert(
+                        "homebrew".to_string(),
+                        serde_json::to_value(homebrew_config).unwrap(),
+                    );
 
                     let mut npm_config = toml::value::Table::new();
                     npm_config.insert("global_only".to_string(), toml::Value::Boolean(false));
-                    plugins_map.insert("npm".to_string(), toml::Value::Table(npm_config));
+                    plugins_map
+                        .insert("npm".to_string(), serde_json::to_value(npm_config).unwrap());
 
                     plugins_map
                 },
@@ -673,6 +845,7 @@ mod tests {
                 }),
             }),
             validation: None,
+            ..Config::default()
         };
 
         // Save the complex configuration
@@ -683,7 +856,7 @@ mod tests {
 
         // Verify all aspects were preserved
         assert_eq!(
-            loaded_config.get_output_dir(),
+            loaded_config.get_output_dir().unwrap(),
             PathBuf::from("/complex/output")
         );
         assert!(loaded_config.is_verbose_default());
@@ -711,4 +884,221 @@ mod tests {
 
         Ok(())
     }
+
+    /// Test the snapshot failure policy setting
+    /// Verifies the default policy and parsing of keyword and list values
+    #[tokio::test]
+    async fn test_config_fail_on() -> Result<()> {
+        assert_eq!(Config::default().get_fail_on(), FailurePolicy::AllFailed);
+
+        let config: Config = toml::from_str(
+            r#"
+            [snapshot]
+            fail_on = "any-failure"
+            "#,
+        )?;
+        assert_eq!(config.get_fail_on(), FailurePolicy::AnyFailure);
+
+        let config: Config = toml::from_str(
+            r#"
+            [snapshot]
+            fail_on = ["homebrew_brewfile"]
+            "#,
+        )?;
+        assert_eq!(
+            config.get_fail_on(),
+            FailurePolicy::Plugins(vec!["homebrew_brewfile".to_string()])
+        );
+
+        Ok(())
+    }
+
+    /// Test reading the skip_unchanged snapshot setting
+    /// Verifies that unchanged snapshots are created unless enabled
+    #[test]
+    fn test_config_skip_unchanged() -> Result<()> {
+        assert!(!Config::default().is_skip_unchanged());
+
+        let config: Config = toml::from_str(
+            r#"
+            [snapshot]
+            skip_unchanged = true
+            "#,
+        )?;
+        assert!(config.is_skip_unchanged());
+
+        Ok(())
+    }
+
+    /// Test applying a profile overlay
+    /// Verifies tables merge, lists and values replace, and unknown profiles fail
+    #[test]
+    fn test_config_profiles() -> Result<()> {
+        let base = layered(
+            r#"
+            output_dir = "/snapshots"
+            include_plugins = ["vscode", "static"]
+
+            [plugins.static]
+            files = ["~/.gitconfig"]
+            hardlink = true
+
+            [global.hooks]
+            pre-snapshot = [{ action = "log", message = "base" }]
+            post-snapshot = [{ action = "log", message = "done" }]
+
+            [profiles.work]
+            output_dir = "/work-snapshots"
+            include_plugins = ["static"]
+
+            [profiles.work.plugins.static]
+            files = ["~/.ssh/config"]
+
+            [[profiles.work.global.hooks.pre-snapshot]]
+            action = "log"
+            message = "work"
+
+            [profiles.bad]
+            logging = { verbose = true }
+            "#,
+        )?;
+
+        let mut layered = base.clone();
+        layered.apply_profile("work")?;
+        let config = layered.to_config()?;
+        assert_eq!(config.active_profile.as_deref(), Some("work"));
+        assert_eq!(
+            config.get_output_dir().unwrap(),
+            PathBuf::from("/work-snapshots")
+        );
+        assert_eq!(
+            config.get_include_plugins(),
+            Some(vec!["static".to_string()])
+        );
+
+        let static_config = config.get_raw_plugin_config("static_files").unwrap();
+        assert_eq!(
+            static_config
+                .get("files")
+                .unwrap()
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            static_config.get("files").unwrap().as_array().unwrap()[0].as_str(),
+            Some("~/.ssh/config")
+        );
+        assert_eq!(
+            static_config.get("hardlink").and_then(|v| v.as_bool()),
+            Some(true)
+        );
+
+        let pre_hooks = config.get_global_pre_snapshot_hooks();
+        assert_eq!(pre_hooks.len(), 1);
+        assert!(matches!(&pre_hooks[0], HookAction::Log { message, .. } if message == "work"));
+        assert_eq!(config.get_global_post_snapshot_hooks().len(), 1);
+
+        let mut layered = base.clone();
+        let error = layered.apply_profile("home").unwrap_err().to_string();
+        assert!(error.contains("available: bad, work"));
+
+        let mut layered = base.clone();
+        let error = layered.apply_profile("bad").unwrap_err().to_string();
+        assert!(error.contains("Profile 'bad' cannot override 'logging'"));
+
+        Ok(())
+    }
+
+    /// Test applying the host section of the current machine
+    /// Verifies short hostnames match and profiles apply on top of host settings
+    #[test]
+    fn test_config_hosts() -> Result<()> {
+        let base = layered(
+            r#"
+            output_dir = "/snapshots"
+
+            [hosts.laptop]
+            output_dir = "/laptop-snapshots"
+            include_plugins = ["static"]
+
+            [profiles.work]
+            output_dir = "/work-snapshots"
+            "#,
+        )?;
+
+        let mut layered = base.clone();
+        assert!(!layered.apply_host("desktop")?);
+        assert_eq!(
+            layered.to_config()?.get_output_dir()?,
+            PathBuf::from("/snapshots")
+        );
+
+        let mut layered = base.clone();
+        assert!(layered.apply_host("LAPTOP.example.com")?);
+        assert_eq!(
+            layered.to_config()?.get_output_dir()?,
+            PathBuf::from("/laptop-snapshots")
+        );
+
+        layered.apply_profile("work")?;
+        let config = layered.to_config()?;
+        assert_eq!(
+            config.get_output_dir().unwrap(),
+            PathBuf::from("/work-snapshots")
+        );
+        assert_eq!(
+            config.get_include_plugins(),
+            Some(vec!["static".to_string()])
+        );
+
+        Ok(())
+    }
+
+    /// Test reading the watch mode settings
+    /// Verifies defaults and that a zero poll interval disables polling
+    #[test]
+    fn test_config_watch() -> Result<()> {
+        let config = Config::default();
+        assert_eq!(config.get_watch_debounce(), Duration::from_secs(2));
+        assert_eq!(
+            config.get_watch_poll_interval(),
+            Some(Duration::from_secs(3600))
+        );
+
+        let config: Config = toml::from_str(
+            r#"
+            [watch]
+            debounce_ms = 500
+            poll_interval = 0
+            "#,
+        )?;
+        assert_eq!(config.get_watch_debounce(), Duration::from_millis(500));
+        assert_eq!(config.get_watch_poll_interval(), None);
+
+        Ok(())
+    }
+
+    /// Test reading the snapshot hash algorithm
+    /// Verifies SHA-256 is the default and unknown algorithms are rejected
+    #[test]
+    fn test_config_hash_algorithm() -> Result<()> {
+        assert_eq!(
+            Config::default().get_hash_algorithm(),
+            HashAlgorithm::Sha256
+        );
+
+        let config: Config = toml::from_str(
+            r#"
+            [snapshot]
+            hash_algorithm = "blake3"
+            "#,
+        )?;
+        assert_eq!(config.get_hash_algorithm(), HashAlgorithm::Blake3);
+
+        assert!(toml::from_str::<Config>("[snapshot]\nhash_algorithm = \"md5\"").is_err());
+
+        Ok(())
+    }
 }
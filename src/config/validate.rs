@@ -0,0 +1,879 @@
+//! Strict validation of configuration files
+//!
+//! Loading a config ignores keys it doesn't know, so typos such as
+//! `restore_targetdir` or `[plugin.vscode_settings]` go unnoticed. The
+//! validator checks a file against the JSON Schema of the configuration
+//! ([`config_file_schema`]), which includes the section of every registered
+//! plugin, and reports unknown keys and plugins, wrong types and invalid
+//! values with their line and column and a suggestion for likely typos.
+//! YAML and JSON files are checked the same way after conversion to TOML,
+//! so their problems have no position.
+
+use anyhow::{Context, Result};
+use schemars::schema::{InstanceType, ObjectValidation, Schema, SchemaObject, SingleOrVec};
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+use toml_edit::{ImDocument, Item, TableLike, Value};
+
+use super::format::ConfigFormat;
+use super::Config;
+use crate::core::config_schema::config_file_schema;
+use crate::core::plugin::PluginDescriptor;
+use crate::core::policy::PLUGINS_PREFIX;
+use crate::core::selector::{PluginSelection, EXCLUDE_PREFIX};
+use crate::plugins::custom::command::{is_builtin_plugin, CUSTOM_SECTION, DEFAULT_CATEGORY};
+use crate::plugins::external::adapter::{
+    find_executables, ExternalPluginsConfig, DEFAULT_CATEGORY as EXTERNAL_CATEGORY,
+    EXTERNAL_SECTION,
+};
+
+/// A problem found in a config file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// Line of the offending key or value, starting at 1; 0 when unknown
+    pub line: usize,
+    /// Column of the offending key or value, starting at 1
+    pub column: usize,
+    /// Description of the problem
+    pub message: String,
+    /// Likely intended key or value
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line > 0 {
+            write!(f, "{}:{}: ", self.line, self.column)?;
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " (did you mean '{suggestion}'?)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Validate a config file in the format of its extension, returning every
+/// problem found
+pub fn validate_file(path: &Path) -> Result<Vec<ConfigError>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    let format = ConfigFormat::from_path(path);
+    if format == ConfigFormat::Toml {
+        return Ok(validate_str(&content));
+    }
+
+    let errors = match format.to_toml_string(&content) {
+        Ok(content) => validate_str(&content),
+        Err(error) => vec![ConfigError {
+            line: 0,
+            column: 0,
+            message: format!("{error:#}"),
+            suggestion: None,
+        }],
+    };
+    Ok(errors
+        .into_iter()
+        .map(|error| ConfigError {
+            line: 0,
+            column: 0,
+            ..error
+        })
+        .collect())
+}
+
+/// Validate the content of a config file
+pub fn validate_str(content: &str) -> Vec<ConfigError> {
+    let document = match ImDocument::parse(content) {
+        Ok(document) => document,
+        Err(error) => {
+            let issue = Issue::new(error.span(), error.message().trim().to_string(), None);
+            return vec![issue.locate(content)];
+        }
+    };
+
+    let schema = config_file_schema();
+    let mut checker = Checker {
+        definitions: &schema.definitions,
+        issues: Vec::new(),
+    };
+    let root = Schema::Object(schema.schema.clone());
+    checker.check(document.as_item(), &root, "", None);
+    check_plugin_selections(document.as_table(), &mut checker.issues);
+    check_custom_plugins(document.as_table(), &mut checker.issues);
+
+    // Anything the schema can't express still fails to deserialize
+    if checker.issues.is_empty() {
+        if let Err(error) = toml::from_str::<Config>(content) {
+            let message = error.message().trim().to_string();
+            checker.issues.push(Issue::new(error.span(), message, None));
+        }
+    }
+
+    let mut errors: Vec<ConfigError> = checker
+        .issues
+        .into_iter()
+        .map(|issue| issue.locate(content))
+        .collect();
+    errors.sort_by_key(|error| (error.line, error.column));
+    errors.dedup();
+    errors
+}
+
+/// A problem with the byte range it refers to
+struct Issue {
+    span: Option<Range<usize>>,
+    message: String,
+    suggestion: Option<String>,
+}
+
+impl Issue {
+    fn new(span: Option<Range<usize>>, message: String, suggestion: Option<String>) -> Self {
+        Self {
+            span,
+            message,
+            suggestion,
+        }
+    }
+
+    fn locate(self, content: &str) -> ConfigError {
+        let offset = self.span.map_or(0, |span| span.start);
+        let before = content.get(..offset).unwrap_or(content);
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        ConfigError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message: self.message,
+            suggestion: self.suggestion,
+        }
+    }
+}
+
+/// Walks a TOML document alongside its JSON Schema
+struct Checker<'a> {
+    definitions: &'a schemars::Map<String, Schema>,
+    issues: Vec<Issue>,
+}
+
+impl<'a> Checker<'a> {
+    /// Check `item`, found at dotted `key`, against `schema`
+    ///
+    /// `fallback` locates the item when it has no span of its own, e.g. an
+    /// implicit table created by a `[a.b]` header.
+    fn check(
+        &mut self,
+        item: &Item,
+        schema: &'a Schema,
+        key: &str,
+        fallback: Option<Range<usize>>,
+    ) {
+        let span = item.span().or(fallback);
+        let object = match schema {
+            Schema::Bool(true) => return,
+            Schema::Bool(false) => {
+                let message = format!("'{key}' is not allowed here");
+                self.issues.push(Issue::new(span, message, None));
+                return;
+            }
+            Schema::Object(_) => match self.resolve(schema) {
+                Some(object) => object,
+                None => return,
+            },
+        };
+
+        if let Some(subschemas) = &object.subschemas {
+            for schema in subschemas.all_of.iter().flatten() {
+                self.check(item, schema, key, span.clone());
+            }
+            if let Some(alternatives) = subschemas.any_of.as_ref().or(subschemas.one_of.as_ref()) {
+                self.check_alternatives(item, alternatives, key, span.clone());
+            }
+        }
+
+        if let Some(types) = &object.instance_type {
+            if !type_matches(types, item) {
+                let message = format!(
+                    "'{key}' should be {}, found {}",
+                    describe_types(types),
+                    describe_type(&kind(item))
+                );
+                self.issues.push(Issue::new(span, message, None));
+                return;
+            }
+        }
+
+        let allowed = object
+            .enum_values
+            .clone()
+            .or_else(|| object.const_value.clone().map(|value| vec![value]));
+        if let (Some(allowed), Some(value)) = (allowed, to_json(item)) {
+            if !allowed.contains(&value) {
+                self.invalid_value(span.clone(), key, &value, &allowed);
+            }
+        }
+
+        if let (Some(validation), Some(table)) = (&object.object, item.as_table_like()) {
+            self.check_table(table, validation, key, span.clone());
+        }
+        if let Some(validation) = &object.array {
+            if let (Some(SingleOrVec::Single(items)), Some(elements)) =
+                (&validation.items, elements(item))
+            {
+                for (index, element) in elements.iter().enumerate() {
+                    self.check(element, items, &format!("{key}[{index}]"), span.clone());
+                }
+            }
+        }
+    }
+
+    fn check_table(
+        &mut self,
+        table: &dyn TableLike,
+        validation: &'a ObjectValidation,
+        key: &str,
+        span: Option<Range<usize>>,
+    ) {
+        for (name, item) in table.iter() {
+            let path = join_key(key, name);
+            let key_span = table.get_key_value(name).and_then(|(key, _)| key.span());
+            if let Some(schema) = validation.properties.get(name) {
+                self.check(item, schema, &path, key_span);
+                continue;
+            }
+            match validation.additional_properties.as_deref() {
+                Some(Schema::Bool(false)) => self.unknown_key(key_span, key, name, validation),
+                Some(schema) => self.check(item, schema, &path, key_span),
+                None if validation.properties.is_empty() => {}
+                None => self.unknown_key(key_span, key, name, validation),
+            }
+        }
+
+        for required in &validation.required {
+            if !table.contains_key(required) {
+                let message = format!("missing required key '{required}' in '{key}'");
+                self.issues.push(Issue::new(span.clone(), message, None));
+            }
+        }
+    }
+
+    /// Check a value that may match any of several schemas
+    fn check_alternatives(
+        &mut self,
+        item: &Item,
+        alternatives: &'a [Schema],
+        key: &str,
+        span: Option<Range<usize>>,
+    ) {
+        // TOML has no null, so `Option<T>` alternatives reduce to `T`
+        let candidates: Vec<&'a Schema> = alternatives
+            .iter()
+            .filter(|schema| !self.is_null(schema))
+            .collect();
+
+        if candidates.len() > 1 {
+            if let Some(tag) = self.tag_property(&candidates) {
+                self.check_tagged(item, &candidates, &tag, key, span);
+                return;
+            }
+
+            let types: Vec<InstanceType> = candidates
+                .iter()
+                .filter_map(|schema| self.resolve(schema)?.instance_type.as_ref())
+                .flat_map(|types| match types {
+                    SingleOrVec::Single(single) => vec![**single],
+                    SingleOrVec::Vec(types) => types.clone(),
+                })
+                .collect();
+            if types.len() == candidates.len()
+                && !type_matches(&SingleOrVec::Vec(types.clone()), item)
+            {
+                let message = format!(
+                    "'{key}' should be {}, found {}",
+                    describe_types(&SingleOrVec::Vec(types)),
+                    describe_type(&kind(item))
+                );
+                self.issues.push(Issue::new(span, message, None));
+                return;
+            }
+        }
+
+        // Alternatives of the item's type explain its problems best
+        let typed: Vec<&'a Schema> = candidates
+            .iter()
+            .copied()
+            .filter(|schema| {
+                self.resolve(schema)
+                    .and_then(|object| object.instance_type.as_ref())
+                    .map_or(true, |types| type_matches(types, item))
+            })
+            .collect();
+        let candidates = if typed.is_empty() { candidates } else { typed };
+
+        // Report the problems of the closest alternative
+        let mut closest: Option<Vec<Issue>> = None;
+        for schema in candidates {
+            let saved = std::mem::take(&mut self.issues);
+            self.check(item, schema, key, span.clone());
+            let issues = std::mem::replace(&mut self.issues, saved);
+            if issues.is_empty() {
+                return;
+            }
+            if closest
+                .as_ref()
+                .map_or(true, |closest| issues.len() < closest.len())
+            {
+                closest = Some(issues);
+            }
+        }
+        self.issues.extend(closest.unwrap_or_default());
+    }
+
+    /// Check a table against the variant selected by its tag, e.g. a hook's `action`
+    fn check_tagged(
+        &mut self,
+        item: &Item,
+        candidates: &[&'a Schema],
+        tag: &str,
+        key: &str,
+        span: Option<Range<usize>>,
+    ) {
+        let Some(table) = item.as_table_like() else {
+            let message = format!(
+                "'{key}' should be a table, found {}",
+                describe_type(&kind(item))
+            );
+            self.issues.push(Issue::new(span, message, None));
+            return;
+        };
+        let Some((tag_key, tag_item)) = table.get_key_value(tag) else {
+            let message = format!("missing required key '{tag}' in '{key}'");
+            self.issues.push(Issue::new(span, message, None));
+            return;
+        };
+
+        let value = to_json(tag_item);
+        for schema in candidates {
+            if value.is_some() && self.tag_value(schema, tag) == value.as_ref() {
+                self.check(item, schema, key, span);
+                return;
+            }
+        }
+
+        let allowed: Vec<serde_json::Value> = candidates
+            .iter()
+            .filter_map(|schema| self.tag_value(schema, tag).cloned())
+            .collect();
+        let tag_span = tag_item.span().or_else(|| tag_key.span());
+        match value {
+            Some(value) => self.invalid_value(tag_span, &join_key(key, tag), &value, &allowed),
+            None => {
+                let message = format!("'{}' should be a string", join_key(key, tag));
+                self.issues.push(Issue::new(tag_span, message, None));
+            }
+        }
+    }
+
+    fn unknown_key(
+        &mut self,
+        span: Option<Range<usize>>,
+        key: &str,
+        name: &str,
+        validation: &ObjectValidation,
+    ) {
+        let what = if key == "plugins" || key.ends_with(".plugins") {
+            "plugin"
+        } else {
+            "key"
+        };
+        let message = if key.is_empty() {
+            format!("unknown {what} '{name}'")
+        } else {
+            format!("unknown {what} '{name}' in [{key}]")
+        };
+        let known: Vec<&str> = validation.properties.keys().map(String::as_str).collect();
+        self.issues
+            .push(Issue::new(span, message, suggest(name, &known)));
+    }
+
+    fn invalid_value(
+        &mut self,
+        span: Option<Range<usize>>,
+        key: &str,
+        value: &serde_json::Value,
+        allowed: &[serde_json::Value],
+    ) {
+        let listed: Vec<String> = allowed.iter().map(ToString::to_string).collect();
+        let message = format!(
+            "invalid value {value} for '{key}', expected one of: {}",
+            listed.join(", ")
+        );
+        let known: Vec<&str> = allowed.iter().filter_map(|value| value.as_str()).collect();
+        let suggestion = value.as_str().and_then(|value| suggest(value, &known));
+        self.issues.push(Issue::new(span, message, suggestion));
+    }
+
+    /// Follow `$ref`s to the schema object they point to
+    fn resolve(&self, schema: &'a Schema) -> Option<&'a SchemaObject> {
+        let Schema::Object(object) = schema else {
+            return None;
+        };
+        match &object.reference {
+            Some(reference) => {
+                let name = reference.trim_start_matches("#/definitions/");
+                self.resolve(self.definitions.get(name)?)
+            }
+            None => Some(object),
+        }
+    }
+
+    fn is_null(&self, schema: &'a Schema) -> bool {
+        self.resolve(schema).is_some_and(|object| {
+            object.instance_type == Some(SingleOrVec::Single(Box::new(InstanceType::Null)))
+        })
+    }
+
+    /// Required property with a single allowed value in every candidate
+    fn tag_property(&self, candidates: &[&'a Schema]) -> Option<String> {
+        let first = self.resolve(candidates.first()?)?.object.as_ref()?;
+        first
+            .required
+            .iter()
+            .find(|name| {
+                candidates
+                    .iter()
+                    .all(|schema| self.tag_value(schema, name).is_some())
+            })
+            .cloned()
+    }
+
+    fn tag_value(&self, schema: &'a Schema, tag: &str) -> Option<&'a serde_json::Value> {
+        let property = self.resolve(schema)?.object.as_ref()?.properties.get(tag)?;
+        match self.resolve(property)?.enum_values.as_deref()? {
+            [value] => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Check that `include_plugins` entries are valid selectors matching a registered plugin
+/// and that failure policies only name known plugins
+fn check_plugin_selections(root: &toml_edit::Table, issues: &mut Vec<Issue>) {
+    let mut selections = vec![("include_plugins".to_string(), root.get("include_plugins"))];
+    for section in ["profiles", "hosts"] {
+        let Some(overlays) = root.get(section).and_then(Item::as_table_like) else {
+            continue;
+        };
+        for (name, overlay) in overlays.iter() {
+            selections.push((
+                format!("{section}.{name}.include_plugins"),
+                overlay.get("include_plugins"),
+            ));
+        }
+    }
+
+    // External plugins are only known by name here, since their category
+    // comes from running them
+    let external: Vec<String> = find_executables(&external_settings(root))
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+
+    // (name, category) of compiled, custom and external plugins
+    let mut plugins: Vec<(&str, &str)> = inventory::iter::<PluginDescriptor>()
+        .map(|descriptor| (descriptor.name, descriptor.category))
+        .collect();
+    plugins.extend(
+        external
+            .iter()
+            .map(|name| (name.as_str(), EXTERNAL_CATEGORY)),
+    );
+    for (name, entry) in custom_table(root).into_iter().flat_map(TableLike::iter) {
+        let category = entry
+            .get("category")
+            .and_then(Item::as_str)
+            .unwrap_or(DEFAULT_CATEGORY);
+        plugins.push((name, category));
+    }
+    let mut known: Vec<&str> = plugins
+        .iter()
+        .flat_map(|(name, category)| [*name, *category])
+        .collect();
+    known.sort_unstable();
+    known.dedup();
+    let candidates: Vec<(String, Option<String>)> = plugins
+        .iter()
+        .map(|(name, category)| (name.to_string(), Some(category.to_string())))
+        .collect();
+
+    for (key, item) in selections {
+        let Some(Value::Array(entries)) = item.and_then(Item::as_value) else {
+            continue;
+        };
+        for entry in entries.iter() {
+            let Some(selection) = entry.as_str() else {
+                continue;
+            };
+            let selects_plugin = match PluginSelection::parse(&[selection]) {
+                Ok(parsed) => parsed.check_matches(&candidates).is_ok(),
+                Err(e) => {
+                    let message = format!("'{key}' entry \"{selection}\" is invalid: {e:#}");
+                    issues.push(Issue::new(entry.span(), message, None));
+                    continue;
+                }
+            };
+            if !selects_plugin {
+                let message = format!("'{key}' entry \"{selection}\" matches no plugin");
+                let word = selection.trim_start_matches(EXCLUDE_PREFIX).trim();
+                issues.push(Issue::new(entry.span(), message, suggest(word, &known)));
+            }
+        }
+    }
+
+    // Critical plugins of failure policies are plugin names, not selectors
+    let names: Vec<&str> = plugins.iter().map(|(name, _)| *name).collect();
+    let entries = root
+        .get("snapshot")
+        .and_then(Item::as_table_like)
+        .and_then(|snapshot| snapshot.get("fail_on"))
+        .and_then(Item::as_value);
+    let critical: Vec<(&str, Option<Range<usize>>)> = match entries {
+        Some(Value::Array(entries)) => entries
+            .iter()
+            .filter_map(|entry| Some((entry.as_str()?, entry.span())))
+            .collect(),
+        Some(value @ Value::String(policy)) => policy
+            .value()
+            .strip_prefix(PLUGINS_PREFIX)
+            .into_iter()
+            .flat_map(|list| list.split(','))
+            .map(|name| (name.trim(), value.span()))
+            .filter(|(name, _)| !name.is_empty())
+            .collect(),
+        _ => Vec::new(),
+    };
+    for (name, span) in critical {
+        if !names.contains(&name) {
+            let message = format!("'snapshot.fail_on' names unknown plugin \"{name}\"");
+            issues.push(Issue::new(span, message, suggest(name, &names)));
+        }
+    }
+}
+
+/// Check that custom plugins don't reuse the name of a compiled plugin
+fn check_custom_plugins(root: &toml_edit::Table, issues: &mut Vec<Issue>) {
+    let Some(custom) = custom_table(root) else {
+        return;
+    };
+    for (name, _) in custom.iter() {
+        if is_builtin_plugin(name) {
+            let span = custom.get_key_value(name).and_then(|(key, _)| key.span());
+            let message = format!("custom plugin '{name}' has the same name as a built-in plugin");
+            issues.push(Issue::new(span, message, None));
+        }
+    }
+}
+
+/// The `[plugins.external]` settings used to find external plugins
+fn external_settings(root: &toml_edit::Table) -> ExternalPluginsConfig {
+    let Some(external) = root
+        .get("plugins")
+        .and_then(Item::as_table_like)
+        .and_then(|plugins| plugins.get(EXTERNAL_SECTION))
+        .and_then(Item::as_table_like)
+    else {
+        return ExternalPluginsConfig::default();
+    };
+    ExternalPluginsConfig {
+        dirs: external.get("dirs").and_then(Item::as_array).map(|dirs| {
+            dirs.iter()
+                .filter_map(|dir| dir.as_str().map(str::to_string))
+                .collect()
+        }),
+        search_path: external.get("search_path").and_then(Item::as_bool),
+        timeout: None,
+    }
+}
+
+/// The `[plugins.custom]` table
+fn custom_table(root: &toml_edit::Table) -> Option<&dyn TableLike> {
+    root.get("plugins")
+        .and_then(Item::as_table_like)
+        .and_then(|plugins| plugins.get(CUSTOM_SECTION))
+        .and_then(Item::as_table_like)
+}
+
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+/// JSON type of a TOML item
+fn kind(item: &Item) -> InstanceType {
+    match item {
+        Item::None => InstanceType::Null,
+        Item::Table(_) => InstanceType::Object,
+        Item::ArrayOfTables(_) => InstanceType::Array,
+        Item::Value(value) => match value {
+            Value::String(_) | Value::Datetime(_) => InstanceType::String,
+            Value::Integer(_) => InstanceType::Integer,
+            Value::Float(_) => InstanceType::Number,
+            Value::Boolean(_) => InstanceType::Boolean,
+            Value::Array(_) => InstanceType::Array,
+            Value::InlineTable(_) => InstanceType::Object,
+        },
+    }
+}
+
+fn type_matches(types: &SingleOrVec<InstanceType>, item: &Item) -> bool {
+    let found = kind(item);
+    let matches = |expected: &InstanceType| {
+        *expected == found || (*expected == InstanceType::Number && found == InstanceType::Integer)
+    };
+    match types {
+        SingleOrVec::Single(expected) => matches(expected),
+        SingleOrVec::Vec(types) => types.iter().any(matches),
+    }
+}
+
+fn describe_type(instance_type: &InstanceType) -> &'static str {
+    match instance_type {
+        InstanceType::Null => "nothing",
+        InstanceType::Boolean => "a boolean",
+        InstanceType::Object => "a table",
+        InstanceType::Array => "an array",
+        InstanceType::Number => "a number",
+        InstanceType::String => "a string",
+        InstanceType::Integer => "an integer",
+    }
+}
+
+fn describe_types(types: &SingleOrVec<InstanceType>) -> String {
+    let types: Vec<&InstanceType> = match types {
+        SingleOrVec::Single(single) => vec![single],
+        SingleOrVec::Vec(types) => types
+            .iter()
+            .filter(|instance_type| **instance_type != InstanceType::Null)
+            .collect(),
+    };
+    let described: Vec<&str> = types.into_iter().map(describe_type).collect();
+    described.join(" or ")
+}
+
+/// Items of an array or array of tables
+fn elements(item: &Item) -> Option<Vec<Item>> {
+    match item {
+        Item::ArrayOfTables(tables) => Some(tables.iter().cloned().map(Item::Table).collect()),
+        Item::Value(Value::Array(array)) => Some(array.iter().cloned().map(Item::Value).collect()),
+        _ => None,
+    }
+}
+
+/// Scalar TOML value as JSON, for comparing against allowed values
+fn to_json(item: &Item) -> Option<serde_json::Value> {
+    match item.as_value()? {
+        Value::String(value) => Some(serde_json::Value::from(value.value().as_str())),
+        Value::Integer(value) => Some(serde_json::Value::from(*value.value())),
+        Value::Float(value) => Some(serde_json::Value::from(*value.value())),
+        Value::Boolean(value) => Some(serde_json::Value::from(*value.value())),
+        _ => None,
+    }
+}
+
+/// Closest candidate to a mistyped word, if it is close enough to be a typo
+pub(crate) fn suggest(word: &str, candidates: &[&str]) -> Option<String> {
+    let word = word.to_lowercase();
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(&word, &candidate.to_lowercase()), *candidate))
+        .filter(|(distance, candidate)| *distance <= (word.len().max(candidate.len()) / 3).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+/// Levenshtein distance between two words
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(content: &str) -> Vec<String> {
+        validate_str(content)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    /// Test validating a config without problems
+    /// Verifies the sample config and hooks, overlays and plugin sections pass
+    #[test]
+    fn test_validate_valid_config() {
+        let sample = include_str!("../../dotsnapshot.toml");
+        assert_eq!(messages(sample), Vec::<String>::new());
+
+        let config = r#"
+            output_dir = "~/snapshots"
+            include_plugins = ["vscode", "static"]
+
+            [snapshot]
+            fail_on = ["homebrew_brewfile"]
+            hash_algorithm = "blake3"
+
+            [plugins.vscode_settings]
+            restore_target_dir = "~/.config/Code/User"
+
+            [plugins.static]
+            files = ["~/.gitconfig"]
+
+            [[plugins.static.hooks.pre-plugin]]
+            action = "log"
+            message = "copying"
+
+            [profiles.work]
+            include_plugins = ["static"]
+        "#;
+        assert_eq!(messages(config), Vec::<String>::new());
+    }
+
+    /// Test reporting unknown keys and plugin sections
+    /// Verifies locations and did-you-mean suggestions
+    #[test]
+    fn test_validate_unknown_keys() {
+        let config = "output_dir = \"/tmp\"\n\
+                      [plugins.vscode_settings]\n\
+                      restore_targetdir = \"~/code\"\n\
+                      [plugin.vscode_settings]\n\
+                      target_path = \"vscode\"\n\
+                      [plugins.vscode_setings]\n";
+        assert_eq!(
+            messages(config),
+            vec![
+                "3:1: unknown key 'restore_targetdir' in [plugins.vscode_settings] \
+                 (did you mean 'restore_target_dir'?)",
+                "4:2: unknown key 'plugin' (did you mean 'plugins'?)",
+                "6:10: unknown plugin 'vscode_setings' in [plugins] \
+                 (did you mean 'vscode_settings'?)",
+            ]
+        );
+    }
+
+    /// Test reporting wrong types and invalid values
+    /// Verifies type mismatches, enum values, hook actions, plugin selections and per-OS paths
+    #[test]
+    fn test_validate_values() {
+        let config = r#"output_dir = 5
+include_plugins = ["vscdoe"]
+
+[snapshot]
+hash_algorithm = "blake"
+
+[[global.hooks.post-snapshot]]
+action = "scirpt"
+command = "notify.sh"
+
+[[global.hooks.pre-snapshot]]
+action = "log"
+
+[plugins.vscode_settings]
+restore_target_dir = { linx = "~/.config/Code/User" }
+"#;
+        assert_eq!(
+            messages(config),
+            vec![
+                "1:14: 'output_dir' should be a string, found an integer",
+                "2:20: 'include_plugins' entry \"vscdoe\" matches no plugin \
+                 (did you mean 'vscode'?)",
+                "5:18: invalid value \"blake\" for 'snapshot.hash_algorithm', \
+                 expected one of: \"sha256\", \"blake3\" (did you mean 'blake3'?)",
+                "8:10: invalid value \"scirpt\" for 'global.hooks.post-snapshot[0].action', \
+                 expected one of: \"script\", \"log\", \"notify\", \"backup\", \"cleanup\" \
+                 (did you mean 'script'?)",
+                "11:1: missing required key 'message' in 'global.hooks.pre-snapshot[0]'",
+                "15:24: unknown key 'linx' in [plugins.vscode_settings.restore_target_dir] \
+                 (did you mean 'linux'?)",
+            ]
+        );
+    }
+
+    /// Test validating failure policies
+    /// Verifies unknown keywords and critical plugins are reported
+    #[test]
+    fn test_validate_failure_policy() {
+        let config = r#"[snapshot]
+fail_on = ["homebrew_brewfile", "npm_cofig"]
+"#;
+        assert_eq!(
+            messages(config),
+            vec![
+                "2:33: 'snapshot.fail_on' names unknown plugin \"npm_cofig\" \
+                 (did you mean 'npm_config'?)"
+            ]
+        );
+
+        let config = "[snapshot]\nfail_on = \"plugins:vscode_setings\"\n";
+        assert_eq!(
+            messages(config),
+            vec![
+                "2:11: 'snapshot.fail_on' names unknown plugin \"vscode_setings\" \
+                 (did you mean 'vscode_settings'?)"
+            ]
+        );
+
+        let config = "[snapshot]\nfail_on = \"any-failures\"\n";
+        let messages = messages(config);
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].contains("unknown failure policy 'any-failures'"));
+    }
+
+    /// Test validating custom plugins
+    /// Verifies entries are checked against their schema and selectable by category
+    #[test]
+    fn test_validate_custom_plugins() {
+        let config = r#"include_plugins = ["python", "gh_extensions"]
+
+[plugins.custom.pip_freeze]
+capture = "pip freeze"
+category = "python"
+
+[plugins.custom.gh_extensions]
+captrue = "gh extension list"
+
+[plugins.custom.homebrew_brewfile]
+capture = "brew list"
+"#;
+        assert_eq!(
+            messages(config),
+            vec![
+                "7:1: missing required key 'capture' in 'plugins.custom.gh_extensions'",
+                "8:1: unknown key 'captrue' in [plugins.custom.gh_extensions] \
+                 (did you mean 'capture'?)",
+                "10:17: custom plugin 'homebrew_brewfile' has the same name as a built-in plugin",
+            ]
+        );
+    }
+
+    /// Test reporting TOML syntax errors
+    /// Verifies the parse error is located
+    #[test]
+    fn test_validate_syntax_error() {
+        let errors = validate_str("output_dir = \"/tmp\"\nverbose = \n");
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].line, errors[0].column), (2, 11));
+    }
+}
@@ -18,6 +18,9 @@ macro_rules! register_plugin {
                         <$plugin_type>::new()
                     })
                 },
+                config_schema: |generator| {
+                    generator.subschema_for::<$crate::plugins::core::mixins::StandardConfig>()
+                },
             }
         }
     };
@@ -34,6 +37,9 @@ macro_rules! register_simple_plugin {
                 factory: |_config| {
                     std::sync::Arc::new(<$plugin_type>::new())
                 },
+                config_schema: |generator| {
+                    generator.subschema_for::<$crate::plugins::core::mixins::StandardConfig>()
+                },
             }
         }
     };
@@ -54,6 +60,11 @@ macro_rules! register_mixin_plugin {
                         <$plugin_type>::new(<$core_type>::default())
                     })
                 },
+                config_schema: |generator| {
+                    generator.subschema_for::<
+                        <$plugin_type as $crate::plugins::core::mixins::ConfigMixin>::Config,
+                    >()
+                },
             }
         }
     };
@@ -1,41 +1,215 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::fs;
-use std::path::Path;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Buffer size used when streaming files through a hasher
+pub const HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Hash algorithm used for file and directory checksums
+///
+/// Plugin output checksums always use SHA-256 so they stay comparable across
+/// snapshots taken with different algorithms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// Hash a byte slice and return the hex digest
+    pub fn hash_bytes(self, bytes: &[u8]) -> String {
+        match self {
+            HashAlgorithm::Sha256 => format!("{:x}", Sha256::digest(bytes)),
+            HashAlgorithm::Blake3 => blake3::hash(bytes).to_hex().to_string(),
+        }
+    }
+
+    /// Hash everything read from `reader` using a fixed-size buffer
+    pub fn hash_reader(self, mut reader: impl Read) -> std::io::Result<String> {
+        let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+        match self {
+            HashAlgorithm::Sha256 => {
+                let mut hasher = Sha256::new();
+                loop {
+                    let read = reader.read(&mut buffer)?;
+                    if read == 0 {
+                        break;
+                    }
+                    hasher.update(&buffer[..read]);
+                }
+                Ok(format!("{:x}", hasher.finalize()))
+            }
+            HashAlgorithm::Blake3 => {
+                let mut hasher = blake3::Hasher::new();
+                loop {
+                    let read = reader.read(&mut buffer)?;
+                    if read == 0 {
+                        break;
+                    }
+                    hasher.update(&buffer[..read]);
+                }
+                Ok(hasher.finalize().to_hex().to_string())
+            }
+        }
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            "blake3" => Ok(HashAlgorithm::Blake3),
+            other => Err(format!(
+                "unknown hash algorithm '{other}', expected sha256 or blake3"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HashAlgorithm::Sha256 => write!(f, "sha256"),
+            HashAlgorithm::Blake3 => write!(f, "blake3"),
+        }
+    }
+}
 
 /// Calculates SHA256 checksum of a string
 pub fn calculate_checksum(content: &str) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(content.as_bytes());
-    format!("{:x}", hasher.finalize())
+    HashAlgorithm::Sha256.hash_bytes(content.as_bytes())
 }
 
-/// Calculates checksum of directory contents (recursive)
-pub fn calculate_directory_checksum(dir_path: &Path) -> Result<String> {
-    let mut file_checksums = Vec::new();
+/// File identity used to tell whether a memoized hash is still valid
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+    #[cfg(unix)]
+    inode: u64,
+}
+
+impl FileStamp {
+    fn of(metadata: &fs::Metadata) -> Self {
+        Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            #[cfg(unix)]
+            inode: std::os::unix::fs::MetadataExt::ino(metadata),
+        }
+    }
+}
+
+/// Hash algorithm of a snapshot run and the file hashes computed during it
+///
+/// Files hashed for a plugin's own checksum are not read again for the
+/// snapshot directory checksum. Hashes are reused for as long as a file's
+/// size, modification time and inode stay the same. Clones share the hashes,
+/// which are dropped with the last clone at the end of the run.
+#[derive(Debug, Clone, Default)]
+pub struct FileHashes {
+    algorithm: HashAlgorithm,
+    memo: Arc<Mutex<HashMap<PathBuf, (FileStamp, String)>>>,
+}
+
+impl FileHashes {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        Self {
+            algorithm,
+            memo: Arc::default(),
+        }
+    }
+
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
+    }
+
+    /// Calculates the checksum of a file by streaming it through the hasher
+    pub fn file_checksum(&self, path: &Path) -> Result<String> {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let stamp = FileStamp::of(&file.metadata()?);
+
+        if let Some((cached_stamp, hash)) = self.memo.lock().unwrap().get(path) {
+            if *cached_stamp == stamp {
+                return Ok(hash.clone());
+            }
+        }
+
+        let hash = self
+            .algorithm
+            .hash_reader(file)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        self.memo
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), (stamp, hash.clone()));
+        Ok(hash)
+    }
 
-    // Collect all files in directory recursively
-    let mut files = Vec::new();
-    collect_files(dir_path, &mut files)?;
+    /// Records a checksum that is already known for a file, e.g. from a cache,
+    /// so later directory checksums do not read the file
+    pub fn record(&self, path: &Path, checksum: &str) -> Result<()> {
+        let stamp = FileStamp::of(&fs::metadata(path)?);
+        self.memo
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), (stamp, checksum.to_string()));
+        Ok(())
+    }
 
-    // Sort files for consistent ordering
-    files.sort();
+    /// Calculates checksum of directory contents (recursive)
+    ///
+    /// Files are hashed in parallel; the result only depends on file contents
+    /// and their paths relative to `dir_path`.
+    pub fn directory_checksum(&self, dir_path: &Path) -> Result<String> {
+        // Collect all files in directory recursively
+        let mut files = Vec::new();
+        collect_files(dir_path, &mut files)?;
 
-    // Calculate checksum for each file
-    for file in files {
-        let content = fs::read(&file)?;
-        let mut hasher = Sha256::new();
-        hasher.update(&content);
+        // Sort files for consistent ordering
+        files.sort();
 
-        // Include relative path in checksum calculation for uniqueness
-        let relative_path = file.strip_prefix(dir_path)?;
-        let file_info = format!("{}:{:x}", relative_path.display(), hasher.finalize());
-        file_checksums.push(file_info);
+        // Calculate checksum for each file
+        let file_checksums = files
+            .par_iter()
+            .map(|file| {
+                // Include relative path in checksum calculation for uniqueness
+                let relative_path = file.strip_prefix(dir_path)?;
+                let checksum = self.file_checksum(file)?;
+                Ok(format!("{}:{}", relative_path.display(), checksum))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Calculate final checksum from all file checksums
+        let combined = file_checksums.join("\n");
+        Ok(self.algorithm.hash_bytes(combined.as_bytes()))
     }
+}
 
-    // Calculate final checksum from all file checksums
-    let combined = file_checksums.join("\n");
-    Ok(calculate_checksum(&combined))
+/// Calculates the checksum of a file by streaming it through the hasher
+// WORKAROUND: only the library API and tests use this, not the binary
+#[allow(dead_code)]
+pub fn calculate_file_checksum(path: &Path, algorithm: HashAlgorithm) -> Result<String> {
+    FileHashes::new(algorithm).file_checksum(path)
+}
+
+/// Calculates checksum of directory contents (recursive)
+pub fn calculate_directory_checksum(dir_path: &Path, algorithm: HashAlgorithm) -> Result<String> {
+    FileHashes::new(algorithm).directory_checksum(dir_path)
 }
 
 /// Recursively collects all files in a directory
@@ -98,13 +272,106 @@ mod tests {
         let mut file2 = File::create(dir_path.join("file2.txt"))?;
         file2.write_all(b"content2")?;
 
-        let checksum = calculate_directory_checksum(dir_path)?;
+        let checksum = calculate_directory_checksum(dir_path, HashAlgorithm::Sha256)?;
         assert_eq!(checksum.len(), 64);
 
         // Same directory should produce same checksum
-        let checksum2 = calculate_directory_checksum(dir_path)?;
+        let checksum2 = calculate_directory_checksum(dir_path, HashAlgorithm::Sha256)?;
         assert_eq!(checksum, checksum2);
 
         Ok(())
     }
+
+    /// Test streaming file hashes for both algorithms
+    /// Verifies streamed digests match hashing the bytes in one go
+    #[test]
+    fn test_calculate_file_checksum_streaming() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("large.bin");
+        // Larger than the hash buffer so several reads are needed
+        let content: Vec<u8> = (0..HASH_BUFFER_SIZE * 3 + 17)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        fs::write(&path, &content)?;
+
+        for algorithm in [HashAlgorithm::Sha256, HashAlgorithm::Blake3] {
+            assert_eq!(
+                calculate_file_checksum(&path, algorithm)?,
+                algorithm.hash_bytes(&content)
+            );
+        }
+        assert_ne!(
+            calculate_file_checksum(&path, HashAlgorithm::Sha256)?,
+            calculate_file_checksum(&path, HashAlgorithm::Blake3)?
+        );
+
+        // A rewritten file is hashed again
+        fs::write(&path, b"changed")?;
+        assert_eq!(
+            calculate_file_checksum(&path, HashAlgorithm::Sha256)?,
+            calculate_checksum("changed")
+        );
+
+        Ok(())
+    }
+
+    /// Test the hashes memoized for a run
+    /// Verifies clones share recorded hashes and changed files are hashed again
+    #[test]
+    fn test_file_hashes_memo() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("file.txt");
+        fs::write(&path, "content")?;
+
+        let hashes = FileHashes::new(HashAlgorithm::Sha256);
+        hashes.clone().record(&path, "recorded")?;
+        assert_eq!(hashes.file_checksum(&path)?, "recorded");
+        assert_eq!(
+            FileHashes::new(HashAlgorithm::Sha256).file_checksum(&path)?,
+            calculate_checksum("content")
+        );
+
+        fs::write(&path, "changed content")?;
+        assert_eq!(
+            hashes.file_checksum(&path)?,
+            calculate_checksum("changed content")
+        );
+
+        Ok(())
+    }
+
+    /// Test that the SHA-256 directory checksum keeps its original format
+    /// Verifies checksums recorded by earlier versions still match
+    #[test]
+    fn test_calculate_directory_checksum_format() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("sub"))?;
+        fs::write(temp_dir.path().join("a.txt"), "a")?;
+        fs::write(temp_dir.path().join("sub").join("b.txt"), "b")?;
+
+        let expected = calculate_checksum(&format!(
+            "a.txt:{}\nsub/b.txt:{}",
+            calculate_checksum("a"),
+            calculate_checksum("b")
+        ));
+        assert_eq!(
+            calculate_directory_checksum(temp_dir.path(), HashAlgorithm::Sha256)?,
+            expected
+        );
+
+        let blake3 = calculate_directory_checksum(temp_dir.path(), HashAlgorithm::Blake3)?;
+        assert_eq!(blake3.len(), 64);
+        assert_ne!(blake3, expected);
+
+        Ok(())
+    }
+
+    /// Test parsing hash algorithm names
+    #[test]
+    fn test_hash_algorithm_from_str() {
+        assert_eq!("sha256".parse(), Ok(HashAlgorithm::Sha256));
+        assert_eq!("BLAKE3".parse(), Ok(HashAlgorithm::Blake3));
+        assert!("md5".parse::<HashAlgorithm>().is_err());
+        assert_eq!(HashAlgorithm::Blake3.to_string(), "blake3");
+    }
 }
@@ -1,7 +1,16 @@
+use crate::config::format::ConfigFormat;
+use crate::config::layers::OVERLAY_KEYS;
+use crate::config::{Config, GlobalConfig, PluginConfigValue};
+use crate::core::plugin::PluginDescriptor;
+use crate::plugins::custom::command::{custom_plugins_schema, CUSTOM_SECTION};
+use crate::plugins::external::adapter::{ExternalPluginsConfig, EXTERNAL_SECTION};
 use crate::symbols::*;
 use anyhow::{Context, Result};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, ObjectValidation, RootSchema, Schema, SchemaObject};
 use schemars::JsonSchema;
 use serde::de::DeserializeOwned;
+use std::path::PathBuf;
 
 /// Trait for plugin configuration validation with schema support
 pub trait ConfigSchema: DeserializeOwned + JsonSchema {
@@ -14,10 +23,10 @@ pub trait ConfigSchema: DeserializeOwned + JsonSchema {
         Ok(())
     }
 
-    /// Parse and validate configuration from TOML value
-    fn from_toml_value(value: &toml::Value) -> Result<Self> {
+    /// Parse and validate configuration from a plugin's config value
+    fn from_value(value: &PluginConfigValue) -> Result<Self> {
         // First, deserialize the configuration
-        let config: Self = value.clone().try_into().with_context(|| {
+        let config: Self = serde_json::from_value(value.clone()).with_context(|| {
             format!(
                 "Failed to parse {} configuration",
                 <Self as ConfigSchema>::schema_name()
@@ -36,6 +45,68 @@ pub trait ConfigSchema: DeserializeOwned + JsonSchema {
     }
 }
 
+/// JSON Schema of the configuration file
+pub fn config_file_schema() -> RootSchema {
+    let mut schema = schemars::schema_for!(Config);
+    schema.schema.metadata().title = Some("dotsnapshot configuration".to_string());
+    schema
+}
+
+/// Schema of the `[plugins]` table: one section per registered plugin
+pub fn plugins_schema(generator: &mut SchemaGenerator) -> Schema {
+    let mut object = ObjectValidation::default();
+    for descriptor in inventory::iter::<PluginDescriptor> {
+        object.properties.insert(
+            Config::plugin_config_key(descriptor.name).to_string(),
+            (descriptor.config_schema)(generator),
+        );
+    }
+    object
+        .properties
+        .insert(CUSTOM_SECTION.to_string(), custom_plugins_schema(generator));
+    object.properties.insert(
+        EXTERNAL_SECTION.to_string(),
+        generator.subschema_for::<ExternalPluginsConfig>(),
+    );
+    closed_object(object)
+}
+
+/// Schema of `[profiles.<name>]` and `[hosts.<hostname>]` overlays
+pub fn overlays_schema(generator: &mut SchemaGenerator) -> Schema {
+    let mut overlay = ObjectValidation::default();
+    for key in OVERLAY_KEYS {
+        let schema = match *key {
+            "output_dir" => generator.subschema_for::<PathBuf>(),
+            "include_plugins" => generator.subschema_for::<Vec<String>>(),
+            "plugins" => plugins_schema(generator),
+            "global" => generator.subschema_for::<GlobalConfig>(),
+            _ => continue,
+        };
+        overlay.properties.insert(key.to_string(), schema);
+    }
+
+    SchemaObject {
+        instance_type: Some(InstanceType::Object.into()),
+        object: Some(Box::new(ObjectValidation {
+            additional_properties: Some(Box::new(closed_object(overlay))),
+            ..ObjectValidation::default()
+        })),
+        ..SchemaObject::default()
+    }
+    .into()
+}
+
+/// Object schema rejecting keys other than its properties
+fn closed_object(mut object: ObjectValidation) -> Schema {
+    object.additional_properties = Some(Box::new(Schema::Bool(false)));
+    SchemaObject {
+        instance_type: Some(InstanceType::Object.into()),
+        object: Some(Box::new(object)),
+        ..SchemaObject::default()
+    }
+    .into()
+}
+
 /// Helper functions for common validation patterns
 pub struct ValidationHelpers;
 
@@ -48,9 +119,11 @@ impl ValidationHelpers {
         for config_path in config_paths {
             if config_path.exists() {
                 if let Ok(config_content) = std::fs::read_to_string(&config_path) {
-                    if let Ok(parsed_config) =
-                        toml::from_str::<crate::config::Config>(&config_content)
-                    {
+                    let parsed_config = ConfigFormat::from_path(&config_path)
+                        .parse(&config_content)
+                        .ok()
+                        .and_then(|table| toml::Value::Table(table).try_into::<Config>().ok());
+                    if let Some(parsed_config) = parsed_config {
                         if let Some(validation_config) = parsed_config.validation {
                             if let Some(custom_list) = validation_config.well_known_no_extension {
                                 return custom_list;
@@ -185,15 +258,15 @@ mod tests {
     /// Test deserializing configuration from TOML with validation
     /// This verifies the complete flow: TOML → struct → validation
     #[test]
-    fn test_config_from_toml_value() {
+    fn test_config_from_value() {
         let toml_str = r#"
             target_path = "test"
             output_file = "test.json"
             timeout = 30
         "#;
 
-        let toml_value: toml::Value = toml::from_str(toml_str).unwrap();
-        let config = TestConfig::from_toml_value(&toml_value);
+        let toml_value: serde_json::Value = toml::from_str(toml_str).unwrap();
+        let config = TestConfig::from_value(&toml_value);
 
         assert!(config.is_ok());
     }
@@ -227,8 +300,8 @@ mod tests {
             output_file = "test.exe"  # Invalid extension
         "#;
 
-        let toml_value: toml::Value = toml::from_str(invalid_toml_str).unwrap();
-        let result = TestConfig::from_toml_value(&toml_value);
+        let toml_value: serde_json::Value = toml::from_str(invalid_toml_str).unwrap();
+        let result = TestConfig::from_value(&toml_value);
 
         assert!(result.is_err());
         let error = result.unwrap_err();
@@ -367,18 +440,18 @@ mod tests {
         assert!(config.validate().is_ok());
     }
 
-    /// Test ConfigSchema from_toml_value with serialization failure
+    /// Test ConfigSchema from_value with serialization failure
     /// This verifies proper error handling when TOML cannot be deserialized
     #[test]
-    fn test_config_from_toml_value_serialization_failure() {
+    fn test_config_from_value_serialization_failure() {
         // TOML with wrong type for expected field
         let invalid_toml_str = r#"
             target_path = 123  # Should be string, not number
             output_file = "test.json"
         "#;
 
-        let toml_value: toml::Value = toml::from_str(invalid_toml_str).unwrap();
-        let result = TestConfig::from_toml_value(&toml_value);
+        let toml_value: serde_json::Value = toml::from_str(invalid_toml_str).unwrap();
+        let result = TestConfig::from_value(&toml_value);
 
         assert!(result.is_err());
         let error = result.unwrap_err();
@@ -387,18 +460,18 @@ mod tests {
             .contains("Failed to parse TestConfig configuration"));
     }
 
-    /// Test ConfigSchema from_toml_value with validation failure
+    /// Test ConfigSchema from_value with validation failure
     /// This verifies that validation errors are properly contextized
     #[test]
-    fn test_config_from_toml_value_validation_failure() {
+    fn test_config_from_value_validation_failure() {
         // Valid TOML but fails validation
         let invalid_toml_str = r#"
             target_path = "test"
             output_file = "test.invalid"  # Invalid extension
         "#;
 
-        let toml_value: toml::Value = toml::from_str(invalid_toml_str).unwrap();
-        let result = TestConfig::from_toml_value(&toml_value);
+        let toml_value: serde_json::Value = toml::from_str(invalid_toml_str).unwrap();
+        let result = TestConfig::from_value(&toml_value);
 
         assert!(result.is_err());
         let error = result.unwrap_err();
@@ -437,4 +510,28 @@ mod tests {
         assert!(error.to_string().contains("File must have an extension"));
         assert!(error.to_string().contains("Allowed extensions: [\"json\"]"));
     }
+
+    /// Test the JSON Schema of the configuration file
+    /// Verifies every registered plugin has a closed section and overlays allow only overlay keys
+    #[test]
+    fn test_config_file_schema() {
+        let schema = config_file_schema();
+        let value = serde_json::to_value(&schema).unwrap();
+        let properties = &value["properties"];
+
+        let plugins = &properties["plugins"];
+        assert_eq!(plugins["additionalProperties"], false);
+        for descriptor in inventory::iter::<PluginDescriptor> {
+            let key = Config::plugin_config_key(descriptor.name);
+            assert!(plugins["properties"].get(key).is_some(), "{key}");
+        }
+        assert!(plugins["properties"].get("static").is_some());
+
+        let overlay = &properties["profiles"]["additionalProperties"];
+        let keys: Vec<&String> = overlay["properties"].as_object().unwrap().keys().collect();
+        assert_eq!(keys.len(), OVERLAY_KEYS.len());
+        assert!(value["definitions"].get("StandardConfig").is_some());
+        assert!(value["definitions"].get("HookAction").is_some());
+        assert_eq!(value["title"], "dotsnapshot configuration");
+    }
 }
@@ -0,0 +1,142 @@
+//! Carrying plugin output forward from the latest snapshot
+//!
+//! Runs that only refresh some plugins (watch mode) copy the output of the
+//! other plugins from the latest snapshot, so every snapshot stays complete
+//! for restores, change detection and hardlinking.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+use super::SnapshotExecutor;
+use crate::core::plugin::PluginStatus;
+use crate::core::report::{path_size, PluginReport};
+use crate::symbols::*;
+
+/// Output in the latest snapshot of the plugins carried forward into a run
+#[derive(Debug, Default)]
+pub struct CarriedOutputs {
+    latest_snapshot: PathBuf,
+    /// Plugin name to its output path in the latest snapshot and checksum
+    outputs: HashMap<String, (PathBuf, String)>,
+}
+
+impl CarriedOutputs {
+    /// Checksum of a plugin's carried output, if it is carried forward
+    pub fn checksum(&self, plugin_name: &str) -> Option<&str> {
+        self.outputs
+            .get(plugin_name)
+            .map(|(_, checksum)| checksum.as_str())
+    }
+
+    pub fn contains(&self, plugin_name: &str) -> bool {
+        self.outputs.contains_key(plugin_name)
+    }
+}
+
+impl SnapshotExecutor {
+    /// Find the latest snapshot's output of the plugins to carry forward
+    ///
+    /// Plugins without output in the latest snapshot are left out and run.
+    pub(super) async fn carried_outputs(&self) -> CarriedOutputs {
+        if self.carry_forward.is_empty() {
+            return CarriedOutputs::default();
+        }
+        let latest_snapshot = match self.snapshot_manager.find_latest_snapshot() {
+            Ok(Some(latest_snapshot)) => latest_snapshot,
+            Ok(None) => return CarriedOutputs::default(),
+            Err(e) => {
+                warn!(
+                    "Running every plugin, no snapshot to carry output from: {}",
+                    e
+                );
+                return CarriedOutputs::default();
+            }
+        };
+        let metadata = match self.snapshot_manager.load_metadata(&latest_snapshot).await {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                warn!(
+                    "Running every plugin, cannot read {}: {}",
+                    latest_snapshot.display(),
+                    e
+                );
+                return CarriedOutputs::default();
+            }
+        };
+
+        let outputs = self
+            .carry_forward
+            .iter()
+            .filter_map(|plugin_name| {
+                let checksum = metadata.checksums.get(plugin_name)?;
+                let path = self.latest_output_path(plugin_name, &latest_snapshot)?;
+                Some((plugin_name.clone(), (path, checksum.clone())))
+            })
+            .collect();
+        CarriedOutputs {
+            latest_snapshot,
+            outputs,
+        }
+    }
+
+    /// Copy a plugin's output from the latest snapshot into `snapshot_dir`
+    ///
+    /// The plugin and its hooks don't run; it is reported as reused.
+    pub(super) async fn carry_forward_plugin(
+        plugin_name: &str,
+        carried: &CarriedOutputs,
+        snapshot_dir: &Path,
+    ) -> Result<PluginReport> {
+        let Some((source, checksum)) = carried.outputs.get(plugin_name) else {
+            return Err(anyhow::anyhow!("No output to carry forward"));
+        };
+        let relative = source.strip_prefix(&carried.latest_snapshot)?;
+        let destination = snapshot_dir.join(relative);
+
+        if *source == destination {
+            // A snapshot created within the same second already holds it
+        } else if source.is_dir() {
+            let (source, destination) = (source.clone(), destination.clone());
+            tokio::task::spawn_blocking(move || copy_dir(&source, &destination)).await??;
+        } else {
+            if let Some(parent) = destination.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::copy(source, &destination)
+                .await
+                .with_context(|| format!("Failed to copy {}", source.display()))?;
+        }
+        info!(
+            "{} Carried {} forward from {}",
+            SYMBOL_ACTION_RESTORE,
+            plugin_name,
+            carried.latest_snapshot.display()
+        );
+
+        let mut report = PluginReport::skipped(plugin_name);
+        report.status = PluginStatus::Reused;
+        report.checksum = Some(checksum.clone());
+        report.bytes_written = path_size(&destination);
+        report.output_paths = vec![destination];
+        Ok(report)
+    }
+}
+
+/// Copy a directory tree
+fn copy_dir(source: &Path, destination: &Path) -> Result<()> {
+    std::fs::create_dir_all(destination)?;
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let path = entry.path();
+        let target = destination.join(entry.file_name());
+        if path.is_dir() {
+            copy_dir(&path, &target)?;
+        } else {
+            std::fs::copy(&path, &target)
+                .with_context(|| format!("Failed to copy {}", path.display()))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,313 @@
+//! Dry-run planning for snapshot creation
+
+use anyhow::Result;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::error;
+
+use super::SnapshotExecutor;
+use crate::core::plugin::{FilePlan, NotApplicable, Plugin, PluginRegistry};
+use crate::core::snapshot::SnapshotManager;
+use crate::symbols::*;
+
+/// What a snapshot run would do with a plugin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlannedAction {
+    /// Plugin output would be captured into the new snapshot
+    Capture,
+    /// Plugin output is unchanged and would be reused from the latest snapshot
+    Reuse,
+    /// Plugin does not apply to this machine
+    NotApplicable,
+    /// Plugin is excluded by the plugin selection
+    Skip,
+    /// Plugin would fail validation or execution
+    Invalid,
+}
+
+impl PlannedAction {
+    /// Symbol used when rendering the action
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            PlannedAction::Capture => SYMBOL_CONTENT_PACKAGE,
+            PlannedAction::Reuse => SYMBOL_ACTION_RESTORE,
+            PlannedAction::NotApplicable => SYMBOL_ACTION_BLOCK,
+            PlannedAction::Skip => SYMBOL_CONTENT_SKIP,
+            PlannedAction::Invalid => SYMBOL_INDICATOR_ERROR,
+        }
+    }
+}
+
+impl std::fmt::Display for PlannedAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlannedAction::Capture => write!(f, "capture"),
+            PlannedAction::Reuse => write!(f, "reuse"),
+            PlannedAction::NotApplicable => write!(f, "not_applicable"),
+            PlannedAction::Skip => write!(f, "skip"),
+            PlannedAction::Invalid => write!(f, "invalid"),
+        }
+    }
+}
+
+/// Dry-run plan for a single plugin
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginPlan {
+    pub plugin_name: String,
+    pub action: PlannedAction,
+    /// Output file or directory, relative to the new snapshot directory
+    pub output: Option<PathBuf>,
+    /// Files that would be copied (plugins that create their own output files)
+    pub files: Option<FilePlan>,
+    /// Plugin hooks that would fire
+    pub hooks: Vec<String>,
+    /// Why the plan is incomplete, such as files that can't be listed in advance
+    pub note: Option<String>,
+    pub error_message: Option<String>,
+}
+
+impl PluginPlan {
+    fn new(plugin_name: &str, action: PlannedAction) -> Self {
+        Self {
+            plugin_name: plugin_name.to_string(),
+            action,
+            output: None,
+            files: None,
+            hooks: Vec::new(),
+            note: None,
+            error_message: None,
+        }
+    }
+}
+
+/// Dry-run plan for a complete snapshot
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotPlan {
+    pub output_dir: PathBuf,
+    /// Snapshot that checksum matching compares against
+    pub latest_snapshot: Option<PathBuf>,
+    pub plugins: Vec<PluginPlan>,
+    /// Global pre-snapshot and post-snapshot hooks that would fire
+    pub hooks: Vec<String>,
+}
+
+impl SnapshotPlan {
+    /// Render the plan as human-readable text
+    pub fn to_table(&self) -> String {
+        let mut lines = vec![
+            format!(
+                "{SYMBOL_ACTION_SEARCH} DRY RUN: snapshot would be created in {}",
+                self.output_dir.display()
+            ),
+            match &self.latest_snapshot {
+                Some(latest) => {
+                    format!("   Comparing against latest snapshot {}", latest.display())
+                }
+                None => "   No previous snapshot to reuse output from".to_string(),
+            },
+            String::new(),
+        ];
+
+        let mut plugins: Vec<&PluginPlan> = self.plugins.iter().collect();
+        plugins.sort_by(|a, b| a.plugin_name.cmp(&b.plugin_name));
+
+        for plugin in plugins {
+            let target = plugin
+                .output
+                .as_ref()
+                .map(|o| format!("-> {}", o.display()))
+                .unwrap_or_default();
+            lines.push(format!(
+                "{} {:<24} {:<14} {}",
+                plugin.action.symbol(),
+                plugin.plugin_name,
+                plugin.action.to_string(),
+                target
+            ));
+
+            if let Some(error) = &plugin.error_message {
+                lines.push(format!("     {error}"));
+            }
+
+            if let Some(note) = &plugin.note {
+                lines.push(format!("     {SYMBOL_INDICATOR_INFO} {note}"));
+            }
+
+            if let Some(files) = &plugin.files {
+                for (source, destination) in &files.copies {
+                    lines.push(format!(
+                        "     {SYMBOL_CONTENT_FILE} {} {SYMBOL_CONTENT_ARROW_RIGHT} {}",
+                        source.display(),
+                        destination.display()
+                    ));
+                }
+                for ignored in &files.ignored {
+                    lines.push(format!(
+                        "     {SYMBOL_ACTION_BLOCK} {} (ignored)",
+                        ignored.display()
+                    ));
+                }
+                for missing in &files.missing {
+                    lines.push(format!(
+                        "     {SYMBOL_INDICATOR_WARNING} {} (not found)",
+                        missing.display()
+                    ));
+                }
+            }
+
+            for hook in &plugin.hooks {
+                lines.push(format!("     {SYMBOL_ACTION_HOOK} {hook}"));
+            }
+        }
+
+        if !self.hooks.is_empty() {
+            lines.push(String::new());
+            for hook in &self.hooks {
+                lines.push(format!("{SYMBOL_ACTION_HOOK} {hook}"));
+            }
+        }
+
+        lines.push(String::new());
+        lines.push(format!(
+            "{SYMBOL_INDICATOR_INFO} Nothing was written to {}",
+            self.output_dir.display()
+        ));
+
+        lines.join("\n")
+    }
+}
+
+impl SnapshotExecutor {
+    /// Plans a snapshot without writing anything to the output directory
+    ///
+    /// Plugins are validated and (except for plugins that create their own
+    /// output files) executed so their checksum can be compared with the
+    /// latest snapshot.
+    pub async fn plan_snapshot(&self) -> Result<SnapshotPlan> {
+        let output_dir = self.snapshot_manager.base_path().clone();
+        let latest_snapshot = self.snapshot_manager.find_latest_snapshot()?;
+
+        let mut hooks = Vec::new();
+        if let Some(config) = &self.config {
+            hooks.extend(
+                config
+                    .get_global_pre_snapshot_hooks()
+                    .iter()
+                    .map(|hook| format!("pre-snapshot: {hook}")),
+            );
+            hooks.extend(
+                config
+                    .get_global_post_snapshot_hooks()
+                    .iter()
+                    .map(|hook| format!("post-snapshot: {hook}")),
+            );
+        }
+
+        let mut plan_tasks = Vec::new();
+        for (plugin_name, plugin) in self.registry.plugins() {
+            let plugin = Arc::clone(plugin);
+            let name = plugin_name.clone();
+            let snapshot_manager = self.snapshot_manager.clone();
+            let task =
+                tokio::spawn(
+                    async move { Self::plan_plugin(&name, plugin, &snapshot_manager).await },
+                );
+            plan_tasks.push((plugin_name.clone(), task));
+        }
+
+        let mut plugins = Vec::new();
+        for (plugin_name, task) in plan_tasks {
+            match task.await {
+                Ok(plan) => plugins.push(plan),
+                Err(e) => {
+                    error!("Plugin task failed for {}: {}", plugin_name, e);
+                    let mut plan = PluginPlan::new(&plugin_name, PlannedAction::Invalid);
+                    plan.error_message = Some(e.to_string());
+                    plugins.push(plan);
+                }
+            }
+        }
+
+        plugins.extend(
+            self.registry
+                .skipped_plugins()
+                .iter()
+                .map(|name| PluginPlan::new(name, PlannedAction::Skip)),
+        );
+
+        Ok(SnapshotPlan {
+            output_dir,
+            latest_snapshot,
+            plugins,
+            hooks,
+        })
+    }
+
+    /// Plans a single plugin: validation, output location, reuse and hooks
+    async fn plan_plugin(
+        plugin_name: &str,
+        plugin: Arc<dyn Plugin>,
+        snapshot_manager: &SnapshotManager,
+    ) -> PluginPlan {
+        let mut plan = PluginPlan::new(plugin_name, PlannedAction::Capture);
+
+        let plugin_hooks = plugin.get_hooks();
+        for stage in ["pre-plugin", "post-plugin"] {
+            plan.hooks
+                .extend(plugin_hooks.iter().map(|hook| format!("{stage}: {hook}")));
+        }
+
+        if let Err(e) = plugin.validate().await {
+            plan.action = if NotApplicable::is_in(&e) {
+                PlannedAction::NotApplicable
+            } else {
+                PlannedAction::Invalid
+            };
+            plan.error_message = Some(format!("Validation failed: {e}"));
+            return plan;
+        }
+
+        if plugin.creates_own_output_files() {
+            plan.output = plugin.get_own_output_dir().map(PathBuf::from);
+            match plugin.plan_files().await {
+                Ok(Some(files)) => plan.files = Some(files),
+                Ok(None) => {
+                    plan.note = Some("Files are only known once the plugin runs".to_string());
+                }
+                Err(e) => {
+                    plan.action = PlannedAction::Invalid;
+                    plan.error_message = Some(e.to_string());
+                }
+            }
+            return plan;
+        }
+
+        let output_file =
+            PluginRegistry::get_plugin_output_file_from_plugin(plugin.as_ref(), plugin_name);
+        plan.output = Some(match plugin.get_target_path() {
+            Some(target_path) => PathBuf::from(target_path).join(&output_file),
+            None => PathBuf::from(&output_file),
+        });
+
+        let content = match plugin.execute().await {
+            Ok(content) => content,
+            Err(e) => {
+                plan.action = PlannedAction::Invalid;
+                plan.error_message = Some(e.to_string());
+                return plan;
+            }
+        };
+
+        let checksum = plugin.output_checksum(&content);
+        if let Ok(Some(_)) = snapshot_manager
+            .find_any_file_by_checksum(plugin_name, &output_file, &checksum)
+            .await
+        {
+            plan.action = PlannedAction::Reuse;
+        }
+
+        plan
+    }
+}
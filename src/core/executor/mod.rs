@@ -1,15 +1,20 @@
 //! Snapshot executor functionality
 
 use anyhow::{Context, Result};
-use std::path::{Path, PathBuf};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::fs as async_fs;
 use tracing::{error, info, warn};
 
 use crate::config::Config;
-use crate::core::checksum::calculate_checksum;
+use crate::core::checksum::FileHashes;
 use crate::core::hooks::{HookContext, HookManager, HookType};
-use crate::core::plugin::{Plugin, PluginRegistry, PluginResult};
+use crate::core::plugin::{
+    ExecutionContext, NotApplicable, Plugin, PluginRegistry, PluginResult, PluginStatus,
+};
+use crate::core::report::{path_size, HookReport, PluginReport, RunReport};
 use crate::core::snapshot::SnapshotManager;
 use crate::symbols::*;
 
@@ -18,6 +23,9 @@ pub struct SnapshotExecutor {
     registry: Arc<PluginRegistry>,
     snapshot_manager: SnapshotManager,
     config: Option<Arc<Config>>,
+    skip_unchanged: bool,
+    /// Plugins whose output is copied from the latest snapshot instead of run
+    carry_forward: BTreeSet<String>,
 }
 
 impl SnapshotExecutor {
@@ -29,16 +37,54 @@ impl SnapshotExecutor {
         Self {
             registry,
             snapshot_manager: SnapshotManager::new(base_path),
+            skip_unchanged: config.is_skip_unchanged(),
             config: Some(config),
+            carry_forward: BTreeSet::new(),
         }
     }
 
-    /// Executes all plugins and creates a snapshot
-    pub async fn execute_snapshot(&self) -> Result<PathBuf> {
+    /// Skip creating a snapshot when plugin output matches the latest snapshot
+    pub fn with_skip_unchanged(mut self, skip_unchanged: bool) -> Self {
+        self.skip_unchanged = skip_unchanged;
+        self
+    }
+
+    /// Copy the output of these plugins from the latest snapshot instead of
+    /// running them, for runs that only refresh the other plugins
+    pub fn with_carried_forward(mut self, plugins: BTreeSet<String>) -> Self {
+        self.carry_forward = plugins;
+        self
+    }
+
+    /// Executes all plugins, creates a snapshot and returns the run report
+    pub async fn execute_snapshot_with_report(&self) -> Result<RunReport> {
         info!("Starting snapshot execution");
 
+        // Files hashed by plugins are not hashed again for the directory checksum
+        let hash_algorithm = self
+            .config
+            .as_ref()
+            .map(|c| c.get_hash_algorithm())
+            .unwrap_or_default();
+        let hashes = FileHashes::new(hash_algorithm);
+
+        let carried = Arc::new(self.carried_outputs().await);
+
+        // Compute plugin output up front when unchanged snapshots are skipped
+        let probe = if self.skip_unchanged {
+            self.probe_changes(&carried).await?
+        } else {
+            None
+        };
+        if let Some(probe) = probe.as_ref().filter(|probe| !probe.changed) {
+            return self.record_unchanged(probe).await;
+        }
+
         // Create snapshot directory
         let snapshot_dir = self.snapshot_manager.create_snapshot_dir().await?;
+        let mut precomputed = self
+            .precomputed_plugins(probe.as_ref(), &snapshot_dir)
+            .await;
         let snapshot_name = snapshot_dir
             .file_name()
             .and_then(|n| n.to_str())
@@ -47,6 +93,8 @@ impl SnapshotExecutor {
 
         info!("Created snapshot directory: {}", snapshot_dir.display());
 
+        let mut report = RunReport::new(snapshot_name.clone(), snapshot_dir.clone());
+
         // Set up hooks manager and context
         let hooks_config = self
             .config
@@ -61,23 +109,46 @@ impl SnapshotExecutor {
         if let Some(config) = &self.config {
             let pre_snapshot_hooks = config.get_global_pre_snapshot_hooks();
             if !pre_snapshot_hooks.is_empty() {
-                hook_manager
+                let hook_results = hook_manager
                     .execute_hooks(&pre_snapshot_hooks, &HookType::PreSnapshot, &hook_context)
                     .await;
+                report.hooks.extend(HookReport::from_results(
+                    &HookType::PreSnapshot,
+                    &hook_results,
+                ));
             }
         }
 
         // Create initial metadata
         let mut metadata = self.snapshot_manager.create_metadata();
+        metadata.hash_algorithm = hash_algorithm;
+        metadata.profile = self
+            .config
+            .as_ref()
+            .and_then(|config| config.active_profile.clone());
 
         // Execute all plugins concurrently
         let plugins = self.registry.plugins();
         let mut plugin_tasks = Vec::new();
 
         for (plugin_name, plugin) in plugins {
-            let plugin_clone = Arc::clone(plugin);
+            if carried.contains(plugin_name) {
+                let plugin_name_clone = plugin_name.clone();
+                let carried = Arc::clone(&carried);
+                let snapshot_dir_clone = snapshot_dir.clone();
+                let task = tokio::spawn(async move {
+                    Self::carry_forward_plugin(&plugin_name_clone, &carried, &snapshot_dir_clone)
+                        .await
+                });
+                plugin_tasks.push((plugin_name.clone(), task));
+                continue;
+            }
+
+            let plugin_clone = precomputed
+                .remove(plugin_name)
+                .unwrap_or_else(|| Arc::clone(plugin));
             let plugin_name_clone = plugin_name.clone();
-            let snapshot_dir_clone = snapshot_dir.clone();
+            let context = ExecutionContext::new(snapshot_dir.clone()).with_hashes(hashes.clone());
             let snapshot_manager_clone = self.snapshot_manager.clone();
             let config_clone = self.config.clone();
             let hook_manager_clone = HookManager::new(hooks_config.clone());
@@ -87,7 +158,7 @@ impl SnapshotExecutor {
                 Self::execute_plugin_with_hooks(
                     plugin_name_clone,
                     plugin_clone,
-                    &snapshot_dir_clone,
+                    &context,
                     &snapshot_manager_clone,
                     config_clone.as_deref(),
                     hook_manager_clone,
@@ -96,43 +167,46 @@ impl SnapshotExecutor {
                 .await
             });
 
-            plugin_tasks.push(task);
+            plugin_tasks.push((plugin_name.clone(), task));
         }
 
         // Wait for all plugins to complete
-        let mut results = Vec::new();
-        for task in plugin_tasks {
+        for (plugin_name, task) in plugin_tasks {
             match task.await {
-                Ok(result) => {
-                    match result {
-                        Ok(plugin_result) => {
-                            results.push(plugin_result);
-                        }
-                        Err(e) => {
-                            error!("Plugin execution failed: {}", e);
-                            // Create error result for failed plugin
-                            results.push(PluginResult {
-                                plugin_name: "unknown".to_string(),
-                                content: String::new(),
-                                checksum: String::new(),
-                                success: false,
-                                error_message: Some(e.to_string()),
-                            });
-                        }
-                    }
+                Ok(Ok(plugin_report)) => {
+                    report.plugins.push(plugin_report);
+                }
+                Ok(Err(e)) => {
+                    error!("Plugin execution failed for {}: {}", plugin_name, e);
+                    report
+                        .plugins
+                        .push(PluginReport::failed(&plugin_name, e.to_string()));
                 }
                 Err(e) => {
-                    error!("Plugin task failed: {}", e);
+                    error!("Plugin task failed for {}: {}", plugin_name, e);
+                    report
+                        .plugins
+                        .push(PluginReport::failed(&plugin_name, e.to_string()));
                 }
             }
         }
 
         // Update metadata with plugin results
-        for result in &results {
-            if result.success {
+        for plugin_report in &report.plugins {
+            if let (true, Some(checksum)) = (plugin_report.has_output(), &plugin_report.checksum) {
                 metadata
                     .checksums
-                    .insert(result.plugin_name.clone(), result.checksum.clone());
+                    .insert(plugin_report.plugin_name.clone(), checksum.clone());
+
+                // Record single output files so the checksum index can find them
+                if let [path] = plugin_report.output_paths.as_slice() {
+                    if let (true, Ok(relative)) = (path.is_file(), path.strip_prefix(&snapshot_dir))
+                    {
+                        metadata
+                            .files
+                            .insert(plugin_report.plugin_name.clone(), relative.to_path_buf());
+                    }
+                }
             }
         }
 
@@ -143,38 +217,60 @@ impl SnapshotExecutor {
 
         // Finalize snapshot (calculate directory checksum)
         self.snapshot_manager
-            .finalize_snapshot(&snapshot_dir)
+            .finalize_snapshot(&snapshot_dir, &hashes)
             .await?;
 
         // Execute post-snapshot hooks (global)
         if let Some(config) = &self.config {
             let post_snapshot_hooks = config.get_global_post_snapshot_hooks();
             if !post_snapshot_hooks.is_empty() {
-                let final_context = hook_context.with_file_count(results.len());
-                hook_manager
+                let final_context = hook_context
+                    .with_file_count(report.plugins.len())
+                    .with_variable("changed".to_string(), "true".to_string());
+                let hook_results = hook_manager
                     .execute_hooks(
                         &post_snapshot_hooks,
                         &HookType::PostSnapshot,
                         &final_context,
                     )
                     .await;
+                report.hooks.extend(HookReport::from_results(
+                    &HookType::PostSnapshot,
+                    &hook_results,
+                ));
             }
         }
 
+        // Record plugins excluded by the plugin selection
+        report.plugins.extend(
+            self.registry
+                .skipped_plugins()
+                .iter()
+                .map(|name| PluginReport::skipped(name)),
+        );
+
+        // Save the run report alongside the snapshot metadata
+        report.finish();
+        report.save(&snapshot_dir).await?;
+
         info!("Snapshot execution completed: {}", snapshot_dir.display());
-        Ok(snapshot_dir)
+        Ok(report)
     }
 
     /// Executes a single plugin with hooks and checksum optimization
     pub async fn execute_plugin_with_hooks(
         plugin_name: String,
         plugin: Arc<dyn Plugin>,
-        snapshot_dir: &Path,
+        context: &ExecutionContext,
         snapshot_manager: &SnapshotManager,
         _config: Option<&Config>,
         hook_manager: HookManager,
         hook_context: HookContext,
-    ) -> Result<PluginResult> {
+    ) -> Result<PluginReport> {
+        let start_time = Instant::now();
+        let snapshot_dir = context.snapshot_dir.as_path();
+        let mut hook_reports = Vec::new();
+
         info!(
             "{} Executing plugin: {}",
             SYMBOL_CONTENT_PACKAGE, plugin_name
@@ -186,28 +282,43 @@ impl SnapshotExecutor {
         // Execute pre-plugin hooks from plugin's own configuration
         let plugin_hooks = plugin.get_hooks();
         if !plugin_hooks.is_empty() {
-            hook_manager
+            let hook_results = hook_manager
                 .execute_hooks(&plugin_hooks, &HookType::PrePlugin, &plugin_hook_context)
                 .await;
+            hook_reports.extend(HookReport::from_results(
+                &HookType::PrePlugin,
+                &hook_results,
+            ));
         }
 
         // Validate plugin can run
         if let Err(e) = plugin.validate().await {
-            warn!("Plugin validation failed for {}: {}", plugin_name, e);
-            return Ok(PluginResult {
+            let status = if NotApplicable::is_in(&e) {
+                info!(
+                    "{} Plugin {} is not applicable: {}",
+                    SYMBOL_ACTION_BLOCK, plugin_name, e
+                );
+                PluginStatus::NotApplicable
+            } else {
+                warn!("Plugin validation failed for {}: {}", plugin_name, e);
+                PluginStatus::Failed
+            };
+            let result = PluginResult {
                 plugin_name: plugin_name.clone(),
                 content: String::new(),
                 checksum: String::new(),
-                success: false,
+                status,
                 error_message: Some(format!("Validation failed: {e}")),
-            });
-        }
+            };
 
-        // Set environment variable for snapshot directory (for plugins that need it)
-        std::env::set_var("DOTSNAPSHOT_SNAPSHOT_DIR", snapshot_dir);
+            let mut plugin_report = PluginReport::from_result(&result);
+            plugin_report.hooks = hook_reports;
+            plugin_report.duration_ms = start_time.elapsed().as_millis() as u64;
+            return Ok(plugin_report);
+        }
 
         // Execute plugin to get content
-        let content = match plugin.execute().await {
+        let content = match plugin.execute_with(context).await {
             Ok(content) => content,
             Err(e) => {
                 error!("Plugin execution failed for {}: {}", plugin_name, e);
@@ -217,51 +328,65 @@ impl SnapshotExecutor {
                 if !plugin_hooks.is_empty() {
                     let error_context =
                         plugin_hook_context.with_variable("error".to_string(), e.to_string());
-                    hook_manager
+                    let hook_results = hook_manager
                         .execute_hooks(&plugin_hooks, &HookType::PostPlugin, &error_context)
                         .await;
+                    hook_reports.extend(HookReport::from_results(
+                        &HookType::PostPlugin,
+                        &hook_results,
+                    ));
                 }
 
-                return Ok(PluginResult {
+                let result = PluginResult {
                     plugin_name: plugin_name.clone(),
                     content: String::new(),
                     checksum: String::new(),
-                    success: false,
+                    status: PluginStatus::Failed,
                     error_message: Some(e.to_string()),
-                });
+                };
+
+                let mut plugin_report = PluginReport::from_result(&result);
+                plugin_report.hooks = hook_reports;
+                plugin_report.duration_ms = start_time.elapsed().as_millis() as u64;
+                return Ok(plugin_report);
             }
         };
 
         // Calculate checksum
-        let checksum = calculate_checksum(&content);
+        let checksum = plugin.output_checksum(&content);
 
-        // Check if we can reuse existing file with same checksum
-        let output_file_for_checksum =
+        // Determine output path for hooks (even if we don't save for static files)
+        let output_file =
             PluginRegistry::get_plugin_output_file_from_plugin(plugin.as_ref(), &plugin_name);
-        if let Ok(Some(_existing_file)) = snapshot_manager
-            .find_file_by_checksum(
-                &plugin_name,
-                &output_file_for_checksum,
-                &checksum,
-                snapshot_dir,
-            )
-            .await
-        {
-            info!(
-                "Reusing existing file for plugin {} (checksum match)",
-                plugin_name
-            );
+        let output_path = if let Some(custom_path) = plugin.get_target_path() {
+            snapshot_dir.join(custom_path).join(&output_file)
+        } else {
+            snapshot_dir.join(&output_file)
+        };
 
-            // Copy file from latest snapshot
-            if snapshot_manager
-                .copy_from_latest(&plugin_name, &output_file_for_checksum, snapshot_dir)
+        // Reuse an existing file with the same checksum from any earlier snapshot
+        if !plugin.creates_own_output_files() {
+            if let Some(source) = snapshot_manager
+                .copy_from_latest(
+                    &plugin_name,
+                    &output_file,
+                    &checksum,
+                    snapshot_dir,
+                    &output_path,
+                )
                 .await?
             {
+                info!(
+                    "Reusing existing file for plugin {} (checksum match with {})",
+                    plugin_name,
+                    source.display()
+                );
+
                 let result = PluginResult {
                     plugin_name: plugin_name.clone(),
                     content,
                     checksum,
-                    success: true,
+                    status: PluginStatus::Reused,
                     error_message: None,
                 };
 
@@ -270,27 +395,31 @@ impl SnapshotExecutor {
                 if !plugin_hooks.is_empty() {
                     let success_context = plugin_hook_context
                         .with_file_count(1)
-                        .with_variable("reused".to_string(), "true".to_string());
-                    hook_manager
+                        .with_variable("reused".to_string(), "true".to_string())
+                        .with_variable(
+                            "output_path".to_string(),
+                            output_path.to_string_lossy().to_string(),
+                        );
+                    let hook_results = hook_manager
                         .execute_hooks(&plugin_hooks, &HookType::PostPlugin, &success_context)
                         .await;
+                    hook_reports.extend(HookReport::from_results(
+                        &HookType::PostPlugin,
+                        &hook_results,
+                    ));
                 }
 
-                return Ok(result);
+                let mut plugin_report = PluginReport::from_result(&result);
+                plugin_report.bytes_written = path_size(&output_path);
+                plugin_report.output_paths = vec![output_path];
+                plugin_report.hooks = hook_reports;
+                plugin_report.duration_ms = start_time.elapsed().as_millis() as u64;
+                return Ok(plugin_report);
             }
         }
 
-        // Determine output path for hooks (even if we don't save for static files)
-        let output_file =
-            PluginRegistry::get_plugin_output_file_from_plugin(plugin.as_ref(), &plugin_name);
-        let output_path = if let Some(custom_path) = plugin.get_target_path() {
-            snapshot_dir.join(custom_path).join(&output_file)
-        } else {
-            snapshot_dir.join(&output_file)
-        };
-
         // Some plugins handle their own file operations, skip output file creation for those
-        if !plugin.creates_own_output_files() {
+        let (output_paths, bytes_written) = if !plugin.creates_own_output_files() {
             // Create parent directory if it doesn't exist
             if let Some(parent) = output_path.parent() {
                 async_fs::create_dir_all(parent).await.context(format!(
@@ -301,7 +430,15 @@ impl SnapshotExecutor {
             async_fs::write(&output_path, &content)
                 .await
                 .context(format!("Failed to write output for plugin {plugin_name}"))?;
-        }
+
+            (vec![output_path.clone()], content.len() as u64)
+        } else if let Some(own_dir) = plugin.get_own_output_dir() {
+            let own_path = snapshot_dir.join(own_dir);
+            let bytes = path_size(&own_path);
+            (vec![own_path], bytes)
+        } else {
+            (Vec::new(), 0)
+        };
 
         info!(
             "{} Plugin {} completed successfully",
@@ -312,7 +449,7 @@ impl SnapshotExecutor {
             plugin_name: plugin_name.clone(),
             content,
             checksum,
-            success: true,
+            status: PluginStatus::Succeeded,
             error_message: None,
         };
 
@@ -323,12 +460,21 @@ impl SnapshotExecutor {
                 "output_path".to_string(),
                 output_path.to_string_lossy().to_string(),
             );
-            hook_manager
+            let hook_results = hook_manager
                 .execute_hooks(&plugin_hooks, &HookType::PostPlugin, &success_context)
                 .await;
+            hook_reports.extend(HookReport::from_results(
+                &HookType::PostPlugin,
+                &hook_results,
+            ));
         }
 
-        Ok(result)
+        let mut plugin_report = PluginReport::from_result(&result);
+        plugin_report.output_paths = output_paths;
+        plugin_report.bytes_written = bytes_written;
+        plugin_report.hooks = hook_reports;
+        plugin_report.duration_ms = start_time.elapsed().as_millis() as u64;
+        Ok(plugin_report)
     }
 }
 
@@ -339,5 +485,9 @@ impl Clone for SnapshotManager {
     }
 }
 
+pub mod carry;
+pub mod dry_run;
+pub mod unchanged;
+
 #[cfg(test)]
 pub mod tests;
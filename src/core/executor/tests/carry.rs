@@ -0,0 +1,81 @@
+//! Tests for carrying plugin output forward from the latest snapshot
+
+#[cfg(test)]
+mod tests {
+    use crate::core::executor::SnapshotExecutor;
+    use crate::core::plugin::{PluginRegistry, PluginStatus};
+    use crate::core::snapshot::SnapshotManager;
+    use anyhow::Result;
+    use std::collections::BTreeSet;
+    use std::path::Path;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    use crate::core::executor::tests::TestPlugin;
+
+    fn executor(output_dir: &Path, first: &str, second: &str) -> SnapshotExecutor {
+        let mut registry = PluginRegistry::new();
+        registry.add_plugin(
+            "first_plugin".to_string(),
+            Arc::new(TestPlugin::new(first.to_string())),
+        );
+        registry.add_plugin(
+            "second_plugin".to_string(),
+            Arc::new(TestPlugin::new(second.to_string())),
+        );
+        SnapshotExecutor::new(Arc::new(registry), output_dir.to_path_buf())
+    }
+
+    /// Test snapshotting some plugins and carrying the others forward
+    /// Verifies carried output is copied unchanged and recorded as reused
+    #[tokio::test]
+    async fn test_carry_forward_unaffected_plugins() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let output_dir = temp_dir.path().join("snapshots");
+
+        executor(&output_dir, "first", "second")
+            .execute_snapshot()
+            .await?;
+
+        // The carried plugin's current output is ignored
+        let report = executor(&output_dir, "first changed", "second changed")
+            .with_skip_unchanged(true)
+            .with_carried_forward(BTreeSet::from(["second_plugin".to_string()]))
+            .execute_snapshot_with_report()
+            .await?;
+        assert!(report.changed);
+        assert_eq!(
+            std::fs::read_to_string(report.snapshot_dir.join("first_plugin.txt"))?,
+            "first changed"
+        );
+        assert_eq!(
+            std::fs::read_to_string(report.snapshot_dir.join("second_plugin.txt"))?,
+            "second"
+        );
+
+        let carried = report
+            .plugins
+            .iter()
+            .find(|p| p.plugin_name == "second_plugin")
+            .unwrap();
+        assert_eq!(carried.status, PluginStatus::Reused);
+
+        let metadata = SnapshotManager::new(output_dir.clone())
+            .load_metadata(&report.snapshot_dir)
+            .await?;
+        assert_eq!(
+            metadata.checksums.get("second_plugin"),
+            Some(&crate::core::checksum::calculate_checksum("second"))
+        );
+
+        // Nothing changed among the plugins that run
+        let report = executor(&output_dir, "first changed", "second changed again")
+            .with_skip_unchanged(true)
+            .with_carried_forward(BTreeSet::from(["second_plugin".to_string()]))
+            .execute_snapshot_with_report()
+            .await?;
+        assert!(!report.changed);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+//! Tests for snapshot dry-run planning
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::core::executor::dry_run::PlannedAction;
+    use crate::core::executor::SnapshotExecutor;
+    use crate::core::plugin::PluginRegistry;
+    use anyhow::Result;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    use crate::core::executor::tests::TestPlugin;
+
+    /// Test that planning a snapshot writes nothing to the output directory
+    /// Verifies actions and output locations for capturing and invalid plugins
+    #[tokio::test]
+    async fn test_plan_snapshot_writes_nothing() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let output_dir = temp_dir.path().join("snapshots");
+
+        let mut registry = PluginRegistry::new();
+        registry.add_plugin(
+            "good_plugin".to_string(),
+            Arc::new(TestPlugin::new("content".to_string())),
+        );
+        registry.add_plugin(
+            "invalid_plugin".to_string(),
+            Arc::new(TestPlugin::new(String::new()).with_validation_error("bad".to_string())),
+        );
+        registry.add_plugin(
+            "own_files_plugin".to_string(),
+            Arc::new(TestPlugin::new(String::new()).with_custom_file_handling()),
+        );
+
+        let executor = SnapshotExecutor::with_config(
+            Arc::new(registry),
+            output_dir.clone(),
+            Arc::new(Config::default()),
+        );
+        let plan = executor.plan_snapshot().await?;
+
+        assert!(!output_dir.exists());
+        assert!(plan.latest_snapshot.is_none());
+
+        let good = plan
+            .plugins
+            .iter()
+            .find(|p| p.plugin_name == "good_plugin")
+            .unwrap();
+        assert_eq!(good.action, PlannedAction::Capture);
+        assert_eq!(good.output, Some(PathBuf::from("good_plugin.txt")));
+
+        let invalid = plan
+            .plugins
+            .iter()
+            .find(|p| p.plugin_name == "invalid_plugin")
+            .unwrap();
+        assert_eq!(invalid.action, PlannedAction::Invalid);
+        assert!(invalid.error_message.as_deref().unwrap().contains("bad"));
+
+        // Files of plugins that can't list them in advance are flagged, not empty
+        let own_files = plan
+            .plugins
+            .iter()
+            .find(|p| p.plugin_name == "own_files_plugin")
+            .unwrap();
+        assert_eq!(own_files.action, PlannedAction::Capture);
+        assert!(own_files.files.is_none());
+        assert!(own_files.note.is_some());
+
+        let table = plan.to_table();
+        assert!(table.contains("DRY RUN"));
+        assert!(table.contains("good_plugin.txt"));
+
+        Ok(())
+    }
+
+    /// Test that unchanged plugin output is planned as reused
+    /// Verifies checksum matching against the latest snapshot during a dry run
+    #[tokio::test]
+    async fn test_plan_snapshot_detects_reuse() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        let mut registry = PluginRegistry::new();
+        registry.add_plugin(
+            "same_plugin".to_string(),
+            Arc::new(TestPlugin::new("unchanged".to_string())),
+        );
+
+        let executor = SnapshotExecutor::with_config(
+            Arc::new(registry),
+            temp_dir.path().to_path_buf(),
+            Arc::new(Config::default()),
+        );
+        let snapshot_dir = executor.execute_snapshot().await?;
+
+        let plan = executor.plan_snapshot().await?;
+        assert_eq!(plan.latest_snapshot, Some(snapshot_dir));
+        assert_eq!(plan.plugins[0].action, PlannedAction::Reuse);
+
+        // Only the snapshot created above exists
+        let snapshots = std::fs::read_dir(temp_dir.path())?
+            .filter(|entry| entry.as_ref().is_ok_and(|e| e.path().is_dir()))
+            .count();
+        assert_eq!(snapshots, 1);
+
+        Ok(())
+    }
+}
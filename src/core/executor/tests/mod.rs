@@ -4,11 +4,15 @@ use crate::core::plugin::Plugin;
 use anyhow::Result;
 use async_trait::async_trait;
 
+pub mod carry;
+pub mod dry_run;
 pub mod hooks;
 pub mod performance;
 pub mod plugins;
+pub mod report;
 pub mod snapshots;
 pub mod test_utils;
+pub mod unchanged;
 
 // Test-only symbol for mock plugins
 const SYMBOL_ACTION_TEST: &str = "🧪";
@@ -4,7 +4,7 @@
 mod tests {
     use crate::config::Config;
     use crate::core::executor::SnapshotExecutor;
-    use crate::core::plugin::{Plugin, PluginRegistry};
+    use crate::core::plugin::{ExecutionContext, Plugin, PluginRegistry};
     use anyhow::Result;
     use async_trait::async_trait;
     use std::sync::Arc;
@@ -165,10 +165,10 @@ mod tests {
         Ok(())
     }
 
-    /// Test plugin environment variable setup
-    /// Verifies that the snapshot directory is made available to plugins via environment
+    /// Test plugin execution context setup
+    /// Verifies that the snapshot directory is passed to plugins in their context
     #[tokio::test]
-    async fn test_plugin_environment_variable() -> Result<()> {
+    async fn test_plugin_execution_context() -> Result<()> {
         struct EnvTestPlugin;
 
         #[async_trait]
@@ -182,9 +182,11 @@ mod tests {
             }
 
             async fn execute(&self) -> Result<String> {
-                let snapshot_dir = std::env::var("DOTSNAPSHOT_SNAPSHOT_DIR")
-                    .map_err(|_| anyhow::anyhow!("Environment variable not set"))?;
-                Ok(format!("Snapshot dir: {snapshot_dir}"))
+                Err(anyhow::anyhow!("Executed without a context"))
+            }
+
+            async fn execute_with(&self, context: &ExecutionContext) -> Result<String> {
+                Ok(format!("Snapshot dir: {}", context.snapshot_dir.display()))
             }
 
             async fn validate(&self) -> Result<()> {
@@ -0,0 +1,197 @@
+//! Tests for the run report produced by snapshot execution
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::core::executor::SnapshotExecutor;
+    use crate::core::plugin::{NotApplicable, Plugin, PluginRegistry, PluginStatus};
+    use crate::core::report::{RunReport, REPORT_FILE_NAME};
+    use anyhow::Result;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+    use tokio::fs as async_fs;
+
+    use crate::core::executor::tests::TestPlugin;
+
+    /// Test that the run report captures per-plugin status and output details
+    /// Verifies that each plugin is reported under its own name with the
+    /// correct status, output path and byte count
+    #[tokio::test]
+    async fn test_report_per_plugin_status() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        let mut registry = PluginRegistry::new();
+        registry.add_plugin(
+            "good_plugin".to_string(),
+            Arc::new(TestPlugin::new("hello".to_string())),
+        );
+        registry.add_plugin(
+            "broken_plugin".to_string(),
+            Arc::new(TestPlugin::new(String::new()).with_execution_failure()),
+        );
+        registry.add_plugin(
+            "invalid_plugin".to_string(),
+            Arc::new(
+                TestPlugin::new(String::new()).with_validation_error("missing tool".to_string()),
+            ),
+        );
+
+        let executor = SnapshotExecutor::with_config(
+            Arc::new(registry),
+            temp_dir.path().to_path_buf(),
+            Arc::new(Config::default()),
+        );
+        let report = executor.execute_snapshot_with_report().await?;
+
+        let find = |name: &str| {
+            report
+                .plugins
+                .iter()
+                .find(|p| p.plugin_name == name)
+                .unwrap_or_else(|| panic!("missing report for {name}"))
+        };
+
+        let good = find("good_plugin");
+        assert_eq!(good.status, PluginStatus::Succeeded);
+        assert_eq!(good.bytes_written, 5);
+        assert_eq!(
+            good.output_paths,
+            vec![report.snapshot_dir.join("good_plugin.txt")]
+        );
+
+        let broken = find("broken_plugin");
+        assert_eq!(broken.status, PluginStatus::Failed);
+        assert!(broken.error_message.is_some());
+
+        let invalid = find("invalid_plugin");
+        assert_eq!(invalid.status, PluginStatus::Failed);
+        assert!(invalid
+            .error_message
+            .as_deref()
+            .unwrap()
+            .contains("missing tool"));
+
+        assert!(report.plugins.iter().all(|p| p.plugin_name != "unknown"));
+        assert_eq!(report.summary.total, 3);
+        assert_eq!(report.summary.succeeded, 1);
+        assert_eq!(report.summary.failed, 2);
+
+        Ok(())
+    }
+
+    /// Test that plugins whose tool is missing are reported as not applicable
+    /// Verifies that a NotApplicable validation error is not counted as a failure
+    #[tokio::test]
+    async fn test_report_not_applicable_status() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        let mut registry = PluginRegistry::new();
+        registry.add_plugin("missing_tool".to_string(), Arc::new(MissingToolPlugin));
+
+        let executor = SnapshotExecutor::with_config(
+            Arc::new(registry),
+            temp_dir.path().to_path_buf(),
+            Arc::new(Config::default()),
+        );
+        let report = executor.execute_snapshot_with_report().await?;
+
+        assert_eq!(report.plugins[0].status, PluginStatus::NotApplicable);
+        assert_eq!(report.summary.not_applicable, 1);
+        assert_eq!(report.summary.failed, 0);
+        assert!(!report.snapshot_dir.join("missing_tool.txt").exists());
+
+        Ok(())
+    }
+
+    /// Test that the run report is persisted in the snapshot metadata directory
+    /// Verifies that report.json exists and deserializes to the returned report
+    #[tokio::test]
+    async fn test_report_saved_to_snapshot() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        let mut registry = PluginRegistry::new();
+        registry.add_plugin(
+            "test_plugin".to_string(),
+            Arc::new(TestPlugin::new("content".to_string())),
+        );
+
+        let executor = SnapshotExecutor::with_config(
+            Arc::new(registry),
+            temp_dir.path().to_path_buf(),
+            Arc::new(Config::default()),
+        );
+        let report = executor.execute_snapshot_with_report().await?;
+
+        let report_path = report.snapshot_dir.join(".snapshot").join(REPORT_FILE_NAME);
+        assert!(report_path.exists());
+
+        let saved: RunReport =
+            serde_json::from_str(&async_fs::read_to_string(&report_path).await?)?;
+        assert_eq!(saved.snapshot_name, report.snapshot_name);
+        assert_eq!(saved.plugins.len(), 1);
+        assert_eq!(saved.plugins[0].plugin_name, "test_plugin");
+
+        Ok(())
+    }
+
+    /// Test that unchanged plugin output is reported as reused
+    /// Verifies that a second snapshot with identical content marks the plugin as reused
+    #[tokio::test]
+    async fn test_report_reused_status() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        let mut registry = PluginRegistry::new();
+        registry.add_plugin(
+            "test_plugin".to_string(),
+            Arc::new(TestPlugin::new("same content".to_string())),
+        );
+        let registry = Arc::new(registry);
+
+        let executor = SnapshotExecutor::with_config(
+            Arc::clone(&registry),
+            temp_dir.path().to_path_buf(),
+            Arc::new(Config::default()),
+        );
+        let first = executor.execute_snapshot_with_report().await?;
+        assert_eq!(first.plugins[0].status, PluginStatus::Succeeded);
+
+        // Snapshot directories are named by second
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+
+        let second = executor.execute_snapshot_with_report().await?;
+        assert_eq!(second.plugins[0].status, PluginStatus::Reused);
+        assert_eq!(second.summary.reused, 1);
+
+        Ok(())
+    }
+
+    /// Plugin whose required tool is not installed
+    struct MissingToolPlugin;
+
+    #[async_trait::async_trait]
+    impl Plugin for MissingToolPlugin {
+        fn description(&self) -> &str {
+            "Plugin requiring a missing tool"
+        }
+
+        fn icon(&self) -> &str {
+            "🧪"
+        }
+
+        async fn execute(&self) -> Result<String> {
+            Ok("unreachable".to_string())
+        }
+
+        async fn validate(&self) -> Result<()> {
+            Err(NotApplicable("tool command not found".to_string()).into())
+        }
+
+        fn get_target_path(&self) -> Option<String> {
+            None
+        }
+
+        fn get_output_file(&self) -> Option<String> {
+            None
+        }
+    }
+}
@@ -338,4 +338,55 @@ mod tests {
         // Verify executor is properly constructed (no panics or errors)
         assert!(executor.config().is_some());
     }
+
+    /// Test snapshots hashed with BLAKE3
+    /// Verifies the configured algorithm is recorded and used for the directory checksum
+    #[tokio::test]
+    async fn test_execute_snapshot_blake3() -> Result<()> {
+        use crate::config::SnapshotConfig;
+        use crate::core::checksum::{calculate_directory_checksum, HashAlgorithm};
+
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path().to_path_buf();
+
+        let mut registry = PluginRegistry::new();
+        registry.add_plugin(
+            "test_plugin".to_string(),
+            Arc::new(TestPlugin::new("content".to_string())),
+        );
+        let config = Config {
+            snapshot: Some(SnapshotConfig {
+                hash_algorithm: Some(HashAlgorithm::Blake3),
+                ..Default::default()
+            }),
+            ..Config::default()
+        };
+
+        let executor =
+            SnapshotExecutor::with_config(Arc::new(registry), base_path.clone(), Arc::new(config));
+        let snapshot_dir = executor.execute_snapshot().await?;
+
+        let metadata = SnapshotManager::new(base_path)
+            .load_metadata(&snapshot_dir)
+            .await?;
+        assert_eq!(metadata.hash_algorithm, HashAlgorithm::Blake3);
+        assert_eq!(
+            metadata.files.get("test_plugin"),
+            Some(&std::path::PathBuf::from("test_plugin.txt"))
+        );
+
+        // The report is written after finalizing, so hash without it
+        async_fs::remove_file(snapshot_dir.join(".snapshot").join("report.json")).await?;
+        let mut expected = metadata.clone();
+        expected.directory_checksum = String::new();
+        SnapshotManager::new(temp_dir.path().to_path_buf())
+            .save_metadata(&snapshot_dir, &expected)
+            .await?;
+        assert_eq!(
+            metadata.directory_checksum,
+            calculate_directory_checksum(&snapshot_dir, HashAlgorithm::Blake3)?
+        );
+
+        Ok(())
+    }
 }
@@ -15,9 +15,16 @@ impl SnapshotExecutor {
             registry,
             snapshot_manager: SnapshotManager::new(base_path),
             config: None,
+            skip_unchanged: false,
+            carry_forward: Default::default(),
         }
     }
 
+    /// Executes all plugins and returns the created snapshot directory
+    pub async fn execute_snapshot(&self) -> anyhow::Result<PathBuf> {
+        Ok(self.execute_snapshot_with_report().await?.snapshot_dir)
+    }
+
     /// Get reference to the config for testing
     pub fn config(&self) -> Option<&Arc<Config>> {
         self.config.as_ref()
@@ -0,0 +1,342 @@
+//! Tests for skipping unchanged snapshots
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{Config, GlobalConfig, GlobalHooks};
+    use crate::core::executor::unchanged::{ChangeProbe, ProbedOutput};
+    use crate::core::executor::SnapshotExecutor;
+    use crate::core::hooks::HookAction;
+    use crate::core::plugin::{ExecutionContext, Plugin, PluginRegistry, PluginStatus};
+    use crate::core::snapshot::SnapshotManager;
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    use crate::core::executor::tests::TestPlugin;
+
+    fn executor(output_dir: &Path, content: &str) -> SnapshotExecutor {
+        let mut registry = PluginRegistry::new();
+        registry.add_plugin(
+            "test_plugin".to_string(),
+            Arc::new(TestPlugin::new(content.to_string())),
+        );
+        SnapshotExecutor::new(Arc::new(registry), output_dir.to_path_buf())
+            .with_skip_unchanged(true)
+    }
+
+    fn snapshot_count(output_dir: &Path) -> usize {
+        std::fs::read_dir(output_dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .count()
+    }
+
+    /// Test that a run with unchanged plugin output creates no snapshot
+    /// Verifies the no-change report points at the latest snapshot
+    #[tokio::test]
+    async fn test_skip_unchanged_snapshot() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let output_dir = temp_dir.path().join("snapshots");
+
+        let first = executor(&output_dir, "content")
+            .execute_snapshot_with_report()
+            .await?;
+        assert!(first.changed);
+        assert_eq!(snapshot_count(&output_dir), 1);
+
+        let second = executor(&output_dir, "content")
+            .execute_snapshot_with_report()
+            .await?;
+        assert!(!second.changed);
+        assert_eq!(second.snapshot_dir, first.snapshot_dir);
+        assert_eq!(snapshot_count(&output_dir), 1);
+        assert_eq!(second.summary.reused, 1);
+
+        let reused = second
+            .plugins
+            .iter()
+            .find(|p| p.plugin_name == "test_plugin")
+            .unwrap();
+        assert_eq!(
+            reused.output_paths,
+            vec![first.snapshot_dir.join("test_plugin.txt")]
+        );
+        assert!(second.to_table().contains("No changes since snapshot"));
+
+        Ok(())
+    }
+
+    /// Test that changed plugin output still creates a snapshot
+    /// Verifies the precomputed output is written without the scratch directory
+    #[tokio::test]
+    async fn test_skip_unchanged_detects_changes() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let output_dir = temp_dir.path().join("snapshots");
+
+        executor(&output_dir, "old content")
+            .execute_snapshot_with_report()
+            .await?;
+
+        let report = executor(&output_dir, "new content")
+            .execute_snapshot_with_report()
+            .await?;
+        assert!(report.changed);
+        assert_eq!(
+            std::fs::read_to_string(report.snapshot_dir.join("test_plugin.txt"))?,
+            "new content"
+        );
+
+        let metadata = SnapshotManager::new(output_dir.clone())
+            .load_metadata(&report.snapshot_dir)
+            .await?;
+        assert_eq!(
+            metadata.checksums.get("test_plugin"),
+            Some(&crate::core::checksum::calculate_checksum("new content"))
+        );
+        assert!(!output_dir
+            .join(crate::core::executor::unchanged::PROBE_DIR_NAME)
+            .exists());
+
+        Ok(())
+    }
+
+    /// Plugin writing its own output file and counting its executions
+    struct OwnFilesPlugin {
+        content: String,
+        executions: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Plugin for OwnFilesPlugin {
+        fn description(&self) -> &str {
+            "Own files plugin"
+        }
+
+        fn icon(&self) -> &str {
+            "📁"
+        }
+
+        async fn execute(&self) -> Result<String> {
+            Err(anyhow::anyhow!("Executed without a context"))
+        }
+
+        async fn execute_with(&self, context: &ExecutionContext) -> Result<String> {
+            self.executions.fetch_add(1, Ordering::SeqCst);
+            let own_dir = context.snapshot_dir.join("own");
+            std::fs::create_dir_all(&own_dir)?;
+            std::fs::write(own_dir.join("file.txt"), &self.content)?;
+            Ok(self.content.clone())
+        }
+
+        async fn validate(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn get_target_path(&self) -> Option<String> {
+            None
+        }
+
+        fn get_output_file(&self) -> Option<String> {
+            None
+        }
+
+        fn creates_own_output_files(&self) -> bool {
+            true
+        }
+
+        fn get_own_output_dir(&self) -> Option<String> {
+            Some("own".to_string())
+        }
+    }
+
+    /// Test that probed files of plugins creating their own output are kept
+    /// Verifies the plugin runs once and its files move into the new snapshot
+    #[tokio::test]
+    async fn test_skip_unchanged_promotes_own_output() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let output_dir = temp_dir.path().join("snapshots");
+        let executions = Arc::new(AtomicUsize::new(0));
+        let run = |content: &str| {
+            let mut registry = PluginRegistry::new();
+            registry.add_plugin(
+                "own_plugin".to_string(),
+                Arc::new(OwnFilesPlugin {
+                    content: content.to_string(),
+                    executions: Arc::clone(&executions),
+                }),
+            );
+            SnapshotExecutor::new(Arc::new(registry), output_dir.clone()).with_skip_unchanged(true)
+        };
+
+        run("old").execute_snapshot_with_report().await?;
+        assert_eq!(executions.load(Ordering::SeqCst), 1);
+
+        let report = run("new").execute_snapshot_with_report().await?;
+        assert!(report.changed);
+        assert_eq!(executions.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            std::fs::read_to_string(report.snapshot_dir.join("own").join("file.txt"))?,
+            "new"
+        );
+        assert!(!output_dir
+            .join(crate::core::executor::unchanged::PROBE_DIR_NAME)
+            .exists());
+
+        Ok(())
+    }
+
+    /// Plugin capturing a file that its hooks may rewrite
+    struct StateFilePlugin {
+        state: PathBuf,
+        hooks: Vec<HookAction>,
+    }
+
+    #[async_trait]
+    impl Plugin for StateFilePlugin {
+        fn description(&self) -> &str {
+            "State file plugin"
+        }
+
+        fn icon(&self) -> &str {
+            "📄"
+        }
+
+        async fn execute(&self) -> Result<String> {
+            Ok(std::fs::read_to_string(&self.state)?)
+        }
+
+        async fn validate(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn get_target_path(&self) -> Option<String> {
+            None
+        }
+
+        fn get_output_file(&self) -> Option<String> {
+            None
+        }
+
+        fn get_hooks(&self) -> Vec<HookAction> {
+            self.hooks.clone()
+        }
+    }
+
+    /// Test that hooks changing what a plugin captures are not skipped
+    /// Verifies pre-plugin and pre-snapshot hooks run before the output is compared
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_skip_unchanged_runs_pre_hooks_first() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new()?;
+        let output_dir = temp_dir.path().join("snapshots");
+        let state = temp_dir.path().join("state.txt");
+        let script = temp_dir.path().join("update.sh");
+        std::fs::write(
+            &script,
+            format!("#!/bin/sh\necho \"$1\" > '{}'\n", state.display()),
+        )?;
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))?;
+        let update = |content: &str| HookAction::Script {
+            command: script.to_string_lossy().to_string(),
+            args: vec![content.to_string()],
+            timeout: 10,
+            working_dir: None,
+            env_vars: HashMap::new(),
+        };
+        let run = |hooks: Vec<HookAction>, config: Config| {
+            let mut registry = PluginRegistry::new();
+            registry.add_plugin(
+                "state_plugin".to_string(),
+                Arc::new(StateFilePlugin {
+                    state: state.clone(),
+                    hooks,
+                }),
+            );
+            SnapshotExecutor::with_config(Arc::new(registry), output_dir.clone(), Arc::new(config))
+                .with_skip_unchanged(true)
+        };
+
+        std::fs::write(&state, "old\n")?;
+        run(Vec::new(), Config::default())
+            .execute_snapshot_with_report()
+            .await?;
+
+        let report = run(vec![update("plugin")], Config::default())
+            .execute_snapshot_with_report()
+            .await?;
+        assert!(report.changed);
+        assert_eq!(
+            std::fs::read_to_string(report.snapshot_dir.join("state_plugin.txt"))?,
+            "plugin\n"
+        );
+
+        let config = Config {
+            global: Some(GlobalConfig {
+                hooks: Some(GlobalHooks {
+                    pre_snapshot: vec![update("snapshot")],
+                    post_snapshot: Vec::new(),
+                }),
+            }),
+            ..Config::default()
+        };
+        let report = run(Vec::new(), config)
+            .execute_snapshot_with_report()
+            .await?;
+        assert!(report.changed);
+        assert_eq!(
+            std::fs::read_to_string(report.snapshot_dir.join("state_plugin.txt"))?,
+            "snapshot\n"
+        );
+
+        Ok(())
+    }
+
+    /// Test change detection against snapshot metadata
+    /// Verifies failures and outputs that disappeared count as changes
+    #[test]
+    fn test_change_probe_compare() {
+        let output = |name: &str, status: PluginStatus, checksum: Option<&str>| ProbedOutput {
+            plugin_name: name.to_string(),
+            status,
+            content: String::new(),
+            checksum: checksum.map(str::to_string),
+            duration_ms: 0,
+        };
+        let mut latest = SnapshotManager::new(std::path::PathBuf::from(".")).create_metadata();
+        latest.checksums.insert("a".to_string(), "sum".to_string());
+
+        assert!(!ChangeProbe::compare(
+            &[output("a", PluginStatus::Succeeded, Some("sum"))],
+            &latest
+        ));
+        assert!(ChangeProbe::compare(
+            &[output("a", PluginStatus::Succeeded, Some("other"))],
+            &latest
+        ));
+        assert!(ChangeProbe::compare(
+            &[output("a", PluginStatus::NotApplicable, None)],
+            &latest
+        ));
+        assert!(ChangeProbe::compare(
+            &[
+                output("a", PluginStatus::Succeeded, Some("sum")),
+                output("b", PluginStatus::Failed, None)
+            ],
+            &latest
+        ));
+        assert!(!ChangeProbe::compare(
+            &[
+                output("a", PluginStatus::Succeeded, Some("sum")),
+                output("b", PluginStatus::NotApplicable, None)
+            ],
+            &latest
+        ));
+    }
+}
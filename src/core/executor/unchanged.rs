@@ -0,0 +1,426 @@
+//! Change detection for snapshot runs that skip unchanged snapshots
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{info, warn};
+
+use super::carry::CarriedOutputs;
+use super::SnapshotExecutor;
+use crate::core::checksum::FileHashes;
+use crate::core::hooks::{HookAction, HookContext, HookManager, HookType};
+use crate::core::plugin::{ExecutionContext, FilePlan, NotApplicable, Plugin, PluginStatus};
+use crate::core::report::{path_size, HookReport, PluginReport, RunReport};
+use crate::core::snapshot::SnapshotMetadata;
+use crate::symbols::*;
+
+/// Directory (inside the output directory) where plugins creating their own
+/// output files write while outputs are compared
+pub const PROBE_DIR_NAME: &str = ".dotsnapshot-probe";
+
+/// Output computed for a single plugin before any snapshot directory exists
+#[derive(Debug, Clone)]
+pub struct ProbedOutput {
+    pub plugin_name: String,
+    pub status: PluginStatus,
+    /// Content returned by the plugin (empty unless it succeeded)
+    pub content: String,
+    pub checksum: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// Plugin outputs of a run compared against the latest snapshot
+#[derive(Debug)]
+pub struct ChangeProbe {
+    pub latest_snapshot: PathBuf,
+    pub outputs: Vec<ProbedOutput>,
+    pub changed: bool,
+}
+
+impl ChangeProbe {
+    /// Compare probed outputs with the latest snapshot's metadata
+    ///
+    /// The run is unchanged when every plugin either produced output with the
+    /// checksum recorded in the latest snapshot, or is not applicable and has
+    /// no output recorded there. Failed plugins always count as a change so the
+    /// failure is reported by a real run.
+    pub fn compare(outputs: &[ProbedOutput], latest: &SnapshotMetadata) -> bool {
+        outputs.iter().any(|output| match output.status {
+            PluginStatus::Failed => true,
+            PluginStatus::NotApplicable | PluginStatus::Skipped => {
+                latest.checksums.contains_key(&output.plugin_name)
+            }
+            PluginStatus::Succeeded | PluginStatus::Reused => {
+                latest.checksums.get(&output.plugin_name) != output.checksum.as_ref()
+            }
+        })
+    }
+
+    /// Plugin reports for a run that created no snapshot, pointing at the
+    /// unchanged output in the latest snapshot
+    pub fn unchanged_reports(&self, executor: &SnapshotExecutor) -> Vec<PluginReport> {
+        self.outputs
+            .iter()
+            .map(|output| {
+                let mut report = PluginReport::skipped(&output.plugin_name);
+                report.status = match output.status {
+                    PluginStatus::Succeeded => PluginStatus::Reused,
+                    status => status,
+                };
+                report.checksum = output.checksum.clone();
+                report.duration_ms = output.duration_ms;
+                if report.status == PluginStatus::Reused {
+                    let path =
+                        executor.latest_output_path(&output.plugin_name, &self.latest_snapshot);
+                    if let Some(path) = path {
+                        report.bytes_written = path_size(&path);
+                        report.output_paths = vec![path];
+                    }
+                }
+                report
+            })
+            .collect()
+    }
+}
+
+impl SnapshotExecutor {
+    /// Execute every plugin without writing a snapshot and compare the outputs
+    /// with the latest snapshot
+    ///
+    /// Returns `None` when there is no previous snapshot to compare against,
+    /// or when pre-snapshot hooks are configured, since they may change what
+    /// plugins capture. Plugins with their own hooks are not probed either and
+    /// make the run count as changed, so they only run after their pre-plugin
+    /// hooks. Plugins that create their own output files write into a scratch
+    /// directory inside the output directory. It is removed when nothing
+    /// changed, and otherwise moved into the new snapshot by
+    /// [`Self::precomputed_plugins`]. Plugins carried forward are not run and
+    /// count as unchanged.
+    pub async fn probe_changes(&self, carried: &CarriedOutputs) -> Result<Option<ChangeProbe>> {
+        let base_path = self.snapshot_manager.base_path().clone();
+        let Some(latest_snapshot) = self.snapshot_manager.find_latest_snapshot()? else {
+            return Ok(None);
+        };
+        let pre_snapshot_hooks = self
+            .config
+            .as_ref()
+            .map(|config| config.get_global_pre_snapshot_hooks())
+            .unwrap_or_default();
+        if !pre_snapshot_hooks.is_empty() {
+            info!("Pre-snapshot hooks are configured, creating a snapshot without comparing");
+            return Ok(None);
+        }
+        let latest = match self.snapshot_manager.load_metadata(&latest_snapshot).await {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                warn!(
+                    "Cannot compare against {}: {}",
+                    latest_snapshot.display(),
+                    e
+                );
+                return Ok(None);
+            }
+        };
+
+        info!(
+            "{} Comparing plugin outputs with {}",
+            SYMBOL_ACTION_SEARCH,
+            latest_snapshot.display()
+        );
+
+        // Output left behind by an interrupted run must not be mistaken for this one's
+        let probe_dir = base_path.join(PROBE_DIR_NAME);
+        Self::remove_probe_dir(&probe_dir).await;
+        tokio::fs::create_dir_all(&probe_dir).await?;
+        let hash_algorithm = self
+            .config
+            .as_ref()
+            .map(|c| c.get_hash_algorithm())
+            .unwrap_or_default();
+        let context =
+            ExecutionContext::new(probe_dir.clone()).with_hashes(FileHashes::new(hash_algorithm));
+
+        let mut outputs = Vec::new();
+        let mut probe_tasks = Vec::new();
+        let mut hooked = false;
+        for (plugin_name, plugin) in self.registry.plugins() {
+            if let Some(checksum) = carried.checksum(plugin_name) {
+                outputs.push(ProbedOutput {
+                    plugin_name: plugin_name.clone(),
+                    status: PluginStatus::Reused,
+                    content: String::new(),
+                    checksum: Some(checksum.to_string()),
+                    duration_ms: 0,
+                });
+                continue;
+            }
+            // Pre-plugin hooks run with the snapshot, so the output can't be known yet
+            if !plugin.get_hooks().is_empty() {
+                hooked = true;
+                continue;
+            }
+            let plugin = Arc::clone(plugin);
+            let name = plugin_name.clone();
+            let context = context.clone();
+            let task =
+                tokio::spawn(async move { Self::probe_plugin(name, plugin, &context).await });
+            probe_tasks.push((plugin_name.clone(), task));
+        }
+
+        for (plugin_name, task) in probe_tasks {
+            outputs.push(task.await.unwrap_or_else(|e| ProbedOutput {
+                plugin_name,
+                status: PluginStatus::Failed,
+                content: e.to_string(),
+                checksum: None,
+                duration_ms: 0,
+            }));
+        }
+
+        let changed = hooked || ChangeProbe::compare(&outputs, &latest);
+        if !changed {
+            Self::remove_probe_dir(&probe_dir).await;
+        }
+        Ok(Some(ChangeProbe {
+            latest_snapshot,
+            outputs,
+            changed,
+        }))
+    }
+
+    /// Report a run whose output matched the latest snapshot
+    ///
+    /// No snapshot directory is created and the latest snapshot is left
+    /// untouched. Pre-snapshot hooks only run when a snapshot is created;
+    /// post-snapshot hooks run against the latest snapshot with `{changed}`
+    /// set to `false`.
+    pub(super) async fn record_unchanged(&self, probe: &ChangeProbe) -> Result<RunReport> {
+        let snapshot_name = probe
+            .latest_snapshot
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        info!(
+            "{} No changes since snapshot {}, skipping snapshot creation",
+            SYMBOL_CONTENT_SKIP, snapshot_name
+        );
+
+        let mut report = RunReport::new(snapshot_name.clone(), probe.latest_snapshot.clone());
+        report.changed = false;
+        report.plugins = probe.unchanged_reports(self);
+
+        if let Some(config) = &self.config {
+            let post_snapshot_hooks = config.get_global_post_snapshot_hooks();
+            if !post_snapshot_hooks.is_empty() {
+                let hooks_config = config.get_hooks_config();
+                let hook_context = HookContext::new(
+                    snapshot_name,
+                    probe.latest_snapshot.clone(),
+                    hooks_config.clone(),
+                )
+                .with_file_count(0)
+                .with_variable("changed".to_string(), "false".to_string());
+                let hook_results = HookManager::new(hooks_config)
+                    .execute_hooks(&post_snapshot_hooks, &HookType::PostSnapshot, &hook_context)
+                    .await;
+                report.hooks.extend(HookReport::from_results(
+                    &HookType::PostSnapshot,
+                    &hook_results,
+                ));
+            }
+        }
+
+        report.plugins.extend(
+            self.registry
+                .skipped_plugins()
+                .iter()
+                .map(|name| PluginReport::skipped(name)),
+        );
+        report.finish();
+        Ok(report)
+    }
+
+    async fn remove_probe_dir(probe_dir: &Path) {
+        if !probe_dir.exists() {
+            return;
+        }
+        if let Err(e) = tokio::fs::remove_dir_all(probe_dir).await {
+            warn!("Failed to remove {}: {}", probe_dir.display(), e);
+        }
+    }
+
+    /// Validate and execute a single plugin without hooks
+    async fn probe_plugin(
+        plugin_name: String,
+        plugin: Arc<dyn Plugin>,
+        context: &ExecutionContext,
+    ) -> ProbedOutput {
+        let start_time = Instant::now();
+        let result = match plugin.validate().await {
+            Ok(()) => plugin.execute_with(context).await,
+            Err(e) => Err(e),
+        };
+
+        let (status, content, checksum) = match result {
+            Ok(content) => {
+                let checksum = plugin.output_checksum(&content);
+                (PluginStatus::Succeeded, content, Some(checksum))
+            }
+            Err(e) if NotApplicable::is_in(&e) => {
+                (PluginStatus::NotApplicable, String::new(), None)
+            }
+            Err(e) => (PluginStatus::Failed, e.to_string(), None),
+        };
+
+        ProbedOutput {
+            plugin_name,
+            status,
+            content,
+            checksum,
+            duration_ms: start_time.elapsed().as_millis() as u64,
+        }
+    }
+
+    /// Location of a plugin's output inside an existing snapshot
+    pub(super) fn latest_output_path(
+        &self,
+        plugin_name: &str,
+        snapshot_dir: &Path,
+    ) -> Option<PathBuf> {
+        let plugin = self.registry.get_plugin(plugin_name)?;
+        if plugin.creates_own_output_files() {
+            return plugin
+                .get_own_output_dir()
+                .map(|dir| snapshot_dir.join(dir))
+                .filter(|path| path.exists());
+        }
+
+        let output_file = crate::core::plugin::PluginRegistry::get_plugin_output_file_from_plugin(
+            plugin.as_ref(),
+            plugin_name,
+        );
+        let mut candidates = Vec::new();
+        if let Some(target_path) = plugin.get_target_path() {
+            candidates.push(snapshot_dir.join(target_path).join(&output_file));
+        }
+        candidates.push(snapshot_dir.join(".snapshot").join(&output_file));
+        candidates.push(snapshot_dir.join(&output_file));
+        candidates.into_iter().find(|path| path.exists())
+    }
+
+    /// Plugins to run for a changed snapshot, reusing probed output so that
+    /// plugins are not executed twice
+    ///
+    /// Files that plugins created in the scratch directory are moved into
+    /// `snapshot_dir`. Plugins whose files can't be moved are run again.
+    pub(super) async fn precomputed_plugins(
+        &self,
+        probe: Option<&ChangeProbe>,
+        snapshot_dir: &Path,
+    ) -> HashMap<String, Arc<dyn Plugin>> {
+        let Some(probe) = probe else {
+            return HashMap::new();
+        };
+        let probe_dir = self.snapshot_manager.base_path().join(PROBE_DIR_NAME);
+
+        let mut precomputed = HashMap::new();
+        for output in &probe.outputs {
+            if output.status != PluginStatus::Succeeded {
+                continue;
+            }
+            let Some(plugin) = self.registry.get_plugin(&output.plugin_name) else {
+                continue;
+            };
+            if plugin.creates_own_output_files() {
+                if let Some(own_dir) = plugin.get_own_output_dir() {
+                    let probed = probe_dir.join(&own_dir);
+                    let target = snapshot_dir.join(&own_dir);
+                    if probed.exists() {
+                        // A snapshot created within the same second is written again
+                        if target.is_dir() {
+                            let _ = tokio::fs::remove_dir_all(&target).await;
+                        }
+                        if let Err(e) = tokio::fs::rename(&probed, &target).await {
+                            warn!(
+                                "Running {} again, its probed output can't be moved: {}",
+                                output.plugin_name, e
+                            );
+                            continue;
+                        }
+                    }
+                }
+            }
+            let plugin: Arc<dyn Plugin> = Arc::new(PrecomputedPlugin {
+                inner: Arc::clone(plugin),
+                content: output.content.clone(),
+            });
+            precomputed.insert(output.plugin_name.clone(), plugin);
+        }
+
+        Self::remove_probe_dir(&probe_dir).await;
+        precomputed
+    }
+}
+
+/// Plugin wrapper returning output that was already computed
+struct PrecomputedPlugin {
+    inner: Arc<dyn Plugin>,
+    content: String,
+}
+
+#[async_trait]
+impl Plugin for PrecomputedPlugin {
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn icon(&self) -> &str {
+        self.inner.icon()
+    }
+
+    async fn execute(&self) -> Result<String> {
+        Ok(self.content.clone())
+    }
+
+    async fn validate(&self) -> Result<()> {
+        // Validated while probing
+        Ok(())
+    }
+
+    fn get_target_path(&self) -> Option<String> {
+        self.inner.get_target_path()
+    }
+
+    fn get_output_file(&self) -> Option<String> {
+        self.inner.get_output_file()
+    }
+
+    fn get_hooks(&self) -> Vec<HookAction> {
+        self.inner.get_hooks()
+    }
+
+    fn creates_own_output_files(&self) -> bool {
+        self.inner.creates_own_output_files()
+    }
+
+    fn get_own_output_dir(&self) -> Option<String> {
+        self.inner.get_own_output_dir()
+    }
+
+    async fn plan_files(&self) -> Result<Option<FilePlan>> {
+        self.inner.plan_files().await
+    }
+
+    fn output_checksum(&self, content: &str) -> String {
+        self.inner.output_checksum(content)
+    }
+
+    async fn watch_paths(&self) -> Result<Vec<PathBuf>> {
+        self.inner.watch_paths().await
+    }
+}
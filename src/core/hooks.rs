@@ -10,6 +10,7 @@ use tokio::process::Command;
 use tokio::time::timeout;
 use tracing::{debug, error, info, warn};
 
+use crate::core::paths::expand;
 use crate::symbols::*;
 
 /// Types of hooks that can be executed
@@ -123,7 +124,7 @@ fn default_log_level() -> String {
 }
 
 /// Configuration for hooks
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct HooksConfig {
     /// Directory where hook scripts are located
     #[serde(default = "default_scripts_dir")]
@@ -158,22 +159,6 @@ impl HooksConfig {
         // If relative path, resolve relative to scripts_dir
         self.scripts_dir.join(command)
     }
-
-    /// Expand tilde (~) to home directory if present
-    pub fn expand_tilde(path: &Path) -> PathBuf {
-        if let Some(path_str) = path.to_str() {
-            if let Some(stripped) = path_str.strip_prefix("~/") {
-                if let Some(home_dir) = dirs::home_dir() {
-                    return home_dir.join(stripped);
-                }
-            } else if path_str == "~" {
-                if let Some(home_dir) = dirs::home_dir() {
-                    return home_dir;
-                }
-            }
-        }
-        path.to_path_buf()
-    }
 }
 
 /// Context provided to hooks during execution
@@ -478,7 +463,7 @@ impl HookExecutor for DefaultHookExecutor {
 
                 // Resolve script path
                 let script_path = context.hooks_config.resolve_script_path(command);
-                let expanded_path = HooksConfig::expand_tilde(&script_path);
+                let expanded_path = expand(&script_path)?;
 
                 if !expanded_path.exists() {
                     return Err(anyhow::anyhow!(
@@ -490,7 +475,7 @@ impl HookExecutor for DefaultHookExecutor {
 
                 // Check working directory exists if specified
                 if let Some(dir) = working_dir {
-                    let expanded_dir = HooksConfig::expand_tilde(dir);
+                    let expanded_dir = expand(dir)?;
                     if !expanded_dir.exists() {
                         return Err(anyhow::anyhow!(
                             "Working directory does not exist: {}",
@@ -518,7 +503,7 @@ impl HookExecutor for DefaultHookExecutor {
                 Ok(())
             }
             HookAction::Backup { path, destination } => {
-                let expanded_path = HooksConfig::expand_tilde(path);
+                let expanded_path = expand(path)?;
                 if !expanded_path.exists() {
                     return Err(anyhow::anyhow!(
                         "Backup source path does not exist: {}",
@@ -526,7 +511,7 @@ impl HookExecutor for DefaultHookExecutor {
                     ));
                 }
 
-                let expanded_dest = HooksConfig::expand_tilde(destination);
+                let expanded_dest = expand(destination)?;
                 if let Some(parent) = expanded_dest.parent() {
                     if !parent.exists() {
                         return Err(anyhow::anyhow!(
@@ -544,7 +529,7 @@ impl HookExecutor for DefaultHookExecutor {
                 ..
             } => {
                 for dir in directories {
-                    let expanded_dir = HooksConfig::expand_tilde(dir);
+                    let expanded_dir = expand(dir)?;
                     if !expanded_dir.exists() {
                         return Err(anyhow::anyhow!(
                             "Cleanup directory does not exist: {}",
@@ -578,7 +563,7 @@ impl DefaultHookExecutor {
     ) -> Result<HookResult> {
         // Resolve script path
         let script_path = context.hooks_config.resolve_script_path(command);
-        let expanded_path = HooksConfig::expand_tilde(&script_path);
+        let expanded_path = expand(&script_path)?;
 
         // Interpolate variables in args
         let interpolated_args: Vec<String> =
@@ -597,7 +582,7 @@ impl DefaultHookExecutor {
 
         // Set working directory if specified
         if let Some(dir) = working_dir {
-            let expanded_dir = HooksConfig::expand_tilde(dir);
+            let expanded_dir = expand(dir)?;
             cmd.current_dir(expanded_dir);
         }
 
@@ -718,8 +703,8 @@ impl DefaultHookExecutor {
         destination: &Path,
         _context: &HookContext,
     ) -> Result<HookResult> {
-        let expanded_path = HooksConfig::expand_tilde(path);
-        let expanded_dest = HooksConfig::expand_tilde(destination);
+        let expanded_path = expand(path)?;
+        let expanded_dest = expand(destination)?;
 
         // Simple backup implementation - copy files/directories
         let result = if expanded_path.is_dir() {
@@ -780,7 +765,7 @@ impl DefaultHookExecutor {
 
         // Clean up specified directories with patterns
         for dir in directories {
-            let expanded_dir = HooksConfig::expand_tilde(dir);
+            let expanded_dir = expand(dir)?;
             for pattern in patterns {
                 match cleanup_pattern(&expanded_dir, pattern).await {
                     Ok(count) => cleaned_files += count,
@@ -1070,7 +1055,7 @@ mod tests {
     fn test_hooks_config_tilde_expansion() {
         // Test tilde expansion
         let home_path = PathBuf::from("~/test/path");
-        let expanded = HooksConfig::expand_tilde(&home_path);
+        let expanded = expand(&home_path).unwrap();
 
         if let Some(home_dir) = dirs::home_dir() {
             assert_eq!(expanded, home_dir.join("test/path"));
@@ -1081,7 +1066,7 @@ mod tests {
 
         // Test non-tilde path (should be unchanged)
         let regular_path = PathBuf::from("/regular/path");
-        let not_expanded = HooksConfig::expand_tilde(&regular_path);
+        let not_expanded = expand(&regular_path).unwrap();
         assert_eq!(not_expanded, regular_path);
     }
 
@@ -1517,12 +1502,12 @@ mod tests {
         assert_eq!(context.variables.get("key2"), Some(&"value2".to_string()));
     }
 
-    /// Test HooksConfig expand_tilde with just tilde
+    /// Test path expansion with just tilde
     /// Verifies that a single tilde expands to home directory
     #[test]
     fn test_hooks_config_expand_tilde_single() {
         let tilde_path = PathBuf::from("~");
-        let expanded = HooksConfig::expand_tilde(&tilde_path);
+        let expanded = expand(&tilde_path).unwrap();
 
         if let Some(home_dir) = dirs::home_dir() {
             assert_eq!(expanded, home_dir);
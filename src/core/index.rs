@@ -0,0 +1,255 @@
+//! Checksum index of plugin output across all snapshots in an output directory
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tokio::fs as async_fs;
+use tracing::warn;
+
+use crate::core::report::{RunReport, REPORT_FILE_NAME};
+use crate::core::snapshot::{SnapshotManager, SnapshotMetadata};
+
+/// Index file name at the root of the output directory
+pub const INDEX_FILE_NAME: &str = ".dotsnapshot-index.json";
+
+/// Location of a plugin output file inside a snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// Snapshot directory name
+    pub snapshot: String,
+    pub plugin_name: String,
+    /// Output file, relative to the snapshot directory
+    pub path: PathBuf,
+}
+
+/// Maps output checksums to the snapshot files holding that content
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotIndex {
+    pub checksums: BTreeMap<String, Vec<IndexEntry>>,
+}
+
+impl SnapshotIndex {
+    /// Path of the index file for an output directory
+    pub fn path(base_path: &Path) -> PathBuf {
+        base_path.join(INDEX_FILE_NAME)
+    }
+
+    /// Load the index of an output directory, or an empty index if none exists
+    pub async fn load(base_path: &Path) -> Result<Self> {
+        let path = Self::path(base_path);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let json = async_fs::read_to_string(&path)
+            .await
+            .with_context(|| format!("Failed to read snapshot index {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| {
+            format!(
+                "Snapshot index {} is corrupt, run 'dotsnapshot index rebuild'",
+                path.display()
+            )
+        })
+    }
+
+    /// Save the index, replacing the previous file atomically
+    pub async fn save(&self, base_path: &Path) -> Result<()> {
+        let path = Self::path(base_path);
+        let temp_path = path.with_extension("json.tmp");
+        let json = serde_json::to_string_pretty(self)?;
+        async_fs::write(&temp_path, json)
+            .await
+            .context("Failed to write snapshot index")?;
+        async_fs::rename(&temp_path, &path)
+            .await
+            .context("Failed to replace snapshot index")?;
+        Ok(())
+    }
+
+    /// Record the output files of a snapshot, replacing earlier entries for it
+    pub fn add_snapshot(&mut self, snapshot: &str, metadata: &SnapshotMetadata) {
+        self.remove_snapshot(snapshot);
+
+        for (plugin_name, checksum) in &metadata.checksums {
+            if let Some(path) = metadata.files.get(plugin_name) {
+                self.checksums
+                    .entry(checksum.clone())
+                    .or_default()
+                    .push(IndexEntry {
+                        snapshot: snapshot.to_string(),
+                        plugin_name: plugin_name.clone(),
+                        path: path.clone(),
+                    });
+            }
+        }
+    }
+
+    /// Drop every entry pointing into a snapshot
+    pub fn remove_snapshot(&mut self, snapshot: &str) {
+        self.checksums.retain(|_, entries| {
+            entries.retain(|entry| entry.snapshot != snapshot);
+            !entries.is_empty()
+        });
+    }
+
+    /// Entries holding content with the given checksum, newest snapshot first
+    /// and entries of `plugin_name` ahead of other plugins within a snapshot
+    pub fn lookup(&self, checksum: &str, plugin_name: &str) -> Vec<&IndexEntry> {
+        let mut entries: Vec<&IndexEntry> = self
+            .checksums
+            .get(checksum)
+            .map(|entries| entries.iter().collect())
+            .unwrap_or_default();
+        entries.sort_by(|a, b| {
+            b.snapshot
+                .cmp(&a.snapshot)
+                .then_with(|| (b.plugin_name == plugin_name).cmp(&(a.plugin_name == plugin_name)))
+        });
+        entries
+    }
+
+    /// Number of indexed output files
+    pub fn len(&self) -> usize {
+        self.checksums.values().map(Vec::len).sum()
+    }
+
+    /// Whether the index has no entries
+    pub fn is_empty(&self) -> bool {
+        self.checksums.is_empty()
+    }
+
+    /// Rebuild the index from the metadata of every snapshot in the output directory
+    ///
+    /// Output file locations come from the snapshot metadata, or from the run
+    /// report for snapshots created before metadata recorded them.
+    pub async fn rebuild(manager: &SnapshotManager) -> Result<Self> {
+        let mut index = Self::default();
+
+        for snapshot_dir in manager.list_snapshots()? {
+            let Some(snapshot) = snapshot_dir.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let mut metadata = match manager.load_metadata(&snapshot_dir).await {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    warn!("Skipping {} while rebuilding index: {}", snapshot, e);
+                    continue;
+                }
+            };
+
+            if metadata.files.is_empty() {
+                metadata.files = Self::files_from_report(&snapshot_dir).await;
+            }
+            index.add_snapshot(snapshot, &metadata);
+        }
+
+        Ok(index)
+    }
+
+    /// Output files recorded in a snapshot's run report
+    async fn files_from_report(snapshot_dir: &Path) -> std::collections::HashMap<String, PathBuf> {
+        let report_path = snapshot_dir.join(".snapshot").join(REPORT_FILE_NAME);
+        let Ok(json) = async_fs::read_to_string(&report_path).await else {
+            return Default::default();
+        };
+        let Ok(report) = serde_json::from_str::<RunReport>(&json) else {
+            return Default::default();
+        };
+
+        report
+            .plugins
+            .iter()
+            .filter_map(|plugin| {
+                let [path] = plugin.output_paths.as_slice() else {
+                    return None;
+                };
+                let relative = path.strip_prefix(&report.snapshot_dir).ok()?;
+                snapshot_dir
+                    .join(relative)
+                    .is_file()
+                    .then(|| (plugin.plugin_name.clone(), relative.to_path_buf()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn metadata(entries: &[(&str, &str, &str)]) -> SnapshotMetadata {
+        let mut metadata = SnapshotManager::new(PathBuf::from(".")).create_metadata();
+        for (plugin_name, checksum, path) in entries {
+            metadata
+                .checksums
+                .insert(plugin_name.to_string(), checksum.to_string());
+            metadata
+                .files
+                .insert(plugin_name.to_string(), PathBuf::from(path));
+        }
+        metadata
+    }
+
+    /// Test adding, replacing and looking up snapshots
+    /// Verifies lookups return the newest snapshot first
+    #[test]
+    fn test_snapshot_index_lookup() {
+        let mut index = SnapshotIndex::default();
+        index.add_snapshot("20240101_000000", &metadata(&[("a", "sum1", "a.txt")]));
+        index.add_snapshot("20240102_000000", &metadata(&[("a", "sum2", "a.txt")]));
+        index.add_snapshot(
+            "20240103_000000",
+            &metadata(&[("a", "sum1", "a.txt"), ("b", "sum1", "b/b.txt")]),
+        );
+
+        let entries = index.lookup("sum1", "a");
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].snapshot, "20240103_000000");
+        assert_eq!(entries[0].plugin_name, "a");
+        assert_eq!(entries[2].snapshot, "20240101_000000");
+        assert!(index.lookup("missing", "a").is_empty());
+
+        index.add_snapshot("20240103_000000", &metadata(&[]));
+        assert_eq!(index.lookup("sum1", "a").len(), 1);
+        assert_eq!(index.len(), 2);
+
+        index.remove_snapshot("20240101_000000");
+        index.remove_snapshot("20240102_000000");
+        assert!(index.is_empty());
+    }
+
+    /// Test saving and rebuilding the index from snapshot metadata
+    /// Verifies a missing index loads empty and rebuild recovers entries
+    #[tokio::test]
+    async fn test_snapshot_index_rebuild() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let manager = SnapshotManager::new(temp_dir.path().to_path_buf());
+        assert!(SnapshotIndex::load(temp_dir.path()).await?.is_empty());
+
+        let snapshot_dir = temp_dir.path().join("20240101_000000");
+        std::fs::create_dir_all(&snapshot_dir)?;
+        manager
+            .save_metadata(&snapshot_dir, &metadata(&[("a", "sum1", "a.txt")]))
+            .await?;
+
+        let index = SnapshotIndex::rebuild(&manager).await?;
+        index.save(temp_dir.path()).await?;
+
+        let loaded = SnapshotIndex::load(temp_dir.path()).await?;
+        assert_eq!(
+            loaded.lookup("sum1", "a"),
+            vec![&IndexEntry {
+                snapshot: "20240101_000000".to_string(),
+                plugin_name: "a".to_string(),
+                path: PathBuf::from("a.txt"),
+            }]
+        );
+
+        std::fs::write(SnapshotIndex::path(temp_dir.path()), "not json")?;
+        assert!(SnapshotIndex::load(temp_dir.path()).await.is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+//! Output directory lock preventing concurrent snapshot runs
+
+use anyhow::{Context, Result};
+use fs4::fs_std::FileExt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Lock file name at the root of the output directory
+pub const LOCK_FILE_NAME: &str = ".dotsnapshot.lock";
+
+/// Exclusive advisory lock on an output directory, released when dropped
+///
+/// The lock is held on the open file rather than on its existence, so a
+/// crashed run never leaves a stale lock behind.
+#[derive(Debug)]
+pub struct SnapshotLock {
+    _file: File,
+    path: PathBuf,
+}
+
+impl SnapshotLock {
+    /// Try to lock the output directory, returning `None` if another run holds it
+    pub fn try_acquire(output_dir: &Path) -> Result<Option<Self>> {
+        let path = output_dir.join(LOCK_FILE_NAME);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open lock file {}", path.display()))?;
+
+        if !file
+            .try_lock_exclusive()
+            .with_context(|| format!("Failed to lock {}", path.display()))?
+        {
+            return Ok(None);
+        }
+
+        // Record the owner for diagnostics
+        file.set_len(0)?;
+        writeln!(file, "{}", std::process::id())?;
+
+        Ok(Some(Self { _file: file, path }))
+    }
+
+    /// Path of the lock file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Process id recorded by the current lock holder, if readable
+    pub fn holder(output_dir: &Path) -> Option<u32> {
+        std::fs::read_to_string(output_dir.join(LOCK_FILE_NAME))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Test that a second lock on the same directory is refused until release
+    #[test]
+    fn test_snapshot_lock_contention() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        let lock = SnapshotLock::try_acquire(temp_dir.path())?.expect("first lock");
+        assert_eq!(lock.path(), temp_dir.path().join(LOCK_FILE_NAME));
+        assert_eq!(
+            SnapshotLock::holder(temp_dir.path()),
+            Some(std::process::id())
+        );
+
+        assert!(SnapshotLock::try_acquire(temp_dir.path())?.is_none());
+
+        drop(lock);
+        assert!(SnapshotLock::try_acquire(temp_dir.path())?.is_some());
+
+        Ok(())
+    }
+}
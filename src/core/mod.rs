@@ -3,6 +3,14 @@ pub mod checksum;
 pub mod config_schema;
 pub mod executor;
 pub mod hooks;
+pub mod index;
+pub mod lock;
+pub mod paths;
 pub mod plugin;
+pub mod policy;
+pub mod report;
 pub mod restore;
+pub mod schedule;
+pub mod selector;
 pub mod snapshot;
+pub mod watch;
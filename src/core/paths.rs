@@ -0,0 +1,210 @@
+//! Expansion of paths written in the configuration
+//!
+//! Every configured path goes through [`expand_path`], which understands:
+//!
+//! - `~` and `~/...` for the home directory
+//! - `$VAR` and `${VAR}`, with `${VAR:-default}` for unset or empty variables
+//! - the XDG base directory variables, which fall back to the locations the
+//!   specification defines when they are unset or empty
+//!
+//! Values that differ between operating systems can be written as a table
+//! keyed by OS, see [`PlatformPath`].
+
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::env::VarError;
+use std::path::{Path, PathBuf};
+
+/// XDG base directory variables and their defaults relative to the home directory
+const XDG_DEFAULTS: &[(&str, &str)] = &[
+    ("XDG_CONFIG_HOME", ".config"),
+    ("XDG_DATA_HOME", ".local/share"),
+    ("XDG_STATE_HOME", ".local/state"),
+    ("XDG_CACHE_HOME", ".cache"),
+];
+
+/// Expand `~` and environment variables in a configured path
+///
+/// Fails with the variable's name when a variable without a default is not
+/// set.
+pub fn expand_path(path: &str) -> Result<PathBuf> {
+    let home_dir = || dirs::home_dir().map(|home| home.to_string_lossy().into_owned());
+    let expanded = shellexpand::full_with_context(path, home_dir, lookup_var).map_err(|error| {
+        anyhow::anyhow!(
+            "Environment variable '{}' is not set (in path '{path}'); \
+             set it or give a default with ${{{}:-default}}",
+            error.var_name,
+            error.var_name
+        )
+    })?;
+    Ok(PathBuf::from(expanded.as_ref()))
+}
+
+/// [`expand_path`] for a path value; non-UTF-8 paths are returned unchanged
+pub fn expand(path: &Path) -> Result<PathBuf> {
+    match path.to_str() {
+        Some(path) => expand_path(path),
+        None => Ok(path.to_path_buf()),
+    }
+}
+
+/// Value of an environment variable, with spec defaults for XDG directories
+fn lookup_var(name: &str) -> Result<Option<String>, VarError> {
+    match std::env::var(name) {
+        Ok(value) if !value.is_empty() => return Ok(Some(value)),
+        Ok(_) | Err(VarError::NotPresent) => {}
+        Err(error) => return Err(error),
+    }
+
+    let home_dir = dirs::home_dir().ok_or(VarError::NotPresent)?;
+    if name == "HOME" {
+        return Ok(Some(home_dir.to_string_lossy().into_owned()));
+    }
+    XDG_DEFAULTS
+        .iter()
+        .find(|(var, _)| *var == name)
+        .map(|(_, default)| Some(home_dir.join(default).to_string_lossy().into_owned()))
+        .ok_or(VarError::NotPresent)
+}
+
+/// A path that is either the same everywhere or chosen by operating system
+///
+/// ```toml
+/// restore_target_dir = "~/.config/Code/User"
+/// restore_target_dir = { linux = "~/.config/Code/User", macos = "~/Library/Application Support/Code/User" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum PlatformPath {
+    /// Used on every operating system
+    Path(String),
+    /// Chosen by the operating system, falling back to `default`
+    PerOs(OsPaths),
+}
+
+/// Paths by operating system
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct OsPaths {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linux: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub macos: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub windows: Option<String>,
+    /// Used on operating systems without their own entry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+impl PlatformPath {
+    /// The unexpanded path for the current operating system, if any
+    pub fn for_current_os(&self) -> Option<&str> {
+        self.for_os(std::env::consts::OS)
+    }
+
+    /// The unexpanded path for an operating system as named by `std::env::consts::OS`
+    pub fn for_os(&self, os: &str) -> Option<&str> {
+        match self {
+            Self::Path(path) => Some(path),
+            Self::PerOs(paths) => match os {
+                "linux" => paths.linux.as_ref(),
+                "macos" => paths.macos.as_ref(),
+                "windows" => paths.windows.as_ref(),
+                _ => None,
+            }
+            .or(paths.default.as_ref())
+            .map(String::as_str),
+        }
+    }
+}
+
+impl From<&str> for PlatformPath {
+    fn from(path: &str) -> Self {
+        Self::Path(path.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test expanding the home directory and environment variables
+    /// Verifies ~, $VAR, ${VAR} and ${VAR:-default} forms
+    #[test]
+    fn test_expand_path() -> Result<()> {
+        let home = dirs::home_dir().unwrap();
+        std::env::set_var("DOTSNAPSHOT_TEST_PATH_VAR", "/from/env");
+
+        assert_eq!(expand_path("~")?, home);
+        assert_eq!(expand_path("~/dotfiles")?, home.join("dotfiles"));
+        assert_eq!(
+            expand_path("$DOTSNAPSHOT_TEST_PATH_VAR/a")?,
+            PathBuf::from("/from/env/a")
+        );
+        assert_eq!(
+            expand_path("${DOTSNAPSHOT_TEST_PATH_VAR}/b")?,
+            PathBuf::from("/from/env/b")
+        );
+        assert_eq!(
+            expand_path("${DOTSNAPSHOT_TEST_UNSET_VAR:-/fallback}/c")?,
+            PathBuf::from("/fallback/c")
+        );
+        assert_eq!(
+            expand_path("relative/path")?,
+            PathBuf::from("relative/path")
+        );
+
+        std::env::remove_var("DOTSNAPSHOT_TEST_PATH_VAR");
+        Ok(())
+    }
+
+    /// Test expanding an undefined variable
+    /// Verifies the error names the variable and the path
+    #[test]
+    fn test_expand_path_undefined_variable() {
+        let error = expand_path("$DOTSNAPSHOT_TEST_MISSING_VAR/snapshots")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("'DOTSNAPSHOT_TEST_MISSING_VAR' is not set"));
+        assert!(error.contains("'$DOTSNAPSHOT_TEST_MISSING_VAR/snapshots'"));
+    }
+
+    /// Test XDG base directory fallbacks
+    /// Verifies unset XDG variables resolve to the spec defaults
+    #[test]
+    fn test_xdg_defaults() -> Result<()> {
+        let home = dirs::home_dir().unwrap();
+        let expected = match std::env::var("XDG_STATE_HOME") {
+            Ok(value) if !value.is_empty() => PathBuf::from(value),
+            _ => home.join(".local/state"),
+        };
+        assert_eq!(
+            expand_path("$XDG_STATE_HOME/dotsnapshot")?,
+            expected.join("dotsnapshot")
+        );
+        assert!(expand_path("$XDG_RUNTIME_DIR_UNKNOWN").is_err());
+        Ok(())
+    }
+
+    /// Test choosing a path by operating system
+    /// Verifies per-OS tables fall back to default and plain strings apply everywhere
+    #[test]
+    fn test_platform_path() {
+        let value: toml::Table = toml::from_str(
+            "plain = \"~/code\"\nper_os = { linux = \"~/.config/Code\", default = \"~/Code\" }",
+        )
+        .unwrap();
+        let plain: PlatformPath = value["plain"].clone().try_into().unwrap();
+        let per_os: PlatformPath = value["per_os"].clone().try_into().unwrap();
+
+        assert_eq!(plain.for_os("windows"), Some("~/code"));
+        assert_eq!(per_os.for_os("linux"), Some("~/.config/Code"));
+        assert_eq!(per_os.for_os("macos"), Some("~/Code"));
+
+        let invalid: Result<PlatformPath, _> =
+            toml::Value::Table(toml::from_str("linx = \"/x\"").unwrap()).try_into();
+        assert!(invalid.is_err());
+    }
+}
@@ -1,19 +1,126 @@
-use crate::config::Config;
+use crate::config::{Config, PluginConfigValue};
+use crate::core::checksum::FileHashes;
+use crate::core::selector::PluginSelection;
+use crate::plugins::custom::command::{CustomCommandPlugin, DEFAULT_CATEGORY};
+use crate::plugins::external::adapter::ExternalPlugin;
+use crate::symbols::*;
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::sync::Arc;
+use tracing::warn;
 
 #[cfg(test)]
 mod tests;
 
+/// Outcome of a plugin execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginStatus {
+    /// Plugin executed and its output was written
+    Succeeded,
+    /// Output was reused from a previous snapshot (checksum match)
+    Reused,
+    /// Plugin does not apply to this machine (tool missing or unsupported OS)
+    NotApplicable,
+    /// Plugin was excluded by the user's plugin selection
+    Skipped,
+    /// Plugin validation, execution or output writing failed
+    Failed,
+}
+
+impl PluginStatus {
+    /// Whether the plugin contributed output to the snapshot
+    pub fn has_output(&self) -> bool {
+        matches!(self, PluginStatus::Succeeded | PluginStatus::Reused)
+    }
+
+    /// Symbol used when displaying the status
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            PluginStatus::Succeeded => SYMBOL_INDICATOR_SUCCESS,
+            PluginStatus::Reused => SYMBOL_ACTION_RESTORE,
+            PluginStatus::NotApplicable => SYMBOL_ACTION_BLOCK,
+            PluginStatus::Skipped => SYMBOL_CONTENT_SKIP,
+            PluginStatus::Failed => SYMBOL_INDICATOR_ERROR,
+        }
+    }
+}
+
+impl std::fmt::Display for PluginStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PluginStatus::Succeeded => write!(f, "succeeded"),
+            PluginStatus::Reused => write!(f, "reused"),
+            PluginStatus::NotApplicable => write!(f, "not_applicable"),
+            PluginStatus::Skipped => write!(f, "skipped"),
+            PluginStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// Error returned from `Plugin::validate` when a plugin does not apply to this
+/// machine, so the run reports it as not applicable rather than failed
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct NotApplicable(pub String);
+
+impl NotApplicable {
+    /// Whether an error (or any error in its context chain) is `NotApplicable`
+    pub fn is_in(error: &anyhow::Error) -> bool {
+        error.chain().any(|cause| cause.is::<NotApplicable>())
+    }
+}
+
+/// Files a plugin would copy into a snapshot, resolved without copying (dry run)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FilePlan {
+    /// (source, destination relative to the snapshot directory) pairs
+    pub copies: Vec<(PathBuf, PathBuf)>,
+    /// Sources excluded by ignore patterns
+    pub ignored: Vec<PathBuf>,
+    /// Configured sources that do not exist (including globs without matches)
+    pub missing: Vec<PathBuf>,
+}
+
+/// Run state passed to a plugin executing for a snapshot
+#[derive(Debug, Clone)]
+pub struct ExecutionContext {
+    /// Directory that plugins creating their own output files write into
+    pub snapshot_dir: PathBuf,
+    /// Hash algorithm of the run and the file hashes computed so far
+    pub hashes: FileHashes,
+}
+
+impl ExecutionContext {
+    pub fn new(snapshot_dir: PathBuf) -> Self {
+        Self {
+            snapshot_dir,
+            hashes: FileHashes::default(),
+        }
+    }
+
+    /// Hash files with the run's algorithm, sharing its computed hashes
+    pub fn with_hashes(mut self, hashes: FileHashes) -> Self {
+        self.hashes = hashes;
+        self
+    }
+
+    /// Context for plugins executed outside a snapshot run, with the snapshot
+    /// directory taken from `DOTSNAPSHOT_SNAPSHOT_DIR`
+    pub fn from_env() -> Option<Self> {
+        std::env::var_os("DOTSNAPSHOT_SNAPSHOT_DIR").map(|dir| Self::new(PathBuf::from(dir)))
+    }
+}
+
 /// Represents the result of a plugin execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginResult {
     pub plugin_name: String,
     pub content: String,
     pub checksum: String,
-    pub success: bool,
+    pub status: PluginStatus,
     pub error_message: Option<String>,
 }
 
@@ -22,7 +129,9 @@ pub struct PluginResult {
 pub struct PluginDescriptor {
     pub name: &'static str,
     pub category: &'static str,
-    pub factory: fn(Option<toml::Value>) -> Arc<dyn Plugin>,
+    pub factory: fn(Option<PluginConfigValue>) -> Arc<dyn Plugin>,
+    /// JSON Schema of the plugin's `[plugins.<name>]` section
+    pub config_schema: fn(&mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema,
 }
 
 // Inventory collection for auto-registering plugins
@@ -37,9 +146,23 @@ pub trait Plugin: Send + Sync {
     /// Returns the icon/emoji to display with this plugin
     fn icon(&self) -> &str;
 
+    /// Category used for selection and listing, when it isn't derived from
+    /// the plugin name (see [`PluginRegistry::extract_category_from_plugin_name`])
+    fn category(&self) -> Option<&str> {
+        None
+    }
+
     /// Executes the plugin and returns the content to be saved
     async fn execute(&self) -> Result<String>;
 
+    /// Executes the plugin for a snapshot run
+    ///
+    /// Plugins creating their own output files write them into
+    /// `context.snapshot_dir`. Defaults to [`Plugin::execute`].
+    async fn execute_with(&self, _context: &ExecutionContext) -> Result<String> {
+        self.execute().await
+    }
+
     /// Validates that the plugin can run (e.g., required binaries exist)
     async fn validate(&self) -> Result<()>;
 
@@ -61,6 +184,12 @@ pub trait Plugin: Send + Sync {
         Ok(dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from(".")))
     }
 
+    /// Default restore target directory for plugins that must run something
+    /// to find it; restores call this rather than the synchronous getter
+    async fn resolve_default_restore_target_dir(&self) -> Result<std::path::PathBuf> {
+        self.get_default_restore_target_dir()
+    }
+
     /// Get plugin hooks from plugin's own configuration
     fn get_hooks(&self) -> Vec<crate::core::hooks::HookAction> {
         Vec::new() // Default: no hooks
@@ -75,6 +204,34 @@ pub trait Plugin: Send + Sync {
         false // Default: executor should save the plugin's output
     }
 
+    /// Directory (relative to the snapshot root) that a plugin creating its own
+    /// output files writes into, used for run reporting
+    fn get_own_output_dir(&self) -> Option<String> {
+        None // Default: plugin output is the single file saved by the executor
+    }
+
+    /// Resolves the files a plugin creating its own output files would copy,
+    /// without writing anything (used by snapshot dry runs)
+    async fn plan_files(&self) -> Result<Option<FilePlan>> {
+        Ok(None) // Default: output is the single file saved by the executor
+    }
+
+    /// Checksum identifying the plugin's output for reuse and change detection
+    ///
+    /// Defaults to the SHA-256 of the returned content. Plugins whose content
+    /// includes per-run statistics hash only the part describing the output.
+    fn output_checksum(&self, content: &str) -> String {
+        crate::core::checksum::calculate_checksum(content)
+    }
+
+    /// Files or directories whose changes affect this plugin's output
+    ///
+    /// Used by watch mode. Plugins returning no paths (such as package managers,
+    /// whose state is only visible through their CLI) are polled instead.
+    async fn watch_paths(&self) -> Result<Vec<std::path::PathBuf>> {
+        Ok(Vec::new())
+    }
+
     /// Restores configuration from a snapshot for this plugin
     ///
     /// This method allows plugins to implement custom restoration logic beyond
@@ -103,12 +260,16 @@ pub trait Plugin: Send + Sync {
 #[derive(Default)]
 pub struct PluginRegistry {
     plugins: Vec<(String, Arc<dyn Plugin>)>, // (name, plugin)
+    skipped: Vec<String>,                    // plugins excluded by selection
+    external: Vec<Arc<ExternalPlugin>>,      // registered external plugins
 }
 
 impl PluginRegistry {
     pub fn new() -> Self {
         Self {
             plugins: Vec::new(),
+            skipped: Vec::new(),
+            external: Vec::new(),
         }
     }
 
@@ -117,6 +278,11 @@ impl PluginRegistry {
         &self.plugins
     }
 
+    /// Returns the names of plugins excluded by the plugin selection
+    pub fn skipped_plugins(&self) -> &[String] {
+        &self.skipped
+    }
+
     /// Derives the default filename for a plugin (always .txt extension)
     pub fn derive_plugin_filename(plugin_name: &str) -> String {
         format!("{plugin_name}.txt")
@@ -148,6 +314,11 @@ impl PluginRegistry {
             return "N/A (copies files directly)".to_string();
         }
 
+        // Plugins writing their own directory show it rather than a file
+        if let Some(own_output_dir) = plugin.get_own_output_dir() {
+            return format!("{own_output_dir}/");
+        }
+
         // Use plugin's own configuration first
         if let Some(custom_output_file) = plugin.get_output_file() {
             return custom_output_file;
@@ -206,7 +377,10 @@ impl PluginRegistry {
         self.plugins
             .iter()
             .map(|(name, plugin)| {
-                let category = Self::extract_category_from_plugin_name(name, config);
+                let category = match plugin.category() {
+                    Some(category) => category.to_string(),
+                    None => Self::extract_category_from_plugin_name(name, config),
+                };
                 let output_file = Self::get_plugin_output_file_from_plugin(plugin.as_ref(), name);
                 (
                     name.clone(),
@@ -220,6 +394,9 @@ impl PluginRegistry {
     }
 
     /// Auto-discover and register all plugins from inventory
+    ///
+    /// With a configuration, custom plugins declared in it and external plugin
+    /// executables are registered too.
     pub fn discover_plugins(config: Option<&Config>) -> Self {
         let mut registry = Self::new();
 
@@ -232,23 +409,70 @@ impl PluginRegistry {
             registry.plugins.push((descriptor.name.to_string(), plugin));
         }
 
+        if let Some(config) = config {
+            for plugin in CustomCommandPlugin::from_config(config) {
+                registry
+                    .plugins
+                    .push((plugin.name().to_string(), Arc::new(plugin)));
+            }
+            let taken = registry.names();
+            for plugin in ExternalPlugin::discover(config, &taken) {
+                let plugin = Arc::new(plugin);
+                registry
+                    .plugins
+                    .push((plugin.name().to_string(), plugin.clone()));
+                registry.external.push(plugin);
+            }
+        }
+
         registry
     }
 
+    /// Describe the registered external plugins that `needed` picks by name
+    ///
+    /// External plugins only know their description and category once their
+    /// executable has run; those failing to describe themselves keep the
+    /// placeholders, with a warning.
+    pub async fn describe_external(&self, needed: impl Fn(&str) -> bool) {
+        for plugin in self.external.iter().filter(|plugin| needed(plugin.name())) {
+            if let Err(e) = plugin.describe().await {
+                warn!("{} {:#}", SYMBOL_INDICATOR_WARNING, e);
+            }
+        }
+    }
+
     /// Register all plugins from descriptors with optional configuration filtering
-    pub fn register_from_descriptors(
+    ///
+    /// Fails when a selector is invalid or matches no plugin; see
+    /// [`PluginSelection`] for the selector syntax.
+    pub async fn register_from_descriptors(
         &mut self,
         config: Option<&Config>,
         selected_plugins: &[&str],
-    ) {
+    ) -> Result<()> {
+        self.register_selection(config, &PluginSelection::parse(selected_plugins)?)
+            .await
+    }
+
+    /// Register the plugins matching a selection
+    ///
+    /// External plugins are only described when their category decides
+    /// whether they are selected.
+    pub async fn register_selection(
+        &mut self,
+        config: Option<&Config>,
+        selection: &PluginSelection,
+    ) -> Result<()> {
+        let mut candidates = Vec::new();
         for descriptor in inventory::iter::<PluginDescriptor> {
+            candidates.push((
+                descriptor.name.to_string(),
+                Some(descriptor.category.to_string()),
+            ));
+
             // Check if this plugin should be included
-            if !selected_plugins.is_empty()
-                && !selected_plugins.contains(&"all")
-                && !selected_plugins
-                    .iter()
-                    .any(|&sel| descriptor.name.contains(sel) || descriptor.category == sel)
-            {
+            if !selection.is_selected(descriptor.name, Some(descriptor.category)) {
+                self.skipped.push(descriptor.name.to_string());
                 continue;
             }
 
@@ -259,5 +483,83 @@ impl PluginRegistry {
             let plugin = (descriptor.factory)(plugin_config);
             self.plugins.push((descriptor.name.to_string(), plugin));
         }
+
+        // Command plugins declared under [plugins.custom]
+        for plugin in config
+            .map(CustomCommandPlugin::from_config)
+            .unwrap_or_default()
+        {
+            let name = plugin.name().to_string();
+            let category = plugin.category().unwrap_or(DEFAULT_CATEGORY).to_string();
+            self.register_if_selected(
+                selection,
+                &mut candidates,
+                name,
+                Some(category),
+                Arc::new(plugin),
+            );
+        }
+
+        // Executables speaking the external plugin protocol
+        let taken = self.names();
+        let external = config.map(|config| ExternalPlugin::discover(config, &taken));
+        for plugin in external.unwrap_or_default() {
+            // A plugin that fails to describe itself has no category and
+            // reports the error when it runs
+            if selection.needs_category(plugin.name()) {
+                if let Err(e) = plugin.describe().await {
+                    warn!("{} {:#}", SYMBOL_INDICATOR_WARNING, e);
+                }
+            }
+            let name = plugin.name().to_string();
+            let category = plugin.category().map(str::to_string);
+            let plugin = Arc::new(plugin);
+            if self.register_if_selected(selection, &mut candidates, name, category, plugin.clone())
+            {
+                self.external.push(plugin);
+            }
+        }
+
+        selection.check_matches(&candidates)
+    }
+
+    /// Register a plugin if selected, returning whether it was
+    fn register_if_selected(
+        &mut self,
+        selection: &PluginSelection,
+        candidates: &mut Vec<(String, Option<String>)>,
+        name: String,
+        category: Option<String>,
+        plugin: Arc<dyn Plugin>,
+    ) -> bool {
+        let selected = selection.is_selected(&name, category.as_deref());
+        if selected {
+            self.plugins.push((name.clone(), plugin));
+        } else {
+            self.skipped.push(name.clone());
+        }
+        candidates.push((name, category));
+        selected
+    }
+
+    /// Category used to select a registered plugin
+    pub fn plugin_category(&self, name: &str) -> Option<String> {
+        inventory::iter::<PluginDescriptor>
+            .into_iter()
+            .find(|descriptor| descriptor.name == name)
+            .map(|descriptor| descriptor.category.to_string())
+            .or_else(|| {
+                let plugin = self.find_plugin(name)?;
+                plugin.category().map(str::to_string)
+            })
+    }
+
+    /// Names of registered and skipped plugins
+    pub fn names(&self) -> std::collections::BTreeSet<String> {
+        self.plugins
+            .iter()
+            .map(|(name, _)| name.clone())
+            .chain(self.skipped.iter().cloned())
+            .collect()
     }
 }
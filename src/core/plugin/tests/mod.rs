@@ -1,7 +1,9 @@
 //! Tests for plugin system functionality
 
 use crate::config::{Config, UiConfig};
-use crate::core::plugin::{Plugin, PluginDescriptor, PluginRegistry, PluginResult};
+use crate::core::plugin::{
+    NotApplicable, Plugin, PluginDescriptor, PluginRegistry, PluginResult, PluginStatus,
+};
 use anyhow::Result;
 use std::collections::HashMap;
 use std::sync::Arc;
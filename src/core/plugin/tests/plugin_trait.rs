@@ -60,7 +60,7 @@ async fn test_plugin_trait_defaults() {
     // Test default restore directory
     let default_dir = plugin.get_default_restore_target_dir().unwrap();
     // Should be either home directory or current directory fallback
-    assert!(default_dir.is_absolute() || default_dir == std::path::PathBuf::from("."));
+    assert!(default_dir.is_absolute() || default_dir == std::path::Path::new("."));
 
     // Test default restore implementation
     let temp_dir = tempfile::TempDir::new().unwrap();
@@ -450,7 +450,7 @@ async fn test_default_restore_target_directory() {
     let restore_dir = plugin.get_default_restore_target_dir().unwrap();
 
     // Should be either home directory or current directory
-    assert!(restore_dir.exists() || restore_dir == std::path::PathBuf::from("."));
+    assert!(restore_dir.exists() || restore_dir == std::path::Path::new("."));
 
     // If home directory is available, it should be that
     if let Some(home) = dirs::home_dir() {
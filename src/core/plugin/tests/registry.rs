@@ -116,6 +116,7 @@ fn test_plugin_registry_list_plugins_detailed_with_config() {
             plugin_categories: Some(categories),
         }),
         validation: None,
+        ..Config::default()
     };
 
     let detailed_list = registry.list_plugins_detailed(Some(&config));
@@ -127,12 +128,15 @@ fn test_plugin_registry_list_plugins_detailed_with_config() {
 
 /// Test PluginRegistry register_from_descriptors with empty selection
 /// Verifies that no plugins are registered when selection is empty (non-all mode)
-#[test]
-fn test_plugin_registry_register_empty_selection() {
+#[tokio::test]
+async fn test_plugin_registry_register_empty_selection() {
     let mut registry = PluginRegistry::new();
     let selected_plugins: &[&str] = &[];
 
-    registry.register_from_descriptors(None, selected_plugins);
+    registry
+        .register_from_descriptors(None, selected_plugins)
+        .await
+        .unwrap();
 
     // Should have no plugins since selection is empty and doesn't contain "all"
     // However, the current implementation actually registers all plugins when empty selection
@@ -147,22 +151,36 @@ fn test_plugin_registry_register_empty_selection() {
 }
 
 /// Test PluginRegistry register_from_descriptors with specific selection
-/// Verifies that only matching plugins are registered when specific selection is provided
-#[test]
-fn test_plugin_registry_register_specific_selection() {
+/// Verifies that only matching plugins are registered and unmatched selectors are rejected
+#[tokio::test]
+async fn test_plugin_registry_register_specific_selection() {
     let mut registry = PluginRegistry::new();
 
-    // Test with a specific selection that likely doesn't match any real plugins
+    // A selector that doesn't match any real plugin is an error
     let selected_plugins = &["nonexistent_plugin_category"];
-    registry.register_from_descriptors(None, selected_plugins);
+    let error = registry
+        .register_from_descriptors(None, selected_plugins)
+        .await
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("'nonexistent_plugin_category' matches no plugin"));
 
-    // Should have fewer plugins since selection is specific and likely doesn't match many
+    let mut registry = PluginRegistry::new();
+    registry
+        .register_from_descriptors(None, &["vscode_settings"])
+        .await
+        .unwrap();
     let specific_count = registry.plugins().len();
+    assert_eq!(specific_count, 1);
 
     // Create another registry with "all" selection for comparison
     let mut all_registry = PluginRegistry::new();
     let all_selection = &["all"];
-    all_registry.register_from_descriptors(None, all_selection);
+    all_registry
+        .register_from_descriptors(None, all_selection)
+        .await
+        .unwrap();
     let all_count = all_registry.plugins().len();
 
     // The all selection should typically have more or equal plugins
@@ -170,6 +188,89 @@ fn test_plugin_registry_register_specific_selection() {
     println!("Specific selection: {specific_count} plugins, All selection: {all_count} plugins");
 }
 
+/// Test PluginRegistry tracks plugins excluded by the selection
+/// Verifies that every descriptor is either registered or recorded as skipped
+#[tokio::test]
+async fn test_plugin_registry_skipped_plugins() {
+    let mut registry = PluginRegistry::new();
+    registry
+        .register_from_descriptors(None, &["static"])
+        .await
+        .unwrap();
+
+    let total = inventory::iter::<PluginDescriptor>.into_iter().count();
+    assert_eq!(
+        registry.plugins().len() + registry.skipped_plugins().len(),
+        total
+    );
+    assert!(registry.find_plugin("static_files").is_some());
+    assert!(!registry
+        .skipped_plugins()
+        .contains(&"static_files".to_string()));
+
+    let mut all_registry = PluginRegistry::new();
+    all_registry
+        .register_from_descriptors(None, &["all"])
+        .await
+        .unwrap();
+    assert!(all_registry.skipped_plugins().is_empty());
+}
+
+/// Test PluginRegistry registers custom command plugins
+/// Verifies custom plugins are selected by name or category and listed with their category
+#[tokio::test]
+async fn test_plugin_registry_custom_plugins() {
+    let config: Config = toml::from_str(
+        r#"
+        [plugins.custom.pip_freeze]
+        capture = "pip freeze"
+        category = "python"
+
+        [plugins.custom.gh_extensions]
+        capture = "gh extension list"
+        "#,
+    )
+    .unwrap();
+
+    let registry = PluginRegistry::discover_plugins(Some(&config));
+    assert!(registry.find_plugin("pip_freeze").is_some());
+    assert!(registry.find_plugin("gh_extensions").is_some());
+    let listed = registry.list_plugins_detailed(Some(&config));
+    let (_, output_file, _, category, _) = listed
+        .iter()
+        .find(|(name, ..)| name == "pip_freeze")
+        .unwrap();
+    assert_eq!(output_file, "pip_freeze.txt");
+    assert_eq!(category, "python");
+
+    let mut selected = PluginRegistry::new();
+    selected
+        .register_from_descriptors(Some(&config), &["python"])
+        .await
+        .unwrap();
+    assert_eq!(selected.plugins().len(), 1);
+    assert!(selected.find_plugin("pip_freeze").is_some());
+    assert!(selected
+        .skipped_plugins()
+        .contains(&"gh_extensions".to_string()));
+
+    let mut custom = PluginRegistry::new();
+    custom
+        .register_from_descriptors(Some(&config), &["custom"])
+        .await
+        .unwrap();
+    assert!(custom.find_plugin("gh_extensions").is_some());
+    assert!(custom.find_plugin("pip_freeze").is_none());
+
+    let mut excluded = PluginRegistry::new();
+    excluded
+        .register_from_descriptors(Some(&config), &["all", "!category:python"])
+        .await
+        .unwrap();
+    assert!(excluded.find_plugin("gh_extensions").is_some());
+    assert!(excluded.find_plugin("pip_freeze").is_none());
+}
+
 /// Test PluginRegistry::discover_plugins functionality
 /// Verifies that auto-discovery mechanism works correctly
 #[test]
@@ -274,12 +375,15 @@ fn test_plugin_registry_list_detailed_with_ui_config() {
 
 /// Test register_from_descriptors with various selection criteria
 /// Verifies that plugin selection filtering works correctly
-#[test]
-fn test_plugin_registry_register_from_descriptors_filtering() {
+#[tokio::test]
+async fn test_plugin_registry_register_from_descriptors_filtering() {
     // Test with empty selection (should register nothing)
     let mut registry = PluginRegistry::new();
     let empty_selection: &[&str] = &[];
-    registry.register_from_descriptors(None, empty_selection);
+    registry
+        .register_from_descriptors(None, empty_selection)
+        .await
+        .unwrap();
 
     // Should have no plugins when selection is empty (but actual count depends on inventory)
     let empty_count = registry.plugins().len();
@@ -291,15 +395,21 @@ fn test_plugin_registry_register_from_descriptors_filtering() {
     // Test with category-based selection
     let mut category_registry = PluginRegistry::new();
     let category_selection = &["vscode"];
-    category_registry.register_from_descriptors(None, category_selection);
+    category_registry
+        .register_from_descriptors(None, category_selection)
+        .await
+        .unwrap();
 
     // Should have registered some plugins (exact count depends on system)
     let category_count = category_registry.plugins().len();
 
     // Test with plugin name selection
     let mut name_registry = PluginRegistry::new();
-    let name_selection = &["extensions"];
-    name_registry.register_from_descriptors(None, name_selection);
+    let name_selection = &["*_extensions"];
+    name_registry
+        .register_from_descriptors(None, name_selection)
+        .await
+        .unwrap();
 
     let name_count = name_registry.plugins().len();
 
@@ -10,14 +10,14 @@ fn test_plugin_result_creation_and_serialization() {
         plugin_name: "test_plugin".to_string(),
         content: "test content".to_string(),
         checksum: "abc123".to_string(),
-        success: true,
+        status: PluginStatus::Succeeded,
         error_message: None,
     };
 
     assert_eq!(result.plugin_name, "test_plugin");
     assert_eq!(result.content, "test content");
     assert_eq!(result.checksum, "abc123");
-    assert!(result.success);
+    assert_eq!(result.status, PluginStatus::Succeeded);
     assert!(result.error_message.is_none());
 
     // Test serialization
@@ -35,12 +35,12 @@ fn test_plugin_result_with_error() {
         plugin_name: "failing_plugin".to_string(),
         content: String::new(),
         checksum: String::new(),
-        success: false,
+        status: PluginStatus::Failed,
         error_message: Some("Plugin execution failed".to_string()),
     };
 
     assert_eq!(result.plugin_name, "failing_plugin");
-    assert!(!result.success);
+    assert_eq!(result.status, PluginStatus::Failed);
     assert_eq!(
         result.error_message.as_ref().unwrap(),
         "Plugin execution failed"
@@ -56,7 +56,7 @@ fn test_plugin_result_deserialization() {
         "plugin_name": "test_plugin",
         "content": "test content",
         "checksum": "abc123",
-        "success": true,
+        "status": "succeeded",
         "error_message": null
     }"#;
 
@@ -64,7 +64,7 @@ fn test_plugin_result_deserialization() {
     assert_eq!(result.plugin_name, "test_plugin");
     assert_eq!(result.content, "test content");
     assert_eq!(result.checksum, "abc123");
-    assert!(result.success);
+    assert_eq!(result.status, PluginStatus::Succeeded);
     assert!(result.error_message.is_none());
 }
 
@@ -78,11 +78,11 @@ fn test_plugin_result_comprehensive_error_scenarios() {
         plugin_name: "test_plugin".to_string(),
         content: "".to_string(),
         checksum: "abc123".to_string(),
-        success: false,
+        status: PluginStatus::Failed,
         error_message: Some(long_error.clone()),
     };
 
-    assert!(!result_with_long_error.success);
+    assert_eq!(result_with_long_error.status, PluginStatus::Failed);
     assert_eq!(
         result_with_long_error.error_message.as_ref().unwrap().len(),
         1000
@@ -95,11 +95,11 @@ fn test_plugin_result_comprehensive_error_scenarios() {
         plugin_name: "special_plugin".to_string(),
         content: "partial content".to_string(),
         checksum: "def456".to_string(),
-        success: false,
+        status: PluginStatus::Failed,
         error_message: Some(special_error.to_string()),
     };
 
-    assert!(!result_with_special_error.success);
+    assert_eq!(result_with_special_error.status, PluginStatus::Failed);
     assert!(result_with_special_error
         .error_message
         .as_ref()
@@ -114,6 +114,40 @@ fn test_plugin_result_comprehensive_error_scenarios() {
     // Test deserialization
     let deserialized: PluginResult = serde_json::from_str(&json).unwrap();
     assert_eq!(deserialized.plugin_name, "special_plugin");
-    assert!(!deserialized.success);
+    assert_eq!(deserialized.status, PluginStatus::Failed);
     assert!(deserialized.error_message.as_ref().unwrap().contains("🚨"));
 }
+
+/// Test PluginStatus helpers and serialization
+/// Verifies which statuses count as output and how they are serialized
+#[test]
+fn test_plugin_status_helpers() {
+    assert!(PluginStatus::Succeeded.has_output());
+    assert!(PluginStatus::Reused.has_output());
+    assert!(!PluginStatus::NotApplicable.has_output());
+    assert!(!PluginStatus::Skipped.has_output());
+    assert!(!PluginStatus::Failed.has_output());
+
+    assert_eq!(
+        serde_json::to_string(&PluginStatus::NotApplicable).unwrap(),
+        "\"not_applicable\""
+    );
+    assert_eq!(PluginStatus::NotApplicable.to_string(), "not_applicable");
+}
+
+/// Test NotApplicable error detection
+/// Verifies that NotApplicable is found even when wrapped in context
+#[test]
+fn test_not_applicable_detection() {
+    use anyhow::Context;
+
+    let error: anyhow::Error = NotApplicable("brew command not found".to_string()).into();
+    assert!(NotApplicable::is_in(&error));
+    assert_eq!(error.to_string(), "brew command not found");
+
+    let wrapped = Err::<(), _>(error).context("validation").unwrap_err();
+    assert!(NotApplicable::is_in(&wrapped));
+
+    let other = anyhow::anyhow!("invalid configuration");
+    assert!(!NotApplicable::is_in(&other));
+}
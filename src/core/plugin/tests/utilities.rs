@@ -49,6 +49,7 @@ fn test_extract_category_from_plugin_name() {
             plugin_categories: Some(categories),
         }),
         validation: None,
+        ..Config::default()
     };
 
     assert_eq!(
@@ -197,7 +198,7 @@ fn test_extract_category_from_plugin_name_edge_cases() {
 /// Verifies that PluginDescriptor can be created with factory function
 #[test]
 fn test_plugin_descriptor_creation() {
-    fn mock_factory(_config: Option<toml::Value>) -> Arc<dyn Plugin> {
+    fn mock_factory(_config: Option<serde_json::Value>) -> Arc<dyn Plugin> {
         Arc::new(MockPlugin)
     }
 
@@ -205,6 +206,7 @@ fn test_plugin_descriptor_creation() {
         name: "test_plugin",
         category: "test",
         factory: mock_factory,
+        config_schema: |generator| generator.subschema_for::<crate::config::PluginConfig>(),
     };
 
     assert_eq!(descriptor.name, "test_plugin");
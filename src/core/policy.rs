@@ -0,0 +1,366 @@
+//! Failure policy and process exit codes for snapshot runs
+
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::config::validate::suggest;
+use crate::core::plugin::PluginStatus;
+use crate::core::report::RunReport;
+
+/// Process exit codes used by snapshot runs
+///
+/// Code 2 is left to clap for command-line usage errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunExitCode {
+    /// Snapshot completed and satisfied the failure policy
+    Success = 0,
+    /// Snapshot could not be created (I/O, configuration, ...)
+    Error = 1,
+    /// Some plugins failed
+    PartialFailure = 3,
+    /// Every plugin that ran failed
+    TotalFailure = 4,
+    /// Plugins succeeded but at least one hook failed
+    HookFailure = 5,
+    /// Another snapshot run holds the output directory lock
+    LockContention = 6,
+}
+
+impl RunExitCode {
+    /// Numeric process exit code
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+}
+
+/// Decides which plugin or hook failures make a snapshot run fail
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "FailurePolicyValue", into = "FailurePolicyValue")]
+pub enum FailurePolicy {
+    /// Always exit successfully once the snapshot is written
+    Never,
+    /// Fail if any plugin or hook fails
+    AnyFailure,
+    /// Fail only if every plugin that ran failed
+    #[default]
+    AllFailed,
+    /// Fail if any of the listed (critical) plugins or any hook fails
+    Plugins(Vec<String>),
+}
+
+impl FailurePolicy {
+    /// Evaluate a finished run report against this policy
+    pub fn evaluate(&self, report: &RunReport) -> RunExitCode {
+        let attempted: Vec<_> = report
+            .plugins
+            .iter()
+            .filter(|p| {
+                !matches!(
+                    p.status,
+                    PluginStatus::Skipped | PluginStatus::NotApplicable
+                )
+            })
+            .collect();
+        let failed: Vec<_> = attempted
+            .iter()
+            .filter(|p| p.status == PluginStatus::Failed)
+            .collect();
+        let all_failed = !attempted.is_empty() && failed.len() == attempted.len();
+        let plugin_failure = if all_failed {
+            RunExitCode::TotalFailure
+        } else {
+            RunExitCode::PartialFailure
+        };
+        let hooks_failed = report.summary.failed_hooks > 0;
+
+        match self {
+            FailurePolicy::Never => RunExitCode::Success,
+            FailurePolicy::AllFailed if all_failed => RunExitCode::TotalFailure,
+            FailurePolicy::AllFailed => RunExitCode::Success,
+            FailurePolicy::AnyFailure if !failed.is_empty() => plugin_failure,
+            FailurePolicy::Plugins(critical)
+                if failed.iter().any(|p| critical.contains(&p.plugin_name)) =>
+            {
+                plugin_failure
+            }
+            FailurePolicy::AnyFailure | FailurePolicy::Plugins(_) if hooks_failed => {
+                RunExitCode::HookFailure
+            }
+            FailurePolicy::AnyFailure | FailurePolicy::Plugins(_) => RunExitCode::Success,
+        }
+    }
+}
+
+/// Prefix of a comma-separated list of critical plugins in the string form
+pub const PLUGINS_PREFIX: &str = "plugins:";
+
+/// Policy keywords accepted in the string form
+const KEYWORDS: &[&str] = &["never", "any-failure", "all-failed"];
+
+impl FailurePolicy {
+    /// Check that every critical plugin is a known plugin
+    ///
+    /// `known` holds the names of all plugins, whether selected for the run or not.
+    pub fn check_plugins(&self, known: &[&str]) -> anyhow::Result<()> {
+        let FailurePolicy::Plugins(plugins) = self else {
+            return Ok(());
+        };
+        for plugin in plugins {
+            if !known.contains(&plugin.as_str()) {
+                let mut message = format!("Failure policy names unknown plugin '{plugin}'");
+                if let Some(suggestion) = suggest(plugin, known) {
+                    message.push_str(&format!(" (did you mean '{suggestion}'?)"));
+                }
+                return Err(anyhow::anyhow!(message));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for FailurePolicy {
+    type Err = String;
+
+    /// Parse `never`, `any-failure`, `all-failed` or `plugins:` followed by a
+    /// comma-separated list of critical plugin names
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "never" => Ok(FailurePolicy::Never),
+            "any-failure" => Ok(FailurePolicy::AnyFailure),
+            "all-failed" => Ok(FailurePolicy::AllFailed),
+            other => {
+                let Some(list) = other.strip_prefix(PLUGINS_PREFIX) else {
+                    let mut message = format!(
+                        "unknown failure policy '{other}', expected never, any-failure, \
+                         all-failed or {PLUGINS_PREFIX}<plugin>,..."
+                    );
+                    if let Some(suggestion) = suggest(other, KEYWORDS) {
+                        message.push_str(&format!(" (did you mean '{suggestion}'?)"));
+                    }
+                    return Err(message);
+                };
+                let plugins: Vec<String> = list
+                    .split(',')
+                    .map(|p| p.trim().to_string())
+                    .filter(|p| !p.is_empty())
+                    .collect();
+                if plugins.is_empty() {
+                    Err(format!("expected plugin names after '{PLUGINS_PREFIX}'"))
+                } else {
+                    Ok(FailurePolicy::Plugins(plugins))
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for FailurePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FailurePolicy::Never => write!(f, "never"),
+            FailurePolicy::AnyFailure => write!(f, "any-failure"),
+            FailurePolicy::AllFailed => write!(f, "all-failed"),
+            FailurePolicy::Plugins(plugins) => write!(f, "{PLUGINS_PREFIX}{}", plugins.join(",")),
+        }
+    }
+}
+
+impl JsonSchema for FailurePolicy {
+    fn schema_name() -> String {
+        "FailurePolicy".to_string()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        FailurePolicyValue::json_schema(generator)
+    }
+}
+
+/// Config representation: a policy keyword (or `plugins:` list) or an array of
+/// critical plugins
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum FailurePolicyValue {
+    Keyword(String),
+    Plugins(Vec<String>),
+}
+
+impl TryFrom<FailurePolicyValue> for FailurePolicy {
+    type Error = String;
+
+    fn try_from(value: FailurePolicyValue) -> Result<Self, Self::Error> {
+        match value {
+            FailurePolicyValue::Keyword(keyword) => keyword.parse(),
+            FailurePolicyValue::Plugins(plugins) => Ok(FailurePolicy::Plugins(plugins)),
+        }
+    }
+}
+
+impl From<FailurePolicy> for FailurePolicyValue {
+    fn from(policy: FailurePolicy) -> Self {
+        match policy {
+            FailurePolicy::Plugins(plugins) => FailurePolicyValue::Plugins(plugins),
+            keyword => FailurePolicyValue::Keyword(keyword.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::report::{HookReport, PluginReport};
+    use std::path::PathBuf;
+
+    fn report(statuses: &[(&str, PluginStatus)], failed_hook: bool) -> RunReport {
+        let mut report = RunReport::new("snap".to_string(), PathBuf::from("/tmp/snap"));
+        for (name, status) in statuses {
+            let mut plugin = PluginReport::failed(name, String::new());
+            plugin.status = *status;
+            report.plugins.push(plugin);
+        }
+        if failed_hook {
+            report.hooks.push(HookReport {
+                hook_type: "post-snapshot".to_string(),
+                action: "script: notify.sh".to_string(),
+                success: false,
+                duration_ms: 0,
+                error: Some("exit 1".to_string()),
+            });
+        }
+        report.finish();
+        report
+    }
+
+    /// Test parsing policies from CLI strings
+    #[test]
+    fn test_failure_policy_from_str() {
+        assert_eq!("never".parse(), Ok(FailurePolicy::Never));
+        assert_eq!("any-failure".parse(), Ok(FailurePolicy::AnyFailure));
+        assert_eq!("all-failed".parse(), Ok(FailurePolicy::AllFailed));
+        let plugins = FailurePolicy::Plugins(vec![
+            "homebrew_brewfile".to_string(),
+            "vscode_settings".to_string(),
+        ]);
+        assert_eq!(
+            "plugins:homebrew_brewfile, vscode_settings".parse(),
+            Ok(plugins.clone())
+        );
+        assert_eq!(plugins.to_string().parse(), Ok(plugins));
+        assert!("plugins: , ".parse::<FailurePolicy>().is_err());
+
+        // Unknown keywords are rejected rather than read as plugin names
+        let error = "any-failures".parse::<FailurePolicy>().unwrap_err();
+        assert!(error.starts_with("unknown failure policy 'any-failures'"));
+        assert!(error.ends_with("(did you mean 'any-failure'?)"));
+        assert!("homebrew_brewfile".parse::<FailurePolicy>().is_err());
+    }
+
+    /// Test checking critical plugins against the known plugins
+    #[test]
+    fn test_failure_policy_check_plugins() {
+        let known = ["homebrew_brewfile", "npm_config"];
+        assert!(FailurePolicy::AnyFailure.check_plugins(&known).is_ok());
+        assert!(FailurePolicy::Plugins(vec!["npm_config".to_string()])
+            .check_plugins(&known)
+            .is_ok());
+
+        let error = FailurePolicy::Plugins(vec!["npm_cofig".to_string()])
+            .check_plugins(&known)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Failure policy names unknown plugin 'npm_cofig' (did you mean 'npm_config'?)"
+        );
+    }
+
+    /// Test reading policies from TOML as a keyword or a plugin list
+    #[test]
+    fn test_failure_policy_toml() {
+        #[derive(Deserialize, Serialize)]
+        struct Section {
+            fail_on: FailurePolicy,
+        }
+
+        let section: Section = toml::from_str(r#"fail_on = "any-failure""#).unwrap();
+        assert_eq!(section.fail_on, FailurePolicy::AnyFailure);
+
+        assert!(toml::from_str::<Section>(r#"fail_on = "any-failures""#).is_err());
+        let section: Section = toml::from_str(r#"fail_on = "plugins:npm_config""#).unwrap();
+        assert_eq!(
+            section.fail_on,
+            FailurePolicy::Plugins(vec!["npm_config".to_string()])
+        );
+
+        let section: Section = toml::from_str(r#"fail_on = ["npm_config"]"#).unwrap();
+        assert_eq!(
+            section.fail_on,
+            FailurePolicy::Plugins(vec!["npm_config".to_string()])
+        );
+        assert_eq!(
+            toml::to_string(&section).unwrap().trim(),
+            r#"fail_on = ["npm_config"]"#
+        );
+    }
+
+    /// Test exit codes for each policy
+    #[test]
+    fn test_failure_policy_evaluate() {
+        use PluginStatus::*;
+
+        let partial = report(&[("a", Succeeded), ("b", Failed)], false);
+        let total = report(&[("a", Failed), ("b", Failed), ("c", NotApplicable)], false);
+        let hooks = report(&[("a", Succeeded), ("b", Skipped)], true);
+
+        assert_eq!(FailurePolicy::Never.evaluate(&total), RunExitCode::Success);
+
+        assert_eq!(
+            FailurePolicy::AllFailed.evaluate(&partial),
+            RunExitCode::Success
+        );
+        assert_eq!(
+            FailurePolicy::AllFailed.evaluate(&total),
+            RunExitCode::TotalFailure
+        );
+        assert_eq!(
+            FailurePolicy::AllFailed.evaluate(&hooks),
+            RunExitCode::Success
+        );
+
+        assert_eq!(
+            FailurePolicy::AnyFailure.evaluate(&partial),
+            RunExitCode::PartialFailure
+        );
+        assert_eq!(
+            FailurePolicy::AnyFailure.evaluate(&total),
+            RunExitCode::TotalFailure
+        );
+        assert_eq!(
+            FailurePolicy::AnyFailure.evaluate(&hooks),
+            RunExitCode::HookFailure
+        );
+
+        let critical = FailurePolicy::Plugins(vec!["a".to_string()]);
+        assert_eq!(critical.evaluate(&partial), RunExitCode::Success);
+        assert_eq!(critical.evaluate(&total), RunExitCode::TotalFailure);
+        assert_eq!(
+            FailurePolicy::Plugins(vec!["b".to_string()]).evaluate(&partial),
+            RunExitCode::PartialFailure
+        );
+    }
+
+    /// Test that runs with no applicable plugins are not total failures
+    #[test]
+    fn test_failure_policy_nothing_attempted() {
+        let report = report(&[("a", PluginStatus::NotApplicable)], false);
+        assert_eq!(
+            FailurePolicy::AnyFailure.evaluate(&report),
+            RunExitCode::Success
+        );
+        assert_eq!(
+            FailurePolicy::AllFailed.evaluate(&report),
+            RunExitCode::Success
+        );
+    }
+}
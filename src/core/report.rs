@@ -0,0 +1,440 @@
+//! Structured run reports for snapshot executions
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::fs as async_fs;
+
+use crate::core::hooks::{HookResult, HookType};
+use crate::core::plugin::{PluginResult, PluginStatus};
+use crate::symbols::*;
+
+/// File name of the run report inside a snapshot's `.snapshot` directory
+pub const REPORT_FILE_NAME: &str = "report.json";
+
+/// Output format for the run report printed after a snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// Human-readable summary table
+    #[default]
+    Table,
+    /// Machine-readable JSON on stdout (logs are sent to stderr)
+    Json,
+}
+
+/// Outcome of a single hook execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookReport {
+    /// Hook stage (pre-snapshot, post-plugin, ...)
+    pub hook_type: String,
+    /// Human-readable description of the hook action
+    pub action: String,
+    /// Whether the hook succeeded
+    pub success: bool,
+    /// Execution time in milliseconds
+    pub duration_ms: u64,
+    /// Error message (if failed)
+    pub error: Option<String>,
+}
+
+impl HookReport {
+    /// Convert hook manager results into report entries
+    pub fn from_results(hook_type: &HookType, results: &[HookResult]) -> Vec<Self> {
+        results
+            .iter()
+            .map(|result| Self {
+                hook_type: hook_type.to_string(),
+                action: result.action.clone(),
+                success: result.success,
+                duration_ms: result.execution_time_ms,
+                error: result.error.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Outcome of a single plugin within a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginReport {
+    pub plugin_name: String,
+    pub status: PluginStatus,
+    /// Wall-clock time spent on the plugin, including its hooks
+    pub duration_ms: u64,
+    /// Files or directories written for this plugin
+    pub output_paths: Vec<PathBuf>,
+    /// Total bytes written for this plugin
+    pub bytes_written: u64,
+    /// Checksum of the plugin output (if it produced any)
+    pub checksum: Option<String>,
+    pub error_message: Option<String>,
+    /// Pre-plugin and post-plugin hook outcomes
+    pub hooks: Vec<HookReport>,
+}
+
+impl PluginReport {
+    /// Build a report entry from a plugin result
+    pub fn from_result(result: &PluginResult) -> Self {
+        Self {
+            plugin_name: result.plugin_name.clone(),
+            status: result.status,
+            duration_ms: 0,
+            output_paths: Vec::new(),
+            bytes_written: 0,
+            checksum: (!result.checksum.is_empty()).then(|| result.checksum.clone()),
+            error_message: result.error_message.clone(),
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Build a failed report entry for a plugin that produced no result
+    pub fn failed(plugin_name: &str, error: String) -> Self {
+        Self::without_result(plugin_name, PluginStatus::Failed, Some(error))
+    }
+
+    /// Build a report entry for a plugin excluded by the plugin selection
+    pub fn skipped(plugin_name: &str) -> Self {
+        Self::without_result(plugin_name, PluginStatus::Skipped, None)
+    }
+
+    fn without_result(plugin_name: &str, status: PluginStatus, error: Option<String>) -> Self {
+        Self {
+            plugin_name: plugin_name.to_string(),
+            status,
+            duration_ms: 0,
+            output_paths: Vec::new(),
+            bytes_written: 0,
+            checksum: None,
+            error_message: error,
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Whether the plugin contributed output to the snapshot
+    pub fn has_output(&self) -> bool {
+        self.status.has_output()
+    }
+}
+
+/// Per-status plugin counts for a run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportSummary {
+    pub total: usize,
+    pub succeeded: usize,
+    pub reused: usize,
+    pub not_applicable: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// Number of hooks (global and plugin) that failed
+    pub failed_hooks: usize,
+}
+
+/// Structured report of a complete snapshot run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunReport {
+    pub snapshot_name: String,
+    pub snapshot_dir: PathBuf,
+    /// False when plugin output matched the latest snapshot and no new
+    /// snapshot was created (`snapshot_dir` then points at the latest one)
+    #[serde(default = "default_changed")]
+    pub changed: bool,
+    pub version: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub summary: ReportSummary,
+    pub plugins: Vec<PluginReport>,
+    /// Global pre-snapshot and post-snapshot hook outcomes
+    pub hooks: Vec<HookReport>,
+}
+
+impl RunReport {
+    /// Start a new report for the given snapshot
+    pub fn new(snapshot_name: String, snapshot_dir: PathBuf) -> Self {
+        Self {
+            snapshot_name,
+            snapshot_dir,
+            changed: true,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            started_at: Utc::now(),
+            duration_ms: 0,
+            summary: ReportSummary::default(),
+            plugins: Vec::new(),
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Recalculate the summary and total duration
+    pub fn finish(&mut self) {
+        let mut summary = ReportSummary {
+            total: self.plugins.len(),
+            ..Default::default()
+        };
+
+        for plugin in &self.plugins {
+            match plugin.status {
+                PluginStatus::Succeeded => summary.succeeded += 1,
+                PluginStatus::Reused => summary.reused += 1,
+                PluginStatus::NotApplicable => summary.not_applicable += 1,
+                PluginStatus::Skipped => summary.skipped += 1,
+                PluginStatus::Failed => summary.failed += 1,
+            }
+        }
+
+        summary.failed_hooks = self
+            .hooks
+            .iter()
+            .chain(self.plugins.iter().flat_map(|p| p.hooks.iter()))
+            .filter(|h| !h.success)
+            .count();
+
+        self.summary = summary;
+        self.duration_ms = Utc::now()
+            .signed_duration_since(self.started_at)
+            .num_milliseconds()
+            .max(0) as u64;
+    }
+
+    /// Save the report as `.snapshot/report.json` in the snapshot directory
+    pub async fn save(&self, snapshot_dir: &Path) -> Result<PathBuf> {
+        let meta_dir = snapshot_dir.join(".snapshot");
+        async_fs::create_dir_all(&meta_dir)
+            .await
+            .context("Failed to create .snapshot directory")?;
+
+        let report_path = meta_dir.join(REPORT_FILE_NAME);
+        let json = serde_json::to_string_pretty(self)?;
+        async_fs::write(&report_path, json)
+            .await
+            .context("Failed to save run report")?;
+
+        Ok(report_path)
+    }
+
+    /// Render the report as a human-readable summary table
+    pub fn to_table(&self) -> String {
+        let mut lines = Vec::new();
+
+        if self.changed {
+            lines.push(format!(
+                "{SYMBOL_INDICATOR_INFO} Snapshot {} ({})",
+                self.snapshot_name,
+                self.snapshot_dir.display()
+            ));
+        } else {
+            lines.push(format!(
+                "{SYMBOL_INDICATOR_INFO} No changes since snapshot {} ({}), no snapshot created",
+                self.snapshot_name,
+                self.snapshot_dir.display()
+            ));
+        }
+        lines.push(String::new());
+        lines.push(format!(
+            "  {:<24} {:<15} {:>10} {:>10}  {}",
+            "PLUGIN", "STATUS", "DURATION", "BYTES", "DETAILS"
+        ));
+
+        let mut plugins: Vec<&PluginReport> = self.plugins.iter().collect();
+        plugins.sort_by(|a, b| a.plugin_name.cmp(&b.plugin_name));
+
+        for plugin in plugins {
+            let details = match &plugin.error_message {
+                Some(error) => error.lines().next().unwrap_or_default().to_string(),
+                None => plugin
+                    .output_paths
+                    .iter()
+                    .filter_map(|p| p.strip_prefix(&self.snapshot_dir).ok())
+                    .map(|p| p.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            };
+
+            lines.push(format!(
+                "{} {:<24} {:<14} {:>10} {:>10}  {}",
+                plugin.status.symbol(),
+                plugin.plugin_name,
+                plugin.status.to_string(),
+                format!("{}ms", plugin.duration_ms),
+                format_bytes(plugin.bytes_written),
+                details
+            ));
+        }
+
+        lines.push(String::new());
+        lines.push(format!(
+            "{SYMBOL_EXPERIENCE_TIME}  {} plugins: {} succeeded, {} reused, {} not applicable, {} skipped, {} failed ({}ms)",
+            self.summary.total,
+            self.summary.succeeded,
+            self.summary.reused,
+            self.summary.not_applicable,
+            self.summary.skipped,
+            self.summary.failed,
+            self.duration_ms
+        ));
+
+        if self.summary.failed_hooks > 0 {
+            lines.push(format!(
+                "{SYMBOL_INDICATOR_WARNING}  {} hook(s) failed",
+                self.summary.failed_hooks
+            ));
+        }
+
+        lines.join("\n")
+    }
+}
+
+fn default_changed() -> bool {
+    true
+}
+
+/// Format a byte count using binary units
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Sum the size of a file, or of every file below a directory
+pub fn path_size(path: &Path) -> u64 {
+    if path.is_file() {
+        return path.metadata().map(|m| m.len()).unwrap_or(0);
+    }
+
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| path_size(&entry.path()))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn plugin_report(name: &str, status: PluginStatus) -> PluginReport {
+        PluginReport {
+            plugin_name: name.to_string(),
+            status,
+            duration_ms: 5,
+            output_paths: Vec::new(),
+            bytes_written: 2048,
+            checksum: None,
+            error_message: None,
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Test that finish() counts plugins and failed hooks per status
+    #[test]
+    fn test_run_report_summary() {
+        let mut report = RunReport::new("snap".to_string(), PathBuf::from("/tmp/snap"));
+        report
+            .plugins
+            .push(plugin_report("a", PluginStatus::Succeeded));
+        report
+            .plugins
+            .push(plugin_report("b", PluginStatus::Reused));
+        report
+            .plugins
+            .push(plugin_report("c", PluginStatus::Failed));
+        report
+            .plugins
+            .push(plugin_report("e", PluginStatus::NotApplicable));
+
+        let mut skipped = plugin_report("d", PluginStatus::Skipped);
+        skipped.hooks.push(HookReport {
+            hook_type: "pre-plugin".to_string(),
+            action: "script: fail.sh".to_string(),
+            success: false,
+            duration_ms: 1,
+            error: Some("boom".to_string()),
+        });
+        report.plugins.push(skipped);
+
+        report.finish();
+
+        assert_eq!(
+            report.summary,
+            ReportSummary {
+                total: 5,
+                succeeded: 1,
+                reused: 1,
+                not_applicable: 1,
+                skipped: 1,
+                failed: 1,
+                failed_hooks: 1,
+            }
+        );
+    }
+
+    /// Test that the report round-trips through report.json
+    #[tokio::test]
+    async fn test_run_report_save() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut report = RunReport::new("snap".to_string(), temp_dir.path().to_path_buf());
+        report
+            .plugins
+            .push(plugin_report("a", PluginStatus::Succeeded));
+        report.finish();
+
+        let path = report.save(temp_dir.path()).await?;
+        assert_eq!(path, temp_dir.path().join(".snapshot").join("report.json"));
+
+        let loaded: RunReport = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+        assert_eq!(loaded.plugins.len(), 1);
+        assert_eq!(loaded.plugins[0].status, PluginStatus::Succeeded);
+        assert!(std::fs::read_to_string(&path)?.contains("\"status\": \"succeeded\""));
+
+        Ok(())
+    }
+
+    /// Test the summary table rendering
+    #[test]
+    fn test_run_report_table() {
+        let mut report = RunReport::new("snap".to_string(), PathBuf::from("/tmp/snap"));
+        let mut failed = plugin_report("npm_config", PluginStatus::Failed);
+        failed.error_message = Some("npm command not found".to_string());
+        report.plugins.push(failed);
+        report.finish();
+
+        let table = report.to_table();
+        assert!(table.contains("npm_config"));
+        assert!(table.contains("failed"));
+        assert!(table.contains("2.0 KiB"));
+        assert!(table.contains("npm command not found"));
+        assert!(table
+            .contains("1 plugins: 0 succeeded, 0 reused, 0 not applicable, 0 skipped, 1 failed"));
+    }
+
+    /// Test byte formatting and path size calculation
+    #[test]
+    fn test_format_bytes_and_path_size() -> Result<()> {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+
+        let temp_dir = TempDir::new()?;
+        std::fs::create_dir_all(temp_dir.path().join("nested"))?;
+        std::fs::write(temp_dir.path().join("a.txt"), "12345")?;
+        std::fs::write(temp_dir.path().join("nested").join("b.txt"), "123")?;
+
+        assert_eq!(path_size(temp_dir.path()), 8);
+        assert_eq!(path_size(&temp_dir.path().join("a.txt")), 5);
+        assert_eq!(path_size(&temp_dir.path().join("missing")), 0);
+
+        Ok(())
+    }
+}
@@ -4,7 +4,9 @@ use std::path::{Path, PathBuf};
 use tracing::{debug, error, info, warn};
 
 use crate::config::Config;
+use crate::core::paths::expand_path;
 use crate::core::plugin::PluginRegistry;
+use crate::core::selector::PluginSelection;
 use crate::symbols::*;
 
 /// Manages the restoration of configuration files from snapshots
@@ -85,6 +87,14 @@ impl RestoreManager {
 
         // Filter plugins based on selection
         let plugins_to_restore = if let Some(selected) = selected_plugins {
+            // External plugins only know their category once described
+            let selection = PluginSelection::parse(&selected)?;
+            self.plugin_registry
+                .describe_external(|name| {
+                    available_plugins.iter().any(|plugin| plugin == name)
+                        && selection.needs_category(name)
+                })
+                .await;
             self.filter_plugins(&available_plugins, &selected)?
         } else {
             available_plugins
@@ -163,40 +173,17 @@ impl RestoreManager {
     }
 
     /// Filter plugins based on user selection
+    ///
+    /// Uses the same selectors as snapshots; categories come from the plugins
+    /// known to the registry.
     fn filter_plugins(&self, available: &[String], selected: &[String]) -> Result<Vec<String>> {
-        let mut filtered = Vec::new();
-
-        for selection in selected {
-            let selection = selection.trim();
-
-            // Handle wildcard patterns
-            if selection.contains('*') {
-                let pattern = selection.replace('*', "");
-                for plugin in available {
-                    if plugin.contains(&pattern) {
-                        filtered.push(plugin.clone());
-                    }
-                }
-            } else if available.contains(&selection.to_string()) {
-                filtered.push(selection.to_string());
-            } else {
-                warn!(
-                    "{} Plugin '{}' not found in snapshot",
-                    SYMBOL_INDICATOR_WARNING, selection
-                );
-            }
-        }
-
-        if filtered.is_empty() && !selected.is_empty() {
-            return Err(anyhow::anyhow!(
-                "None of the selected plugins were found in the snapshot"
-            ));
-        }
+        let candidates: Vec<(String, Option<String>)> = available
+            .iter()
+            .map(|name| (name.clone(), self.plugin_registry.plugin_category(name)))
+            .collect();
+        let mut filtered = PluginSelection::parse(selected)?.select(&candidates)?;
 
-        // Remove duplicates
         filtered.sort();
-        filtered.dedup();
-
         Ok(filtered)
     }
 
@@ -235,9 +222,9 @@ impl RestoreManager {
             let target_directory = if let Some(global_override) = &self.global_target_override {
                 global_override.clone()
             } else if let Some(plugin_target) = plugin.get_restore_target_dir() {
-                PathBuf::from(shellexpand::tilde(&plugin_target).as_ref())
+                expand_path(&plugin_target)?
             } else {
-                plugin.get_default_restore_target_dir()?
+                plugin.resolve_default_restore_target_dir().await?
             };
 
             // Get restored files from plugin-specific logic
@@ -727,8 +714,40 @@ mod tests {
         assert_eq!(filtered, vec!["vscode_extensions", "vscode_settings"]); // Sorted
     }
 
+    /// Test filter plugins with categories and excludes
+    /// Verifies restore uses the same selectors as snapshots
+    #[test]
+    fn test_filter_plugins_category_and_exclude() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = RestoreManager::new(
+            temp_dir.path().to_path_buf(),
+            temp_dir.path().to_path_buf(),
+            None,
+            Config::default(),
+            false,
+            false,
+            false,
+        );
+
+        let available = vec![
+            "vscode_settings".to_string(),
+            "vscode_extensions".to_string(),
+            "npm_config".to_string(),
+        ];
+        let selected = vec![
+            "category:vscode".to_string(),
+            "!vscode_extensions".to_string(),
+        ];
+        let filtered = manager.filter_plugins(&available, &selected).unwrap();
+        assert_eq!(filtered, vec!["vscode_settings"]);
+
+        // Names match exactly rather than as substrings
+        let selected = vec!["settings".to_string()];
+        assert!(manager.filter_plugins(&available, &selected).is_err());
+    }
+
     /// Test filter plugins with no matches
-    /// Verifies error when no plugins match selection
+    /// Verifies error when a selector matches no plugin in the snapshot
     #[test]
     fn test_filter_plugins_no_matches() {
         let temp_dir = TempDir::new().unwrap();
@@ -750,7 +769,7 @@ mod tests {
         assert!(result
             .unwrap_err()
             .to_string()
-            .contains("None of the selected plugins"));
+            .contains("Plugin selector 'nonexistent' matches no plugin"));
     }
 
     /// Test filter plugins with duplicates
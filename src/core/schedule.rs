@@ -0,0 +1,425 @@
+//! Scheduled snapshots: systemd user timer and crontab generation
+//!
+//! Everything here is pure text generation so the output can be tested
+//! without touching the init system; `cli::schedule` installs it.
+
+use anyhow::{Context, Result};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Name of the generated systemd units (`dotsnapshot.service` and `.timer`)
+pub const UNIT_NAME: &str = "dotsnapshot";
+
+/// Comment line placed above the managed crontab entry
+pub const CRONTAB_MARKER: &str = "# dotsnapshot schedule (managed by 'dotsnapshot schedule')";
+
+/// Name of the systemd units for a profile (`dotsnapshot-<profile>`)
+///
+/// Each profile gets its own units so several schedules can coexist.
+pub fn unit_name(profile: Option<&str>) -> String {
+    match profile {
+        Some(profile) => format!("{UNIT_NAME}-{profile}"),
+        None => UNIT_NAME.to_string(),
+    }
+}
+
+/// Marker line of the managed crontab entry for a profile
+pub fn crontab_marker(profile: Option<&str>) -> String {
+    match profile {
+        Some(profile) => format!("{CRONTAB_MARKER} [profile {profile}]"),
+        None => CRONTAB_MARKER.to_string(),
+    }
+}
+
+/// Check that a profile name can be used in unit names and crontab markers
+pub fn check_profile_name(profile: &str) -> Result<()> {
+    if profile.is_empty()
+        || !profile
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(anyhow::anyhow!(
+            "Profile '{profile}' cannot be scheduled; use letters, digits, '-' and '_' only"
+        ));
+    }
+    Ok(())
+}
+
+/// Interval between scheduled snapshots, e.g. `30m`, `6h` or `1d`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduleInterval {
+    value: u64,
+    unit: char,
+}
+
+impl ScheduleInterval {
+    /// Interval length in seconds
+    pub fn as_secs(&self) -> u64 {
+        self.value
+            * match self.unit {
+                'm' => 60,
+                'h' => 3600,
+                'd' => 86400,
+                _ => 1,
+            }
+    }
+
+    /// Cron schedule expression, if cron can run at exactly this interval
+    ///
+    /// Cron steps restart every hour or day, so only intervals dividing an
+    /// hour or a day evenly (or exactly one day) can be expressed.
+    pub fn cron_expression(&self) -> Result<String> {
+        let secs = self.as_secs();
+        let expression = match secs {
+            60 => "* * * * *".to_string(),
+            s if s < 3600 && s % 60 == 0 && 3600 % s == 0 => format!("*/{} * * * *", s / 60),
+            3600 => "0 * * * *".to_string(),
+            s if s < 86400 && s % 3600 == 0 && 86400 % s == 0 => {
+                format!("0 */{} * * *", s / 3600)
+            }
+            86400 => "0 0 * * *".to_string(),
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Cron cannot run every {self}; use an interval that divides an hour or a day evenly, or the systemd backend"
+                ))
+            }
+        };
+        Ok(expression)
+    }
+}
+
+impl FromStr for ScheduleInterval {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let unit = s
+            .chars()
+            .last()
+            .filter(|unit| matches!(unit, 'm' | 'h' | 'd'))
+            .with_context(|| format!("Invalid interval '{s}', expected e.g. 30m, 6h or 1d"))?;
+        let value: u64 = s[..s.len() - 1]
+            .parse()
+            .with_context(|| format!("Invalid interval '{s}', expected e.g. 30m, 6h or 1d"))?;
+        if value == 0 {
+            return Err(anyhow::anyhow!("Interval must be greater than zero"));
+        }
+        Ok(Self { value, unit })
+    }
+}
+
+impl fmt::Display for ScheduleInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.value, self.unit)
+    }
+}
+
+/// Which system runs the scheduled snapshots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ScheduleBackend {
+    /// systemd user service and timer
+    Systemd,
+    /// Entry in the user's crontab
+    Cron,
+}
+
+/// What a scheduled snapshot runs
+#[derive(Debug, Clone)]
+pub struct ScheduleSpec {
+    /// Absolute path of the dotsnapshot binary
+    pub binary: PathBuf,
+    /// Absolute path of the config file, if any
+    pub config: Option<PathBuf>,
+    /// Output directory override
+    pub output: Option<PathBuf>,
+    /// Config profile passed to scheduled runs
+    pub profile: Option<String>,
+    pub every: ScheduleInterval,
+    /// File receiving the output of cron runs (systemd runs log to the journal)
+    pub log_file: PathBuf,
+}
+
+impl ScheduleSpec {
+    /// Command line of a scheduled run
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec![self.binary.display().to_string()];
+        if let Some(config) = &self.config {
+            args.push("--config".to_string());
+            args.push(config.display().to_string());
+        }
+        if let Some(output) = &self.output {
+            args.push("--output".to_string());
+            args.push(output.display().to_string());
+        }
+        if let Some(profile) = &self.profile {
+            args.push("--profile".to_string());
+            args.push(profile.clone());
+        }
+        args
+    }
+
+    /// Name of the systemd units of this schedule
+    pub fn unit_name(&self) -> String {
+        unit_name(self.profile.as_deref())
+    }
+
+    /// Contents of `dotsnapshot.service`
+    pub fn systemd_service(&self) -> String {
+        let exec_start = self
+            .args()
+            .iter()
+            .map(|arg| systemd_quote(arg))
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!(
+            "[Unit]\n\
+             Description=Snapshot dotfiles and configuration with dotsnapshot\n\
+             Documentation={}\n\
+             \n\
+             [Service]\n\
+             Type=oneshot\n\
+             WorkingDirectory=%h\n\
+             ExecStart={exec_start}\n",
+            env!("CARGO_PKG_REPOSITORY")
+        )
+    }
+
+    /// Contents of `dotsnapshot.timer`
+    pub fn systemd_timer(&self) -> String {
+        let description = match &self.profile {
+            Some(profile) => format!(
+                "Run dotsnapshot with profile {profile} every {}",
+                self.every
+            ),
+            None => format!("Run dotsnapshot every {}", self.every),
+        };
+        format!(
+            "[Unit]\n\
+             Description={description}\n\
+             \n\
+             [Timer]\n\
+             OnBootSec=5min\n\
+             OnUnitActiveSec={every}\n\
+             Unit={unit}.service\n\
+             \n\
+             [Install]\n\
+             WantedBy=timers.target\n",
+            every = self.every,
+            unit = self.unit_name()
+        )
+    }
+
+    /// Managed crontab lines (marker comment and entry)
+    pub fn crontab_entry(&self) -> Result<String> {
+        let command = self
+            .args()
+            .iter()
+            .map(|arg| shell_quote(arg))
+            .collect::<Vec<_>>()
+            .join(" ");
+        Ok(format!(
+            "{}\n{} {command} >> {} 2>&1\n",
+            crontab_marker(self.profile.as_deref()),
+            self.every.cron_expression()?,
+            shell_quote(&self.log_file.display().to_string())
+        ))
+    }
+}
+
+/// Directory holding systemd user units
+pub fn systemd_user_dir(config_dir: &Path) -> PathBuf {
+    config_dir.join("systemd").join("user")
+}
+
+/// Crontab with the managed entry (identified by its marker line) removed
+pub fn crontab_without_entry(crontab: &str, marker: &str) -> String {
+    let mut lines = Vec::new();
+    let mut skip_next = false;
+    for line in crontab.lines() {
+        if skip_next {
+            skip_next = false;
+            continue;
+        }
+        if line == marker {
+            skip_next = true;
+            continue;
+        }
+        lines.push(line);
+    }
+
+    let mut result = lines.join("\n");
+    if !result.is_empty() {
+        result.push('\n');
+    }
+    result
+}
+
+/// Crontab with the managed entry added, replacing an earlier one
+///
+/// `entry` starts with its marker line, as produced by [`ScheduleSpec::crontab_entry`].
+pub fn crontab_with_entry(crontab: &str, entry: &str) -> String {
+    let marker = entry.lines().next().unwrap_or_default();
+    crontab_without_entry(crontab, marker) + entry
+}
+
+/// The managed crontab entry, if installed
+pub fn find_crontab_entry<'a>(crontab: &'a str, marker: &str) -> Option<&'a str> {
+    let mut lines = crontab.lines();
+    lines.find(|line| *line == marker)?;
+    lines.next()
+}
+
+/// Quote an argument for a systemd `ExecStart=` line
+fn systemd_quote(arg: &str) -> String {
+    let escaped = arg.replace('%', "%%");
+    if escaped.contains([' ', '"', '\\', '\'']) {
+        format!("\"{}\"", escaped.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        escaped
+    }
+}
+
+/// Quote an argument for `/bin/sh` (cron runs entries through the shell)
+fn shell_quote(arg: &str) -> String {
+    let is_safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/._-+=:@,".contains(c));
+    let quoted = if is_safe {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    };
+    // Cron turns unescaped % into newlines
+    quoted.replace('%', "\\%")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(every: &str) -> ScheduleSpec {
+        ScheduleSpec {
+            binary: PathBuf::from("/usr/local/bin/dotsnapshot"),
+            config: Some(PathBuf::from("/home/user/.config/dotsnapshot/config.toml")),
+            output: None,
+            profile: None,
+            every: every.parse().unwrap(),
+            log_file: PathBuf::from("/home/user/.local/share/dotsnapshot/schedule.log"),
+        }
+    }
+
+    /// Test generated systemd units against the golden files
+    #[test]
+    fn test_systemd_units_golden() {
+        let spec = spec("6h");
+        assert_eq!(
+            spec.systemd_service(),
+            include_str!("../../tests/golden/schedule/dotsnapshot.service")
+        );
+        assert_eq!(
+            spec.systemd_timer(),
+            include_str!("../../tests/golden/schedule/dotsnapshot.timer")
+        );
+    }
+
+    /// Test the generated crontab entry against the golden file
+    #[test]
+    fn test_crontab_entry_golden() -> Result<()> {
+        assert_eq!(
+            spec("6h").crontab_entry()?,
+            include_str!("../../tests/golden/schedule/crontab")
+        );
+        Ok(())
+    }
+
+    /// Test parsing intervals and converting them to cron expressions
+    /// Verifies intervals cron can't run exactly are rejected
+    #[test]
+    fn test_schedule_interval() -> Result<()> {
+        let cron = |every: &str| every.parse::<ScheduleInterval>()?.cron_expression();
+        assert_eq!(cron("1m")?, "* * * * *");
+        assert_eq!(cron("15m")?, "*/15 * * * *");
+        assert_eq!(cron("60m")?, "0 * * * *");
+        assert_eq!(cron("6h")?, "0 */6 * * *");
+        assert_eq!(cron("24h")?, "0 0 * * *");
+        assert_eq!(cron("1d")?, "0 0 * * *");
+        assert!(cron("7h").is_err());
+        assert!(cron("2d").is_err());
+
+        assert_eq!("6h".parse::<ScheduleInterval>()?.as_secs(), 21600);
+        assert!("0h".parse::<ScheduleInterval>().is_err());
+        assert!("6".parse::<ScheduleInterval>().is_err());
+        assert!("h".parse::<ScheduleInterval>().is_err());
+        assert!("6w".parse::<ScheduleInterval>().is_err());
+        Ok(())
+    }
+
+    /// Test adding, replacing and removing the managed crontab entry
+    /// Verifies other crontab lines are preserved
+    #[test]
+    fn test_crontab_editing() -> Result<()> {
+        let existing = "MAILTO=me\n0 1 * * * backup.sh\n";
+        let entry = spec("6h").crontab_entry()?;
+
+        let installed = crontab_with_entry(existing, &entry);
+        assert_eq!(installed, format!("{existing}{entry}"));
+        assert!(find_crontab_entry(&installed, CRONTAB_MARKER)
+            .unwrap()
+            .starts_with("0 */6 * * *"));
+
+        let replaced = crontab_with_entry(&installed, &spec("1d").crontab_entry()?);
+        assert_eq!(replaced.matches(CRONTAB_MARKER).count(), 1);
+        assert!(find_crontab_entry(&replaced, CRONTAB_MARKER)
+            .unwrap()
+            .starts_with("0 0 * * *"));
+
+        assert_eq!(crontab_without_entry(&replaced, CRONTAB_MARKER), existing);
+        assert_eq!(find_crontab_entry(existing, CRONTAB_MARKER), None);
+        assert_eq!(crontab_without_entry("", CRONTAB_MARKER), "");
+        Ok(())
+    }
+
+    /// Test schedules for a profile
+    /// Verifies the profile is passed to runs and gets its own units and crontab entry
+    #[test]
+    fn test_schedule_profile() -> Result<()> {
+        let mut work = spec("6h");
+        work.profile = Some("work".to_string());
+
+        assert_eq!(work.args()[3..], ["--profile", "work"]);
+        assert_eq!(work.unit_name(), "dotsnapshot-work");
+        assert!(work
+            .systemd_timer()
+            .contains("Unit=dotsnapshot-work.service"));
+
+        let both = crontab_with_entry(&spec("6h").crontab_entry()?, &work.crontab_entry()?);
+        let work_marker = crontab_marker(Some("work"));
+        assert!(find_crontab_entry(&both, &work_marker)
+            .unwrap()
+            .ends_with("--profile work >> /home/user/.local/share/dotsnapshot/schedule.log 2>&1"));
+        assert_eq!(
+            crontab_without_entry(&both, &work_marker),
+            spec("6h").crontab_entry()?
+        );
+
+        check_profile_name("work_laptop-2")?;
+        assert!(check_profile_name("my work").is_err());
+        assert!(check_profile_name("").is_err());
+        Ok(())
+    }
+
+    /// Test quoting arguments containing spaces and special characters
+    #[test]
+    fn test_schedule_quoting() {
+        assert_eq!(
+            systemd_quote("/usr/bin/dotsnapshot"),
+            "/usr/bin/dotsnapshot"
+        );
+        assert_eq!(systemd_quote("/my dir/100%"), "\"/my dir/100%%\"");
+        assert_eq!(shell_quote("/usr/bin/dotsnapshot"), "/usr/bin/dotsnapshot");
+        assert_eq!(shell_quote("/my dir/it's"), "'/my dir/it'\\''s'");
+        assert_eq!(shell_quote("/a%b"), "'/a\\%b'");
+    }
+}
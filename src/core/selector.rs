@@ -0,0 +1,303 @@
+//! Plugin selection shared by snapshot, restore, watch and `--list`
+//!
+//! A selection is a list of selectors, each one of:
+//!
+//! - `all`: every plugin
+//! - `<name>`: the plugin with that name, or the plugins of that category
+//! - `category:<name>`: the plugins of a category
+//! - a glob such as `vscode_*` or `*_settings`, matched against plugin names
+//!
+//! Any selector prefixed with `!` excludes the plugins it matches instead.
+//! A plugin is selected when it matches an including selector (or there are
+//! none) and no excluding one.
+
+use anyhow::{anyhow, Context, Result};
+use glob::Pattern;
+
+use crate::config::validate::suggest;
+
+/// Prefix of selectors matching a category
+pub const CATEGORY_PREFIX: &str = "category:";
+
+/// Prefix of selectors excluding the plugins they match
+pub const EXCLUDE_PREFIX: &str = "!";
+
+/// A parsed list of plugin selectors
+#[derive(Debug, Clone, Default)]
+pub struct PluginSelection {
+    selectors: Vec<Selector>,
+}
+
+#[derive(Debug, Clone)]
+struct Selector {
+    text: String,
+    exclude: bool,
+    kind: SelectorKind,
+}
+
+#[derive(Debug, Clone)]
+enum SelectorKind {
+    All,
+    NameOrCategory(String),
+    Category(String),
+    Glob(Pattern),
+}
+
+impl Selector {
+    fn parse(text: &str) -> Result<Self> {
+        let (exclude, selector) = match text.strip_prefix(EXCLUDE_PREFIX) {
+            Some(selector) => (true, selector.trim()),
+            None => (false, text),
+        };
+        let kind = if selector == "all" {
+            SelectorKind::All
+        } else if let Some(category) = selector.strip_prefix(CATEGORY_PREFIX) {
+            if category.is_empty() {
+                return Err(anyhow!("Plugin selector '{text}' names no category"));
+            }
+            SelectorKind::Category(category.to_string())
+        } else if selector.contains(['*', '?', '[']) {
+            let pattern = Pattern::new(selector)
+                .with_context(|| format!("Invalid plugin selector '{text}'"))?;
+            SelectorKind::Glob(pattern)
+        } else if selector.is_empty() {
+            return Err(anyhow!("Plugin selector '{text}' names no plugin"));
+        } else {
+            SelectorKind::NameOrCategory(selector.to_string())
+        };
+        Ok(Self {
+            text: text.to_string(),
+            exclude,
+            kind,
+        })
+    }
+
+    /// Whether matching a plugin with this name depends on its category
+    fn needs_category(&self, name: &str) -> bool {
+        match &self.kind {
+            SelectorKind::All | SelectorKind::Glob(_) => false,
+            SelectorKind::NameOrCategory(selector) => selector != name,
+            SelectorKind::Category(_) => true,
+        }
+    }
+
+    fn matches(&self, name: &str, category: Option<&str>) -> bool {
+        match &self.kind {
+            SelectorKind::All => true,
+            SelectorKind::NameOrCategory(selector) => {
+                name == selector || category == Some(selector.as_str())
+            }
+            SelectorKind::Category(selector) => category == Some(selector.as_str()),
+            SelectorKind::Glob(pattern) => pattern.matches(name),
+        }
+    }
+}
+
+impl PluginSelection {
+    /// Parse selectors, ignoring empty entries
+    pub fn parse<S: AsRef<str>>(selectors: &[S]) -> Result<Self> {
+        let selectors = selectors
+            .iter()
+            .map(|selector| selector.as_ref().trim())
+            .filter(|selector| !selector.is_empty())
+            .map(Selector::parse)
+            .collect::<Result<_>>()?;
+        Ok(Self { selectors })
+    }
+
+    /// Whether a plugin is selected
+    pub fn is_selected(&self, name: &str, category: Option<&str>) -> bool {
+        let mut includes = self.selectors.iter().filter(|s| !s.exclude).peekable();
+        let included =
+            includes.peek().is_none() || includes.any(|selector| selector.matches(name, category));
+        included
+            && !self
+                .selectors
+                .iter()
+                .any(|selector| selector.exclude && selector.matches(name, category))
+    }
+
+    /// Whether selecting a plugin with this name depends on its category
+    ///
+    /// Lets callers skip looking up categories that are costly to find, such
+    /// as those of external plugins.
+    pub fn needs_category(&self, name: &str) -> bool {
+        self.selectors
+            .iter()
+            .any(|selector| selector.needs_category(name))
+    }
+
+    /// Check that every selector matches at least one of the candidates
+    ///
+    /// Candidates are (name, category) pairs of every plugin that could be
+    /// selected, whether or not another selector excludes it.
+    pub fn check_matches(&self, candidates: &[(String, Option<String>)]) -> Result<()> {
+        let unmatched: Vec<&Selector> = self
+            .selectors
+            .iter()
+            .filter(|selector| {
+                !candidates
+                    .iter()
+                    .any(|(name, category)| selector.matches(name, category.as_deref()))
+            })
+            .collect();
+        let Some(first) = unmatched.first() else {
+            return Ok(());
+        };
+
+        let mut known: Vec<&str> = candidates
+            .iter()
+            .flat_map(|(name, category)| [Some(name.as_str()), category.as_deref()])
+            .flatten()
+            .collect();
+        known.sort_unstable();
+        known.dedup();
+
+        let texts: Vec<String> = unmatched
+            .iter()
+            .map(|selector| format!("'{}'", selector.text))
+            .collect();
+        let mut message = if texts.len() == 1 {
+            format!("Plugin selector {} matches no plugin", texts[0])
+        } else {
+            format!("Plugin selectors {} match no plugin", texts.join(", "))
+        };
+        let word = first.text.trim_start_matches(EXCLUDE_PREFIX).trim();
+        if let Some(suggestion) = suggest(word, &known) {
+            message.push_str(&format!(" (did you mean '{suggestion}'?)"));
+        }
+        Err(anyhow!(message))
+    }
+
+    /// Names of the selected candidates, after checking every selector matches one
+    pub fn select(&self, candidates: &[(String, Option<String>)]) -> Result<Vec<String>> {
+        self.check_matches(candidates)?;
+        Ok(candidates
+            .iter()
+            .filter(|(name, category)| self.is_selected(name, category.as_deref()))
+            .map(|(name, _)| name.clone())
+            .collect())
+    }
+}
+
+/// Selectors excluding the plugins of a comma-separated `--exclude` value
+pub fn exclude_selectors(exclude: &str) -> Vec<String> {
+    exclude
+        .split(',')
+        .map(str::trim)
+        .filter(|selector| !selector.is_empty())
+        .map(|selector| {
+            if selector.starts_with(EXCLUDE_PREFIX) {
+                selector.to_string()
+            } else {
+                format!("{EXCLUDE_PREFIX}{selector}")
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates() -> Vec<(String, Option<String>)> {
+        [
+            ("vscode_settings", "vscode"),
+            ("vscode_extensions", "vscode"),
+            ("cursor_settings", "cursor"),
+            ("npm_config", "npm"),
+            ("npm_global", "npm"),
+            ("static_files", "static"),
+        ]
+        .into_iter()
+        .map(|(name, category)| (name.to_string(), Some(category.to_string())))
+        .collect()
+    }
+
+    fn select(selectors: &[&str]) -> Result<Vec<String>> {
+        PluginSelection::parse(selectors)?.select(&candidates())
+    }
+
+    /// Test selecting by name, category and glob
+    /// Verifies names match exactly rather than as substrings
+    #[test]
+    fn test_select_includes() -> Result<()> {
+        assert_eq!(select(&[])?.len(), candidates().len());
+        assert_eq!(select(&["all"])?.len(), candidates().len());
+        assert_eq!(select(&["npm_config"])?, vec!["npm_config"]);
+        assert_eq!(select(&["npm"])?, vec!["npm_config", "npm_global"]);
+        assert_eq!(
+            select(&["category:vscode"])?,
+            vec!["vscode_settings", "vscode_extensions"]
+        );
+        assert_eq!(
+            select(&["*_settings"])?,
+            vec!["vscode_settings", "cursor_settings"]
+        );
+        assert_eq!(
+            select(&["npm_?onfig", "static"])?,
+            vec!["npm_config", "static_files"]
+        );
+        assert!(select(&["settings"]).is_err());
+        Ok(())
+    }
+
+    /// Test excluding plugins with negated selectors
+    /// Verifies excludes apply after includes and on their own
+    #[test]
+    fn test_select_excludes() -> Result<()> {
+        assert_eq!(
+            select(&["vscode", "!vscode_extensions"])?,
+            vec!["vscode_settings"]
+        );
+        assert_eq!(
+            select(&["!npm", "!category:vscode"])?,
+            vec!["cursor_settings", "static_files"]
+        );
+        assert_eq!(select(&["all", "!*"])?, Vec::<String>::new());
+
+        let mut selectors = vec!["npm".to_string()];
+        selectors.extend(exclude_selectors("npm_global, ,!npm_config"));
+        assert_eq!(selectors, vec!["npm", "!npm_global", "!npm_config"]);
+        assert_eq!(
+            PluginSelection::parse(&selectors)?.select(&candidates())?,
+            Vec::<String>::new()
+        );
+        Ok(())
+    }
+
+    /// Test which selections depend on plugin categories
+    /// Verifies names, globs and `all` are decided without the category
+    #[test]
+    fn test_needs_category() -> Result<()> {
+        let needs = |selectors: &[&str]| -> Result<bool> {
+            Ok(PluginSelection::parse(selectors)?.needs_category("gh_extensions"))
+        };
+        assert!(!needs(&[])?);
+        assert!(!needs(&["all", "!npm_*"])?);
+        assert!(!needs(&["gh_extensions"])?);
+        assert!(needs(&["all", "!npm"])?);
+        assert!(needs(&["category:external"])?);
+        Ok(())
+    }
+
+    /// Test selectors that match nothing or don't parse
+    /// Verifies every unmatched selector is reported with a suggestion
+    #[test]
+    fn test_select_errors() {
+        let error = select(&["vscdoe"]).unwrap_err().to_string();
+        assert_eq!(
+            error,
+            "Plugin selector 'vscdoe' matches no plugin (did you mean 'vscode'?)"
+        );
+
+        let error = select(&["npm", "!brew*", "category:npm_config"])
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("Plugin selectors '!brew*', 'category:npm_config' match"));
+
+        assert!(select(&["[vscode"]).is_err());
+        assert!(select(&["category:"]).is_err());
+        assert!(select(&["!"]).is_err());
+    }
+}
@@ -5,8 +5,12 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use tokio::fs as async_fs;
+use tracing::warn;
 
-use crate::core::checksum::{calculate_directory_checksum, checksums_equal};
+use crate::core::checksum::{
+    calculate_directory_checksum, checksums_equal, FileHashes, HashAlgorithm,
+};
+use crate::core::index::SnapshotIndex;
 
 /// Metadata for a snapshot
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +19,78 @@ pub struct SnapshotMetadata {
     pub version: String,
     pub checksums: HashMap<String, String>, // plugin_name -> checksum
     pub directory_checksum: String,
+    /// Algorithm used for `directory_checksum` (SHA-256 for older snapshots)
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
+    /// Output file of each plugin, relative to the snapshot directory
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub files: HashMap<String, PathBuf>,
+    /// Config profile the snapshot was taken with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Machine the snapshot was taken on (missing in older snapshots)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<HostInfo>,
+}
+
+impl SnapshotMetadata {
+    /// Whether the snapshot was taken on the given host
+    pub fn is_from_host(&self, hostname: &str) -> bool {
+        self.host
+            .as_ref()
+            .is_some_and(|host| host.matches(hostname))
+    }
+}
+
+/// Machine a snapshot was taken on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostInfo {
+    pub hostname: String,
+    pub os: String,
+    pub arch: String,
+    pub username: String,
+    pub home_dir: Option<PathBuf>,
+}
+
+impl HostInfo {
+    /// Describe the machine this process runs on
+    pub fn current() -> Self {
+        Self {
+            hostname: current_hostname(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            username: whoami::username(),
+            home_dir: dirs::home_dir(),
+        }
+    }
+
+    /// Whether this is the given host
+    ///
+    /// Hostnames compare case-insensitively, and a short name matches the
+    /// fully qualified one (`laptop` matches `laptop.example.com`).
+    pub fn matches(&self, hostname: &str) -> bool {
+        hostname_matches(&self.hostname, hostname)
+    }
+}
+
+/// Whether `hostname` is the host named `name`, see [`HostInfo::matches`]
+pub fn hostname_matches(hostname: &str, name: &str) -> bool {
+    hostname.eq_ignore_ascii_case(name)
+        || (!name.contains('.')
+            && hostname
+                .split('.')
+                .next()
+                .is_some_and(|short| short.eq_ignore_ascii_case(name)))
+}
+
+/// Hostname of this machine, `unknown` if it can't be determined
+pub fn current_hostname() -> String {
+    whoami::fallible::hostname().unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// Whether a directory name has the snapshot timestamp format (YYYYMMDD_HHMMSS)
+pub fn is_snapshot_name(name: &str) -> bool {
+    name.len() == 15 && name.chars().nth(8) == Some('_')
 }
 
 /// Manages snapshot creation and validation
@@ -87,10 +163,10 @@ impl SnapshotManager {
         Ok(metadata)
     }
 
-    /// Finds the most recent snapshot directory excluding a specific directory
-    pub fn find_latest_snapshot_excluding(&self, exclude_dir: &Path) -> Result<Option<PathBuf>> {
+    /// Lists snapshot directories in the output directory, oldest first
+    pub fn list_snapshots(&self) -> Result<Vec<PathBuf>> {
         if !self.base_path.exists() {
-            return Ok(None);
+            return Ok(Vec::new());
         }
 
         let mut snapshots = Vec::new();
@@ -99,10 +175,10 @@ impl SnapshotManager {
             let entry = entry?;
             let path = entry.path();
 
-            if path.is_dir() && path != exclude_dir {
+            if path.is_dir() {
                 if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
                     // Check if directory name matches timestamp format
-                    if name.len() == 15 && name.chars().nth(8) == Some('_') {
+                    if is_snapshot_name(name) {
                         snapshots.push(path);
                     }
                 }
@@ -112,10 +188,27 @@ impl SnapshotManager {
         // Sort by name (which is timestamp-based)
         snapshots.sort();
 
-        Ok(snapshots.last().cloned())
+        Ok(snapshots)
+    }
+
+    /// Finds the most recent snapshot directory
+    pub fn find_latest_snapshot(&self) -> Result<Option<PathBuf>> {
+        Ok(self.list_snapshots()?.pop())
     }
 
-    /// Checks if a file with the given checksum exists in the latest snapshot
+    /// Finds the most recent snapshot directory excluding a specific directory
+    pub fn find_latest_snapshot_excluding(&self, exclude_dir: &Path) -> Result<Option<PathBuf>> {
+        Ok(self
+            .list_snapshots()?
+            .into_iter()
+            .rfind(|path| path != exclude_dir))
+    }
+
+    /// Finds a file with the given checksum in any earlier snapshot
+    ///
+    /// The checksum index is searched first (newest snapshot first). Snapshots
+    /// that are not indexed yet are covered by checking the latest snapshot's
+    /// metadata for `filename` in its `.snapshot` directory or root.
     pub async fn find_file_by_checksum(
         &self,
         plugin_name: &str,
@@ -123,9 +216,53 @@ impl SnapshotManager {
         checksum: &str,
         exclude_dir: &Path,
     ) -> Result<Option<PathBuf>> {
-        let latest_snapshot = match self.find_latest_snapshot_excluding(exclude_dir)? {
-            Some(path) => path,
-            None => return Ok(None),
+        self.lookup_checksum(plugin_name, filename, checksum, Some(exclude_dir))
+            .await
+    }
+
+    /// Finds a file with the given checksum in any existing snapshot, the same
+    /// way as [`Self::find_file_by_checksum`]
+    pub async fn find_any_file_by_checksum(
+        &self,
+        plugin_name: &str,
+        filename: &str,
+        checksum: &str,
+    ) -> Result<Option<PathBuf>> {
+        self.lookup_checksum(plugin_name, filename, checksum, None)
+            .await
+    }
+
+    async fn lookup_checksum(
+        &self,
+        plugin_name: &str,
+        filename: &str,
+        checksum: &str,
+        exclude_dir: Option<&Path>,
+    ) -> Result<Option<PathBuf>> {
+        let index = match SnapshotIndex::load(&self.base_path).await {
+            Ok(index) => index,
+            Err(e) => {
+                warn!("Ignoring snapshot index: {:#}", e);
+                SnapshotIndex::default()
+            }
+        };
+        let excluded = exclude_dir.and_then(|dir| dir.file_name().and_then(|n| n.to_str()));
+        for entry in index.lookup(checksum, plugin_name) {
+            if Some(entry.snapshot.as_str()) == excluded {
+                continue;
+            }
+            let path = self.base_path.join(&entry.snapshot).join(&entry.path);
+            if path.is_file() {
+                return Ok(Some(path));
+            }
+        }
+
+        let latest_snapshot = match exclude_dir {
+            Some(exclude_dir) => self.find_latest_snapshot_excluding(exclude_dir)?,
+            None => self.find_latest_snapshot()?,
+        };
+        let Some(latest_snapshot) = latest_snapshot else {
+            return Ok(None);
         };
 
         let metadata = self.load_metadata(&latest_snapshot).await?;
@@ -149,60 +286,60 @@ impl SnapshotManager {
         Ok(None)
     }
 
-    /// Copies a file from the latest snapshot to the current snapshot
+    /// Copies earlier output with the given checksum to `destination` in the
+    /// current snapshot, returning the file it was copied from
     pub async fn copy_from_latest(
         &self,
-        _plugin_name: &str,
+        plugin_name: &str,
         filename: &str,
-        target_dir: &Path,
-    ) -> Result<bool> {
-        let latest_snapshot = match self.find_latest_snapshot_excluding(target_dir)? {
-            Some(path) => path,
-            None => return Ok(false),
+        checksum: &str,
+        snapshot_dir: &Path,
+        destination: &Path,
+    ) -> Result<Option<PathBuf>> {
+        let Some(source) = self
+            .find_file_by_checksum(plugin_name, filename, checksum, snapshot_dir)
+            .await?
+        else {
+            return Ok(None);
         };
 
-        // Try .snapshot subdirectory first
-        let snapshot_subdir_source = latest_snapshot.join(".snapshot").join(filename);
-        let snapshot_subdir_target = target_dir.join(".snapshot").join(filename);
-
-        if snapshot_subdir_source.exists() {
-            // Create target .snapshot directory if it doesn't exist
-            if let Some(parent) = snapshot_subdir_target.parent() {
-                async_fs::create_dir_all(parent)
-                    .await
-                    .context("Failed to create .snapshot directory")?;
-            }
-
-            async_fs::copy(&snapshot_subdir_source, &snapshot_subdir_target)
+        if let Some(parent) = destination.parent() {
+            async_fs::create_dir_all(parent)
                 .await
-                .context("Failed to copy file from latest snapshot")?;
-            return Ok(true);
+                .context("Failed to create output directory")?;
         }
 
-        // Fallback to root directory
-        let root_source = latest_snapshot.join(filename);
-        let root_target = target_dir.join(filename);
-
-        if root_source.exists() {
-            async_fs::copy(&root_source, &root_target)
-                .await
-                .context("Failed to copy file from latest snapshot")?;
-            return Ok(true);
-        }
-
-        Ok(false)
+        async_fs::copy(&source, destination)
+            .await
+            .context("Failed to copy file from earlier snapshot")?;
+        Ok(Some(source))
     }
 
-    /// Calculates and updates the directory checksum for a snapshot
-    pub async fn finalize_snapshot(&self, snapshot_dir: &Path) -> Result<()> {
-        let directory_checksum = calculate_directory_checksum(snapshot_dir)?;
-
+    /// Calculates and updates the directory checksum for a snapshot and
+    /// records its output files in the checksum index
+    ///
+    /// `hashes` holds the file hashes computed while the snapshot was written.
+    pub async fn finalize_snapshot(&self, snapshot_dir: &Path, hashes: &FileHashes) -> Result<()> {
         // Update metadata with directory checksum
         let mut metadata = self.load_metadata(snapshot_dir).await?;
-        metadata.directory_checksum = directory_checksum;
+        metadata.directory_checksum = if hashes.algorithm() == metadata.hash_algorithm {
+            hashes.directory_checksum(snapshot_dir)?
+        } else {
+            calculate_directory_checksum(snapshot_dir, metadata.hash_algorithm)?
+        };
 
         self.save_metadata(snapshot_dir, &metadata).await?;
 
+        // A missing or corrupt index is rebuilt so earlier snapshots stay reusable
+        let mut index = match SnapshotIndex::load(&self.base_path).await {
+            Ok(index) if SnapshotIndex::path(&self.base_path).exists() => index,
+            _ => SnapshotIndex::rebuild(self).await?,
+        };
+        if let Some(name) = snapshot_dir.file_name().and_then(|n| n.to_str()) {
+            index.add_snapshot(name, &metadata);
+        }
+        index.save(&self.base_path).await?;
+
         Ok(())
     }
 
@@ -213,8 +350,24 @@ impl SnapshotManager {
             version: env!("CARGO_PKG_VERSION").to_string(),
             checksums: HashMap::new(),
             directory_checksum: String::new(),
+            hash_algorithm: HashAlgorithm::default(),
+            files: HashMap::new(),
+            profile: None,
+            host: Some(HostInfo::current()),
         }
     }
+
+    /// Loads every snapshot with its metadata, oldest first
+    ///
+    /// Metadata is `None` for snapshots whose metadata can't be read.
+    pub async fn load_snapshots(&self) -> Result<Vec<(PathBuf, Option<SnapshotMetadata>)>> {
+        let mut snapshots = Vec::new();
+        for path in self.list_snapshots()? {
+            let metadata = self.load_metadata(&path).await.ok();
+            snapshots.push((path, metadata));
+        }
+        Ok(snapshots)
+    }
 }
 
 #[cfg(test)]
@@ -264,6 +417,10 @@ mod tests {
             version: "1.0.0".to_string(),
             checksums: HashMap::new(),
             directory_checksum: "old_checksum".to_string(),
+            hash_algorithm: HashAlgorithm::Sha256,
+            files: HashMap::new(),
+            profile: None,
+            host: None,
         };
         let old_path = snapshot_dir.join("metadata.json");
         let json = serde_json::to_string_pretty(&old_metadata)?;
@@ -385,6 +542,10 @@ mod tests {
             version: "1.0.0".to_string(),
             checksums,
             directory_checksum: String::new(),
+            hash_algorithm: HashAlgorithm::Sha256,
+            files: HashMap::new(),
+            profile: None,
+            host: None,
         };
         manager.save_metadata(&snapshot_dir, &metadata).await?;
 
@@ -420,6 +581,10 @@ mod tests {
             version: "1.0.0".to_string(),
             checksums,
             directory_checksum: String::new(),
+            hash_algorithm: HashAlgorithm::Sha256,
+            files: HashMap::new(),
+            profile: None,
+            host: None,
         };
         manager.save_metadata(&snapshot_dir, &metadata).await?;
 
@@ -452,6 +617,10 @@ mod tests {
             version: "1.0.0".to_string(),
             checksums,
             directory_checksum: String::new(),
+            hash_algorithm: HashAlgorithm::Sha256,
+            files: HashMap::new(),
+            profile: None,
+            host: None,
         };
         manager.save_metadata(&snapshot_dir, &metadata).await?;
 
@@ -484,6 +653,10 @@ mod tests {
             version: "1.0.0".to_string(),
             checksums,
             directory_checksum: String::new(),
+            hash_algorithm: HashAlgorithm::Sha256,
+            files: HashMap::new(),
+            profile: None,
+            host: None,
         };
         manager.save_metadata(&snapshot_dir, &metadata).await?;
 
@@ -507,14 +680,20 @@ mod tests {
         fs::create_dir_all(&target_dir)?;
 
         let result = manager
-            .copy_from_latest("plugin", "file.txt", &target_dir)
+            .copy_from_latest(
+                "plugin",
+                "file.txt",
+                "checksum123",
+                &target_dir,
+                &target_dir.join("file.txt"),
+            )
             .await?;
-        assert!(!result);
+        assert!(result.is_none());
 
         Ok(())
     }
 
-    /// Test copy_from_latest with file in .snapshot subdirectory
+    /// Test copy_from_latest with file in .snapshot subdirectory of an unindexed snapshot
     #[tokio::test]
     async fn test_copy_from_latest_snapshot_subdir() -> Result<()> {
         let temp_dir = TempDir::new()?;
@@ -525,52 +704,72 @@ mod tests {
         let source_snapshot_dir = source_dir.join(".snapshot");
         fs::create_dir_all(&source_snapshot_dir)?;
         fs::write(source_snapshot_dir.join("config.json"), "config content")?;
+        let mut metadata = manager.create_metadata();
+        metadata
+            .checksums
+            .insert("static".to_string(), "abc123".to_string());
+        manager.save_metadata(&source_dir, &metadata).await?;
 
         // Create target directory
         let target_dir = temp_dir.path().join("20240118_100000");
         fs::create_dir_all(&target_dir)?;
+        let target_file = target_dir.join(".snapshot").join("config.json");
 
         // Copy file
         let result = manager
-            .copy_from_latest("static", "config.json", &target_dir)
+            .copy_from_latest("static", "config.json", "abc123", &target_dir, &target_file)
             .await?;
-        assert!(result);
+        assert_eq!(result, Some(source_snapshot_dir.join("config.json")));
 
         // Verify file was copied
-        let target_file = target_dir.join(".snapshot").join("config.json");
-        assert!(target_file.exists());
         let content = fs::read_to_string(&target_file)?;
         assert_eq!(content, "config content");
 
         Ok(())
     }
 
-    /// Test copy_from_latest with file in root directory
+    /// Test copy_from_latest reusing output from a snapshot older than the latest
     #[tokio::test]
-    async fn test_copy_from_latest_root_dir() -> Result<()> {
+    async fn test_copy_from_latest_indexed_older_snapshot() -> Result<()> {
         let temp_dir = TempDir::new()?;
         let manager = SnapshotManager::new(temp_dir.path().to_path_buf());
 
-        // Create source snapshot
-        let source_dir = temp_dir.path().join("20240117_100000");
-        fs::create_dir_all(&source_dir)?;
-        fs::write(source_dir.join("data.txt"), "data content")?;
+        // Output flips back to the state of the oldest snapshot
+        for (name, checksum, content) in [
+            ("20240116_100000", "sum_a", "state a"),
+            ("20240117_100000", "sum_b", "state b"),
+        ] {
+            let snapshot_dir = temp_dir.path().join(name);
+            fs::create_dir_all(snapshot_dir.join("tools"))?;
+            fs::write(snapshot_dir.join("tools").join("data.txt"), content)?;
+            let mut metadata = manager.create_metadata();
+            metadata
+                .checksums
+                .insert("plugin".to_string(), checksum.to_string());
+            metadata
+                .files
+                .insert("plugin".to_string(), PathBuf::from("tools/data.txt"));
+            manager.save_metadata(&snapshot_dir, &metadata).await?;
+            manager
+                .finalize_snapshot(&snapshot_dir, &FileHashes::default())
+                .await?;
+        }
+        assert!(SnapshotIndex::path(temp_dir.path()).exists());
 
         // Create target directory
         let target_dir = temp_dir.path().join("20240118_100000");
         fs::create_dir_all(&target_dir)?;
+        let target_file = target_dir.join("tools").join("data.txt");
 
         // Copy file
         let result = manager
-            .copy_from_latest("plugin", "data.txt", &target_dir)
+            .copy_from_latest("plugin", "data.txt", "sum_a", &target_dir, &target_file)
             .await?;
-        assert!(result);
-
-        // Verify file was copied
-        let target_file = target_dir.join("data.txt");
-        assert!(target_file.exists());
-        let content = fs::read_to_string(&target_file)?;
-        assert_eq!(content, "data content");
+        assert_eq!(
+            result,
+            Some(temp_dir.path().join("20240116_100000/tools/data.txt"))
+        );
+        assert_eq!(fs::read_to_string(&target_file)?, "state a");
 
         Ok(())
     }
@@ -584,6 +783,11 @@ mod tests {
         // Create source snapshot without the file
         let source_dir = temp_dir.path().join("20240117_100000");
         fs::create_dir_all(&source_dir)?;
+        let mut metadata = manager.create_metadata();
+        metadata
+            .checksums
+            .insert("plugin".to_string(), "abc123".to_string());
+        manager.save_metadata(&source_dir, &metadata).await?;
 
         // Create target directory
         let target_dir = temp_dir.path().join("20240118_100000");
@@ -591,9 +795,16 @@ mod tests {
 
         // Try to copy non-existent file
         let result = manager
-            .copy_from_latest("plugin", "missing.txt", &target_dir)
+            .copy_from_latest(
+                "plugin",
+                "missing.txt",
+                "abc123",
+                &target_dir,
+                &target_dir.join("missing.txt"),
+            )
             .await?;
-        assert!(!result);
+        assert!(result.is_none());
+        assert!(!target_dir.join("missing.txt").exists());
 
         Ok(())
     }
@@ -614,7 +825,9 @@ mod tests {
         manager.save_metadata(&snapshot_dir, &metadata).await?;
 
         // Finalize snapshot
-        manager.finalize_snapshot(&snapshot_dir).await?;
+        manager
+            .finalize_snapshot(&snapshot_dir, &FileHashes::default())
+            .await?;
 
         // Load metadata and verify directory checksum was updated
         let updated_metadata = manager.load_metadata(&snapshot_dir).await?;
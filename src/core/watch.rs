@@ -0,0 +1,245 @@
+//! Watch mode: which plugins to snapshot when tracked files change
+
+use notify::RecursiveMode;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::core::plugin::PluginRegistry;
+
+/// Files watched for each plugin, and the plugins that can only be polled
+#[derive(Debug, Default)]
+pub struct WatchPlan {
+    /// Watched file or directory and the plugins whose output depends on it
+    pub paths: BTreeMap<PathBuf, Vec<String>>,
+    /// Plugins without watchable files (package managers), snapshotted on an interval
+    pub polled: Vec<String>,
+}
+
+impl WatchPlan {
+    /// Ask every registered plugin which paths affect its output
+    pub async fn build(registry: &PluginRegistry) -> Self {
+        let mut plan = Self::default();
+
+        for (name, plugin) in registry.plugins() {
+            match plugin.watch_paths().await {
+                Ok(paths) if !paths.is_empty() => {
+                    for path in paths {
+                        plan.paths.entry(path).or_default().push(name.clone());
+                    }
+                }
+                Ok(_) => plan.polled.push(name.clone()),
+                Err(e) => {
+                    warn!("Polling {}, could not resolve its files: {}", name, e);
+                    plan.polled.push(name.clone());
+                }
+            }
+        }
+
+        plan
+    }
+
+    /// Directories to register with the file watcher
+    ///
+    /// Files are watched through their parent directory so that editors
+    /// replacing a file on save are still noticed. Directories are watched
+    /// recursively. Paths that don't exist (yet) are skipped.
+    pub fn watch_roots(&self) -> Vec<(PathBuf, RecursiveMode)> {
+        let mut roots: BTreeMap<PathBuf, RecursiveMode> = BTreeMap::new();
+
+        for path in self.paths.keys() {
+            let (root, mode) = if path.is_dir() {
+                (path.clone(), RecursiveMode::Recursive)
+            } else {
+                match path.parent().filter(|parent| parent.is_dir()) {
+                    Some(parent) => (parent.to_path_buf(), RecursiveMode::NonRecursive),
+                    None => continue,
+                }
+            };
+
+            let entry = roots.entry(root).or_insert(mode);
+            if mode == RecursiveMode::Recursive {
+                *entry = mode;
+            }
+        }
+
+        roots.into_iter().collect()
+    }
+
+    /// Plugins affected by changes to the given paths
+    pub fn affected_plugins<'a>(
+        &self,
+        changed: impl IntoIterator<Item = &'a PathBuf>,
+    ) -> BTreeSet<String> {
+        let mut affected = BTreeSet::new();
+
+        for changed_path in changed {
+            for (watched, plugins) in &self.paths {
+                if changed_path.starts_with(watched) {
+                    affected.extend(plugins.iter().cloned());
+                }
+            }
+        }
+
+        affected
+    }
+}
+
+/// Collects changed paths until no change arrived for the debounce period
+#[derive(Debug)]
+pub struct Debouncer {
+    delay: Duration,
+    pending: BTreeSet<PathBuf>,
+    last_change: Option<Instant>,
+}
+
+impl Debouncer {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            pending: BTreeSet::new(),
+            last_change: None,
+        }
+    }
+
+    /// Record changed paths, ignoring those inside `ignored` (the output directory)
+    pub fn push(&mut self, paths: impl IntoIterator<Item = PathBuf>, ignored: &Path, now: Instant) {
+        let before = self.pending.len();
+        self.pending
+            .extend(paths.into_iter().filter(|path| !path.starts_with(ignored)));
+        if self.pending.len() > before {
+            self.last_change = Some(now);
+        }
+    }
+
+    /// When the pending changes become ready, if there are any
+    pub fn deadline(&self) -> Option<Instant> {
+        self.last_change.map(|last_change| last_change + self.delay)
+    }
+
+    /// Take the pending changes once the debounce period has passed
+    pub fn take_ready(&mut self, now: Instant) -> Option<BTreeSet<PathBuf>> {
+        if self.deadline().is_some_and(|deadline| now >= deadline) {
+            self.last_change = None;
+            Some(std::mem::take(&mut self.pending))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::plugin::Plugin;
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    struct WatchedPlugin(Vec<PathBuf>);
+
+    #[async_trait]
+    impl Plugin for WatchedPlugin {
+        fn description(&self) -> &str {
+            "Watch test plugin"
+        }
+
+        fn icon(&self) -> &str {
+            "🧪"
+        }
+
+        async fn execute(&self) -> Result<String> {
+            Ok(String::new())
+        }
+
+        async fn validate(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn get_target_path(&self) -> Option<String> {
+            None
+        }
+
+        fn get_output_file(&self) -> Option<String> {
+            None
+        }
+
+        async fn watch_paths(&self) -> Result<Vec<PathBuf>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    /// Test building a watch plan from plugin watch paths
+    /// Verifies changed files map to their plugins and plugins without files are polled
+    #[tokio::test]
+    async fn test_watch_plan() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let settings = temp_dir.path().join("settings.json");
+        let dotfiles = temp_dir.path().join("dotfiles");
+        std::fs::write(&settings, "{}")?;
+        std::fs::create_dir(&dotfiles)?;
+
+        let mut registry = PluginRegistry::new();
+        registry.add_plugin(
+            "app_settings".to_string(),
+            Arc::new(WatchedPlugin(vec![settings.clone()])),
+        );
+        registry.add_plugin(
+            "static_files".to_string(),
+            Arc::new(WatchedPlugin(vec![dotfiles.clone(), settings.clone()])),
+        );
+        registry.add_plugin(
+            "brew_packages".to_string(),
+            Arc::new(WatchedPlugin(Vec::new())),
+        );
+
+        let plan = WatchPlan::build(&registry).await;
+        assert_eq!(plan.polled, vec!["brew_packages".to_string()]);
+        assert_eq!(
+            plan.watch_roots(),
+            vec![
+                (temp_dir.path().to_path_buf(), RecursiveMode::NonRecursive),
+                (dotfiles.clone(), RecursiveMode::Recursive),
+            ]
+        );
+
+        let affected = plan.affected_plugins(&[dotfiles.join("nested").join(".vimrc")]);
+        assert_eq!(affected, BTreeSet::from(["static_files".to_string()]));
+        let affected = plan.affected_plugins([&settings]);
+        assert_eq!(affected.len(), 2);
+        assert!(plan
+            .affected_plugins(&[temp_dir.path().join("other.txt")])
+            .is_empty());
+
+        Ok(())
+    }
+
+    /// Test debouncing bursts of file changes
+    /// Verifies changes are released only after a quiet period and output is ignored
+    #[test]
+    fn test_debouncer() {
+        let start = Instant::now();
+        let output = PathBuf::from("/snapshots");
+        let mut debouncer = Debouncer::new(Duration::from_secs(2));
+        assert!(debouncer.deadline().is_none());
+
+        debouncer.push([output.join("20240101_000000")], &output, start);
+        assert!(debouncer.deadline().is_none());
+
+        debouncer.push([PathBuf::from("/home/a")], &output, start);
+        let later = start + Duration::from_secs(1);
+        debouncer.push([PathBuf::from("/home/b")], &output, later);
+        assert!(debouncer
+            .take_ready(start + Duration::from_secs(2))
+            .is_none());
+
+        let ready = debouncer
+            .take_ready(later + Duration::from_secs(2))
+            .unwrap();
+        assert_eq!(ready.len(), 2);
+        assert!(debouncer.deadline().is_none());
+    }
+}
@@ -6,7 +6,8 @@ use std::io;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Instant;
-use tracing::{error, info};
+use tracing::{debug, error, info};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 
 mod cli;
 mod config;
@@ -14,9 +15,16 @@ mod core;
 mod plugins;
 mod symbols;
 
+use config::layers::{ConfigLayer, ConfigSource, LoadOptions};
 use config::Config;
 use core::executor::SnapshotExecutor;
+use core::lock::SnapshotLock;
 use core::plugin::PluginRegistry;
+use core::policy::{FailurePolicy, RunExitCode};
+use core::report::ReportFormat;
+use core::schedule::{ScheduleBackend, ScheduleInterval};
+use core::selector::exclude_selectors;
+use core::snapshot::current_hostname;
 // Auto-registration system means we don't need explicit plugin imports
 // The inventory system will discover all plugins automatically
 use symbols::*;
@@ -38,6 +46,10 @@ struct Args {
     #[arg(short, long, global = true)]
     config: Option<PathBuf>,
 
+    /// Config profile to apply on top of the base config ([profiles.<name>])
+    #[arg(long, global = true, env = "DOTSNAPSHOT_PROFILE")]
+    profile: Option<String>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 
@@ -45,10 +57,15 @@ struct Args {
     #[arg(short, long)]
     output: Option<PathBuf>,
 
-    /// Specify which plugins to run (comma-separated) - used when no subcommand
+    /// Specify which plugins to run (comma-separated): names, categories, category:<name>,
+    /// globs and !<selector> to exclude - used when no subcommand
     #[arg(short, long)]
     plugins: Option<String>,
 
+    /// Plugins not to run (comma-separated selectors) - used when no subcommand
+    #[arg(long, value_name = "PLUGINS")]
+    exclude: Option<String>,
+
     /// List available plugins - used when no subcommand
     #[arg(short, long)]
     list: bool,
@@ -64,6 +81,24 @@ struct Args {
     /// Generate man page - used when no subcommand
     #[arg(long)]
     man: bool,
+
+    /// Format of the run report printed after a snapshot - used when no subcommand
+    #[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+    report_format: ReportFormat,
+
+    /// Failure policy: never, any-failure, all-failed or plugins:<name>,... listing critical plugins
+    /// (overrides config file) - used when no subcommand
+    #[arg(long, value_name = "POLICY")]
+    fail_on: Option<FailurePolicy>,
+
+    /// Show what a snapshot would capture without writing anything - used when no subcommand
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Don't create a snapshot when no plugin output changed since the latest one
+    /// (overrides config file) - used when no subcommand
+    #[arg(long)]
+    skip_unchanged: bool,
 }
 
 #[derive(Parser)]
@@ -82,10 +117,14 @@ enum Commands {
         #[arg(long)]
         latest: bool,
 
-        /// Restore only specific plugins (comma-separated)
+        /// Restore only specific plugins (comma-separated selectors, as for snapshots)
         #[arg(short, long)]
         plugins: Option<String>,
 
+        /// Plugins not to restore (comma-separated selectors)
+        #[arg(long, value_name = "PLUGINS")]
+        exclude: Option<String>,
+
         /// Preview changes without applying them
         #[arg(long)]
         dry_run: bool,
@@ -101,6 +140,156 @@ enum Commands {
         /// Custom target directory for restoration
         #[arg(long)]
         target_dir: Option<PathBuf>,
+
+        /// With --latest, use the latest snapshot taken on this host
+        /// (the current one when no hostname is given)
+        #[arg(long, value_name = "HOSTNAME")]
+        host: Option<Option<String>>,
+    },
+    /// List snapshots with the host and profile they were taken with
+    List {
+        /// Output directory containing the snapshots (overrides config file)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Only list snapshots taken on this host (the current one when no hostname is given)
+        #[arg(long, value_name = "HOSTNAME")]
+        host: Option<Option<String>>,
+    },
+    /// Manage the checksum index used to reuse output from earlier snapshots
+    Index {
+        #[command(subcommand)]
+        command: IndexCommands,
+    },
+    /// Watch tracked files and snapshot affected plugins when they change
+    Watch {
+        /// Output directory for snapshots (overrides config file)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Watch only specific plugins (comma-separated selectors, as for snapshots)
+        #[arg(short, long)]
+        plugins: Option<String>,
+
+        /// Plugins not to watch (comma-separated selectors)
+        #[arg(long, value_name = "PLUGINS")]
+        exclude: Option<String>,
+
+        /// Quiet period after the last change before snapshotting, in milliseconds
+        /// (overrides config file)
+        #[arg(long, value_name = "MS")]
+        debounce_ms: Option<u64>,
+
+        /// Interval for snapshotting package-manager plugins, in seconds; 0 disables
+        /// polling (overrides config file)
+        #[arg(long, value_name = "SECONDS")]
+        poll_interval: Option<u64>,
+    },
+    /// Schedule snapshots with a systemd user timer or a crontab entry
+    Schedule {
+        #[command(subcommand)]
+        command: ScheduleCommands,
+    },
+    /// Detect installed tools and write a commented config file
+    Init {
+        /// Snapshot directory written to the config (default: the platform's data directory)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Don't ask; enable every detected plugin and dotfile
+        #[arg(long)]
+        non_interactive: bool,
+
+        /// Overwrite an existing config file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Inspect the configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+}
+
+#[derive(Parser)]
+enum ConfigCommands {
+    /// Show the config layers, or the merged configuration with --effective
+    Show {
+        /// Print the configuration merged from every layer
+        #[arg(long)]
+        effective: bool,
+
+        /// Annotate each value with the layers that set it
+        #[arg(long, requires = "effective")]
+        origin: bool,
+    },
+    /// Check config files for unknown keys, wrong types and invalid values
+    Validate,
+    /// Print the JSON Schema of the config file for editor completion and validation
+    Schema,
+    /// Print the effective value of a key, e.g. `plugins.static.files`
+    Get {
+        /// Dotted key path
+        key: String,
+    },
+    /// Set a key in the config file, keeping its comments and formatting
+    Set {
+        /// Dotted key path
+        key: String,
+
+        /// Value as a TOML literal (`true`, `30`, `["a", "b"]`); anything else is a string
+        value: String,
+    },
+    /// Remove a key from the config file
+    Unset {
+        /// Dotted key path
+        key: String,
+    },
+    /// Upgrade config files to the current format, keeping a backup of each
+    Migrate {
+        /// Show the changes without writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Parser)]
+enum ScheduleCommands {
+    /// Install (or replace) the scheduled snapshot
+    Install {
+        /// Interval between snapshots, e.g. 30m, 6h or 1d
+        #[arg(long)]
+        every: ScheduleInterval,
+
+        /// Output directory for snapshots (overrides config file)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Scheduler to use (default: systemd on Linux when available, otherwise cron)
+        #[arg(long, value_enum)]
+        backend: Option<ScheduleBackend>,
+
+        /// Print the generated units or crontab entry without installing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show the installed schedule
+    Status,
+    /// Remove the scheduled snapshot
+    Remove {
+        /// Show what would be removed without removing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Parser)]
+enum IndexCommands {
+    /// Rebuild the checksum index from the metadata of every snapshot
+    Rebuild {
+        /// Output directory containing the snapshots (defaults to the config file)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 }
 
@@ -281,6 +470,16 @@ struct HookActionArgs {
 fn create_subscriber(
     debug: bool,
     time_format: String,
+) -> Box<dyn tracing::Subscriber + Send + Sync> {
+    create_subscriber_with_writer(debug, time_format, BoxMakeWriter::new(io::stdout))
+}
+
+/// Creates the logging subscriber writing to the given writer (stderr keeps
+/// stdout clean for machine-readable output)
+fn create_subscriber_with_writer(
+    debug: bool,
+    time_format: String,
+    writer: BoxMakeWriter,
 ) -> Box<dyn tracing::Subscriber + Send + Sync> {
     let level = if debug {
         tracing::Level::DEBUG
@@ -295,6 +494,7 @@ fn create_subscriber(
             Box::new(
                 tracing_subscriber::fmt()
                     .with_max_level(level)
+                    .with_writer(writer)
                     .with_timer(tracing_subscriber::fmt::time::LocalTime::new(format_desc))
                     .finish(),
             )
@@ -305,6 +505,7 @@ fn create_subscriber(
             Box::new(
                 tracing_subscriber::fmt()
                     .with_max_level(level)
+                    .with_writer(writer)
                     .with_timer(tracing_subscriber::fmt::time::LocalTime::new(format_desc))
                     .finish(),
             )
@@ -316,6 +517,7 @@ fn create_subscriber(
             Box::new(
                 tracing_subscriber::fmt()
                     .with_max_level(level)
+                    .with_writer(writer)
                     .with_timer(tracing_subscriber::fmt::time::LocalTime::new(format_desc))
                     .finish(),
             )
@@ -333,6 +535,7 @@ fn create_subscriber(
             Box::new(
                 tracing_subscriber::fmt()
                     .with_max_level(level)
+                    .with_writer(writer)
                     .with_timer(tracing_subscriber::fmt::time::LocalTime::new(format_desc))
                     .finish(),
             )
@@ -340,7 +543,73 @@ fn create_subscriber(
     }
 }
 
-async fn list_plugins() {
+/// Config layers for `--config`, `--profile` and the flags overriding config values
+fn load_options(args: &Args) -> Result<LoadOptions> {
+    let flag = |flag: &str, key: &str, value: toml::Value| {
+        ConfigLayer {
+            source: ConfigSource::Cli(flag.to_string()),
+            table: toml::Table::new(),
+        }
+        .with_value(key, value)
+    };
+
+    let mut cli = Vec::new();
+    if let Some(output) = &args.output {
+        let output = output.display().to_string();
+        cli.push(flag("--output", "output_dir", toml::Value::String(output)));
+    }
+    if let Some(plugins) = &args.plugins {
+        let plugins = plugins
+            .split(',')
+            .map(|plugin| toml::Value::String(plugin.to_string()))
+            .collect();
+        cli.push(flag(
+            "--plugins",
+            "include_plugins",
+            toml::Value::Array(plugins),
+        ));
+    }
+    if args.verbose {
+        cli.push(flag(
+            "--verbose",
+            "logging.verbose",
+            toml::Value::Boolean(true),
+        ));
+    }
+    if let Some(fail_on) = &args.fail_on {
+        let fail_on = toml::Value::try_from(fail_on)?;
+        cli.push(flag("--fail-on", "snapshot.fail_on", fail_on));
+    }
+    if args.skip_unchanged {
+        let skip = toml::Value::Boolean(true);
+        cli.push(flag("--skip-unchanged", "snapshot.skip_unchanged", skip));
+    }
+
+    Ok(LoadOptions {
+        config_path: args.config.clone(),
+        profile: args.profile.clone(),
+        hostname: Some(current_hostname()),
+        cli,
+    })
+}
+
+/// Resolve a `--host` option, where a bare `--host` means this machine
+fn resolve_host(host: Option<Option<String>>) -> Option<String> {
+    host.map(|host| host.unwrap_or_else(current_hostname))
+}
+
+/// Plugin selectors for a snapshot: include_plugins (default: all) and --exclude
+fn selected_plugins(config: &Config, exclude: Option<&str>) -> Vec<String> {
+    let mut selected_plugins = config
+        .get_include_plugins()
+        .unwrap_or_else(|| vec!["all".to_string()]);
+    if let Some(exclude) = exclude {
+        selected_plugins.extend(exclude_selectors(exclude));
+    }
+    selected_plugins
+}
+
+async fn list_plugins(config: &Config, selected_plugins: &[String]) -> Result<()> {
     use std::collections::HashMap;
 
     // Type alias to simplify the complex type
@@ -350,14 +619,16 @@ async fn list_plugins() {
     println!("Available plugins:");
     println!();
 
-    // Load config for UI customization (optional)
-    let config = Config::load().await.ok();
-
-    // Auto-discover and register all plugins
-    let registry = PluginRegistry::discover_plugins(config.as_ref());
+    // Register the selected plugins, including custom and external ones
+    let mut registry = PluginRegistry::new();
+    let selected_plugins_refs: Vec<&str> = selected_plugins.iter().map(|s| s.as_str()).collect();
+    registry
+        .register_from_descriptors(Some(config), &selected_plugins_refs)
+        .await?;
+    registry.describe_external(|_| true).await;
 
     // Get detailed plugin information with category names and icons
-    let plugins_detailed = registry.list_plugins_detailed(config.as_ref());
+    let plugins_detailed = registry.list_plugins_detailed(Some(config));
 
     // Group plugins by category dynamically
     let mut plugin_groups: HashMap<String, PluginGroup> = HashMap::new();
@@ -388,7 +659,12 @@ async fn list_plugins() {
     println!("Usage:");
     println!("  --plugins <plugin1>,<plugin2>  Run specific plugins");
     println!("  --plugins homebrew,vscode      Run all homebrew and vscode plugins");
+    println!("  --plugins category:vscode      Run the plugins of a category");
+    println!("  --plugins '*_settings'         Run plugins matching a glob");
+    println!("  --plugins 'npm,!npm_global'    Exclude with ! inside a selection");
+    println!("  --exclude npm_global           Run all plugins except npm_global");
     println!("  (no --plugins)                 Run all plugins");
+    Ok(())
 }
 
 #[tokio::main]
@@ -397,20 +673,31 @@ async fn main() -> Result<()> {
     let args = Args::parse();
 
     // Initialize logging early for subcommands that need it
-    let config = if let Some(config_path) = &args.config {
-        if config_path.exists() {
-            Config::load_from_file(config_path).await?
-        } else {
+    let options = load_options(&args)?;
+    let config = match Config::load_with(&options).await {
+        Ok(config) => config,
+        // `config` commands inspect a broken configuration and `init` replaces it
+        Err(_)
+            if matches!(
+                args.command,
+                Some(Commands::Config { .. } | Commands::Init { .. })
+            ) =>
+        {
             Config::default()
         }
-    } else {
-        Config::load().await.unwrap_or_default()
+        Err(error) => return Err(error),
     };
 
-    let _verbose = args.verbose || config.is_verbose_default();
+    let _verbose = config.is_verbose_default();
     let debug = args.debug;
     let time_format = config.get_time_format();
-    let subscriber = create_subscriber(debug, time_format);
+    // JSON reports own stdout, so logs go to stderr to keep the output parseable
+    let subscriber = match args.report_format {
+        ReportFormat::Json => {
+            create_subscriber_with_writer(debug, time_format, BoxMakeWriter::new(io::stderr))
+        }
+        ReportFormat::Table => create_subscriber(debug, time_format),
+    };
     tracing::subscriber::set_global_default(subscriber).expect("Failed to set default subscriber");
 
     // Handle subcommands
@@ -423,23 +710,119 @@ async fn main() -> Result<()> {
                 snapshot_path,
                 latest,
                 plugins,
+                exclude,
                 dry_run,
                 backup,
                 force,
                 target_dir,
+                host,
             } => {
                 return cli::restore::handle_restore_command(
                     snapshot_path,
                     latest,
                     plugins,
+                    exclude,
                     dry_run,
                     backup,
                     force,
                     target_dir,
                     args.config,
+                    args.profile,
+                    resolve_host(host),
                 )
                 .await;
             }
+            Commands::List { output, host } => {
+                return cli::list::handle_list_command(
+                    output,
+                    resolve_host(host),
+                    args.config,
+                    args.profile,
+                )
+                .await;
+            }
+            Commands::Index { command } => match command {
+                IndexCommands::Rebuild { output } => {
+                    return cli::index::handle_index_rebuild(output, args.config, args.profile)
+                        .await;
+                }
+            },
+            Commands::Watch {
+                output,
+                plugins,
+                exclude,
+                debounce_ms,
+                poll_interval,
+            } => {
+                let options = cli::watch::WatchOptions {
+                    output,
+                    plugins,
+                    exclude,
+                    debounce_ms,
+                    poll_interval,
+                };
+                return cli::watch::handle_watch_command(options, args.config, args.profile).await;
+            }
+            Commands::Init {
+                output,
+                non_interactive,
+                force,
+            } => {
+                let options = cli::init::InitOptions {
+                    config_path: args.config,
+                    output,
+                    non_interactive,
+                    force,
+                };
+                return cli::init::handle_init_command(options).await;
+            }
+            Commands::Config { command } => match command {
+                ConfigCommands::Show { effective, origin } => {
+                    return cli::config::handle_config_show(&options, effective, origin);
+                }
+                ConfigCommands::Validate => {
+                    return cli::config::handle_config_validate(&options);
+                }
+                ConfigCommands::Schema => {
+                    return cli::config::handle_config_schema();
+                }
+                ConfigCommands::Get { key } => {
+                    return cli::config::handle_config_get(&options, &key);
+                }
+                ConfigCommands::Set { key, value } => {
+                    return cli::config::handle_config_set(&options, &key, &value).await;
+                }
+                ConfigCommands::Unset { key } => {
+                    return cli::config::handle_config_unset(&options, &key).await;
+                }
+                ConfigCommands::Migrate { dry_run } => {
+                    return cli::config::handle_config_migrate(&options, dry_run).await;
+                }
+            },
+            Commands::Schedule { command } => match command {
+                ScheduleCommands::Install {
+                    every,
+                    output,
+                    backend,
+                    dry_run,
+                } => {
+                    return cli::schedule::handle_schedule_install(
+                        every,
+                        output,
+                        backend,
+                        dry_run,
+                        args.config,
+                        args.profile,
+                    )
+                    .await;
+                }
+                ScheduleCommands::Status => {
+                    return cli::schedule::handle_schedule_status(args.profile).await;
+                }
+                ScheduleCommands::Remove { dry_run } => {
+                    return cli::schedule::handle_schedule_remove(dry_run, args.profile).await;
+                }
+            },
         }
     }
 
@@ -486,6 +869,11 @@ async fn main() -> Result<()> {
         println!("{SYMBOL_ACTION_LAUNCH} Usage:");
         println!("   dotsnapshot [OPTIONS]              Create a snapshot (default)");
         println!("   dotsnapshot hooks <SUBCOMMAND>     Manage plugin hooks");
+        println!("   dotsnapshot list                   List snapshots and where they were taken");
+        println!("   dotsnapshot watch                  Snapshot tracked files as they change");
+        println!("   dotsnapshot schedule <SUBCOMMAND>  Schedule snapshots (systemd or cron)");
+        println!("   dotsnapshot config show            Show config layers and merged values");
+        println!("   dotsnapshot config validate        Check config files for mistakes");
         println!("   Use --help for detailed options");
         println!();
         println!("{SYMBOL_TOOL_CONFIG} Shell Completions:");
@@ -502,7 +890,13 @@ async fn main() -> Result<()> {
 
     // Handle --list flag early
     if args.list {
-        list_plugins().await;
+        // Only --plugins and --exclude narrow the list, not include_plugins from files
+        let selected_plugins = if args.plugins.is_some() || args.exclude.is_some() {
+            selected_plugins(&config, args.exclude.as_deref())
+        } else {
+            Vec::new()
+        };
+        list_plugins(&config, &selected_plugins).await?;
         return Ok(());
     }
 
@@ -517,49 +911,106 @@ async fn main() -> Result<()> {
             config_path.display()
         );
     }
+    if let Some(profile) = &config.active_profile {
+        info!("{} Using profile: {}", SYMBOL_INDICATOR_INFO, profile);
+    }
 
-    // Determine final settings (CLI args override config file)
-    let output_dir = args.output.unwrap_or_else(|| config.get_output_dir());
+    // CLI args are merged into the config as its highest-precedence layer
+    let output_dir = config.get_output_dir()?;
 
-    // Create output directory if it doesn't exist
-    tokio::fs::create_dir_all(&output_dir).await?;
-
-    // Determine which plugins to run
-    let selected_plugins: Vec<String> = if let Some(cli_plugins) = args.plugins.as_deref() {
-        // CLI argument takes precedence
-        cli_plugins.split(',').map(|s| s.to_string()).collect()
-    } else if let Some(config_plugins) = config.get_include_plugins() {
-        // Use config file plugins
-        config_plugins
-    } else {
-        // Default: run all plugins
-        vec!["all".to_string()]
-    };
+    // Determine which plugins to run (default: all plugins)
+    let selected_plugins = selected_plugins(&config, args.exclude.as_deref());
 
     // Auto-discover and register plugins with filtering
     let mut registry = PluginRegistry::new();
     let selected_plugins_refs: Vec<&str> = selected_plugins.iter().map(|s| s.as_str()).collect();
-    registry.register_from_descriptors(Some(&config), &selected_plugins_refs);
+    registry
+        .register_from_descriptors(Some(&config), &selected_plugins_refs)
+        .await?;
+
+    // Critical plugins must exist, or their failures would never be noticed
+    let fail_on = config.get_fail_on();
+    let known_plugins = registry.names();
+    let known_plugins: Vec<&str> = known_plugins.iter().map(String::as_str).collect();
+    fail_on.check_plugins(&known_plugins)?;
+
+    // Dry run: plan the snapshot without touching the output directory
+    if args.dry_run {
+        let executor =
+            SnapshotExecutor::with_config(Arc::new(registry), output_dir, Arc::new(config));
+        let plan = executor.plan_snapshot().await?;
+        match args.report_format {
+            ReportFormat::Table => println!("{}", plan.to_table()),
+            ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&plan)?),
+        }
+        return Ok(());
+    }
+
+    // Create output directory if it doesn't exist
+    tokio::fs::create_dir_all(&output_dir).await?;
+
+    // Only one snapshot run may write to an output directory at a time
+    let _lock = match SnapshotLock::try_acquire(&output_dir)? {
+        Some(lock) => {
+            debug!("Acquired output directory lock {}", lock.path().display());
+            lock
+        }
+        None => {
+            let holder = SnapshotLock::holder(&output_dir)
+                .map(|pid| format!(" (pid {pid})"))
+                .unwrap_or_default();
+            error!(
+                "{} Another snapshot run{} is using {}",
+                SYMBOL_INDICATOR_ERROR,
+                holder,
+                output_dir.display()
+            );
+            std::process::exit(RunExitCode::LockContention.code());
+        }
+    };
 
     // Create executor and run snapshot
-    let executor = SnapshotExecutor::with_config(Arc::new(registry), output_dir, Arc::new(config));
+    let skip_unchanged = config.is_skip_unchanged();
+    let executor = SnapshotExecutor::with_config(Arc::new(registry), output_dir, Arc::new(config))
+        .with_skip_unchanged(skip_unchanged);
 
-    match executor.execute_snapshot().await {
-        Ok(snapshot_path) => {
+    match executor.execute_snapshot_with_report().await {
+        Ok(report) => {
             let duration = start_time.elapsed();
-            info!(
-                "{} Snapshot created successfully at: {}",
-                SYMBOL_INDICATOR_SUCCESS,
-                snapshot_path.display()
-            );
+            match args.report_format {
+                ReportFormat::Table => println!("{}", report.to_table()),
+                ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            }
+            if report.changed {
+                info!(
+                    "{} Snapshot created successfully at: {}",
+                    SYMBOL_INDICATOR_SUCCESS,
+                    report.snapshot_dir.display()
+                );
+            } else {
+                info!(
+                    "{} Nothing changed since {}, no snapshot created",
+                    SYMBOL_INDICATOR_SUCCESS,
+                    report.snapshot_dir.display()
+                );
+            }
             info!(
                 "{}  Execution time: {:.2?}",
                 SYMBOL_EXPERIENCE_TIME, duration
             );
+
+            let exit_code = fail_on.evaluate(&report);
+            if exit_code != RunExitCode::Success {
+                error!(
+                    "{} Snapshot run failed the '{}' failure policy ({:?})",
+                    SYMBOL_INDICATOR_ERROR, fail_on, exit_code
+                );
+                std::process::exit(exit_code.code());
+            }
         }
         Err(e) => {
             error!("{} Snapshot creation failed: {}", SYMBOL_INDICATOR_ERROR, e);
-            std::process::exit(1);
+            std::process::exit(RunExitCode::Error.code());
         }
     }
 
@@ -570,6 +1021,275 @@ async fn main() -> Result<()> {
 mod tests {
     use super::*;
 
+    /// Test parsing of the report format option
+    /// Verifies that the run report defaults to a table and accepts json
+    #[test]
+    fn test_report_format_parsing() {
+        let args = Args::parse_from(["dotsnapshot"]);
+        assert_eq!(args.report_format, ReportFormat::Table);
+
+        let args = Args::parse_from(["dotsnapshot", "--report-format", "json"]);
+        assert_eq!(args.report_format, ReportFormat::Json);
+
+        assert!(Args::try_parse_from(["dotsnapshot", "--report-format", "xml"]).is_err());
+    }
+
+    /// Test parsing of the snapshot dry-run flag
+    /// Verifies that --dry-run is off by default and does not clash with restore
+    #[test]
+    fn test_dry_run_parsing() {
+        assert!(!Args::parse_from(["dotsnapshot"]).dry_run);
+        assert!(Args::parse_from(["dotsnapshot", "--dry-run"]).dry_run);
+
+        let args = Args::parse_from(["dotsnapshot", "restore", "--latest", "--dry-run"]);
+        assert!(!args.dry_run);
+        assert!(matches!(
+            args.command,
+            Some(Commands::Restore { dry_run: true, .. })
+        ));
+    }
+
+    /// Test parsing of the skip-unchanged flag
+    /// Verifies that unchanged snapshots are created by default
+    #[test]
+    fn test_skip_unchanged_parsing() {
+        assert!(!Args::parse_from(["dotsnapshot"]).skip_unchanged);
+        assert!(Args::parse_from(["dotsnapshot", "--skip-unchanged"]).skip_unchanged);
+    }
+
+    /// Test parsing of the watch subcommand
+    /// Verifies that debounce and poll interval overrides are optional
+    #[test]
+    fn test_watch_parsing() {
+        let args = Args::parse_from(["dotsnapshot", "watch"]);
+        assert!(matches!(
+            args.command,
+            Some(Commands::Watch {
+                debounce_ms: None,
+                poll_interval: None,
+                ..
+            })
+        ));
+
+        let args = Args::parse_from([
+            "dotsnapshot",
+            "watch",
+            "--plugins",
+            "static",
+            "--debounce-ms",
+            "500",
+            "--poll-interval",
+            "0",
+        ]);
+        let Some(Commands::Watch {
+            plugins,
+            debounce_ms,
+            poll_interval,
+            ..
+        }) = args.command
+        else {
+            panic!("expected watch command");
+        };
+        assert_eq!(plugins.as_deref(), Some("static"));
+        assert_eq!(debounce_ms, Some(500));
+        assert_eq!(poll_interval, Some(0));
+    }
+
+    /// Test parsing of the --host filter
+    /// Verifies a bare --host selects the current host
+    #[test]
+    fn test_host_parsing() {
+        let args = Args::parse_from(["dotsnapshot", "list", "--host", "laptop"]);
+        let Some(Commands::List { host, .. }) = args.command else {
+            panic!("expected list command");
+        };
+        assert_eq!(resolve_host(host), Some("laptop".to_string()));
+
+        let args = Args::parse_from(["dotsnapshot", "restore", "--latest", "--host"]);
+        let Some(Commands::Restore { host, .. }) = args.command else {
+            panic!("expected restore command");
+        };
+        assert_eq!(resolve_host(host), Some(current_hostname()));
+
+        let args = Args::parse_from(["dotsnapshot", "list"]);
+        let Some(Commands::List { host, .. }) = args.command else {
+            panic!("expected list command");
+        };
+        assert_eq!(resolve_host(host), None);
+    }
+
+    /// Test parsing of schedule subcommands
+    /// Verifies that intervals and --profile are parsed and invalid intervals are rejected
+    #[test]
+    fn test_schedule_parsing() {
+        let args = Args::parse_from([
+            "dotsnapshot",
+            "schedule",
+            "install",
+            "--every",
+            "6h",
+            "--backend",
+            "cron",
+            "--dry-run",
+        ]);
+        let Some(Commands::Schedule {
+            command:
+                ScheduleCommands::Install {
+                    every,
+                    backend,
+                    dry_run,
+                    ..
+                },
+        }) = args.command
+        else {
+            panic!("expected schedule install command");
+        };
+        assert_eq!(every.as_secs(), 21600);
+        assert_eq!(backend, Some(ScheduleBackend::Cron));
+        assert!(dry_run);
+        assert_eq!(args.profile, None);
+
+        let args = Args::parse_from([
+            "dotsnapshot",
+            "schedule",
+            "install",
+            "--every",
+            "1d",
+            "--profile",
+            "work",
+        ]);
+        assert_eq!(args.profile.as_deref(), Some("work"));
+        assert!(matches!(
+            args.command,
+            Some(Commands::Schedule {
+                command: ScheduleCommands::Install { .. }
+            })
+        ));
+
+        let args = Args::parse_from(["dotsnapshot", "schedule", "remove"]);
+        assert!(matches!(
+            args.command,
+            Some(Commands::Schedule {
+                command: ScheduleCommands::Remove { dry_run: false }
+            })
+        ));
+
+        assert!(
+            Args::try_parse_from(["dotsnapshot", "schedule", "install", "--every", "6x"]).is_err()
+        );
+    }
+
+    /// Test parsing the config show command and flag layers
+    /// Verifies --origin requires --effective and flags become CLI config layers
+    #[test]
+    fn test_config_show_parsing() -> Result<()> {
+        let args = Args::parse_from(["dotsnapshot", "config", "show", "--effective", "--origin"]);
+        assert!(matches!(
+            args.command,
+            Some(Commands::Config {
+                command: ConfigCommands::Show {
+                    effective: true,
+                    origin: true
+                }
+            })
+        ));
+        assert!(Args::try_parse_from(["dotsnapshot", "config", "show", "--origin"]).is_err());
+        let args = Args::parse_from(["dotsnapshot", "config", "validate"]);
+        assert!(matches!(
+            args.command,
+            Some(Commands::Config {
+                command: ConfigCommands::Validate
+            })
+        ));
+        let args = Args::parse_from(["dotsnapshot", "config", "schema"]);
+        assert!(matches!(
+            args.command,
+            Some(Commands::Config {
+                command: ConfigCommands::Schema
+            })
+        ));
+        let args = Args::parse_from(["dotsnapshot", "config", "migrate", "--dry-run"]);
+        assert!(matches!(
+            args.command,
+            Some(Commands::Config {
+                command: ConfigCommands::Migrate { dry_run: true }
+            })
+        ));
+        let args = Args::parse_from(["dotsnapshot", "init", "--non-interactive"]);
+        assert!(matches!(
+            args.command,
+            Some(Commands::Init {
+                output: None,
+                non_interactive: true,
+                force: false
+            })
+        ));
+        let args = Args::parse_from(["dotsnapshot", "config", "set", "output_dir", "~/snaps"]);
+        let Some(Commands::Config {
+            command: ConfigCommands::Set { key, value },
+        }) = args.command
+        else {
+            panic!("expected config set command");
+        };
+        assert_eq!(key, "output_dir");
+        assert_eq!(value, "~/snaps");
+        assert!(Args::try_parse_from(["dotsnapshot", "config", "set", "output_dir"]).is_err());
+
+        let args = Args::parse_from([
+            "dotsnapshot",
+            "--output",
+            "/cli",
+            "--plugins",
+            "static,vscode",
+            "--skip-unchanged",
+        ]);
+        let options = load_options(&args)?;
+        let sources: Vec<String> = options
+            .cli
+            .iter()
+            .map(|layer| layer.source.to_string())
+            .collect();
+        assert_eq!(
+            sources,
+            vec!["cli: --output", "cli: --plugins", "cli: --skip-unchanged"]
+        );
+        assert_eq!(
+            options.cli[1].table["include_plugins"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+        Ok(())
+    }
+
+    /// Test parsing of the failure policy option
+    /// Verifies that keywords and critical plugin lists are accepted
+    #[test]
+    fn test_fail_on_parsing() {
+        let args = Args::parse_from(["dotsnapshot"]);
+        assert!(args.fail_on.is_none());
+
+        let args = Args::parse_from(["dotsnapshot", "--fail-on", "any-failure"]);
+        assert_eq!(args.fail_on, Some(FailurePolicy::AnyFailure));
+
+        let args = Args::parse_from([
+            "dotsnapshot",
+            "--fail-on",
+            "plugins:homebrew_brewfile,npm_config",
+        ]);
+        assert_eq!(
+            args.fail_on,
+            Some(FailurePolicy::Plugins(vec![
+                "homebrew_brewfile".to_string(),
+                "npm_config".to_string()
+            ]))
+        );
+
+        // A mistyped keyword is a usage error, not a critical plugin
+        assert!(Args::try_parse_from(["dotsnapshot", "--fail-on", "any-failures"]).is_err());
+    }
+
     /// Test basic argument parsing with default values
     /// Verifies that command-line arguments are parsed correctly
     #[test]
@@ -1124,13 +1844,20 @@ mod tests {
     }
 
     /// Test list_plugins function
-    /// Verifies that plugin listing works correctly
+    /// Verifies that plugin listing works correctly and rejects unmatched selectors
     #[tokio::test]
     async fn test_list_plugins() {
         // This test verifies that list_plugins doesn't panic and can discover plugins
         // We can't easily test the exact output without mocking, but we can test execution
-        list_plugins().await;
-        // If we reach here, the function completed without panicking
+        list_plugins(&Config::default(), &[]).await.unwrap();
+        list_plugins(&Config::default(), &["!static".to_string()])
+            .await
+            .unwrap();
+        assert!(
+            list_plugins(&Config::default(), &["no_such_plugin".to_string()])
+                .await
+                .is_err()
+        );
     }
 
     /// Test config loading scenarios
@@ -1377,14 +2104,18 @@ mod tests {
             Some(Commands::Restore {
                 snapshot_path,
                 plugins,
+                exclude,
                 dry_run,
                 backup,
                 force,
                 target_dir,
                 latest,
+                host,
             }) => {
                 assert_eq!(snapshot_path, Some(PathBuf::from("/path/to/snapshot")));
+                assert_eq!(host, None);
                 assert_eq!(plugins, Some("vscode,cursor,homebrew".to_string()));
+                assert_eq!(exclude, None);
                 assert!(dry_run);
                 assert!(backup);
                 assert!(force);
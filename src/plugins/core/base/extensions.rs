@@ -3,6 +3,7 @@ use async_trait::async_trait;
 use std::path::PathBuf;
 use tracing::{info, warn};
 
+use crate::config::PluginConfigValue;
 use crate::core::plugin::Plugin;
 use crate::plugins::core::mixins::{
     CommandMixin, ConfigMixin, FilesMixin, StandardConfig, StandardConfigMixin,
@@ -74,12 +75,12 @@ impl<T: ExtensionsCore + CommandMixin> ExtensionsPlugin<T> {
     }
 
     /// Create a new extensions plugin with configuration
-    pub fn with_config(core: T, config: toml::Value) -> Self {
+    pub fn with_config(core: T, config: PluginConfigValue) -> Self {
         let (parsed_config, is_valid) = Self::with_config_validation(
             config,
             &format!("{} Extensions plugin", core.app_name()),
             &format!("{}_extensions", core.app_name().to_lowercase()),
-            "target_path (string), output_file (string), restore_target_dir (string), hooks (object)",
+            "target_path (string), output_file (string), restore_target_dir (string or table by OS), hooks (object)",
             &format!(
                 "target_path = \"{}\", output_file = \"{}\"",
                 core.app_name().to_lowercase(),
@@ -494,7 +495,7 @@ mod tests {
         assert_eq!(core.allowed_extensions(), &["txt", "list"]);
 
         let restore_dir = core.get_default_restore_dir().unwrap();
-        assert!(restore_dir.is_dir() || restore_dir == PathBuf::from("."));
+        assert!(restore_dir.is_dir() || restore_dir == std::path::Path::new("."));
     }
 
     #[test]
@@ -506,7 +507,8 @@ mod tests {
             restore_target_dir = "/custom/restore"
         };
 
-        let plugin = ExtensionsPlugin::with_config(core, toml::Value::Table(config_toml));
+        let plugin =
+            ExtensionsPlugin::with_config(core, serde_json::to_value(config_toml).unwrap());
         assert!(plugin.config.is_some());
 
         let config = plugin.config.as_ref().unwrap();
@@ -515,10 +517,7 @@ mod tests {
             config.output_file,
             Some("custom_extensions.txt".to_string())
         );
-        assert_eq!(
-            config.restore_target_dir,
-            Some("/custom/restore".to_string())
-        );
+        assert_eq!(config.restore_target_dir, Some("/custom/restore".into()));
     }
 
     #[test]
@@ -529,7 +528,8 @@ mod tests {
             output_file = "invalid_file.exe" // Invalid extension
         };
 
-        let plugin = ExtensionsPlugin::with_config(core, toml::Value::Table(config_toml));
+        let plugin =
+            ExtensionsPlugin::with_config(core, serde_json::to_value(config_toml).unwrap());
         assert!(plugin.config.is_some());
         // Config is still created even with invalid extension (warning is logged)
     }
@@ -543,7 +543,8 @@ mod tests {
             restore_target_dir = "/test/restore"
         };
 
-        let plugin = ExtensionsPlugin::with_config(core, toml::Value::Table(config_toml));
+        let plugin =
+            ExtensionsPlugin::with_config(core, serde_json::to_value(config_toml).unwrap());
 
         assert_eq!(
             ConfigMixin::get_target_path(&plugin),
@@ -597,7 +598,7 @@ mod tests {
         assert!(hooks.is_empty());
 
         let default_restore_dir = plugin.get_default_restore_target_dir().unwrap();
-        assert!(default_restore_dir.is_dir() || default_restore_dir == PathBuf::from("."));
+        assert!(default_restore_dir.is_dir() || default_restore_dir == std::path::Path::new("."));
     }
 
     struct CustomExtensionsCore;
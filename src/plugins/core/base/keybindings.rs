@@ -2,7 +2,8 @@ use anyhow::Result;
 use async_trait::async_trait;
 use std::path::PathBuf;
 
-use crate::core::plugin::Plugin;
+use crate::config::PluginConfigValue;
+use crate::core::plugin::{NotApplicable, Plugin};
 use crate::plugins::core::mixins::{
     CommandMixin, ConfigMixin, FilesMixin, StandardConfig, StandardConfigMixin,
 };
@@ -43,10 +44,9 @@ impl<T: KeybindingsCore> KeybindingsPlugin<T> {
     }
 
     /// Create a new keybindings plugin with configuration
-    pub fn with_config(core: T, config: toml::Value) -> Self {
-        let parsed_config = config
-            .try_into()
-            .unwrap_or_else(|_| StandardConfig::default());
+    pub fn with_config(core: T, config: PluginConfigValue) -> Self {
+        let parsed_config =
+            serde_json::from_value(config).unwrap_or_else(|_| StandardConfig::default());
 
         Self {
             core,
@@ -73,11 +73,12 @@ impl<T: KeybindingsCore> Plugin for KeybindingsPlugin<T> {
         // Check if keybindings directory exists
         let keybindings_dir = self.core.get_keybindings_dir()?;
         if !keybindings_dir.exists() {
-            return Err(anyhow::anyhow!(
+            return Err(NotApplicable(format!(
                 "{} keybindings directory not found: {}",
                 self.core.app_name(),
                 keybindings_dir.display()
-            ));
+            ))
+            .into());
         }
         Ok(())
     }
@@ -90,6 +91,13 @@ impl<T: KeybindingsCore> Plugin for KeybindingsPlugin<T> {
         ConfigMixin::get_output_file(self)
     }
 
+    async fn watch_paths(&self) -> Result<Vec<PathBuf>> {
+        Ok(vec![self
+            .core
+            .get_keybindings_dir()?
+            .join(self.core.keybindings_file_name())])
+    }
+
     async fn restore(
         &self,
         snapshot_dir: &std::path::Path,
@@ -228,7 +236,7 @@ mod tests {
             target_path = "test"
             output_file = "test_keybindings.json"
         "#;
-        let config: toml::Value = toml::from_str(config_toml).unwrap();
+        let config: serde_json::Value = toml::from_str(config_toml).unwrap();
         let plugin = KeybindingsPlugin::with_config(MockKeybindingsCore, config);
 
         assert_eq!(
@@ -426,7 +434,7 @@ mod tests {
     /// Verifies that invalid config falls back to defaults
     #[tokio::test]
     async fn test_keybindings_plugin_with_invalid_config() {
-        let invalid_config = toml::Value::String("invalid".to_string());
+        let invalid_config = serde_json::Value::String("invalid".to_string());
         let plugin = KeybindingsPlugin::with_config(MockKeybindingsCore, invalid_config);
 
         // Should fall back to defaults
@@ -485,7 +493,7 @@ mod tests {
             pre_snapshot = ["echo before"]
             post_snapshot = ["echo after"]
         "#;
-        let config: toml::Value = toml::from_str(config_toml).unwrap();
+        let config: serde_json::Value = toml::from_str(config_toml).unwrap();
         let plugin = KeybindingsPlugin::with_config(MockKeybindingsCore, config);
 
         assert_eq!(
@@ -3,6 +3,7 @@ use async_trait::async_trait;
 use std::path::PathBuf;
 use tracing::{info, warn};
 
+use crate::config::PluginConfigValue;
 use crate::core::plugin::Plugin;
 use crate::plugins::core::mixins::{
     CommandMixin, ConfigMixin, StandardConfig, StandardConfigMixin,
@@ -73,12 +74,12 @@ impl<T: PackageCore + CommandMixin> PackagePlugin<T> {
     }
 
     /// Create a new package plugin with configuration
-    pub fn with_config(core: T, config: toml::Value) -> Self {
+    pub fn with_config(core: T, config: PluginConfigValue) -> Self {
         let (parsed_config, is_valid) = Self::with_config_validation(
             config,
             &format!("{} plugin", core.package_manager_name()),
             &format!("{}_config", core.package_manager_name().to_lowercase()),
-            "target_path (string), output_file (string), restore_target_dir (string), hooks (object)",
+            "target_path (string), output_file (string), restore_target_dir (string or table by OS), hooks (object)",
             &format!(
                 "target_path = \"{}\", output_file = \"{}\"",
                 core.package_manager_name().to_lowercase(),
@@ -478,7 +479,7 @@ mod tests {
         assert_eq!(core.allowed_extensions(), &["txt"]);
 
         let restore_dir = core.get_default_restore_dir().unwrap();
-        assert!(restore_dir.is_dir() || restore_dir == PathBuf::from("."));
+        assert!(restore_dir.is_dir() || restore_dir == std::path::Path::new("."));
     }
 
     #[test]
@@ -490,16 +491,13 @@ mod tests {
             restore_target_dir = "/custom/restore"
         };
 
-        let plugin = PackagePlugin::with_config(core, toml::Value::Table(config_toml));
+        let plugin = PackagePlugin::with_config(core, serde_json::to_value(config_toml).unwrap());
         assert!(plugin.config.is_some());
 
         let config = plugin.config.as_ref().unwrap();
         assert_eq!(config.target_path, Some("custom/path".to_string()));
         assert_eq!(config.output_file, Some("custom_config.txt".to_string()));
-        assert_eq!(
-            config.restore_target_dir,
-            Some("/custom/restore".to_string())
-        );
+        assert_eq!(config.restore_target_dir, Some("/custom/restore".into()));
     }
 
     #[test]
@@ -510,7 +508,7 @@ mod tests {
             output_file = "invalid_file.exe" // Invalid extension
         };
 
-        let plugin = PackagePlugin::with_config(core, toml::Value::Table(config_toml));
+        let plugin = PackagePlugin::with_config(core, serde_json::to_value(config_toml).unwrap());
         assert!(plugin.config.is_some());
         // Config is still created even with invalid extension (warning is logged)
     }
@@ -524,7 +522,7 @@ mod tests {
             restore_target_dir = "/test/restore"
         };
 
-        let plugin = PackagePlugin::with_config(core, toml::Value::Table(config_toml));
+        let plugin = PackagePlugin::with_config(core, serde_json::to_value(config_toml).unwrap());
 
         assert_eq!(
             ConfigMixin::get_target_path(&plugin),
@@ -576,7 +574,7 @@ mod tests {
         assert!(hooks.is_empty());
 
         let default_restore_dir = plugin.get_default_restore_target_dir().unwrap();
-        assert!(default_restore_dir.is_dir() || default_restore_dir == PathBuf::from("."));
+        assert!(default_restore_dir.is_dir() || default_restore_dir == std::path::Path::new("."));
     }
 
     #[tokio::test]
@@ -592,7 +590,7 @@ mod tests {
         let config_toml = toml::toml! {
             output_file = "custom_packages.txt"
         };
-        let plugin = PackagePlugin::with_config(core, toml::Value::Table(config_toml));
+        let plugin = PackagePlugin::with_config(core, serde_json::to_value(config_toml).unwrap());
 
         let temp_dir = TempDir::new().unwrap();
         let snapshot_dir = temp_dir.path().join("snapshot");
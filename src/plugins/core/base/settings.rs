@@ -3,7 +3,8 @@ use async_trait::async_trait;
 use std::path::PathBuf;
 use tracing::warn;
 
-use crate::core::plugin::Plugin;
+use crate::config::PluginConfigValue;
+use crate::core::plugin::{NotApplicable, Plugin};
 use crate::plugins::core::mixins::{ConfigMixin, FilesMixin, StandardConfig, StandardConfigMixin};
 
 /// Core trait that defines application-specific settings behavior
@@ -46,12 +47,12 @@ impl<T: SettingsCore> SettingsPlugin<T> {
     }
 
     /// Create a new settings plugin with configuration
-    pub fn with_config(core: T, config: toml::Value) -> Self {
+    pub fn with_config(core: T, config: PluginConfigValue) -> Self {
         let (parsed_config, is_valid) = Self::with_config_validation(
             config,
             &format!("{} Settings plugin", core.app_name()),
             &format!("{}_settings", core.app_name().to_lowercase()),
-            "target_path (string), output_file (string), restore_target_dir (string), hooks (object)",
+            "target_path (string), output_file (string), restore_target_dir (string or table by OS), hooks (object)",
             &format!(
                 "target_path = \"{}\", output_file = \"{}\"",
                 core.app_name().to_lowercase(),
@@ -133,11 +134,12 @@ impl<T: SettingsCore + Send + Sync> Plugin for SettingsPlugin<T> {
     async fn validate(&self) -> Result<()> {
         let settings_dir = self.core.get_settings_dir()?;
         if !self.is_dir_accessible(&settings_dir).await {
-            return Err(anyhow::anyhow!(
+            return Err(NotApplicable(format!(
                 "{} settings directory not found: {}",
                 self.core.app_name(),
                 settings_dir.display()
-            ));
+            ))
+            .into());
         }
         Ok(())
     }
@@ -150,6 +152,13 @@ impl<T: SettingsCore + Send + Sync> Plugin for SettingsPlugin<T> {
         ConfigMixin::get_output_file(self)
     }
 
+    async fn watch_paths(&self) -> Result<Vec<PathBuf>> {
+        Ok(vec![self
+            .core
+            .get_settings_dir()?
+            .join(self.core.settings_file_name())])
+    }
+
     fn get_restore_target_dir(&self) -> Option<String> {
         ConfigMixin::get_restore_target_dir(self)
     }
@@ -272,7 +281,7 @@ mod tests {
             target_path = "testapp"
             output_file = "settings.json"
         "#;
-        let config: toml::Value = toml::from_str(config_toml).unwrap();
+        let config: serde_json::Value = toml::from_str(config_toml).unwrap();
         let plugin = SettingsPlugin::with_config(core, config);
 
         assert_eq!(
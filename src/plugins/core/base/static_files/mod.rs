@@ -4,13 +4,14 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use crate::config::Config;
-use crate::core::plugin::Plugin;
+use crate::core::checksum::FileHashes;
+use crate::core::plugin::{ExecutionContext, FilePlan, Plugin};
 use crate::plugins::core::mixins::{CommandMixin, FilesMixin};
 
 /// Core trait for static files-specific functionality
 ///
 /// This trait handles the unique requirements of static file plugins:
-/// - Uses Arc<Config> instead of toml::Value for configuration
+/// - Uses Arc<Config> instead of a raw plugin config value for configuration
 /// - Handles complex file operations with ignore patterns
 /// - Manages custom directory structures and restoration logic
 pub trait StaticFilesCore: Send + Sync {
@@ -26,6 +27,11 @@ pub trait StaticFilesCore: Send + Sync {
     /// Get ignore patterns from configuration
     fn get_ignore_patterns(&self, config: Option<&Arc<Config>>) -> Vec<String>;
 
+    /// Whether files unchanged since the previous snapshot are hardlinked
+    fn get_hardlink(&self, _config: Option<&Arc<Config>>) -> bool {
+        false
+    }
+
     /// Check if a path should be ignored based on ignore patterns
     fn should_ignore(&self, path: &std::path::Path, ignore_patterns: &[String]) -> bool;
 
@@ -38,8 +44,20 @@ pub trait StaticFilesCore: Send + Sync {
         file_paths: Vec<PathBuf>,
         static_dir: &'a std::path::Path,
         ignore_patterns: &'a [String],
+        hardlink: bool,
+        hashes: &'a FileHashes,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<String>> + Send + 'a>>;
 
+    /// Resolve which files would be copied into the static folder, without copying
+    fn plan_files(
+        &self,
+        _file_paths: Vec<PathBuf>,
+        _static_dir: &std::path::Path,
+        _ignore_patterns: &[String],
+    ) -> FilePlan {
+        FilePlan::default()
+    }
+
     /// Restore static files from snapshot back to their original locations
     fn restore_static_files<'a>(
         &'a self,
@@ -69,7 +87,6 @@ impl<T: StaticFilesCore> StaticFilesPlugin<T> {
     }
 
     /// Create a new static files plugin with configuration
-    #[cfg(test)]
     pub fn with_config(core: T, config: Arc<Config>) -> Self {
         Self {
             core,
@@ -97,6 +114,20 @@ impl<T: StaticFilesCore> Plugin for StaticFilesPlugin<T> {
     }
 
     async fn execute(&self) -> Result<String> {
+        // Outside a snapshot run the directory comes from the environment
+        let snapshot_dir = if let Some(context) = ExecutionContext::from_env() {
+            context.snapshot_dir
+        } else if let Some(snapshot_dir) = &self.snapshot_dir {
+            snapshot_dir.clone()
+        } else {
+            // Fallback: create static directory in current directory
+            std::env::current_dir()?
+        };
+        self.execute_with(&ExecutionContext::new(snapshot_dir))
+            .await
+    }
+
+    async fn execute_with(&self, context: &ExecutionContext) -> Result<String> {
         let file_paths = match self.core.read_config(self.config.as_ref()).await {
             Ok(paths) => paths,
             Err(e) => {
@@ -122,28 +153,28 @@ impl<T: StaticFilesCore> Plugin for StaticFilesPlugin<T> {
             }))?);
         }
 
-        // Get snapshot directory from environment variable set by executor
-        let static_dir = if let Ok(snapshot_dir_str) = std::env::var("DOTSNAPSHOT_SNAPSHOT_DIR") {
-            let snapshot_dir = PathBuf::from(snapshot_dir_str);
-            snapshot_dir.join("static")
-        } else if let Some(snapshot_dir) = &self.snapshot_dir {
-            snapshot_dir.join("static")
-        } else {
-            // Fallback: create static directory in current directory
-            std::env::current_dir()?.join("static")
-        };
+        let static_dir = context.snapshot_dir.join("static");
 
         // Get ignore patterns
         let ignore_patterns = self.core.get_ignore_patterns(self.config.as_ref());
+        let hardlink = self.core.get_hardlink(self.config.as_ref());
 
         let summary = self
             .core
-            .copy_files(file_paths, &static_dir, &ignore_patterns)
+            .copy_files(
+                file_paths,
+                &static_dir,
+                &ignore_patterns,
+                hardlink,
+                &context.hashes,
+            )
             .await?;
 
         // Calculate checksum of the static directory contents for better change detection
         let directory_checksum = if static_dir.exists() {
-            crate::core::checksum::calculate_directory_checksum(&static_dir)
+            context
+                .hashes
+                .directory_checksum(&static_dir)
                 .unwrap_or_else(|_| "error_calculating_checksum".to_string())
         } else {
             "no_static_directory".to_string()
@@ -189,6 +220,43 @@ impl<T: StaticFilesCore> Plugin for StaticFilesPlugin<T> {
         true // Static files plugin handles its own file operations
     }
 
+    fn get_own_output_dir(&self) -> Option<String> {
+        Some("static".to_string())
+    }
+
+    async fn watch_paths(&self) -> Result<Vec<PathBuf>> {
+        self.core.read_config(self.config.as_ref()).await
+    }
+
+    fn output_checksum(&self, content: &str) -> String {
+        // Cache and hardlink statistics vary between runs of identical content
+        let (header, body) = content.split_once('\n').unwrap_or((content, ""));
+        match serde_json::from_str::<serde_json::Value>(body) {
+            Ok(mut summary_json) => {
+                if let Some(summary_obj) = summary_json
+                    .get_mut("summary")
+                    .and_then(|summary| summary.as_object_mut())
+                {
+                    summary_obj.remove("cache");
+                    summary_obj.remove("hardlinked");
+                }
+                crate::core::checksum::calculate_checksum(&format!("{header}\n{summary_json}"))
+            }
+            Err(_) => crate::core::checksum::calculate_checksum(content),
+        }
+    }
+
+    async fn plan_files(&self) -> Result<Option<FilePlan>> {
+        let file_paths = self.core.read_config(self.config.as_ref()).await?;
+        let ignore_patterns = self.core.get_ignore_patterns(self.config.as_ref());
+
+        Ok(Some(self.core.plan_files(
+            file_paths,
+            std::path::Path::new("static"),
+            &ignore_patterns,
+        )))
+    }
+
     fn get_restore_target_dir(&self) -> Option<String> {
         // Static files plugin doesn't use standard config pattern,
         // so this returns None and restoration uses default target
@@ -259,7 +327,7 @@ impl<T: StaticFilesCore> Plugin for StaticFilesPlugin<T> {
 }
 
 // Static files plugins don't use the standard config mixins
-// because they need Arc<Config> access instead of toml::Value
+// because they need Arc<Config> access instead of a raw plugin config value
 // They implement the Plugin trait methods directly instead of using ConfigMixin
 
 impl<T: StaticFilesCore> FilesMixin for StaticFilesPlugin<T> {
@@ -4,6 +4,7 @@
 mod tests {
     use super::super::test_utils::{AdvancedMockCore, MinimalStaticFilesCore, MockStaticFilesCore};
     use crate::config::{Config, StaticFilesConfig};
+    use crate::core::checksum::FileHashes;
     use crate::core::plugin::Plugin;
     use crate::plugins::core::base::static_files::{StaticFilesCore, StaticFilesPlugin};
     use std::path::PathBuf;
@@ -28,6 +29,7 @@ mod tests {
             plugins: None,
             ui: None,
             validation: None,
+            ..Config::default()
         });
 
         let plugin = StaticFilesPlugin::with_config(MockStaticFilesCore::new(), config.clone());
@@ -77,7 +79,13 @@ mod tests {
         let ignore_patterns = vec!["*.tmp".to_string()];
 
         let result = core
-            .copy_files(file_paths.clone(), &static_dir, &ignore_patterns)
+            .copy_files(
+                file_paths.clone(),
+                &static_dir,
+                &ignore_patterns,
+                false,
+                &FileHashes::default(),
+            )
             .await
             .unwrap();
 
@@ -104,6 +112,7 @@ mod tests {
             plugins: None,
             ui: None,
             validation: None,
+            ..Config::default()
         });
         let plugin = StaticFilesPlugin::with_config(MockStaticFilesCore::new(), empty_config);
 
@@ -140,6 +149,7 @@ mod tests {
             plugins: None,
             ui: None,
             validation: None,
+            ..Config::default()
         });
 
         let plugin = StaticFilesPlugin::with_config(MockStaticFilesCore::new(), config);
@@ -246,6 +256,7 @@ mod tests {
             plugins: None,
             ui: None,
             validation: None,
+            ..Config::default()
         });
 
         let plugin = StaticFilesPlugin::with_config(MockStaticFilesCore::new(), config);
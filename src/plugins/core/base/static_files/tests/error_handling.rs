@@ -6,6 +6,7 @@ mod tests {
         AdvancedMockCore, ErrorMockCore, ErrorProneMockCore, JsonErrorMockCore, MockStaticFilesCore,
     };
     use crate::config::Config;
+    use crate::core::checksum::FileHashes;
     use crate::core::plugin::Plugin;
     use crate::plugins::core::base::static_files::{StaticFilesCore, StaticFilesPlugin};
     use anyhow::Result;
@@ -217,6 +218,8 @@ mod tests {
                 _file_paths: Vec<PathBuf>,
                 _static_dir: &std::path::Path,
                 _ignore_patterns: &[String],
+                _hardlink: bool,
+                _hashes: &FileHashes,
             ) -> Pin<Box<dyn std::future::Future<Output = Result<String>> + Send + '_>>
             {
                 Box::pin(async move { Err(anyhow::anyhow!("Copy files error")) })
@@ -248,7 +251,13 @@ mod tests {
             .contains("Config read error"));
 
         let copy_result = core
-            .copy_files(vec![], &PathBuf::from("/static"), &[])
+            .copy_files(
+                vec![],
+                &PathBuf::from("/static"),
+                &[],
+                false,
+                &FileHashes::default(),
+            )
             .await;
         assert!(copy_result.is_err());
         assert!(copy_result
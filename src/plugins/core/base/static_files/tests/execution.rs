@@ -225,4 +225,31 @@ mod tests {
         // Just verify it's a valid response
         assert!(result.contains("total_files"));
     }
+
+    /// Test that the output checksum ignores static file cache statistics
+    /// Verifies identical output hashes the same regardless of cache hits
+    #[test]
+    fn test_static_files_output_checksum_ignores_cache() {
+        let plugin = StaticFilesPlugin::new(MockStaticFilesCore::new());
+        let content = |hits: usize, hashed: usize| {
+            format!(
+                "STATIC_DIR_CHECKSUM:abc\n{}",
+                serde_json::json!({
+                    "summary": {
+                        "copied": 1,
+                        "cache": { "hits": hits, "hashed": hashed }
+                    }
+                })
+            )
+        };
+
+        assert_eq!(
+            plugin.output_checksum(&content(0, 1)),
+            plugin.output_checksum(&content(1, 0))
+        );
+        assert_ne!(
+            plugin.output_checksum(&content(0, 1)),
+            plugin.output_checksum(&content(0, 1).replace("abc", "def"))
+        );
+    }
 }
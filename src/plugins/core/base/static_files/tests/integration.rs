@@ -7,6 +7,7 @@ mod tests {
         create_mock_snapshot_dir, AdvancedMockCore, MinimalStaticFilesCore, MockStaticFilesCore,
     };
     use crate::config::Config;
+    use crate::core::checksum::FileHashes;
     use crate::core::plugin::Plugin;
     use crate::plugins::core::base::static_files::{StaticFilesCore, StaticFilesPlugin};
     use std::path::PathBuf;
@@ -52,7 +53,13 @@ mod tests {
 
         // Test copy_files with empty list
         let copy_empty = core
-            .copy_files(vec![], &PathBuf::from("/static"), &[])
+            .copy_files(
+                vec![],
+                &PathBuf::from("/static"),
+                &[],
+                false,
+                &FileHashes::default(),
+            )
             .await
             .unwrap();
         assert!(copy_empty.contains("total_files"));
@@ -280,6 +287,7 @@ mod tests {
             plugins: None,
             ui: None,
             validation: None,
+            ..Config::default()
         });
 
         let plugin = StaticFilesPlugin::with_config(AdvancedMockCore::new(), config);
@@ -3,6 +3,7 @@
 #[cfg(test)]
 mod tests {
     use super::super::test_utils::{AdvancedMockCore, MinimalStaticFilesCore, MockStaticFilesCore};
+    use crate::core::checksum::FileHashes;
     use crate::core::plugin::Plugin;
     use crate::plugins::core::base::static_files::{StaticFilesCore, StaticFilesPlugin};
     use std::path::PathBuf;
@@ -147,7 +148,13 @@ mod tests {
         assert!(config_result.is_empty());
 
         let copy_result = core
-            .copy_files(vec![], &PathBuf::from("/static"), &[])
+            .copy_files(
+                vec![],
+                &PathBuf::from("/static"),
+                &[],
+                false,
+                &FileHashes::default(),
+            )
             .await
             .unwrap();
         // MinimalStaticFilesCore returns JSON, not empty string
@@ -193,6 +200,8 @@ mod tests {
                 vec![PathBuf::from("/test/file")],
                 &PathBuf::from("/static"),
                 &[],
+                false,
+                &FileHashes::default(),
             )
             .await;
         assert!(copy_result.is_ok());
@@ -221,7 +230,13 @@ mod tests {
         assert!(config_result.unwrap().is_empty());
 
         let copy_result = core
-            .copy_files(vec![], &PathBuf::from("/static"), &[])
+            .copy_files(
+                vec![],
+                &PathBuf::from("/static"),
+                &[],
+                false,
+                &FileHashes::default(),
+            )
             .await;
         assert!(copy_result.is_ok());
         // MinimalStaticFilesCore returns JSON, not empty string
@@ -328,7 +343,13 @@ mod tests {
         // Test all async methods return appropriate results
         assert!(core.read_config(None).await.unwrap().is_empty());
         assert!(core
-            .copy_files(vec![PathBuf::from("/file")], &PathBuf::from("/static"), &[])
+            .copy_files(
+                vec![PathBuf::from("/file")],
+                &PathBuf::from("/static"),
+                &[],
+                false,
+                &FileHashes::default(),
+            )
             .await
             .unwrap()
             .contains("total_files"));
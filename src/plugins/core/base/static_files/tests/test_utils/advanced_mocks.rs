@@ -1,6 +1,7 @@
 //! Advanced mock implementations for comprehensive testing scenarios
 
 use crate::config::Config;
+use crate::core::checksum::FileHashes;
 use crate::plugins::core::base::static_files::StaticFilesCore;
 use anyhow::Result;
 use std::collections::HashMap;
@@ -147,6 +148,8 @@ impl StaticFilesCore for AdvancedMockCore {
         file_paths: Vec<PathBuf>,
         static_dir: &'a std::path::Path,
         ignore_patterns: &'a [String],
+        _hardlink: bool,
+        _hashes: &'a FileHashes,
     ) -> Pin<Box<dyn std::future::Future<Output = Result<String>> + Send + 'a>> {
         Box::pin(async move {
             if let Some(error) = self.error_scenarios.get("copy_files") {
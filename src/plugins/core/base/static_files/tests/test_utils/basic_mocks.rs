@@ -1,6 +1,7 @@
 //! Basic mock implementations for StaticFilesCore trait testing
 
 use crate::config::Config;
+use crate::core::checksum::FileHashes;
 use crate::plugins::core::base::static_files::StaticFilesCore;
 use anyhow::Result;
 use std::collections::HashMap;
@@ -120,6 +121,8 @@ impl StaticFilesCore for MockStaticFilesCore {
         file_paths: Vec<PathBuf>,
         static_dir: &'a std::path::Path,
         _ignore_patterns: &'a [String],
+        _hardlink: bool,
+        _hashes: &'a FileHashes,
     ) -> Pin<Box<dyn std::future::Future<Output = Result<String>> + Send + 'a>> {
         Box::pin(async move {
             if self.copy_files_error {
@@ -187,6 +190,8 @@ impl StaticFilesCore for MinimalStaticFilesCore {
         file_paths: Vec<PathBuf>,
         static_dir: &'a std::path::Path,
         _ignore_patterns: &'a [String],
+        _hardlink: bool,
+        _hashes: &'a FileHashes,
     ) -> Pin<Box<dyn std::future::Future<Output = Result<String>> + Send + 'a>> {
         Box::pin(async move {
             let summary = serde_json::json!({
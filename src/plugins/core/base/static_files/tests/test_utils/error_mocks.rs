@@ -1,6 +1,7 @@
 //! Error-focused mock implementations for testing failure scenarios
 
 use crate::config::Config;
+use crate::core::checksum::FileHashes;
 use crate::plugins::core::base::static_files::StaticFilesCore;
 use anyhow::Result;
 use std::collections::HashMap;
@@ -74,6 +75,8 @@ impl StaticFilesCore for ErrorMockCore {
         _file_paths: Vec<PathBuf>,
         _static_dir: &'a std::path::Path,
         _ignore_patterns: &'a [String],
+        _hardlink: bool,
+        _hashes: &'a FileHashes,
     ) -> Pin<Box<dyn std::future::Future<Output = Result<String>> + Send + 'a>> {
         Box::pin(async move {
             if let Some(error) = self.operation_errors.get("copy_files") {
@@ -131,6 +134,8 @@ impl StaticFilesCore for JsonErrorMockCore {
         _file_paths: Vec<PathBuf>,
         _static_dir: &'a std::path::Path,
         _ignore_patterns: &'a [String],
+        _hardlink: bool,
+        _hashes: &'a FileHashes,
     ) -> Pin<Box<dyn std::future::Future<Output = Result<String>> + Send + 'a>> {
         Box::pin(async move {
             // Return malformed JSON to test error handling
@@ -202,6 +207,8 @@ impl StaticFilesCore for ErrorProneMockCore {
         file_paths: Vec<PathBuf>,
         static_dir: &'a std::path::Path,
         _ignore_patterns: &'a [String],
+        _hardlink: bool,
+        _hashes: &'a FileHashes,
     ) -> Pin<Box<dyn std::future::Future<Output = Result<String>> + Send + 'a>> {
         Box::pin(async move {
             let summary = serde_json::json!({
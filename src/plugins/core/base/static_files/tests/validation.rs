@@ -3,6 +3,7 @@
 #[cfg(test)]
 mod tests {
     use super::super::test_utils::{AdvancedMockCore, MinimalStaticFilesCore, MockStaticFilesCore};
+    use crate::core::checksum::FileHashes;
     use crate::core::plugin::Plugin;
     use crate::plugins::core::base::static_files::{StaticFilesCore, StaticFilesPlugin};
     use crate::plugins::core::mixins::FilesMixin;
@@ -142,6 +143,8 @@ mod tests {
                 vec![PathBuf::from("/test/file.txt")],
                 &PathBuf::from("/target"),
                 &[],
+                false,
+                &FileHashes::default(),
             )
             .await;
         assert!(copy_result.is_ok());
@@ -174,6 +177,7 @@ mod tests {
             plugins: None,
             ui: None,
             validation: None,
+            ..Config::default()
         });
 
         // The with_config constructor should be available for testing validation scenarios
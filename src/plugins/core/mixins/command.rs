@@ -4,6 +4,8 @@ use tokio::process::Command;
 use tracing::debug;
 use which::which;
 
+use crate::core::plugin::NotApplicable;
+
 /// Provides command execution capabilities to plugins
 #[async_trait::async_trait]
 pub trait CommandMixin: Send + Sync {
@@ -50,7 +52,9 @@ pub trait CommandMixin: Send + Sync {
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + '_>> {
         let cmd = cmd.to_string();
         Box::pin(async move {
-            which(&cmd).with_context(|| format!("{cmd} command not found. Please install it."))?;
+            which(&cmd).map_err(|_| {
+                NotApplicable(format!("{cmd} command not found. Please install it."))
+            })?;
             Ok(())
         })
     }
@@ -2,8 +2,10 @@ use anyhow::Result;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::config::PluginConfigValue;
 use crate::core::config_schema::{ConfigSchema, ValidationHelpers};
 use crate::core::hooks::HookAction;
+use crate::core::paths::PlatformPath;
 
 /// Standard configuration structure shared across most plugins
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Default)]
@@ -14,8 +16,10 @@ pub struct StandardConfig {
     #[schemars(description = "Custom filename for the plugin output")]
     pub output_file: Option<String>,
 
-    #[schemars(description = "Custom target directory for restoration")]
-    pub restore_target_dir: Option<String>,
+    #[schemars(
+        description = "Custom target directory for restoration, or a table of paths by OS (linux, macos, windows, default)"
+    )]
+    pub restore_target_dir: Option<PlatformPath>,
 
     #[schemars(description = "Plugin-specific hooks configuration")]
     pub hooks: Option<StandardHooks>,
@@ -68,7 +72,7 @@ pub trait ConfigMixin {
 
     /// Create plugin with configuration and validation
     fn with_config_validation(
-        config: toml::Value,
+        config: PluginConfigValue,
         plugin_name: &str,
         config_section: &str,
         expected_fields: &str,
@@ -77,7 +81,7 @@ pub trait ConfigMixin {
     where
         Self: Sized,
     {
-        match Self::Config::from_toml_value(&config) {
+        match Self::Config::from_value(&config) {
             Ok(parsed_config) => (parsed_config, true),
             Err(e) => {
                 // Use shared error formatting
@@ -109,7 +113,8 @@ pub trait StandardConfigMixin: ConfigMixin<Config = StandardConfig> {
     }
 
     fn get_standard_restore_target_dir(&self) -> Option<String> {
-        self.config()?.restore_target_dir.clone()
+        let restore_target_dir = self.config()?.restore_target_dir.as_ref()?;
+        restore_target_dir.for_current_os().map(str::to_string)
     }
 }
 
@@ -175,7 +180,7 @@ mod tests {
         let config_with_values = StandardConfig {
             target_path: Some("custom/path".to_string()),
             output_file: Some("custom.json".to_string()),
-            restore_target_dir: Some("/custom/restore".to_string()),
+            restore_target_dir: Some("/custom/restore".into()),
             hooks: Some(StandardHooks {
                 pre_plugin: vec![],
                 post_plugin: vec![],
@@ -224,7 +229,7 @@ mod tests {
         let config = StandardConfig {
             target_path: Some("test/path".to_string()),
             output_file: Some("test.json".to_string()),
-            restore_target_dir: Some("/test/restore".to_string()),
+            restore_target_dir: Some("/test/restore".into()),
             hooks: None,
         };
 
@@ -249,7 +254,7 @@ mod tests {
             output_file = "custom.json"
             restore_target_dir = "/custom/restore"
         "#;
-        let toml_value: toml::Value = toml::from_str(toml_str).unwrap();
+        let toml_value: serde_json::Value = toml::from_str(toml_str).unwrap();
 
         let (config, is_valid) = MockPlugin::with_config_validation(
             toml_value,
@@ -262,10 +267,7 @@ mod tests {
         assert!(is_valid);
         assert_eq!(config.target_path, Some("custom/path".to_string()));
         assert_eq!(config.output_file, Some("custom.json".to_string()));
-        assert_eq!(
-            config.restore_target_dir,
-            Some("/custom/restore".to_string())
-        );
+        assert_eq!(config.restore_target_dir, Some("/custom/restore".into()));
     }
 
     /// Test with_config_validation with hooks
@@ -282,7 +284,7 @@ mod tests {
                 { action = "script", command = "echo", args = ["post"], timeout = 30 }
             ]
         "#;
-        let toml_value: toml::Value = toml::from_str(toml_str).unwrap();
+        let toml_value: serde_json::Value = toml::from_str(toml_str).unwrap();
 
         let (config, is_valid) = MockPlugin::with_config_validation(
             toml_value,
@@ -307,7 +309,7 @@ mod tests {
         // Create invalid TOML value (wrong type for target_path)
         let mut invalid_config = toml::Table::new();
         invalid_config.insert("target_path".to_string(), toml::Value::Integer(123));
-        let toml_value = toml::Value::Table(invalid_config);
+        let toml_value = serde_json::to_value(invalid_config).unwrap();
 
         let (config, is_valid) = MockPlugin::with_config_validation(
             toml_value,
@@ -327,7 +329,7 @@ mod tests {
     /// Test with_config_validation with empty TOML
     #[test]
     fn test_with_config_validation_empty() {
-        let toml_value = toml::Value::Table(toml::Table::new());
+        let toml_value = serde_json::json!({});
 
         let (config, is_valid) = MockPlugin::with_config_validation(
             toml_value,
@@ -349,7 +351,7 @@ mod tests {
         let config = StandardConfig {
             target_path: Some("mixin/path".to_string()),
             output_file: Some("mixin.json".to_string()),
-            restore_target_dir: Some("/mixin/restore".to_string()),
+            restore_target_dir: Some("/mixin/restore".into()),
             hooks: None,
         };
 
@@ -387,7 +389,7 @@ mod tests {
         let config = StandardConfig {
             target_path: Some("serde/path".to_string()),
             output_file: Some("serde.json".to_string()),
-            restore_target_dir: Some("/serde/restore".to_string()),
+            restore_target_dir: Some("/serde/restore".into()),
             hooks: Some(StandardHooks {
                 pre_plugin: vec![HookAction::Script {
                     command: "echo".to_string(),
@@ -429,7 +431,7 @@ mod tests {
                 { action = "script", command = "post1", args = ["post_arg"], timeout = 30, working_dir = "/tmp" }
             ]
         "#;
-        let toml_value: toml::Value = toml::from_str(toml_str).unwrap();
+        let toml_value: serde_json::Value = toml::from_str(toml_str).unwrap();
 
         let (config, is_valid) = MockPlugin::with_config_validation(
             toml_value,
@@ -476,39 +478,36 @@ mod tests {
         }
     }
 
-    /// Test StandardConfig from_toml_value method
+    /// Test StandardConfig from_value method
     /// Verifies the ConfigSchema trait implementation on StandardConfig
     #[test]
-    fn test_standard_config_from_toml_value() {
+    fn test_standard_config_from_value() {
         let toml_str = r#"
             target_path = "from_toml/path"
             output_file = "from_toml.json"
             restore_target_dir = "/from_toml/restore"
         "#;
-        let toml_value: toml::Value = toml::from_str(toml_str).unwrap();
+        let toml_value: serde_json::Value = toml::from_str(toml_str).unwrap();
 
-        let result = StandardConfig::from_toml_value(&toml_value);
+        let result = StandardConfig::from_value(&toml_value);
         assert!(result.is_ok());
 
         let config = result.unwrap();
         assert_eq!(config.target_path, Some("from_toml/path".to_string()));
         assert_eq!(config.output_file, Some("from_toml.json".to_string()));
-        assert_eq!(
-            config.restore_target_dir,
-            Some("/from_toml/restore".to_string())
-        );
+        assert_eq!(config.restore_target_dir, Some("/from_toml/restore".into()));
     }
 
-    /// Test StandardConfig from_toml_value with invalid data
+    /// Test StandardConfig from_value with invalid data
     /// Verifies error handling in ConfigSchema trait implementation
     #[test]
-    fn test_standard_config_from_toml_value_invalid() {
+    fn test_standard_config_from_value_invalid() {
         // Create invalid TOML with wrong type
         let mut invalid_config = toml::Table::new();
         invalid_config.insert("target_path".to_string(), toml::Value::Integer(456));
-        let toml_value = toml::Value::Table(invalid_config);
+        let toml_value = serde_json::to_value(invalid_config).unwrap();
 
-        let result = StandardConfig::from_toml_value(&toml_value);
+        let result = StandardConfig::from_value(&toml_value);
         assert!(result.is_err());
 
         let error = result.unwrap_err();
@@ -529,7 +528,7 @@ mod tests {
                 { action = "invalid_action", command = "test" }
             ]
         "#;
-        let toml_value: toml::Value = toml::from_str(toml_str).unwrap();
+        let toml_value: serde_json::Value = toml::from_str(toml_str).unwrap();
 
         let (config, is_valid) = MockPlugin::with_config_validation(
             toml_value,
@@ -606,7 +605,7 @@ mod tests {
             toml::Value::String("valid/path".to_string()),
         );
         mixed_config.insert("output_file".to_string(), toml::Value::Integer(789)); // Invalid type
-        let toml_value = toml::Value::Table(mixed_config);
+        let toml_value = serde_json::to_value(mixed_config).unwrap();
 
         let (config, is_valid) = MockPlugin::with_config_validation(
             toml_value,
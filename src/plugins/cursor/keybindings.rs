@@ -92,7 +92,7 @@ mod tests {
             target_path = "cursor"
             output_file = "keybindings.json"
         "#;
-        let config: toml::Value = toml::from_str(config_toml).unwrap();
+        let config: serde_json::Value = toml::from_str(config_toml).unwrap();
         let plugin = KeybindingsPlugin::with_config(CursorKeybindingsCore, config);
 
         assert_eq!(
@@ -203,7 +203,7 @@ mod tests {
             output_file = "keybindings.json"
             restore_target_dir = "/custom/cursor/path"
         "#;
-        let config: toml::Value = toml::from_str(config_toml).unwrap();
+        let config: serde_json::Value = toml::from_str(config_toml).unwrap();
         let plugin_with_config = KeybindingsPlugin::with_config(CursorKeybindingsCore, config);
 
         assert_eq!(
@@ -102,7 +102,7 @@ mod tests {
             target_path = "cursor"
             output_file = "settings.json"
         "#;
-        let config: toml::Value = toml::from_str(config_toml).unwrap();
+        let config: serde_json::Value = toml::from_str(config_toml).unwrap();
         let plugin = SettingsPlugin::with_config(CursorCore, config);
 
         assert_eq!(
@@ -206,7 +206,7 @@ mod tests {
             output_file = "settings.json"
             restore_target_dir = "/custom/cursor/path"
         "#;
-        let config: toml::Value = toml::from_str(config_toml).unwrap();
+        let config: serde_json::Value = toml::from_str(config_toml).unwrap();
         let plugin_with_config = SettingsPlugin::with_config(CursorCore, config);
 
         assert_eq!(
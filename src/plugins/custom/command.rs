@@ -0,0 +1,470 @@
+//! Command plugins declared under `[plugins.custom.<name>]`
+//!
+//! Each entry saves the output of a shell command, such as `gh extension list`
+//! or `pip freeze`, and can restore it with a second command. They run like
+//! compiled plugins: selected by name or category, validated by checking
+//! their required commands exist, and saved under their own directory.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, ObjectValidation, Schema, SchemaObject};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+use tracing::{debug, info, warn};
+
+use crate::config::Config;
+use crate::core::config_schema::ConfigSchema;
+use crate::core::paths::PlatformPath;
+use crate::core::plugin::{NotApplicable, Plugin, PluginDescriptor};
+use crate::symbols::*;
+
+/// Key of the table holding custom plugins under `[plugins]`
+pub const CUSTOM_SECTION: &str = "custom";
+
+/// Category of custom plugins that don't set one
+pub const DEFAULT_CATEGORY: &str = "custom";
+
+/// Seconds a capture or restore command may run unless configured
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+/// Configuration of one `[plugins.custom.<name>]` entry
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct CustomPluginConfig {
+    #[schemars(description = "Shell command whose output is saved in the snapshot")]
+    pub capture: String,
+
+    #[schemars(
+        description = "Shell command restoring the saved output; {file} is replaced by the path of the saved file"
+    )]
+    pub restore: Option<String>,
+
+    #[schemars(
+        description = "Commands that must be installed for the plugin to run (default: the first word of capture)"
+    )]
+    pub requires: Option<Vec<String>>,
+
+    #[schemars(description = "Seconds the capture or restore command may run (default: 60)")]
+    pub timeout: Option<u64>,
+
+    #[schemars(description = "Category used to select and list the plugin (default: custom)")]
+    pub category: Option<String>,
+
+    #[schemars(description = "Description shown in the plugin list")]
+    pub description: Option<String>,
+
+    #[schemars(
+        description = "Custom directory path within the snapshot for this plugin's output (default: the plugin name)"
+    )]
+    pub target_path: Option<String>,
+
+    #[schemars(description = "Custom filename for the plugin output (default: <name>.txt)")]
+    pub output_file: Option<String>,
+
+    #[schemars(
+        description = "Custom target directory for restoration, or a table of paths by OS (linux, macos, windows, default)"
+    )]
+    pub restore_target_dir: Option<PlatformPath>,
+}
+
+impl ConfigSchema for CustomPluginConfig {
+    fn schema_name() -> &'static str {
+        "CustomPluginConfig"
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.capture.trim().is_empty() {
+            return Err(anyhow::anyhow!("capture command cannot be empty"));
+        }
+        if self.timeout == Some(0) {
+            return Err(anyhow::anyhow!("timeout must be at least 1 second"));
+        }
+        Ok(())
+    }
+}
+
+/// Schema of the `[plugins.custom]` table: any name mapping to a custom plugin
+pub fn custom_plugins_schema(generator: &mut SchemaGenerator) -> Schema {
+    SchemaObject {
+        instance_type: Some(InstanceType::Object.into()),
+        object: Some(Box::new(ObjectValidation {
+            additional_properties: Some(Box::new(generator.subschema_for::<CustomPluginConfig>())),
+            ..ObjectValidation::default()
+        })),
+        ..SchemaObject::default()
+    }
+    .into()
+}
+
+/// Plugin saving the output of a configured command
+pub struct CustomCommandPlugin {
+    name: String,
+    description: String,
+    config: CustomPluginConfig,
+}
+
+impl CustomCommandPlugin {
+    pub fn new(name: &str, config: CustomPluginConfig) -> Self {
+        let description = config
+            .description
+            .clone()
+            .unwrap_or_else(|| format!("Output of `{}`", config.capture.trim()));
+        Self {
+            name: name.to_string(),
+            description,
+            config,
+        }
+    }
+
+    /// Custom plugins declared in the configuration, sorted by name
+    ///
+    /// Entries that fail to parse or reuse the name of a compiled plugin are
+    /// skipped with a warning, as `dotsnapshot config validate` reports them.
+    pub fn from_config(config: &Config) -> Vec<Self> {
+        let Some(entries) = config
+            .get_raw_plugin_config(CUSTOM_SECTION)
+            .and_then(|value| value.as_object())
+        else {
+            return Vec::new();
+        };
+
+        let mut plugins = Vec::new();
+        for (name, value) in entries {
+            if is_builtin_plugin(name) {
+                warn!(
+                    "{} Ignoring custom plugin '{}': a built-in plugin has the same name",
+                    SYMBOL_INDICATOR_WARNING, name
+                );
+                continue;
+            }
+            match CustomPluginConfig::from_value(value) {
+                Ok(plugin_config) => plugins.push(Self::new(name, plugin_config)),
+                Err(e) => warn!(
+                    "{} Ignoring custom plugin '{}': {:#}",
+                    SYMBOL_INDICATOR_WARNING, name, e
+                ),
+            }
+        }
+        plugins.sort_by(|a, b| a.name.cmp(&b.name));
+        plugins
+    }
+
+    /// Name of the plugin, its key under `[plugins.custom]`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Commands that must be installed for the plugin to run
+    fn required_commands(&self) -> Vec<String> {
+        match &self.config.requires {
+            Some(requires) => requires.clone(),
+            None => self
+                .config
+                .capture
+                .split_whitespace()
+                .next()
+                .map(str::to_string)
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.config.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS))
+    }
+
+    /// Run a command through the shell and return its standard output
+    async fn run(&self, command: &str, working_dir: Option<&Path>) -> Result<String> {
+        debug!("Executing custom plugin command: {}", command);
+
+        let mut cmd = shell_command(command);
+        cmd.stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(dir) = working_dir {
+            cmd.current_dir(dir);
+        }
+
+        let output = tokio::time::timeout(self.timeout(), cmd.output())
+            .await
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "Command '{}' timed out after {} seconds",
+                    command,
+                    self.timeout().as_secs()
+                )
+            })?
+            .with_context(|| format!("Failed to execute command: {command}"))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow::anyhow!(
+                "Command '{}' failed with exit code {}: {}",
+                command,
+                output.status.code().unwrap_or(-1),
+                stderr.trim()
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+#[async_trait]
+impl Plugin for CustomCommandPlugin {
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn icon(&self) -> &str {
+        SYMBOL_TOOL_COMPUTER
+    }
+
+    fn category(&self) -> Option<&str> {
+        Some(self.config.category.as_deref().unwrap_or(DEFAULT_CATEGORY))
+    }
+
+    async fn execute(&self) -> Result<String> {
+        self.run(&self.config.capture, None).await
+    }
+
+    async fn validate(&self) -> Result<()> {
+        for command in self.required_commands() {
+            which::which(&command).map_err(|_| {
+                NotApplicable(format!("{command} command not found. Please install it."))
+            })?;
+        }
+        Ok(())
+    }
+
+    fn get_target_path(&self) -> Option<String> {
+        Some(
+            self.config
+                .target_path
+                .clone()
+                .unwrap_or_else(|| self.name.clone()),
+        )
+    }
+
+    fn get_output_file(&self) -> Option<String> {
+        Some(
+            self.config
+                .output_file
+                .clone()
+                .unwrap_or_else(|| format!("{}.txt", self.name)),
+        )
+    }
+
+    fn get_restore_target_dir(&self) -> Option<String> {
+        let restore_target_dir = self.config.restore_target_dir.as_ref()?;
+        restore_target_dir.for_current_os().map(str::to_string)
+    }
+
+    async fn restore(
+        &self,
+        snapshot_path: &Path,
+        target_path: &Path,
+        dry_run: bool,
+    ) -> Result<Vec<PathBuf>> {
+        // Without a restore command the saved file is copied like any other
+        let Some(restore) = &self.config.restore else {
+            return Ok(Vec::new());
+        };
+        let Some(output_file) = self.get_output_file() else {
+            return Ok(Vec::new());
+        };
+        let file = snapshot_path.join(output_file);
+        if !file.exists() {
+            return Ok(Vec::new());
+        }
+
+        let command = restore.replace("{file}", &shell_quote(&file));
+        if dry_run {
+            warn!(
+                "DRY RUN: Would run '{}' in {}",
+                command,
+                target_path.display()
+            );
+            return Ok(vec![target_path.to_path_buf()]);
+        }
+
+        tokio::fs::create_dir_all(target_path)
+            .await
+            .context("Failed to create target directory for restore command")?;
+        self.run(&command, Some(target_path))
+            .await
+            .with_context(|| format!("Failed to restore custom plugin {}", self.name))?;
+
+        info!(
+            "Restored custom plugin {} from {}",
+            self.name,
+            file.display()
+        );
+        Ok(vec![target_path.to_path_buf()])
+    }
+}
+
+/// Whether a compiled plugin is registered under this name
+pub fn is_builtin_plugin(name: &str) -> bool {
+    inventory::iter::<PluginDescriptor>().any(|descriptor| descriptor.name == name)
+}
+
+/// Command running a command line through the platform's shell
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+/// Quote a path for use as a single shell argument
+fn shell_quote(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    if cfg!(windows) {
+        format!("\"{path}\"")
+    } else {
+        format!("'{}'", path.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn plugin(config: CustomPluginConfig) -> CustomCommandPlugin {
+        CustomCommandPlugin::new("tool_list", config)
+    }
+
+    /// Test reading custom plugins from the configuration
+    /// Verifies entries are parsed with their defaults and invalid ones are skipped
+    #[test]
+    fn test_from_config() {
+        let config: Config = toml::from_str(
+            r#"
+            [plugins.custom.pip_freeze]
+            capture = "pip freeze"
+            category = "python"
+
+            [plugins.custom.empty]
+            capture = " "
+
+            [plugins.custom.homebrew_brewfile]
+            capture = "brew list"
+            "#,
+        )
+        .unwrap();
+
+        let plugins = CustomCommandPlugin::from_config(&config);
+        assert_eq!(plugins.len(), 1);
+        let plugin = &plugins[0];
+        assert_eq!(plugin.name(), "pip_freeze");
+        assert_eq!(plugin.category(), Some("python"));
+        assert_eq!(plugin.description(), "Output of `pip freeze`");
+        assert_eq!(plugin.get_target_path().as_deref(), Some("pip_freeze"));
+        assert_eq!(plugin.get_output_file().as_deref(), Some("pip_freeze.txt"));
+        assert_eq!(plugin.required_commands(), vec!["pip".to_string()]);
+
+        assert!(CustomCommandPlugin::from_config(&Config::default()).is_empty());
+    }
+
+    /// Test capturing command output
+    /// Verifies the capture command runs through the shell and failures are errors
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute() {
+        let echo = plugin(CustomPluginConfig {
+            capture: "printf 'one\\ntwo\\n' | sort -r".to_string(),
+            ..CustomPluginConfig::default()
+        });
+        assert!(echo.validate().await.is_ok());
+        assert_eq!(echo.execute().await.unwrap(), "two\none\n");
+
+        let failing = plugin(CustomPluginConfig {
+            capture: "echo broken >&2; exit 3".to_string(),
+            ..CustomPluginConfig::default()
+        });
+        let error = failing.execute().await.unwrap_err().to_string();
+        assert!(error.contains("exit code 3: broken"), "{error}");
+
+        let slow = plugin(CustomPluginConfig {
+            capture: "sleep 5".to_string(),
+            timeout: Some(1),
+            ..CustomPluginConfig::default()
+        });
+        let error = slow.execute().await.unwrap_err().to_string();
+        assert!(error.contains("timed out after 1 seconds"), "{error}");
+    }
+
+    /// Test validating required commands
+    /// Verifies a missing required command makes the plugin not applicable
+    #[tokio::test]
+    async fn test_validate_missing_command() {
+        let missing = plugin(CustomPluginConfig {
+            capture: "dotsnapshot-missing-tool list".to_string(),
+            ..CustomPluginConfig::default()
+        });
+        let error = missing.validate().await.unwrap_err();
+        assert!(NotApplicable::is_in(&error));
+
+        let explicit = plugin(CustomPluginConfig {
+            capture: "true".to_string(),
+            requires: Some(vec!["dotsnapshot-missing-tool".to_string()]),
+            ..CustomPluginConfig::default()
+        });
+        assert!(explicit.validate().await.is_err());
+    }
+
+    /// Test restoring with the restore command
+    /// Verifies {file} is replaced by the saved file and dry runs run nothing
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_restore_command() {
+        let temp_dir = TempDir::new().unwrap();
+        let snapshot_path = temp_dir.path().join("snapshot").join("tool_list");
+        let target_path = temp_dir.path().join("target");
+        std::fs::create_dir_all(&snapshot_path).unwrap();
+        std::fs::write(snapshot_path.join("tool_list.txt"), "alpha\n").unwrap();
+
+        let restoring = plugin(CustomPluginConfig {
+            capture: "true".to_string(),
+            restore: Some("cp {file} restored.txt".to_string()),
+            ..CustomPluginConfig::default()
+        });
+
+        let planned = restoring
+            .restore(&snapshot_path, &target_path, true)
+            .await
+            .unwrap();
+        assert_eq!(planned, vec![target_path.clone()]);
+        assert!(!target_path.exists());
+
+        restoring
+            .restore(&snapshot_path, &target_path, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(target_path.join("restored.txt")).unwrap(),
+            "alpha\n"
+        );
+
+        let copying = plugin(CustomPluginConfig {
+            capture: "true".to_string(),
+            ..CustomPluginConfig::default()
+        });
+        assert!(copying
+            .restore(&snapshot_path, &target_path, false)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
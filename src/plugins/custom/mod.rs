@@ -0,0 +1,4 @@
+pub mod command;
+
+// Custom plugins are declared in the configuration rather than registered
+// through inventory; the plugin registry instantiates them from `[plugins.custom]`
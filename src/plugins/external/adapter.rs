@@ -0,0 +1,510 @@
+//! External plugins wrapped as [`Plugin`]s
+//!
+//! Executables named `dotsnapshot-plugin-<name>` in the plugins directories,
+//! and on `PATH` when enabled, become the plugin `<name>` (with `-` replaced
+//! by `_`). Their captured files are written under `<name>/` in the snapshot.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use tracing::{debug, warn};
+
+use super::protocol::{
+    Capture, Description, PluginProcess, Request, RestoreDir, RestoreParams, Restored, Unsupported,
+};
+use crate::config::Config;
+use crate::core::paths::expand_path;
+use crate::core::plugin::{ExecutionContext, Plugin};
+use crate::symbols::*;
+
+/// Key of the external plugin settings under `[plugins]`
+pub const EXTERNAL_SECTION: &str = "external";
+
+/// Prefix of external plugin executables
+pub const EXECUTABLE_PREFIX: &str = "dotsnapshot-plugin-";
+
+/// Category of external plugins that don't describe one
+pub const DEFAULT_CATEGORY: &str = "external";
+
+/// Description of external plugins that haven't described themselves yet
+const UNDESCRIBED: &str = "External plugin";
+
+/// Seconds an external plugin may take to answer unless configured
+const DEFAULT_TIMEOUT_SECS: u64 = 120;
+
+/// Settings of `[plugins.external]`
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct ExternalPluginsConfig {
+    #[schemars(
+        description = "Directories searched for dotsnapshot-plugin-* executables before PATH (default: ~/.config/dotsnapshot/plugins)"
+    )]
+    pub dirs: Option<Vec<String>>,
+
+    #[schemars(description = "Whether to also look for external plugins on PATH (default: false)")]
+    pub search_path: Option<bool>,
+
+    #[schemars(description = "Seconds an external plugin may take to answer (default: 120)")]
+    pub timeout: Option<u64>,
+}
+
+impl ExternalPluginsConfig {
+    /// Settings from the configuration, defaults when absent or invalid
+    pub fn from_config(config: &Config) -> Self {
+        config
+            .get_raw_plugin_config(EXTERNAL_SECTION)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Directories searched for plugin executables, in order
+    pub fn search_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = match &self.dirs {
+            Some(dirs) => dirs
+                .iter()
+                .filter_map(|dir| expand_path(dir).ok())
+                .collect(),
+            None => dirs::config_dir()
+                .map(|dir| dir.join("dotsnapshot").join("plugins"))
+                .into_iter()
+                .collect(),
+        };
+        if self.search_path.unwrap_or(false) {
+            if let Some(path) = std::env::var_os("PATH") {
+                dirs.extend(std::env::split_paths(&path));
+            }
+        }
+        dirs
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS))
+    }
+}
+
+/// Plugin executables in the search directories as (plugin name, path)
+///
+/// The first executable found for a name wins, so the plugins directories
+/// take precedence over `PATH`.
+pub fn find_executables(settings: &ExternalPluginsConfig) -> Vec<(String, PathBuf)> {
+    let mut names = BTreeSet::new();
+    let mut executables = Vec::new();
+    for dir in settings.search_dirs() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut found: Vec<(String, PathBuf)> = entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let name = plugin_name(&path)?;
+                is_executable(&path).then_some((name, path))
+            })
+            .collect();
+        found.sort();
+        for (name, path) in found {
+            if names.insert(name.clone()) {
+                executables.push((name, path));
+            }
+        }
+    }
+    executables
+}
+
+/// Plugin name of an executable named `dotsnapshot-plugin-<name>`
+fn plugin_name(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    let file_name = if cfg!(windows) {
+        file_name.strip_suffix(".exe").unwrap_or(file_name)
+    } else {
+        file_name
+    };
+    let name = file_name.strip_prefix(EXECUTABLE_PREFIX)?;
+    (!name.is_empty()).then(|| name.replace('-', "_"))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "exe") && path.is_file()
+}
+
+/// Plugin backed by an external executable
+///
+/// The executable is only asked to describe itself when the description or
+/// category is needed, see [`ExternalPlugin::describe`].
+pub struct ExternalPlugin {
+    name: String,
+    /// Description, or why describing failed
+    description: OnceCell<Result<Description, String>>,
+    process: PluginProcess,
+}
+
+impl ExternalPlugin {
+    pub fn new(name: &str, executable: PathBuf, timeout: Duration) -> Self {
+        Self {
+            name: name.to_string(),
+            description: OnceCell::new(),
+            process: PluginProcess::new(executable, timeout),
+        }
+    }
+
+    /// Ask the executable to describe the plugin, once
+    ///
+    /// Until it has, [`Plugin::description`] and [`Plugin::category`] return
+    /// placeholders. When it failed, the plugin has no category and fails to
+    /// validate with the same error.
+    pub async fn describe(&self) -> Result<&Description> {
+        self.description
+            .get_or_init(|| async {
+                self.process.call(&Request::Describe).await.map_err(|e| {
+                    format!("Failed to describe external plugin {}: {:#}", self.name, e)
+                })
+            })
+            .await
+            .as_ref()
+            .map_err(|e| anyhow::anyhow!("{e}"))
+    }
+
+    /// Description, once the executable described the plugin
+    fn described(&self) -> Option<&Description> {
+        self.description
+            .get()
+            .and_then(|description| description.as_ref().ok())
+    }
+
+    /// External plugins found for the configuration, sorted by name
+    ///
+    /// Executables whose name is already taken by another plugin are skipped
+    /// with a warning. None of them is started.
+    pub fn discover(config: &Config, taken: &BTreeSet<String>) -> Vec<Self> {
+        let settings = ExternalPluginsConfig::from_config(config);
+
+        let mut plugins = Vec::new();
+        for (name, executable) in find_executables(&settings) {
+            if taken.contains(&name) {
+                warn!(
+                    "{} Ignoring external plugin {}: a plugin named '{}' already exists",
+                    SYMBOL_INDICATOR_WARNING,
+                    executable.display(),
+                    name
+                );
+                continue;
+            }
+            plugins.push(Self::new(&name, executable, settings.timeout()));
+        }
+        plugins.sort_by(|a, b| a.name.cmp(&b.name));
+        plugins
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Ask the executable for its files
+    async fn capture(&self) -> Result<Capture> {
+        let capture: Capture = self.process.call(&Request::Capture).await?;
+        if let Some(file) = capture
+            .files
+            .iter()
+            .find(|file| !is_relative_path(&file.path))
+        {
+            return Err(anyhow::anyhow!(
+                "External plugin {} returned a file outside its directory: {}",
+                self.name,
+                file.path.display()
+            ));
+        }
+        Ok(capture)
+    }
+}
+
+#[async_trait]
+impl Plugin for ExternalPlugin {
+    fn description(&self) -> &str {
+        self.described()
+            .map_or(UNDESCRIBED, |description| &description.description)
+    }
+
+    fn icon(&self) -> &str {
+        self.described()
+            .and_then(|description| description.icon.as_deref())
+            .unwrap_or(SYMBOL_TOOL_PLUGIN)
+    }
+
+    fn category(&self) -> Option<&str> {
+        match self.description.get() {
+            Some(Err(_)) => None,
+            _ => Some(
+                self.described()
+                    .and_then(|description| description.category.as_deref())
+                    .unwrap_or(DEFAULT_CATEGORY),
+            ),
+        }
+    }
+
+    async fn execute(&self) -> Result<String> {
+        let context = ExecutionContext::from_env().context("Snapshot directory is not set")?;
+        self.execute_with(&context).await
+    }
+
+    async fn execute_with(&self, context: &ExecutionContext) -> Result<String> {
+        let capture = self.capture().await?;
+        let plugin_dir = context.snapshot_dir.join(&self.name);
+
+        let mut manifest = Vec::new();
+        for file in &capture.files {
+            let path = plugin_dir.join(&file.path);
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(&path, &file.content)
+                .await
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            manifest.push(serde_json::json!({
+                "path": file.path,
+                "checksum": crate::core::checksum::calculate_checksum(&file.content),
+            }));
+        }
+        debug!(
+            "External plugin {} captured {} file(s)",
+            self.name,
+            manifest.len()
+        );
+
+        Ok(serde_json::to_string_pretty(
+            &serde_json::json!({ "files": manifest }),
+        )?)
+    }
+
+    async fn validate(&self) -> Result<()> {
+        if let Some(Err(e)) = self.description.get() {
+            return Err(anyhow::anyhow!("{e}"));
+        }
+        match self.process.call::<IgnoredAny>(&Request::Validate).await {
+            Err(e) if Unsupported::is_in(&e) => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+
+    fn get_target_path(&self) -> Option<String> {
+        None // Files are written under get_own_output_dir
+    }
+
+    fn get_output_file(&self) -> Option<String> {
+        None
+    }
+
+    async fn resolve_default_restore_target_dir(&self) -> Result<PathBuf> {
+        match self
+            .process
+            .call::<RestoreDir>(&Request::DefaultRestoreDir)
+            .await
+        {
+            Ok(restore_dir) => Ok(restore_dir.path),
+            Err(e) if Unsupported::is_in(&e) => self.get_default_restore_target_dir(),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn creates_own_output_files(&self) -> bool {
+        true
+    }
+
+    fn get_own_output_dir(&self) -> Option<String> {
+        Some(self.name.clone())
+    }
+
+    async fn restore(
+        &self,
+        snapshot_path: &Path,
+        target_path: &Path,
+        dry_run: bool,
+    ) -> Result<Vec<PathBuf>> {
+        let request = Request::Restore(RestoreParams {
+            snapshot_dir: snapshot_path.to_path_buf(),
+            target_dir: target_path.to_path_buf(),
+            dry_run,
+        });
+        match self.process.call::<Restored>(&request).await {
+            Ok(restored) => Ok(restored.restored),
+            // Without restore support the captured files are copied
+            Err(e) if Unsupported::is_in(&e) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Whether a path stays inside the directory it is relative to
+fn is_relative_path(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test naming and finding plugin executables
+    /// Verifies only executables with the prefix are found and the first of a name wins
+    #[cfg(unix)]
+    #[test]
+    fn test_find_executables() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let first = temp_dir.path().join("first");
+        let second = temp_dir.path().join("second");
+        for dir in [&first, &second] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        let create = |path: PathBuf, mode: u32| {
+            std::fs::write(&path, "#!/bin/sh\n").unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+        };
+        create(first.join("dotsnapshot-plugin-gh-extensions"), 0o755);
+        create(first.join("dotsnapshot-plugin-notes"), 0o644);
+        create(first.join("other-tool"), 0o755);
+        create(second.join("dotsnapshot-plugin-gh-extensions"), 0o755);
+        create(second.join("dotsnapshot-plugin-pip"), 0o755);
+
+        let settings = ExternalPluginsConfig {
+            dirs: Some(vec![
+                first.display().to_string(),
+                second.display().to_string(),
+            ]),
+            search_path: Some(false),
+            timeout: None,
+        };
+        assert_eq!(
+            find_executables(&settings),
+            vec![
+                (
+                    "gh_extensions".to_string(),
+                    first.join("dotsnapshot-plugin-gh-extensions")
+                ),
+                ("pip".to_string(), second.join("dotsnapshot-plugin-pip")),
+            ]
+        );
+    }
+
+    /// Test registering and planning an external plugin
+    /// Verifies the executable only describes itself when a category selector
+    /// needs it, and never captures for a dry run
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_external_plugin_lazy_describe() -> Result<()> {
+        use crate::core::plugin::PluginRegistry;
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new()?;
+        let executable = temp_dir.path().join("dotsnapshot-plugin-tool");
+        let log = temp_dir.path().join("requests.log");
+        std::fs::write(
+            &executable,
+            format!(
+                r#"#!/bin/sh
+request=$(cat)
+echo "$request" >> '{}'
+case "$request" in
+  *describe*) echo '{{"protocol": 1, "result": {{"description": "Tool settings", "category": "tools"}}}}' ;;
+  *capture*) echo '{{"protocol": 1, "result": {{"files": [{{"path": "settings.json", "content": "{{}}"}}]}}}}' ;;
+esac
+"#,
+                log.display()
+            ),
+        )?;
+        std::fs::set_permissions(&executable, std::fs::Permissions::from_mode(0o755))?;
+        let config: Config = toml::from_str(&format!(
+            "[plugins.external]\ndirs = ['{}']\nsearch_path = false\n",
+            temp_dir.path().display()
+        ))?;
+        let describes = || {
+            std::fs::read_to_string(&log)
+                .unwrap_or_default()
+                .matches("describe")
+                .count()
+        };
+
+        let mut registry = PluginRegistry::new();
+        registry
+            .register_from_descriptors(Some(&config), &["all", "!npm_*"])
+            .await?;
+        let plugin = registry.find_plugin("tool").unwrap();
+        assert_eq!(describes(), 0);
+        assert_eq!(plugin.description(), UNDESCRIBED);
+
+        // Dry runs must not run the plugin's capture
+        assert!(plugin.plan_files().await?.is_none());
+        assert!(!std::fs::read_to_string(&log)
+            .unwrap_or_default()
+            .contains("capture"));
+
+        let mut registry = PluginRegistry::new();
+        registry
+            .register_from_descriptors(Some(&config), &["category:tools"])
+            .await?;
+        assert_eq!(describes(), 1);
+        let plugin = registry.find_plugin("tool").unwrap();
+        assert_eq!(plugin.description(), "Tool settings");
+        assert_eq!(plugin.category(), Some("tools"));
+        Ok(())
+    }
+
+    /// Test registering an external plugin that fails to describe itself
+    /// Verifies it is registered without a category and fails to validate
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_external_plugin_describe_failure() -> Result<()> {
+        use crate::core::plugin::PluginRegistry;
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new()?;
+        let executable = temp_dir.path().join("dotsnapshot-plugin-broken");
+        std::fs::write(&executable, "#!/bin/sh\necho 'crashed' >&2\nexit 1\n")?;
+        std::fs::set_permissions(&executable, std::fs::Permissions::from_mode(0o755))?;
+        let config: Config = toml::from_str(&format!(
+            "[plugins.external]\ndirs = ['{}']\n",
+            temp_dir.path().display()
+        ))?;
+
+        let mut registry = PluginRegistry::new();
+        registry
+            .register_from_descriptors(Some(&config), &["broken", "!category:static"])
+            .await?;
+        let plugin = registry.find_plugin("broken").unwrap();
+        assert_eq!(plugin.description(), UNDESCRIBED);
+        assert_eq!(plugin.category(), None);
+
+        let error = plugin.validate().await.unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Failed to describe external plugin broken"));
+        Ok(())
+    }
+
+    /// Test checking captured file paths
+    /// Verifies files can't be written outside the plugin's directory
+    #[test]
+    fn test_is_relative_path() {
+        assert!(is_relative_path(Path::new("settings.json")));
+        assert!(is_relative_path(Path::new("nested/settings.json")));
+        assert!(!is_relative_path(Path::new("")));
+        assert!(!is_relative_path(Path::new("../settings.json")));
+        assert!(!is_relative_path(Path::new("/etc/passwd")));
+        assert!(!is_relative_path(Path::new("./settings.json")));
+    }
+}
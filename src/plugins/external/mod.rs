@@ -0,0 +1,5 @@
+pub mod adapter;
+pub mod protocol;
+
+// External plugins are executables discovered at runtime rather than
+// registered through inventory; see `adapter` for how they are found
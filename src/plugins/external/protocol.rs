@@ -0,0 +1,386 @@
+//! JSON-over-stdio protocol spoken by external plugins
+//!
+//! Each request starts the plugin executable, writes one JSON object to its
+//! standard input and closes it, then reads one JSON object from its standard
+//! output. Standard error is only logged. Requests and responses carry the
+//! protocol version, and a plugin answering with another version is rejected.
+//!
+//! ```json
+//! {"protocol": 1, "method": "restore", "params": {"snapshot_dir": "...", "target_dir": "...", "dry_run": false}}
+//! {"protocol": 1, "result": {"restored": ["/home/me/.config/tool/settings.json"]}}
+//! {"protocol": 1, "error": {"kind": "not_applicable", "message": "tool is not installed"}}
+//! ```
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::process::{ExitStatus, Stdio};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tracing::debug;
+
+use crate::core::plugin::NotApplicable;
+
+/// Version of the protocol implemented by this build
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// A request sent to an external plugin
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum Request {
+    /// Describe the plugin: [`Description`]
+    Describe,
+    /// Check the plugin can run on this machine: empty result
+    Validate,
+    /// Capture the plugin's files: [`Capture`]
+    Capture,
+    /// Restore from a snapshot: [`Restored`]
+    Restore(RestoreParams),
+    /// Directory restored into when none is configured: [`RestoreDir`]
+    DefaultRestoreDir,
+}
+
+/// Parameters of [`Request::Restore`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestoreParams {
+    /// The plugin's directory in the snapshot, holding the captured files
+    pub snapshot_dir: PathBuf,
+    /// Directory to restore into
+    pub target_dir: PathBuf,
+    /// Report what would be restored without changing anything
+    pub dry_run: bool,
+}
+
+/// Result of [`Request::Describe`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Description {
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+}
+
+/// Result of [`Request::Capture`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Capture {
+    pub files: Vec<CapturedFile>,
+}
+
+/// A file returned by [`Request::Capture`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapturedFile {
+    /// Path relative to the plugin's directory in the snapshot
+    pub path: PathBuf,
+    pub content: String,
+}
+
+/// Result of [`Request::Restore`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Restored {
+    /// Files restored, or that would be restored in a dry run
+    pub restored: Vec<PathBuf>,
+}
+
+/// Result of [`Request::DefaultRestoreDir`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestoreDir {
+    pub path: PathBuf,
+}
+
+/// Envelope of a request on the plugin's standard input
+#[derive(Debug, Serialize)]
+struct RequestMessage<'a> {
+    protocol: u32,
+    #[serde(flatten)]
+    request: &'a Request,
+}
+
+/// Envelope of a response on the plugin's standard output
+#[derive(Debug, Deserialize)]
+struct ResponseMessage {
+    protocol: u32,
+    #[serde(default)]
+    result: Option<serde_json::Value>,
+    #[serde(default)]
+    error: Option<ResponseError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseError {
+    #[serde(default)]
+    kind: ErrorKind,
+    message: String,
+}
+
+/// Kind of an error response
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ErrorKind {
+    /// The plugin does not apply to this machine
+    NotApplicable,
+    /// The plugin does not implement the method
+    Unsupported,
+    #[default]
+    #[serde(other)]
+    Failed,
+}
+
+/// Error returned when a plugin does not implement a method, so callers can
+/// fall back to the default behaviour
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct Unsupported(pub String);
+
+impl Unsupported {
+    /// Whether an error (or any error in its context chain) is `Unsupported`
+    pub fn is_in(error: &anyhow::Error) -> bool {
+        error.chain().any(|cause| cause.is::<Unsupported>())
+    }
+}
+
+/// An external plugin executable
+#[derive(Debug, Clone)]
+pub struct PluginProcess {
+    executable: PathBuf,
+    timeout: Duration,
+}
+
+impl PluginProcess {
+    pub fn new(executable: PathBuf, timeout: Duration) -> Self {
+        Self {
+            executable,
+            timeout,
+        }
+    }
+
+    /// Send a request and decode its result
+    pub async fn call<T: DeserializeOwned>(&self, request: &Request) -> Result<T> {
+        let input = self.encode(request)?;
+        let mut child = tokio::process::Command::new(&self.executable)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start {}", self.executable.display()))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // A plugin may exit without reading its input
+            let _ = stdin.write_all(&input).await;
+        }
+
+        let output = tokio::time::timeout(self.timeout, child.wait_with_output())
+            .await
+            .map_err(|_| self.timed_out())?
+            .with_context(|| format!("Failed to run {}", self.executable.display()))?;
+
+        self.decode(request, output.status, &output.stdout, &output.stderr)
+    }
+
+    fn encode(&self, request: &Request) -> Result<Vec<u8>> {
+        debug!(
+            "Sending {:?} to external plugin {}",
+            request,
+            self.executable.display()
+        );
+        let mut input = serde_json::to_vec(&RequestMessage {
+            protocol: PROTOCOL_VERSION,
+            request,
+        })?;
+        input.push(b'\n');
+        Ok(input)
+    }
+
+    fn decode<T: DeserializeOwned>(
+        &self,
+        request: &Request,
+        status: ExitStatus,
+        stdout: &[u8],
+        stderr: &[u8],
+    ) -> Result<T> {
+        let stderr = String::from_utf8_lossy(stderr);
+        if !stderr.trim().is_empty() {
+            debug!(
+                "External plugin {} stderr: {}",
+                self.executable.display(),
+                stderr.trim()
+            );
+        }
+
+        // A plugin exiting with an error is only trusted to explain it with an
+        // error response
+        let parsed = serde_json::from_slice::<ResponseMessage>(stdout);
+        if !status.success() && !parsed.as_ref().is_ok_and(|r| r.error.is_some()) {
+            return Err(anyhow::anyhow!(
+                "{} failed with exit code {}: {}",
+                self.executable.display(),
+                status.code().unwrap_or(-1),
+                stderr.trim()
+            ));
+        }
+        let response = parsed.with_context(|| {
+            format!(
+                "{} did not answer with a protocol response",
+                self.executable.display()
+            )
+        })?;
+
+        if response.protocol != PROTOCOL_VERSION {
+            return Err(anyhow::anyhow!(
+                "{} speaks plugin protocol version {}, expected {}",
+                self.executable.display(),
+                response.protocol,
+                PROTOCOL_VERSION
+            ));
+        }
+
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(match error.kind {
+                ErrorKind::NotApplicable => NotApplicable(error.message).into(),
+                ErrorKind::Unsupported => Unsupported(error.message).into(),
+                ErrorKind::Failed => anyhow::anyhow!(error.message),
+            }),
+            (Some(result), None) => serde_json::from_value(result).with_context(|| {
+                format!(
+                    "Invalid {} result from {}",
+                    method_name(request),
+                    self.executable.display()
+                )
+            }),
+            (None, None) => Err(anyhow::anyhow!(
+                "{} answered without a result or error",
+                self.executable.display()
+            )),
+        }
+    }
+
+    fn timed_out(&self) -> anyhow::Error {
+        anyhow::anyhow!(
+            "{} timed out after {} seconds",
+            self.executable.display(),
+            self.timeout.as_secs()
+        )
+    }
+}
+
+/// Name of a request's method as sent on the wire
+pub fn method_name(request: &Request) -> &'static str {
+    match request {
+        Request::Describe => "describe",
+        Request::Validate => "validate",
+        Request::Capture => "capture",
+        Request::Restore(_) => "restore",
+        Request::DefaultRestoreDir => "default_restore_dir",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test the wire format of requests
+    /// Verifies the protocol version, method and params are sent as documented
+    #[test]
+    fn test_request_encoding() {
+        let process = PluginProcess::new(PathBuf::from("plugin"), Duration::from_secs(1));
+        let describe = process.encode(&Request::Describe).unwrap();
+        assert_eq!(
+            String::from_utf8(describe).unwrap(),
+            "{\"protocol\":1,\"method\":\"describe\"}\n"
+        );
+
+        let restore = Request::Restore(RestoreParams {
+            snapshot_dir: PathBuf::from("/snapshot/tool"),
+            target_dir: PathBuf::from("/home/me"),
+            dry_run: true,
+        });
+        let value: serde_json::Value =
+            serde_json::from_slice(&process.encode(&restore).unwrap()).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "protocol": 1,
+                "method": "restore",
+                "params": {
+                    "snapshot_dir": "/snapshot/tool",
+                    "target_dir": "/home/me",
+                    "dry_run": true
+                }
+            })
+        );
+    }
+
+    /// Test calling a plugin over stdio
+    /// Verifies results, error kinds, exit status and protocol version checks
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_call() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let exiting = |name: &str, response: &str, code: i32| {
+            use std::os::unix::fs::PermissionsExt;
+            let path = temp_dir.path().join(name);
+            std::fs::write(
+                &path,
+                format!("#!/bin/sh\ncat >/dev/null\necho '{response}'\nexit {code}\n"),
+            )
+            .unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+            PluginProcess::new(path, Duration::from_secs(5))
+        };
+        let plugin = |name: &str, response: &str| exiting(name, response, 0);
+
+        let describing = plugin(
+            "describing",
+            r#"{"protocol": 1, "result": {"description": "Tool settings", "category": "tools"}}"#,
+        );
+        let description: Description = describing.call(&Request::Describe).await.unwrap();
+        assert_eq!(description.category.as_deref(), Some("tools"));
+
+        let missing = plugin(
+            "missing",
+            r#"{"protocol": 1, "error": {"kind": "not_applicable", "message": "tool missing"}}"#,
+        );
+        let error = missing
+            .call::<serde::de::IgnoredAny>(&Request::Validate)
+            .await
+            .unwrap_err();
+        assert!(NotApplicable::is_in(&error));
+
+        let partial = plugin(
+            "partial",
+            r#"{"protocol": 1, "error": {"kind": "unsupported", "message": "no restore"}}"#,
+        );
+        let error = partial
+            .call::<RestoreDir>(&Request::DefaultRestoreDir)
+            .await
+            .unwrap_err();
+        assert!(Unsupported::is_in(&error));
+
+        let newer = plugin("newer", r#"{"protocol": 2, "result": {}}"#);
+        let error = newer
+            .call::<serde::de::IgnoredAny>(&Request::Validate)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("protocol version 2, expected 1"));
+
+        let crashed = exiting("crashed", r#"{"protocol": 1, "result": {}}"#, 3);
+        let error = crashed
+            .call::<serde::de::IgnoredAny>(&Request::Validate)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("failed with exit code 3"));
+
+        let refused = exiting(
+            "refused",
+            r#"{"protocol": 1, "error": {"kind": "failed", "message": "no access"}}"#,
+            1,
+        );
+        let error = refused
+            .call::<serde::de::IgnoredAny>(&Request::Validate)
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "no access");
+    }
+}
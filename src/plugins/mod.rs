@@ -1,5 +1,7 @@
 pub mod core;
 pub mod cursor;
+pub mod custom;
+pub mod external;
 pub mod homebrew;
 pub mod npm;
 pub mod r#static;
@@ -169,7 +169,7 @@ mod tests {
             target_path = "npm"
             output_file = "npmrc.txt"
         "#;
-        let config: toml::Value = toml::from_str(config_toml).unwrap();
+        let config: serde_json::Value = toml::from_str(config_toml).unwrap();
         let plugin = SettingsPlugin::with_config(NpmConfigCore, config);
 
         assert_eq!(
@@ -257,7 +257,7 @@ audit=false
             output_file = "npmrc.txt"
             restore_target_dir = "/custom/npm/path"
         "#;
-        let config: toml::Value = toml::from_str(config_toml).unwrap();
+        let config: serde_json::Value = toml::from_str(config_toml).unwrap();
         let plugin_with_config = SettingsPlugin::with_config(NpmConfigCore, config);
 
         assert_eq!(
@@ -389,7 +389,7 @@ audit=false
 
         // Test default restore target directory
         let default_dir = plugin.get_default_restore_target_dir().unwrap();
-        assert!(default_dir.is_absolute() || default_dir == std::path::PathBuf::from("."));
+        assert!(default_dir.is_absolute() || default_dir == std::path::Path::new("."));
     }
 
     /// Test NPM config plugin execute with missing npm
@@ -413,7 +413,7 @@ audit=false
             target_path = 123
             invalid_field = "should_be_ignored"
         "#;
-        let config: toml::Value = toml::from_str(invalid_config_toml).unwrap();
+        let config: serde_json::Value = toml::from_str(invalid_config_toml).unwrap();
         let plugin = SettingsPlugin::with_config(NpmConfigCore, config);
 
         // Should handle invalid config gracefully
@@ -432,7 +432,7 @@ audit=false
             target_path = "custom_npm"
             output_file = "custom_npmrc.txt"
         "#;
-        let config: toml::Value = toml::from_str(config_toml).unwrap();
+        let config: serde_json::Value = toml::from_str(config_toml).unwrap();
         let plugin = SettingsPlugin::with_config(NpmConfigCore, config);
 
         let temp_dir = TempDir::new().unwrap();
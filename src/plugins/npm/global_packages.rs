@@ -255,7 +255,7 @@ mod tests {
             target_path = "npm"
             output_file = "global_packages.txt"
         "#;
-        let config: toml::Value = toml::from_str(config_toml).unwrap();
+        let config: serde_json::Value = toml::from_str(config_toml).unwrap();
         let plugin = PackagePlugin::with_config(NpmGlobalCore, config);
 
         assert_eq!(
@@ -314,7 +314,7 @@ mod tests {
         let plugin = PackagePlugin::new(NpmGlobalCore);
 
         let default_dir = plugin.get_default_restore_target_dir().unwrap();
-        assert!(default_dir.is_absolute() || default_dir == PathBuf::from("."));
+        assert!(default_dir.is_absolute() || default_dir == std::path::Path::new("."));
 
         assert_eq!(ConfigMixin::get_restore_target_dir(&plugin), None);
     }
@@ -327,7 +327,7 @@ mod tests {
         let restore_dir = core.get_default_restore_dir().unwrap();
 
         // Should be current directory or absolute path
-        assert!(restore_dir.is_absolute() || restore_dir == PathBuf::from("."));
+        assert!(restore_dir.is_absolute() || restore_dir == std::path::Path::new("."));
     }
 
     /// Test NPM global core validate_command_exists success
@@ -385,7 +385,7 @@ mod tests {
 
         // Test default restore target directory
         let default_dir = plugin.get_default_restore_target_dir().unwrap();
-        assert!(default_dir.is_absolute() || default_dir == PathBuf::from("."));
+        assert!(default_dir.is_absolute() || default_dir == std::path::Path::new("."));
     }
 
     /// Test NPM global plugin execute with missing npm
@@ -409,7 +409,7 @@ mod tests {
             target_path = 123
             invalid_field = "should_be_ignored"
         "#;
-        let config: toml::Value = toml::from_str(invalid_config_toml).unwrap();
+        let config: serde_json::Value = toml::from_str(invalid_config_toml).unwrap();
         let plugin = PackagePlugin::with_config(NpmGlobalCore, config);
 
         // Should handle invalid config gracefully
@@ -428,7 +428,7 @@ mod tests {
             target_path = "custom_npm"
             output_file = "custom_global_packages.txt"
         "#;
-        let config: toml::Value = toml::from_str(config_toml).unwrap();
+        let config: serde_json::Value = toml::from_str(config_toml).unwrap();
         let plugin = PackagePlugin::with_config(NpmGlobalCore, config);
 
         let temp_dir = TempDir::new().unwrap();
@@ -0,0 +1,263 @@
+//! Per-output-directory cache of static file checksums
+//!
+//! Static files are identified by their source path, size, modification time
+//! and inode. When none of these changed since the previous run, the checksum
+//! recorded then is reused instead of hashing the copied file again.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tracing::warn;
+
+use crate::core::checksum::{FileHashes, HashAlgorithm};
+use crate::core::executor::unchanged::PROBE_DIR_NAME;
+use crate::core::snapshot::is_snapshot_name;
+
+/// Cache file name at the root of the output directory
+pub const CACHE_FILE_NAME: &str = ".dotsnapshot-static-cache.json";
+
+/// Source file identity recorded with a cached checksum
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceStamp {
+    pub size: u64,
+    /// Modification time in nanoseconds since the Unix epoch
+    pub mtime_ns: u64,
+    pub inode: u64,
+}
+
+impl SourceStamp {
+    /// Stamp of a source file, if its metadata can be read
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        let mtime_ns = metadata
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_nanos()
+            .try_into()
+            .ok()?;
+        #[cfg(unix)]
+        let inode = std::os::unix::fs::MetadataExt::ino(&metadata);
+        #[cfg(not(unix))]
+        let inode = 0;
+
+        Some(Self {
+            size: metadata.len(),
+            mtime_ns,
+            inode,
+        })
+    }
+}
+
+/// Cached checksum of a static source file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    #[serde(flatten)]
+    pub stamp: SourceStamp,
+    pub hash_algorithm: HashAlgorithm,
+    pub checksum: String,
+    /// The snapshot copy written from the source, as it was after capturing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copy: Option<CopyStamp>,
+}
+
+/// Path and identity of a source's copy in the snapshot it was captured into
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CopyStamp {
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub stamp: SourceStamp,
+}
+
+/// Checksum cache used while capturing static files into a snapshot
+#[derive(Debug, Default)]
+pub struct StaticFileCache {
+    /// Cache file location, `None` when the static directory is not inside a
+    /// snapshot and nothing should be persisted
+    path: Option<PathBuf>,
+    hashes: FileHashes,
+    previous: HashMap<PathBuf, CacheEntry>,
+    /// Entries for files captured in this run; saving drops everything else
+    current: HashMap<PathBuf, CacheEntry>,
+    pub hits: usize,
+    pub hashed: usize,
+}
+
+impl StaticFileCache {
+    /// Load the cache of the output directory containing `static_dir`
+    ///
+    /// A missing or unreadable cache starts empty. Checksums are computed and
+    /// recorded with the run's `hashes`.
+    pub fn load(static_dir: &Path, hashes: FileHashes) -> Self {
+        let path = Self::output_dir(static_dir).map(|dir| dir.join(CACHE_FILE_NAME));
+        let previous = path
+            .as_deref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|json| match serde_json::from_str(&json) {
+                Ok(entries) => Some(entries),
+                Err(e) => {
+                    warn!("Ignoring corrupt static file cache: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        Self {
+            path,
+            hashes,
+            previous,
+            ..Self::default()
+        }
+    }
+
+    /// Output directory for a static directory inside a snapshot (or the
+    /// scratch directory used to detect changes)
+    pub fn output_dir(static_dir: &Path) -> Option<&Path> {
+        let snapshot_dir = static_dir.parent()?;
+        let name = snapshot_dir.file_name()?.to_str()?;
+        if is_snapshot_name(name) || name == PROBE_DIR_NAME {
+            snapshot_dir.parent()
+        } else {
+            None
+        }
+    }
+
+    /// Whether `previous` is the copy the previous run made of `source`,
+    /// untouched since, and `source` still has the identity it had then
+    ///
+    /// The copy's path ties the entry to the snapshot it was captured into,
+    /// and its identity catches copies edited or replaced afterwards.
+    pub fn is_unchanged_copy(
+        &self,
+        source: &Path,
+        stamp: Option<&SourceStamp>,
+        previous: &Path,
+    ) -> bool {
+        let Some(entry) = stamp.and_then(|stamp| {
+            self.previous
+                .get(source)
+                .filter(|entry| entry.stamp == *stamp)
+        }) else {
+            return false;
+        };
+        entry.copy.as_ref().is_some_and(|copy| {
+            copy.path == previous && SourceStamp::of(previous).as_ref() == Some(&copy.stamp)
+        })
+    }
+
+    /// Record the checksum of `copy`, freshly copied from `source`
+    ///
+    /// `stamp` is the source identity taken before copying. A matching cache
+    /// entry provides the checksum without reading the copy.
+    pub fn capture(
+        &mut self,
+        source: &Path,
+        stamp: Option<SourceStamp>,
+        copy: &Path,
+    ) -> Result<()> {
+        // Only cache files that did not change while being copied
+        let stamp = stamp.filter(|stamp| SourceStamp::of(source).as_ref() == Some(stamp));
+
+        let cached = stamp.as_ref().and_then(|stamp| {
+            self.previous.get(source).filter(|entry| {
+                entry.stamp == *stamp && entry.hash_algorithm == self.hashes.algorithm()
+            })
+        });
+        let checksum = match cached {
+            Some(entry) => {
+                self.hashes.record(copy, &entry.checksum)?;
+                self.hits += 1;
+                entry.checksum.clone()
+            }
+            None => {
+                self.hashed += 1;
+                self.hashes.file_checksum(copy)?
+            }
+        };
+
+        if let Some(stamp) = stamp {
+            let copy = SourceStamp::of(copy).map(|stamp| CopyStamp {
+                path: copy.to_path_buf(),
+                stamp,
+            });
+            self.current.insert(
+                source.to_path_buf(),
+                CacheEntry {
+                    stamp,
+                    hash_algorithm: self.hashes.algorithm(),
+                    checksum,
+                    copy,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Persist entries for the files captured in this run
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let temp_path = path.with_extension("json.tmp");
+        std::fs::write(&temp_path, serde_json::to_string(&self.current)?)
+            .context("Failed to write static file cache")?;
+        std::fs::rename(&temp_path, path).context("Failed to replace static file cache")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Test that unchanged sources reuse their cached checksum
+    /// Verifies hits, misses after modification and persistence per output directory
+    #[test]
+    fn test_static_file_cache_hits() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let source = temp_dir.path().join("source.txt");
+        std::fs::write(&source, "original")?;
+
+        let capture = |snapshot: &str| -> Result<StaticFileCache> {
+            let static_dir = temp_dir.path().join("out").join(snapshot).join("static");
+            std::fs::create_dir_all(&static_dir)?;
+            let copy = static_dir.join("source.txt");
+            let stamp = SourceStamp::of(&source);
+            std::fs::copy(&source, &copy)?;
+
+            let mut cache =
+                StaticFileCache::load(&static_dir, FileHashes::new(HashAlgorithm::Sha256));
+            cache.capture(&source, stamp, &copy)?;
+            cache.save()?;
+            Ok(cache)
+        };
+
+        let first = capture("20240101_000000")?;
+        assert_eq!((first.hits, first.hashed), (0, 1));
+        assert!(temp_dir.path().join("out").join(CACHE_FILE_NAME).exists());
+
+        let second = capture("20240102_000000")?;
+        assert_eq!((second.hits, second.hashed), (1, 0));
+
+        std::fs::write(&source, "modified content")?;
+        let third = capture("20240103_000000")?;
+        assert_eq!((third.hits, third.hashed), (0, 1));
+
+        Ok(())
+    }
+
+    /// Test that static directories outside a snapshot are not cached
+    #[test]
+    fn test_static_file_cache_outside_snapshot() {
+        assert!(StaticFileCache::output_dir(Path::new("/tmp/work/static")).is_none());
+        assert_eq!(
+            StaticFileCache::output_dir(Path::new("/out/20240101_000000/static")),
+            Some(Path::new("/out"))
+        );
+    }
+}
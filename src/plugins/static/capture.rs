@@ -0,0 +1,266 @@
+//! Copying static files into a snapshot
+//!
+//! Tracks the checksum cache for a run and, when hardlinking is enabled, links
+//! files that are unchanged since the previous snapshot to the copy at the
+//! same relative path there instead of copying them (like rsync's
+//! `--link-dest`).
+
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use crate::core::checksum::{FileHashes, HASH_BUFFER_SIZE};
+use crate::core::snapshot::SnapshotManager;
+use crate::plugins::r#static::cache::{SourceStamp, StaticFileCache};
+
+/// Static directory being written and the one of the previous snapshot
+#[derive(Debug)]
+struct LinkDest {
+    static_dir: PathBuf,
+    previous_static_dir: PathBuf,
+}
+
+/// State shared by every file copied into the static directory during a run
+#[derive(Debug, Default)]
+pub struct StaticCapture {
+    pub cache: StaticFileCache,
+    link_dest: Option<LinkDest>,
+    /// Files hardlinked to the previous snapshot instead of copied
+    pub linked: usize,
+}
+
+impl StaticCapture {
+    /// Prepare capturing into `static_dir`
+    ///
+    /// Hardlinking only applies when `static_dir` is inside a snapshot and an
+    /// earlier snapshot with static files exists.
+    pub fn new(static_dir: &Path, hashes: FileHashes, hardlink: bool) -> Self {
+        let link_dest = if hardlink {
+            Self::previous_static_dir(static_dir).map(|previous_static_dir| LinkDest {
+                static_dir: static_dir.to_path_buf(),
+                previous_static_dir,
+            })
+        } else {
+            None
+        };
+
+        Self {
+            cache: StaticFileCache::load(static_dir, hashes),
+            link_dest,
+            linked: 0,
+        }
+    }
+
+    /// Static directory of the newest snapshot other than the one being written
+    fn previous_static_dir(static_dir: &Path) -> Option<PathBuf> {
+        let output_dir = StaticFileCache::output_dir(static_dir)?;
+        let current = static_dir.parent()?.file_name()?;
+        SnapshotManager::new(output_dir.to_path_buf())
+            .list_snapshots()
+            .ok()?
+            .into_iter()
+            .rfind(|snapshot| snapshot.file_name() != Some(current))
+            .map(|snapshot| snapshot.join(static_dir.file_name().unwrap_or_default()))
+            .filter(|previous| previous.is_dir())
+    }
+
+    /// Copy (or hardlink) `source` to `dest` and record its checksum
+    pub async fn copy_file(&mut self, source: &Path, dest: &Path) -> Result<()> {
+        let stamp = SourceStamp::of(source);
+        if self.link_dest.is_some() {
+            // Never write through an existing link into an earlier snapshot
+            let _ = std::fs::remove_file(dest);
+        }
+        if !self.link_unchanged(source, stamp.as_ref(), dest) {
+            tokio::fs::copy(source, dest)
+                .await
+                .context(format!("Failed to copy file: {}", source.display()))?;
+        }
+        self.cache.capture(source, stamp, dest)
+    }
+
+    /// Hardlink `dest` to the previous snapshot's copy when it is unchanged
+    ///
+    /// When the checksum cache shows that neither the source nor the previous
+    /// copy changed since the previous run captured it, the copy is linked
+    /// without reading it. Otherwise the two are compared byte for byte.
+    /// Returns false when the file has to be copied, including when linking
+    /// fails because the snapshots are on different filesystems.
+    fn link_unchanged(&mut self, source: &Path, stamp: Option<&SourceStamp>, dest: &Path) -> bool {
+        let Some(link_dest) = &self.link_dest else {
+            return false;
+        };
+        let Ok(relative) = dest.strip_prefix(&link_dest.static_dir) else {
+            return false;
+        };
+        let previous = link_dest.previous_static_dir.join(relative);
+        let unchanged = if self.cache.is_unchanged_copy(source, stamp, &previous) {
+            matches!(same_file_metadata(source, &previous), Ok(true))
+        } else {
+            matches!(same_file_contents(source, &previous), Ok(true))
+        };
+        if !unchanged {
+            return false;
+        }
+
+        match std::fs::hard_link(&previous, dest) {
+            Ok(()) => {
+                self.linked += 1;
+                true
+            }
+            Err(e) => {
+                debug!("Copying {} instead of hardlinking: {}", source.display(), e);
+                false
+            }
+        }
+    }
+
+    /// Persist the checksum cache
+    pub fn save(&self) -> Result<()> {
+        self.cache.save()
+    }
+}
+
+/// Whether `b` is a file with the size and permissions of `a`
+fn same_file_metadata(a: &Path, b: &Path) -> std::io::Result<bool> {
+    let (metadata_a, metadata_b) = (std::fs::metadata(a)?, std::fs::metadata(b)?);
+    Ok(metadata_b.is_file()
+        && metadata_a.len() == metadata_b.len()
+        && metadata_a.permissions() == metadata_b.permissions())
+}
+
+/// Whether two files have the same permissions and byte-identical contents
+fn same_file_contents(a: &Path, b: &Path) -> std::io::Result<bool> {
+    if !same_file_metadata(a, b)? {
+        return Ok(false);
+    }
+    let (file_a, file_b) = (File::open(a)?, File::open(b)?);
+
+    let mut reader_a = BufReader::with_capacity(HASH_BUFFER_SIZE, file_a);
+    let mut reader_b = BufReader::with_capacity(HASH_BUFFER_SIZE, file_b);
+    loop {
+        let chunk_a = reader_a.fill_buf()?;
+        let chunk_b = reader_b.fill_buf()?;
+        if chunk_a.is_empty() || chunk_b.is_empty() {
+            return Ok(chunk_a.is_empty() && chunk_b.is_empty());
+        }
+
+        let len = chunk_a.len().min(chunk_b.len());
+        if chunk_a[..len] != chunk_b[..len] {
+            return Ok(false);
+        }
+        reader_a.consume(len);
+        reader_b.consume(len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Test hardlinking unchanged files to the previous snapshot
+    /// Verifies identical files share an inode and modified files are copied
+    #[tokio::test]
+    async fn test_static_capture_hardlinks_unchanged_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let source = temp_dir.path().join("source.txt");
+        std::fs::write(&source, "original")?;
+
+        let capture = |snapshot: &'static str| {
+            let static_dir = temp_dir.path().join("out").join(snapshot).join("static");
+            let source = source.clone();
+            async move {
+                std::fs::create_dir_all(&static_dir)?;
+                let dest = static_dir.join("source.txt");
+                let mut capture = StaticCapture::new(&static_dir, FileHashes::default(), true);
+                capture.copy_file(&source, &dest).await?;
+                Ok::<_, anyhow::Error>((capture.linked, dest))
+            }
+        };
+
+        let (linked, first) = capture("20240101_000000").await?;
+        assert_eq!(linked, 0);
+
+        let (linked, second) = capture("20240102_000000").await?;
+        assert_eq!(linked, 1);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            assert_eq!(
+                std::fs::metadata(&first)?.ino(),
+                std::fs::metadata(&second)?.ino()
+            );
+        }
+
+        std::fs::write(&source, "modified")?;
+        let (linked, third) = capture("20240103_000000").await?;
+        assert_eq!(linked, 0);
+        assert_eq!(std::fs::read_to_string(&third)?, "modified");
+        assert_eq!(std::fs::read_to_string(&first)?, "original");
+
+        Ok(())
+    }
+
+    /// Test deciding from the checksum cache which files are unchanged
+    /// Verifies a previous copy edited in place is copied again, not linked
+    #[tokio::test]
+    async fn test_static_capture_links_cache_hits() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let source = temp_dir.path().join("source.txt");
+        std::fs::write(&source, "original")?;
+
+        let capture = |snapshot: &'static str| {
+            let static_dir = temp_dir.path().join("out").join(snapshot).join("static");
+            let source = source.clone();
+            async move {
+                std::fs::create_dir_all(&static_dir)?;
+                let dest = static_dir.join("source.txt");
+                let mut capture = StaticCapture::new(&static_dir, FileHashes::default(), true);
+                capture.copy_file(&source, &dest).await?;
+                capture.save()?;
+                Ok::<_, anyhow::Error>((capture.linked, capture.cache.hits, dest))
+            }
+        };
+
+        let (linked, hits, first) = capture("20240101_000000").await?;
+        assert_eq!((linked, hits), (0, 0));
+
+        // Same size, different bytes in the previous copy: it must not be linked
+        std::fs::write(&first, "ORIGINAL")?;
+        let (linked, hits, second) = capture("20240102_000000").await?;
+        assert_eq!((linked, hits), (0, 1));
+        assert_eq!(std::fs::read_to_string(&second)?, "original");
+
+        // An untouched copy recorded by the cache is linked
+        let (linked, hits, third) = capture("20240103_000000").await?;
+        assert_eq!((linked, hits), (1, 1));
+        assert_eq!(std::fs::read_to_string(&third)?, "original");
+
+        Ok(())
+    }
+
+    /// Test comparing file contents
+    #[test]
+    fn test_same_file_contents() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let a = temp_dir.path().join("a");
+        let b = temp_dir.path().join("b");
+        let content: Vec<u8> = (0..HASH_BUFFER_SIZE * 2 + 5)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        std::fs::write(&a, &content)?;
+        std::fs::write(&b, &content)?;
+        assert!(same_file_contents(&a, &b)?);
+
+        let mut changed = content.clone();
+        changed[HASH_BUFFER_SIZE + 1] ^= 1;
+        std::fs::write(&b, &changed)?;
+        assert!(!same_file_contents(&a, &b)?);
+        assert!(same_file_contents(&a, &temp_dir.path().join("missing")).is_err());
+
+        Ok(())
+    }
+}
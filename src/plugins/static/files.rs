@@ -3,10 +3,14 @@ use glob::Pattern;
 use serde_json;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::config::{Config, StaticPluginConfig};
+use crate::core::checksum::FileHashes;
+use crate::core::paths::expand_path;
+use crate::core::plugin::FilePlan;
 use crate::plugins::core::base::static_files::{StaticFilesCore, StaticFilesPlugin};
+use crate::plugins::r#static::capture::StaticCapture;
 use crate::symbols::*;
 
 /// Static files implementation using the mixin architecture
@@ -37,7 +41,7 @@ impl StaticFilesCore for StaticFilesAppCore {
             let static_config = match &config.plugins {
                 Some(plugins) => match plugins.plugins.get("static") {
                     Some(static_value) => {
-                        match static_value.clone().try_into::<StaticPluginConfig>() {
+                        match serde_json::from_value::<StaticPluginConfig>(static_value.clone()) {
                             Ok(static_config) => static_config,
                             Err(_) => {
                                 // No static files plugin configuration section
@@ -72,8 +76,30 @@ impl StaticFilesCore for StaticFilesAppCore {
                     continue;
                 }
 
-                // Expand path variables
-                let expanded_path = self.expand_path(file_path_str.trim())?;
+                // Expand path variables; an unset variable only drops this entry
+                let expanded_path = match self.expand_path(file_path_str.trim()) {
+                    Ok(path) => path,
+                    Err(e) => {
+                        warn!("{} Skipping static file: {:#}", SYMBOL_INDICATOR_WARNING, e);
+                        continue;
+                    }
+                };
+
+                // Expand glob patterns; patterns without matches are kept so they
+                // are reported as missing
+                let pattern = expanded_path.to_string_lossy();
+                if pattern.contains(['*', '?', '[']) {
+                    let mut matches: Vec<PathBuf> = glob::glob(&pattern)
+                        .with_context(|| format!("Invalid glob pattern: {pattern}"))?
+                        .filter_map(|entry| entry.ok())
+                        .collect();
+                    if !matches.is_empty() {
+                        matches.sort();
+                        file_paths.extend(matches);
+                        continue;
+                    }
+                }
+
                 file_paths.push(expanded_path);
             }
 
@@ -85,7 +111,8 @@ impl StaticFilesCore for StaticFilesAppCore {
         if let Some(config) = config {
             if let Some(plugins) = &config.plugins {
                 if let Some(static_value) = plugins.plugins.get("static") {
-                    if let Ok(static_config) = static_value.clone().try_into::<StaticPluginConfig>()
+                    if let Ok(static_config) =
+                        serde_json::from_value::<StaticPluginConfig>(static_value.clone())
                     {
                         if let Some(ignore_patterns) = &static_config.ignore {
                             return ignore_patterns.clone();
@@ -97,6 +124,17 @@ impl StaticFilesCore for StaticFilesAppCore {
         Vec::new()
     }
 
+    fn get_hardlink(&self, config: Option<&Arc<Config>>) -> bool {
+        config
+            .and_then(|config| config.plugins.as_ref())
+            .and_then(|plugins| plugins.plugins.get("static"))
+            .and_then(|static_value| {
+                serde_json::from_value::<StaticPluginConfig>(static_value.clone()).ok()
+            })
+            .and_then(|static_config| static_config.hardlink)
+            .unwrap_or(false)
+    }
+
     fn should_ignore(&self, path: &Path, ignore_patterns: &[String]) -> bool {
         let path_str = path.to_string_lossy();
 
@@ -127,21 +165,7 @@ impl StaticFilesCore for StaticFilesAppCore {
     }
 
     fn expand_path(&self, path: &str) -> Result<PathBuf> {
-        let expanded = if path.starts_with('~') {
-            let home = dirs::home_dir().context("Could not determine home directory")?;
-            home.join(&path[2..]) // Skip "~/"
-        } else if path.contains('$') {
-            // Simple environment variable expansion
-            let mut expanded = path.to_string();
-            if let Ok(home) = std::env::var("HOME") {
-                expanded = expanded.replace("$HOME", &home);
-            }
-            PathBuf::from(expanded)
-        } else {
-            PathBuf::from(path)
-        };
-
-        Ok(expanded)
+        expand_path(path)
     }
 
     fn copy_files<'a>(
@@ -149,6 +173,8 @@ impl StaticFilesCore for StaticFilesAppCore {
         file_paths: Vec<PathBuf>,
         static_dir: &'a Path,
         ignore_patterns: &'a [String],
+        hardlink: bool,
+        hashes: &'a FileHashes,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<String>> + Send + 'a>> {
         Box::pin(async move {
             let mut copied_files = Vec::new();
@@ -160,6 +186,8 @@ impl StaticFilesCore for StaticFilesAppCore {
                 .await
                 .context("Failed to create static directory")?;
 
+            let mut capture = StaticCapture::new(static_dir, hashes.clone(), hardlink);
+
             for file_path in file_paths {
                 // Check if this path should be ignored
                 if self.should_ignore(&file_path, ignore_patterns) {
@@ -173,7 +201,7 @@ impl StaticFilesCore for StaticFilesAppCore {
                 }
 
                 match self
-                    .copy_single_file(&file_path, static_dir, ignore_patterns)
+                    .copy_single_file(&file_path, static_dir, ignore_patterns, &mut capture)
                     .await
                 {
                     Ok(dest_path) => {
@@ -202,6 +230,23 @@ impl StaticFilesCore for StaticFilesAppCore {
                 }
             }
 
+            if let Err(e) = capture.save() {
+                info!(
+                    "{} Failed to save static file cache: {}",
+                    SYMBOL_INDICATOR_WARNING, e
+                );
+            }
+            info!(
+                "{} Static file cache: {} hits, {} files hashed",
+                SYMBOL_ACTION_SEARCH, capture.cache.hits, capture.cache.hashed
+            );
+            if hardlink {
+                info!(
+                    "{} Hardlinked {} unchanged static files to the previous snapshot",
+                    SYMBOL_ACTION_SEARCH, capture.linked
+                );
+            }
+
             // Create summary
             let summary = serde_json::json!({
                 "summary": {
@@ -212,7 +257,17 @@ impl StaticFilesCore for StaticFilesAppCore {
                     "copied_files": copied_files,
                     "failed_files": failed_files,
                     "ignored_files": ignored_files,
-                    "static_directory": static_dir.display().to_string()
+                    // Relative to the snapshot so unchanged files produce the same summary
+                    "static_directory": static_dir
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    // Run statistics, excluded from the plugin's output checksum
+                    "cache": {
+                        "hits": capture.cache.hits,
+                        "hashed": capture.cache.hashed
+                    },
+                    "hardlinked": capture.linked
                 }
             });
 
@@ -220,6 +275,32 @@ impl StaticFilesCore for StaticFilesAppCore {
         })
     }
 
+    fn plan_files(
+        &self,
+        file_paths: Vec<PathBuf>,
+        static_dir: &Path,
+        ignore_patterns: &[String],
+    ) -> FilePlan {
+        let mut plan = FilePlan::default();
+
+        for file_path in file_paths {
+            if self.should_ignore(&file_path, ignore_patterns) {
+                plan.ignored.push(file_path);
+            } else if !file_path.exists() {
+                plan.missing.push(file_path);
+            } else {
+                let dest_path = self.destination_path(&file_path, static_dir);
+                if file_path.is_dir() {
+                    self.plan_directory(&file_path, &dest_path, ignore_patterns, &mut plan);
+                } else {
+                    plan.copies.push((file_path, dest_path));
+                }
+            }
+        }
+
+        plan
+    }
+
     fn restore_static_files<'a>(
         &'a self,
         static_snapshot_dir: &'a Path,
@@ -227,7 +308,6 @@ impl StaticFilesCore for StaticFilesAppCore {
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Vec<PathBuf>>> + Send + 'a>>
     {
         Box::pin(async move {
-            use tracing::warn;
             let mut restored_files = Vec::new();
 
             // Read the static directory structure and restore files
@@ -305,19 +385,10 @@ impl StaticFilesCore for StaticFilesAppCore {
 }
 
 impl StaticFilesAppCore {
-    /// Copies a single file or directory to the static directory, preserving directory structure
-    async fn copy_single_file(
-        &self,
-        file_path: &Path,
-        static_dir: &Path,
-        ignore_patterns: &[String],
-    ) -> Result<PathBuf> {
-        if !file_path.exists() {
-            return Err(anyhow::anyhow!("Path does not exist"));
-        }
-
-        // Create a destination path that preserves directory structure but skips $HOME
-        let dest_path = if file_path.is_absolute() {
+    /// Maps a source path to its location inside the static directory
+    fn destination_path(&self, file_path: &Path, static_dir: &Path) -> PathBuf {
+        // Preserve directory structure but skip $HOME
+        if file_path.is_absolute() {
             // Check if path is in user's home directory
             if let Some(home_dir) = dirs::home_dir() {
                 if let Ok(relative_to_home) = file_path.strip_prefix(&home_dir) {
@@ -363,11 +434,61 @@ impl StaticFilesAppCore {
         } else {
             // For relative paths, just join with static dir
             static_dir.join(file_path)
+        }
+    }
+
+    /// Recursively collects the files below a directory that would be copied
+    fn plan_directory(
+        &self,
+        src_dir: &Path,
+        dest_dir: &Path,
+        ignore_patterns: &[String],
+        plan: &mut FilePlan,
+    ) {
+        let Ok(entries) = std::fs::read_dir(src_dir) else {
+            plan.missing.push(src_dir.to_path_buf());
+            return;
         };
 
+        let mut paths: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+        paths.sort();
+
+        for src_path in paths {
+            if self.should_ignore(&src_path, ignore_patterns) {
+                plan.ignored.push(src_path);
+                continue;
+            }
+
+            let Some(file_name) = src_path.file_name() else {
+                continue;
+            };
+            let dest_path = dest_dir.join(file_name);
+
+            if src_path.is_dir() {
+                self.plan_directory(&src_path, &dest_path, ignore_patterns, plan);
+            } else {
+                plan.copies.push((src_path, dest_path));
+            }
+        }
+    }
+
+    /// Copies a single file or directory to the static directory, preserving directory structure
+    async fn copy_single_file(
+        &self,
+        file_path: &Path,
+        static_dir: &Path,
+        ignore_patterns: &[String],
+        capture: &mut StaticCapture,
+    ) -> Result<PathBuf> {
+        if !file_path.exists() {
+            return Err(anyhow::anyhow!("Path does not exist"));
+        }
+
+        let dest_path = self.destination_path(file_path, static_dir);
+
         if file_path.is_dir() {
             // Copy entire directory recursively
-            self.copy_directory_recursive(file_path, &dest_path, ignore_patterns)
+            self.copy_directory_recursive(file_path, &dest_path, ignore_patterns, capture)
                 .await?;
         } else {
             // Create parent directories if they don't exist
@@ -378,9 +499,7 @@ impl StaticFilesAppCore {
             }
 
             // Copy the file
-            tokio::fs::copy(file_path, &dest_path)
-                .await
-                .context("Failed to copy file")?;
+            capture.copy_file(file_path, &dest_path).await?;
         }
 
         Ok(dest_path)
@@ -392,6 +511,7 @@ impl StaticFilesAppCore {
         src_dir: &'a Path,
         dest_dir: &'a Path,
         ignore_patterns: &'a [String],
+        capture: &'a mut StaticCapture,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + 'a>> {
         Box::pin(async move {
             // Create the destination directory
@@ -427,13 +547,11 @@ impl StaticFilesAppCore {
 
                 if src_path.is_dir() {
                     // Recursively copy subdirectory
-                    self.copy_directory_recursive(&src_path, &dest_path, ignore_patterns)
+                    self.copy_directory_recursive(&src_path, &dest_path, ignore_patterns, capture)
                         .await?;
                 } else {
                     // Copy file
-                    tokio::fs::copy(&src_path, &dest_path)
-                        .await
-                        .context(format!("Failed to copy file: {}", src_path.display()))?;
+                    capture.copy_file(&src_path, &dest_path).await?;
                 }
             }
 
@@ -493,6 +611,7 @@ impl StaticFilesAppCore {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::format::to_plugin_value;
     use crate::config::{Config, PluginsConfig, StaticPluginConfig};
     use crate::core::plugin::Plugin;
     use std::sync::Arc;
@@ -547,11 +666,13 @@ mod tests {
                     let mut map = std::collections::HashMap::new();
                     map.insert(
                         "static".to_string(),
-                        toml::Value::try_from(StaticPluginConfig {
+                        to_plugin_value(&StaticPluginConfig {
                             target_path: None,
                             output_file: None,
                             files: Some(vec!["/etc/hosts".to_string()]),
                             ignore: None,
+                            hardlink: None,
+                            hooks: None,
                         })
                         .unwrap(),
                     );
@@ -560,6 +681,7 @@ mod tests {
             }),
             ui: None,
             validation: None,
+            ..Config::default()
         };
 
         let plugin = StaticFilesPlugin::with_config(StaticFilesAppCore, Arc::new(config));
@@ -575,6 +697,64 @@ mod tests {
         std::env::remove_var("DOTSNAPSHOT_SNAPSHOT_DIR");
     }
 
+    #[tokio::test]
+    async fn test_static_files_plan_with_globs_and_ignores() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        fs::create_dir_all(source.join("nested")).await.unwrap();
+        fs::write(source.join("a.toml"), "a").await.unwrap();
+        fs::write(source.join("b.toml"), "b").await.unwrap();
+        fs::write(source.join("c.txt"), "c").await.unwrap();
+        fs::write(source.join("nested").join("keep.txt"), "k")
+            .await
+            .unwrap();
+        fs::write(source.join("nested").join("secret.key"), "s")
+            .await
+            .unwrap();
+
+        let static_value = to_plugin_value(&StaticPluginConfig {
+            target_path: None,
+            output_file: None,
+            files: Some(vec![
+                format!("{}/*.toml", source.display()),
+                source.join("nested").display().to_string(),
+                format!("{}/missing*", source.display()),
+            ]),
+            ignore: Some(vec!["*.key".to_string()]),
+            hardlink: None,
+            hooks: None,
+        })
+        .unwrap();
+        let config = Config {
+            plugins: Some(PluginsConfig {
+                plugins: std::collections::HashMap::from([("static".to_string(), static_value)]),
+            }),
+            ..Config::default()
+        };
+
+        let plugin = StaticFilesPlugin::with_config(StaticFilesAppCore, Arc::new(config));
+        let plan = plugin.plan_files().await.unwrap().unwrap();
+
+        let sources: Vec<PathBuf> = plan.copies.iter().map(|(src, _)| src.clone()).collect();
+        assert_eq!(
+            sources,
+            vec![
+                source.join("a.toml"),
+                source.join("b.toml"),
+                source.join("nested").join("keep.txt"),
+            ]
+        );
+        assert!(plan
+            .copies
+            .iter()
+            .all(|(_, dest)| dest.starts_with("static")));
+        assert_eq!(plan.ignored, vec![source.join("nested").join("secret.key")]);
+        assert_eq!(
+            plan.missing,
+            vec![PathBuf::from(format!("{}/missing*", source.display()))]
+        );
+    }
+
     #[tokio::test]
     async fn test_ignore_patterns() {
         let core = StaticFilesAppCore;
@@ -746,11 +926,13 @@ mod tests {
                     let mut map = std::collections::HashMap::new();
                     map.insert(
                         "static".to_string(),
-                        toml::Value::try_from(StaticPluginConfig {
+                        to_plugin_value(&StaticPluginConfig {
                             target_path: None,
                             output_file: None,
                             files: None,
                             ignore: Some(vec!["*.tmp".to_string(), "cache/".to_string()]),
+                            hardlink: None,
+                            hooks: None,
                         })
                         .unwrap(),
                     );
@@ -759,6 +941,7 @@ mod tests {
             }),
             ui: None,
             validation: None,
+            ..Config::default()
         };
 
         let patterns = core.get_ignore_patterns(Some(&Arc::new(config)));
@@ -777,7 +960,14 @@ mod tests {
         fs::create_dir_all(&static_dir).await.unwrap();
 
         let non_existent = temp_dir.path().join("does_not_exist.txt");
-        let result = core.copy_single_file(&non_existent, &static_dir, &[]).await;
+        let result = core
+            .copy_single_file(
+                &non_existent,
+                &static_dir,
+                &[],
+                &mut StaticCapture::default(),
+            )
+            .await;
 
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("does not exist"));
@@ -800,7 +990,14 @@ mod tests {
         let current_dir = std::env::current_dir().unwrap();
         let relative_path = test_file.strip_prefix(&current_dir).unwrap_or(&test_file);
 
-        let result = core.copy_single_file(relative_path, &static_dir, &[]).await;
+        let result = core
+            .copy_single_file(
+                relative_path,
+                &static_dir,
+                &[],
+                &mut StaticCapture::default(),
+            )
+            .await;
         assert!(result.is_ok());
 
         let dest_path = result.unwrap();
@@ -830,7 +1027,9 @@ mod tests {
             .await
             .unwrap();
 
-        let result = core.copy_single_file(&test_dir, &static_dir, &[]).await;
+        let result = core
+            .copy_single_file(&test_dir, &static_dir, &[], &mut StaticCapture::default())
+            .await;
         assert!(result.is_ok());
 
         let dest_path = result.unwrap();
@@ -866,9 +1065,14 @@ mod tests {
 
         let ignore_patterns = vec!["*.tmp".to_string()];
 
-        core.copy_directory_recursive(&src_dir, &dest_dir, &ignore_patterns)
-            .await
-            .unwrap();
+        core.copy_directory_recursive(
+            &src_dir,
+            &dest_dir,
+            &ignore_patterns,
+            &mut StaticCapture::default(),
+        )
+        .await
+        .unwrap();
 
         // Verify only non-ignored files were copied
         assert!(dest_dir.join("keep.txt").exists());
@@ -934,6 +1138,7 @@ mod tests {
             plugins: None,
             ui: None,
             validation: None,
+            ..Config::default()
         };
         let files = core.read_config(Some(&Arc::new(config))).await.unwrap();
         assert!(files.is_empty());
@@ -951,11 +1156,13 @@ mod tests {
                     let mut map = std::collections::HashMap::new();
                     map.insert(
                         "static".to_string(),
-                        toml::Value::try_from(StaticPluginConfig {
+                        to_plugin_value(&StaticPluginConfig {
                             target_path: None,
                             output_file: None,
                             files: None,
                             ignore: None,
+                            hardlink: None,
+                            hooks: None,
                         })
                         .unwrap(),
                     );
@@ -964,6 +1171,7 @@ mod tests {
             }),
             ui: None,
             validation: None,
+            ..Config::default()
         };
         let files = core.read_config(Some(&Arc::new(config))).await.unwrap();
         assert!(files.is_empty());
@@ -981,7 +1189,7 @@ mod tests {
                     let mut map = std::collections::HashMap::new();
                     map.insert(
                         "static".to_string(),
-                        toml::Value::try_from(StaticPluginConfig {
+                        to_plugin_value(&StaticPluginConfig {
                             target_path: None,
                             output_file: None,
                             files: Some(vec![
@@ -991,6 +1199,8 @@ mod tests {
                                 "/etc/hosts".to_string(),
                             ]),
                             ignore: None,
+                            hardlink: None,
+                            hooks: None,
                         })
                         .unwrap(),
                     );
@@ -999,9 +1209,38 @@ mod tests {
             }),
             ui: None,
             validation: None,
+            ..Config::default()
         };
         let files = core.read_config(Some(&Arc::new(config))).await.unwrap();
         assert_eq!(files.len(), 2); // Only non-empty paths
+
+        // Test with a path using an unset environment variable
+        let config = Config {
+            plugins: Some(PluginsConfig {
+                plugins: {
+                    let mut map = std::collections::HashMap::new();
+                    map.insert(
+                        "static".to_string(),
+                        to_plugin_value(&StaticPluginConfig {
+                            target_path: None,
+                            output_file: None,
+                            files: Some(vec![
+                                "$DOTSNAPSHOT_TEST_UNSET_STATIC_DIR/file.txt".to_string(),
+                                "/etc/hosts".to_string(),
+                            ]),
+                            ignore: None,
+                            hardlink: None,
+                            hooks: None,
+                        })
+                        .unwrap(),
+                    );
+                    map
+                },
+            }),
+            ..Config::default()
+        };
+        let files = core.read_config(Some(&Arc::new(config))).await.unwrap();
+        assert_eq!(files, vec![PathBuf::from("/etc/hosts")]); // Only that entry is skipped
     }
 
     /// Test copy_files with various scenarios
@@ -1025,7 +1264,13 @@ mod tests {
         let ignore_patterns = vec!["*.tmp".to_string()];
 
         let summary = core
-            .copy_files(file_paths, &static_dir, &ignore_patterns)
+            .copy_files(
+                file_paths,
+                &static_dir,
+                &ignore_patterns,
+                false,
+                &FileHashes::default(),
+            )
             .await
             .unwrap();
 
@@ -1153,7 +1398,9 @@ mod tests {
         let test_file = temp_dir.path().join("absolute_test.txt");
         fs::write(&test_file, "absolute content").await.unwrap();
 
-        let result = core.copy_single_file(&test_file, &static_dir, &[]).await;
+        let result = core
+            .copy_single_file(&test_file, &static_dir, &[], &mut StaticCapture::default())
+            .await;
         assert!(result.is_ok());
 
         let dest_path = result.unwrap();
@@ -1175,7 +1422,7 @@ mod tests {
 
         // Try to copy non-existent directory
         let result = core
-            .copy_directory_recursive(&src_dir, &dest_dir, &[])
+            .copy_directory_recursive(&src_dir, &dest_dir, &[], &mut StaticCapture::default())
             .await;
         assert!(result.is_err());
         assert!(result
@@ -1216,7 +1463,7 @@ mod tests {
 //
 // Unlike the original static files plugin that used manual inventory submission,
 // this new implementation can use a simplified registration since it follows
-// the mixin architecture pattern, even though it uses Arc<Config> instead of toml::Value.
+// the mixin architecture pattern, even though it uses Arc<Config> instead of a raw plugin config value.
 //
 // The factory function ignores the _config parameter and creates the plugin using
 // the special StaticFilesPlugin pattern that gets its configuration during execution.
@@ -1224,10 +1471,27 @@ inventory::submit! {
     crate::core::plugin::PluginDescriptor {
         name: "static_files",
         category: "static",
-        factory: |_config| {
-            // NOTE: _config parameter is ignored because static files plugin
-            // gets its configuration through Arc<Config> during execution
-            std::sync::Arc::new(StaticFilesPlugin::new(StaticFilesAppCore))
+        factory: |config| {
+            // Static files plugin reads its `[plugins.static]` section through Arc<Config>
+            match config {
+                Some(static_value) => {
+                    let config = Config {
+                        plugins: Some(crate::config::PluginsConfig {
+                            plugins: std::collections::HashMap::from([(
+                                "static".to_string(),
+                                static_value,
+                            )]),
+                        }),
+                        ..Config::default()
+                    };
+                    std::sync::Arc::new(StaticFilesPlugin::with_config(
+                        StaticFilesAppCore,
+                        std::sync::Arc::new(config),
+                    ))
+                }
+                None => std::sync::Arc::new(StaticFilesPlugin::new(StaticFilesAppCore)),
+            }
         },
+        config_schema: |generator| generator.subschema_for::<crate::config::StaticPluginConfig>(),
     }
 }
@@ -1,3 +1,5 @@
+pub mod cache;
+pub mod capture;
 pub mod files;
 
 // Auto-registration means we don't need to export plugin types anymore
@@ -104,7 +104,7 @@ mod tests {
             target_path = "vscode"
             output_file = "extensions.txt"
         "#;
-        let config: toml::Value = toml::from_str(config_toml).unwrap();
+        let config: serde_json::Value = toml::from_str(config_toml).unwrap();
         let plugin = ExtensionsPlugin::with_config(VSCodeExtensionsCore, config);
 
         assert_eq!(
@@ -152,7 +152,7 @@ mod tests {
         let plugin = ExtensionsPlugin::new(VSCodeExtensionsCore);
 
         let default_dir = plugin.get_default_restore_target_dir().unwrap();
-        assert!(default_dir.is_absolute() || default_dir == std::path::PathBuf::from("."));
+        assert!(default_dir.is_absolute() || default_dir == std::path::Path::new("."));
 
         assert_eq!(ConfigMixin::get_restore_target_dir(&plugin), None);
     }
@@ -102,7 +102,7 @@ mod tests {
             target_path = "vscode"
             output_file = "settings.json"
         "#;
-        let config: toml::Value = toml::from_str(config_toml).unwrap();
+        let config: serde_json::Value = toml::from_str(config_toml).unwrap();
         let plugin = SettingsPlugin::with_config(VSCodeCore, config);
 
         assert_eq!(
@@ -200,3 +200,814 @@ fn test_cli_restore_without_args() {
             "Either provide a snapshot path or use --latest",
         ));
 }
+
+#[test]
+fn test_cli_snapshot_lock_contention_exit_code() {
+    let temp_dir = TempDir::new().unwrap();
+    let _lock = dotsnapshot::core::lock::SnapshotLock::try_acquire(temp_dir.path())
+        .unwrap()
+        .unwrap();
+
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .args([
+            "--config",
+            "/nonexistent/config.toml",
+            "--output",
+            temp_dir.path().to_str().unwrap(),
+            "--plugins",
+            "static",
+        ])
+        .assert()
+        .code(6)
+        .stdout(predicates::str::contains("Another snapshot run"));
+}
+
+#[test]
+fn test_cli_snapshot_fail_on_any_failure_success() {
+    let temp_dir = TempDir::new().unwrap();
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .args([
+            "--config",
+            "/nonexistent/config.toml",
+            "--output",
+            temp_dir.path().to_str().unwrap(),
+            "--plugins",
+            "static",
+            "--fail-on",
+            "any-failure",
+            "--report-format",
+            "json",
+        ])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("\"status\": \"succeeded\""));
+}
+
+#[test]
+fn test_cli_snapshot_dry_run_writes_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_dir = temp_dir.path().join("snapshots");
+
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .args([
+            "--config",
+            "/nonexistent/config.toml",
+            "--output",
+            output_dir.to_str().unwrap(),
+            "--plugins",
+            "static",
+            "--dry-run",
+        ])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("DRY RUN"));
+
+    assert!(!output_dir.exists());
+}
+
+#[test]
+fn test_cli_snapshot_skip_unchanged() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_dir = temp_dir.path().join("snapshots");
+    let dotfile = temp_dir.path().join(".testrc");
+    std::fs::write(&dotfile, "alias ll='ls -l'\n").unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        format!(
+            "[plugins.static]\nfiles = [\"{}\"]\n",
+            dotfile.display().to_string().replace('\\', "\\\\")
+        ),
+    )
+    .unwrap();
+
+    let run = || {
+        Command::cargo_bin("dotsnapshot")
+            .unwrap()
+            .args([
+                "--config",
+                config_path.to_str().unwrap(),
+                "--output",
+                output_dir.to_str().unwrap(),
+                "--plugins",
+                "static",
+                "--skip-unchanged",
+            ])
+            .assert()
+            .success()
+    };
+    let snapshot_count = || {
+        std::fs::read_dir(&output_dir)
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().path().is_dir())
+            .count()
+    };
+
+    run();
+    assert_eq!(snapshot_count(), 1);
+
+    run().stdout(predicates::str::contains("No changes since snapshot"));
+    assert_eq!(snapshot_count(), 1);
+}
+
+#[test]
+fn test_cli_index_rebuild() {
+    let temp_dir = TempDir::new().unwrap();
+    let index_path = temp_dir.path().join(".dotsnapshot-index.json");
+
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .args([
+            "--config",
+            "/nonexistent/config.toml",
+            "--output",
+            temp_dir.path().to_str().unwrap(),
+            "--plugins",
+            "static",
+        ])
+        .assert()
+        .success();
+    assert!(index_path.exists());
+
+    std::fs::remove_file(&index_path).unwrap();
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .args([
+            "--config",
+            "/nonexistent/config.toml",
+            "index",
+            "rebuild",
+            "--output",
+            temp_dir.path().to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+    assert!(index_path.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_snapshot_static_hardlink() {
+    use std::os::unix::fs::MetadataExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let output_dir = temp_dir.path().join("snapshots");
+    let dotfile = temp_dir.path().join(".testrc");
+    std::fs::write(&dotfile, "alias ll='ls -l'\n").unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        format!(
+            "[plugins.static]\nfiles = [\"{}\"]\nhardlink = true\n",
+            dotfile.display()
+        ),
+    )
+    .unwrap();
+
+    let run = || {
+        Command::cargo_bin("dotsnapshot")
+            .unwrap()
+            .args([
+                "--config",
+                config_path.to_str().unwrap(),
+                "--output",
+                output_dir.to_str().unwrap(),
+                "--plugins",
+                "static",
+            ])
+            .assert()
+            .success();
+    };
+    run();
+    // Snapshot names have one-second resolution
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    run();
+
+    let mut copies: Vec<_> = std::fs::read_dir(&output_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir())
+        .map(|snapshot| {
+            let static_dir = snapshot.join("static");
+            walk_files(&static_dir)
+        })
+        .collect();
+    assert_eq!(copies.len(), 2);
+    let second = copies.pop().unwrap();
+    let first = copies.pop().unwrap();
+    assert_eq!(first.len(), 1);
+    assert_eq!(
+        std::fs::metadata(&first[0]).unwrap().ino(),
+        std::fs::metadata(&second[0]).unwrap().ino()
+    );
+}
+
+#[cfg(unix)]
+fn walk_files(dir: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files.extend(walk_files(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    files
+}
+
+#[test]
+fn test_cli_watch_help() {
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .args(["watch", "--help"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("--poll-interval"));
+}
+
+#[test]
+fn test_cli_schedule_install_dry_run() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(&config_path, "# test config").unwrap();
+
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .args(["--config", config_path.to_str().unwrap()])
+        .args([
+            "schedule",
+            "install",
+            "--every",
+            "6h",
+            "--backend",
+            "cron",
+            "--dry-run",
+        ])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("0 */6 * * *"))
+        .stdout(predicates::str::contains("--config"));
+}
+
+#[test]
+fn test_cli_snapshot_with_profile() {
+    let temp_dir = TempDir::new().unwrap();
+    let work_output = temp_dir.path().join("work-snapshots");
+    let dotfile = temp_dir.path().join(".workrc");
+    std::fs::write(&dotfile, "export WORK=1\n").unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        format!(
+            "output_dir = \"{}\"\n\n[profiles.work]\noutput_dir = \"{}\"\ninclude_plugins = [\"static\"]\n\n[profiles.work.plugins.static]\nfiles = [\"{}\"]\n",
+            temp_dir.path().join("snapshots").display().to_string().replace('\\', "\\\\"),
+            work_output.display().to_string().replace('\\', "\\\\"),
+            dotfile.display().to_string().replace('\\', "\\\\")
+        ),
+    )
+    .unwrap();
+
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .args(["--config", config_path.to_str().unwrap()])
+        .env("DOTSNAPSHOT_PROFILE", "work")
+        .assert()
+        .success();
+
+    let snapshot_dir = std::fs::read_dir(&work_output)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.is_dir())
+        .unwrap();
+    let metadata =
+        std::fs::read_to_string(snapshot_dir.join(".snapshot").join("checksum.json")).unwrap();
+    assert!(metadata.contains("\"profile\": \"work\""));
+    assert!(!temp_dir.path().join("snapshots").exists());
+
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .args([
+            "--config",
+            config_path.to_str().unwrap(),
+            "--profile",
+            "home",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Profile 'home' not found"));
+}
+
+#[test]
+fn test_cli_list_snapshots_by_host() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_dir = temp_dir.path().join("snapshots");
+
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .args([
+            "--config",
+            "/nonexistent/config.toml",
+            "--output",
+            output_dir.to_str().unwrap(),
+            "--plugins",
+            "static",
+        ])
+        .assert()
+        .success();
+
+    let list = |host: &[&str]| {
+        let output = Command::cargo_bin("dotsnapshot")
+            .unwrap()
+            .args(["--config", "/nonexistent/config.toml", "list", "--output"])
+            .arg(&output_dir)
+            .arg("--host")
+            .args(host)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    // A bare --host means the current machine
+    assert!(list(&[]).contains("SNAPSHOT"));
+    assert!(!list(&["no-such-host"]).contains("SNAPSHOT"));
+}
+
+#[test]
+fn test_cli_config_show_effective_origin() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        "output_dir = \"/from-file\"\ninclude_plugins = [\"static\"]\n",
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .env("DOTSNAPSHOT_OUTPUT_DIR", "/from-env")
+        .arg("--config")
+        .arg(&config_path)
+        .args([
+            "--plugins",
+            "vscode",
+            "config",
+            "show",
+            "--effective",
+            "--origin",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("output_dir = \"/from-env\"  # env: DOTSNAPSHOT_OUTPUT_DIR"));
+    assert!(stdout.contains("include_plugins = [\"vscode\"]  # cli: --plugins"));
+}
+
+#[test]
+fn test_cli_config_validate() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        "output_dir = \"/snapshots\"\n\n[plugins.vscode_settings]\nrestore_targetdir = \"~/code\"\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .arg("--config")
+        .arg(&config_path)
+        .args(["config", "validate"])
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains(format!(
+            "{}:4:1: unknown key 'restore_targetdir' in [plugins.vscode_settings] \
+             (did you mean 'restore_target_dir'?)",
+            config_path.display()
+        )));
+
+    std::fs::write(&config_path, "output_dir = \"/snapshots\"\n").unwrap();
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .arg("--config")
+        .arg(&config_path)
+        .args(["config", "validate"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("is valid"));
+}
+
+#[test]
+fn test_cli_config_schema() {
+    let output = Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .args(["config", "schema"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(schema["title"], "dotsnapshot configuration");
+    let plugins = &schema["properties"]["plugins"]["properties"];
+    assert!(plugins.get("static").is_some());
+    assert!(plugins.get("vscode_settings").is_some());
+    assert!(schema["definitions"].get("HookAction").is_some());
+}
+
+#[test]
+fn test_cli_config_set_get_unset() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        "# Snapshot location\noutput_dir = \"/snapshots\" # keep me\n",
+    )
+    .unwrap();
+
+    let config = |args: &[&str]| {
+        let mut command = Command::cargo_bin("dotsnapshot").unwrap();
+        command
+            .arg("--config")
+            .arg(&config_path)
+            .arg("config")
+            .args(args);
+        command
+    };
+
+    config(&["set", "output_dir", "/backups"])
+        .assert()
+        .success();
+    config(&["set", "plugins.vscode_settings.target_path", "vscode"])
+        .assert()
+        .success();
+    let content = std::fs::read_to_string(&config_path).unwrap();
+    assert!(content.starts_with("# Snapshot location\noutput_dir = \"/backups\" # keep me\n"));
+    assert!(content.contains("[plugins.vscode_settings]\ntarget_path = \"vscode\""));
+
+    config(&["get", "output_dir"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("/backups"));
+
+    config(&["set", "plugins.vscode_settings.target_pth", "vscode"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("unknown key 'target_pth'"));
+    config(&["set", "skip_unchanged", "yes"]).assert().failure();
+
+    config(&["unset", "output_dir"]).assert().success();
+    config(&["get", "output_dir"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("is not set"));
+}
+
+#[test]
+fn test_cli_init_non_interactive() {
+    let temp_dir = TempDir::new().unwrap();
+    let home_dir = temp_dir.path().join("home");
+    std::fs::create_dir_all(&home_dir).unwrap();
+    std::fs::write(home_dir.join(".zshrc"), "").unwrap();
+    let config_path = temp_dir.path().join("dotsnapshot").join("config.toml");
+
+    let init = || {
+        let mut command = Command::cargo_bin("dotsnapshot").unwrap();
+        command
+            .env("HOME", &home_dir)
+            .arg("--config")
+            .arg(&config_path)
+            .args(["init", "--non-interactive"]);
+        command
+    };
+
+    init().assert().success();
+    let content = std::fs::read_to_string(&config_path).unwrap();
+    assert!(content.starts_with("# dotsnapshot configuration"));
+    assert!(content.contains("\"~/.zshrc\""));
+    assert!(temp_dir.path().join("dotsnapshot").join("scripts").is_dir());
+
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .arg("--config")
+        .arg(&config_path)
+        .args(["config", "validate"])
+        .assert()
+        .success();
+
+    init()
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("already exists"));
+    init().arg("--force").assert().success();
+}
+
+#[test]
+fn test_cli_undefined_path_variable() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        "output_dir = \"$DOTSNAPSHOT_UNDEFINED_TEST_DIR/snapshots\"\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .env_remove("DOTSNAPSHOT_UNDEFINED_TEST_DIR")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("list")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Environment variable 'DOTSNAPSHOT_UNDEFINED_TEST_DIR' is not set",
+        ));
+
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .env("DOTSNAPSHOT_UNDEFINED_TEST_DIR", temp_dir.path())
+        .arg("--config")
+        .arg(&config_path)
+        .arg("list")
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_cli_hooks_edit_included_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    let base_path = temp_dir.path().join("base.toml");
+    let local_path = temp_dir.path().join("local.toml");
+    std::fs::write(
+        &config_path,
+        "include = [\"base.toml\"]\nlocal_config = \"local.toml\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        &base_path,
+        "[[global.hooks.pre-snapshot]]\naction = \"log\"\nmessage = \"base\"\n",
+    )
+    .unwrap();
+
+    let hooks = |args: &[&str]| {
+        let mut command = Command::cargo_bin("dotsnapshot").unwrap();
+        command
+            .arg("--config")
+            .arg(&config_path)
+            .arg("hooks")
+            .arg("add")
+            .args(args);
+        command
+    };
+
+    hooks(&["--pre-snapshot", "--log", "second"])
+        .assert()
+        .success();
+    hooks(&["--post-snapshot", "--log", "done"])
+        .assert()
+        .success();
+
+    let base = std::fs::read_to_string(&base_path).unwrap();
+    assert!(base.contains("message = \"base\""));
+    assert!(base.contains("message = \"second\""));
+    let local = std::fs::read_to_string(&local_path).unwrap();
+    assert!(local.contains("[[global.hooks.post-snapshot]]"));
+    assert!(!std::fs::read_to_string(&config_path)
+        .unwrap()
+        .contains("hooks"));
+
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .arg("--config")
+        .arg(&config_path)
+        .args(["config", "validate"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("local.toml is valid"));
+}
+
+#[test]
+fn test_cli_config_migrate() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    let legacy = "# Files to keep\n[static]\nfiles = [\"~/.zshrc\"]\n";
+    std::fs::write(&config_path, legacy).unwrap();
+
+    let migrate = |args: &[&str]| {
+        let mut command = Command::cargo_bin("dotsnapshot").unwrap();
+        command
+            .arg("--config")
+            .arg(&config_path)
+            .args(["config", "migrate"])
+            .args(args);
+        command
+    };
+
+    migrate(&["--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("-[static]"))
+        .stdout(predicates::str::contains("+[plugins.static]"));
+    assert_eq!(std::fs::read_to_string(&config_path).unwrap(), legacy);
+
+    migrate(&[]).assert().success();
+    let content = std::fs::read_to_string(&config_path).unwrap();
+    assert!(content.starts_with("version = 2\n# Files to keep\n[plugins.static]\n"));
+    assert_eq!(
+        std::fs::read_to_string(temp_dir.path().join("config.toml.v1.bak")).unwrap(),
+        legacy
+    );
+
+    migrate(&[])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("is up to date"));
+}
+
+#[test]
+fn test_cli_yaml_and_json_configs() {
+    let temp_dir = TempDir::new().unwrap();
+    let yaml_path = temp_dir.path().join("config.yaml");
+    std::fs::write(
+        &yaml_path,
+        "output_dir: /tmp/yaml-snapshots
+",
+    )
+    .unwrap();
+
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .arg("--config")
+        .arg(&yaml_path)
+        .args(["hooks", "add", "--pre-snapshot", "--log", "starting"])
+        .assert()
+        .success();
+    let content = std::fs::read_to_string(&yaml_path).unwrap();
+    assert!(content.contains("output_dir: /tmp/yaml-snapshots"));
+    assert!(content.contains("message: starting"));
+
+    let json_path = temp_dir.path().join("config.json");
+    std::fs::write(&json_path, r#"{"output_dir": "/tmp/json-snapshots"}"#).unwrap();
+
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .arg("--config")
+        .arg(&json_path)
+        .args(["config", "get", "output_dir"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("/tmp/json-snapshots"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_custom_command_plugin() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_dir = temp_dir.path().join("snapshots");
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        "[plugins.custom.tool_list]\ncapture = \"echo alpha\"\ncategory = \"tools\"\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--list")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("tool_list"));
+
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output")
+        .arg(&output_dir)
+        .args(["--plugins", "tools"])
+        .assert()
+        .success();
+
+    let snapshot = std::fs::read_dir(&output_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.is_dir())
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(snapshot.join("tool_list").join("tool_list.txt")).unwrap(),
+        "alpha\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_external_plugin() {
+    if !std::process::Command::new("python3")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+    {
+        return;
+    }
+
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("example");
+    std::fs::create_dir_all(&source).unwrap();
+    std::fs::write(source.join("settings.toml"), "theme = \"dark\"\n").unwrap();
+    let output_dir = temp_dir.path().join("snapshots");
+
+    let plugins_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/plugins");
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        format!("[plugins.external]\ndirs = ['{}']\n", plugins_dir.display()),
+    )
+    .unwrap();
+    let dotsnapshot = |args: &[&str]| {
+        let mut command = Command::cargo_bin("dotsnapshot").unwrap();
+        command
+            .env("EXAMPLE_PLUGIN_SOURCE", &source)
+            .arg("--config")
+            .arg(&config_path)
+            .args(args);
+        command
+    };
+
+    dotsnapshot(&["--list"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("example"))
+        .stdout(predicates::str::contains("Files of the example tool"));
+
+    dotsnapshot(&[
+        "--output",
+        output_dir.to_str().unwrap(),
+        "--plugins",
+        "example",
+    ])
+    .assert()
+    .success();
+    let snapshot = std::fs::read_dir(&output_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.is_dir())
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(snapshot.join("example").join("settings.toml")).unwrap(),
+        "theme = \"dark\"\n"
+    );
+}
+
+#[test]
+fn test_cli_plugin_selectors() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(&config_path, "").unwrap();
+
+    let output = Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .arg("--config")
+        .arg(&config_path)
+        .args(["--list", "--plugins", "category:vscode"])
+        .args(["--exclude", "vscode_extensions"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("vscode_settings"));
+    assert!(!stdout.contains("vscode_extensions"));
+    assert!(!stdout.contains("npm_config"));
+
+    let output = Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .arg("--config")
+        .arg(&config_path)
+        .args(["--list", "--plugins", "*_settings,!cursor_*"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("vscode_settings"));
+    assert!(!stdout.contains("cursor_settings"));
+
+    // Selectors match names exactly, so a fragment matches nothing
+    Command::cargo_bin("dotsnapshot")
+        .unwrap()
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output")
+        .arg(temp_dir.path().join("snapshots"))
+        .args(["--plugins", "settings"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Plugin selector 'settings' matches no plugin",
+        ));
+}
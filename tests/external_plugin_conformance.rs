@@ -0,0 +1,288 @@
+//! Conformance kit for external plugins
+//!
+//! Checks that an executable speaks the external plugin protocol the way
+//! dotsnapshot expects. By default the example plugin in `tests/plugins` is
+//! checked; point the kit at your own plugin with
+//!
+//! ```sh
+//! DOTSNAPSHOT_PLUGIN_UNDER_TEST=/path/to/dotsnapshot-plugin-foo \
+//!     cargo test --test external_plugin_conformance
+//! ```
+#![cfg(unix)]
+
+use anyhow::Result;
+use dotsnapshot::core::plugin::{NotApplicable, Plugin};
+use dotsnapshot::plugins::external::adapter::ExternalPlugin;
+use dotsnapshot::plugins::external::protocol::{
+    Capture, Description, PluginProcess, Request, RestoreDir, RestoreParams, Restored, Unsupported,
+    PROTOCOL_VERSION,
+};
+use serde::de::IgnoredAny;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tempfile::TempDir;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Files the example plugin captures
+const EXAMPLE_FILES: &[(&str, &str)] = &[
+    ("settings.toml", "theme = \"dark\"\n"),
+    ("profiles/work.toml", "email = \"me@example.com\"\n"),
+];
+
+/// The executable under test, or `None` when the example plugin can't run
+fn plugin_under_test() -> Option<&'static PathBuf> {
+    static PLUGIN: OnceLock<Option<PathBuf>> = OnceLock::new();
+    PLUGIN
+        .get_or_init(|| {
+            if let Some(plugin) = std::env::var_os("DOTSNAPSHOT_PLUGIN_UNDER_TEST") {
+                return Some(PathBuf::from(plugin));
+            }
+            let python = Command::new("python3").arg("--version").output();
+            if !python.is_ok_and(|output| output.status.success()) {
+                eprintln!("python3 not found, skipping the example plugin");
+                return None;
+            }
+
+            // The example plugin captures this directory for every test
+            let source = TempDir::new().unwrap().keep();
+            for (path, content) in EXAMPLE_FILES {
+                let path = source.join(path);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(path, content).unwrap();
+            }
+            std::env::set_var("EXAMPLE_PLUGIN_SOURCE", &source);
+            Some(
+                PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                    .join("tests/plugins/dotsnapshot-plugin-example"),
+            )
+        })
+        .as_ref()
+}
+
+/// Whether the example plugin, rather than one given by the user, is checked
+fn is_example() -> bool {
+    std::env::var_os("DOTSNAPSHOT_PLUGIN_UNDER_TEST").is_none()
+}
+
+fn process(plugin: &Path) -> PluginProcess {
+    PluginProcess::new(plugin.to_path_buf(), TIMEOUT)
+}
+
+/// Send raw input and parse the single JSON object answered
+fn send_raw(plugin: &Path, input: &str) -> serde_json::Value {
+    let mut child = Command::new(plugin)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+    let output = child.wait_with_output().unwrap();
+    serde_json::from_slice(&output.stdout).unwrap_or_else(|e| {
+        panic!(
+            "answer to {input} is not one JSON object ({e}): {}",
+            String::from_utf8_lossy(&output.stdout)
+        )
+    })
+}
+
+/// Whether validation passes, treating "not applicable" as a valid answer
+async fn is_applicable(plugin: &Path) -> Result<bool> {
+    match process(plugin).call::<IgnoredAny>(&Request::Validate).await {
+        Ok(_) => Ok(true),
+        Err(e) if NotApplicable::is_in(&e) => Ok(false),
+        Err(e) if Unsupported::is_in(&e) => Ok(true),
+        Err(e) => Err(e),
+    }
+}
+
+#[tokio::test]
+async fn conformance_describe() -> Result<()> {
+    let Some(plugin) = plugin_under_test() else {
+        return Ok(());
+    };
+    let description: Description = process(plugin).call(&Request::Describe).await?;
+    assert!(
+        !description.description.trim().is_empty(),
+        "describe must return a description"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn conformance_validate() -> Result<()> {
+    let Some(plugin) = plugin_under_test() else {
+        return Ok(());
+    };
+    // Either result is fine, but any other error is not
+    is_applicable(plugin).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn conformance_capture() -> Result<()> {
+    let Some(plugin) = plugin_under_test() else {
+        return Ok(());
+    };
+    if !is_applicable(plugin).await? {
+        return Ok(());
+    }
+
+    let capture: Capture = process(plugin).call(&Request::Capture).await?;
+    let mut paths = BTreeSet::new();
+    for file in &capture.files {
+        assert!(
+            file.path.components().next().is_some()
+                && file
+                    .path
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_))),
+            "captured path {} must be relative and stay in the plugin's directory",
+            file.path.display()
+        );
+        assert!(
+            paths.insert(file.path.clone()),
+            "captured path {} is returned twice",
+            file.path.display()
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn conformance_restore_dry_run() -> Result<()> {
+    let Some(plugin) = plugin_under_test() else {
+        return Ok(());
+    };
+    if !is_applicable(plugin).await? {
+        return Ok(());
+    }
+
+    let snapshot = TempDir::new()?;
+    let capture: Capture = process(plugin).call(&Request::Capture).await?;
+    for file in &capture.files {
+        let path = snapshot.path().join(&file.path);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(path, &file.content)?;
+    }
+
+    let target = TempDir::new()?;
+    let request = Request::Restore(RestoreParams {
+        snapshot_dir: snapshot.path().to_path_buf(),
+        target_dir: target.path().to_path_buf(),
+        dry_run: true,
+    });
+    match process(plugin).call::<Restored>(&request).await {
+        Ok(_) => {}
+        Err(e) if Unsupported::is_in(&e) => {}
+        Err(e) => return Err(e),
+    }
+    assert_eq!(
+        std::fs::read_dir(target.path())?.count(),
+        0,
+        "a dry run restore must not write anything"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn conformance_default_restore_dir() -> Result<()> {
+    let Some(plugin) = plugin_under_test() else {
+        return Ok(());
+    };
+    match process(plugin)
+        .call::<RestoreDir>(&Request::DefaultRestoreDir)
+        .await
+    {
+        Ok(restore_dir) => assert!(restore_dir.path.is_absolute()),
+        Err(e) if Unsupported::is_in(&e) => {}
+        Err(e) => return Err(e),
+    }
+    Ok(())
+}
+
+#[test]
+fn conformance_unknown_method() {
+    let Some(plugin) = plugin_under_test() else {
+        return;
+    };
+    let response = send_raw(
+        plugin,
+        &format!("{{\"protocol\": {PROTOCOL_VERSION}, \"method\": \"no_such_method\"}}\n"),
+    );
+    assert_eq!(response["protocol"], PROTOCOL_VERSION);
+    assert_eq!(
+        response["error"]["kind"], "unsupported",
+        "unknown methods must be answered with an unsupported error: {response}"
+    );
+}
+
+#[test]
+fn conformance_other_protocol_version() {
+    let Some(plugin) = plugin_under_test() else {
+        return;
+    };
+    let response = send_raw(
+        plugin,
+        &format!(
+            "{{\"protocol\": {}, \"method\": \"describe\"}}\n",
+            PROTOCOL_VERSION + 1
+        ),
+    );
+    assert!(
+        response["error"]["message"].is_string(),
+        "requests in another protocol version must be answered with an error: {response}"
+    );
+}
+
+#[tokio::test]
+async fn conformance_adapter_snapshot() -> Result<()> {
+    let Some(plugin) = plugin_under_test() else {
+        return Ok(());
+    };
+    let adapter = ExternalPlugin::new("under_test", plugin.clone(), TIMEOUT);
+    adapter.describe().await?;
+    if !is_applicable(plugin).await? {
+        return Ok(());
+    }
+
+    let snapshot = TempDir::new()?;
+    std::env::set_var("DOTSNAPSHOT_SNAPSHOT_DIR", snapshot.path());
+    let manifest = adapter.execute().await?;
+    let manifest: serde_json::Value = serde_json::from_str(&manifest)?;
+
+    for file in manifest["files"].as_array().unwrap() {
+        let path = snapshot
+            .path()
+            .join("under_test")
+            .join(file["path"].as_str().unwrap());
+        assert!(path.is_file(), "{} was not written", path.display());
+    }
+
+    assert!(
+        adapter.plan_files().await?.is_none(),
+        "a dry run must not capture"
+    );
+
+    if is_example() {
+        assert_eq!(
+            manifest["files"].as_array().unwrap().len(),
+            EXAMPLE_FILES.len()
+        );
+
+        let target = TempDir::new()?;
+        let restored = adapter
+            .restore(&snapshot.path().join("under_test"), target.path(), false)
+            .await?;
+        assert_eq!(restored.len(), EXAMPLE_FILES.len());
+        for (path, content) in EXAMPLE_FILES {
+            assert_eq!(std::fs::read_to_string(target.path().join(path))?, *content);
+        }
+    }
+    Ok(())
+}
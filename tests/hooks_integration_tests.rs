@@ -1,6 +1,7 @@
 use anyhow::Result;
+use dotsnapshot::config::format::to_plugin_value;
 use dotsnapshot::config::{
-    Config, GlobalConfig, GlobalHooks, PluginConfig, PluginHooks, PluginsConfig,
+    Config, GlobalConfig, GlobalHooks, PluginConfig, PluginConfigValue, PluginHooks, PluginsConfig,
 };
 use dotsnapshot::core::hooks::{HookAction, HooksConfig};
 use std::collections::HashMap;
@@ -14,11 +15,11 @@ use tokio::fs;
 /// It is specifically designed to reduce code duplication when constructing
 /// test configurations.
 fn insert_plugin_config(
-    map: &mut HashMap<String, toml::Value>,
+    map: &mut HashMap<String, PluginConfigValue>,
     plugin_name: &str,
     config: PluginConfig,
 ) -> Result<()> {
-    let value = toml::Value::try_from(config).map_err(|e| {
+    let value = to_plugin_value(&config).map_err(|e| {
         anyhow::anyhow!("Failed to serialize plugin config '{}': {}", plugin_name, e)
     })?;
     map.insert(plugin_name.to_string(), value);
@@ -142,6 +143,7 @@ async fn test_config_with_hooks_serialization() -> Result<()> {
         }),
         ui: None,
         validation: None,
+        ..Config::default()
     };
 
     // Save config
@@ -208,7 +210,7 @@ async fn test_config_with_hooks_serialization() -> Result<()> {
 
     assert!(plugins.plugins.contains_key("homebrew_brewfile"));
     let homebrew_value = &plugins.plugins["homebrew_brewfile"];
-    let homebrew_config: PluginConfig = homebrew_value.clone().try_into().unwrap();
+    let homebrew_config: PluginConfig = serde_json::from_value(homebrew_value.clone()).unwrap();
     assert!(homebrew_config.hooks.is_some());
     let homebrew_hooks = homebrew_config.hooks.unwrap();
 
@@ -316,6 +318,7 @@ async fn test_config_hooks_helper_methods() -> Result<()> {
         }),
         ui: None,
         validation: None,
+        ..Config::default()
     };
 
     // Save and reload config
@@ -386,7 +389,10 @@ level = "info"
     let config = Config::load_from_file(&config_path).await?;
 
     // Should use defaults for other fields
-    assert_eq!(config.get_output_dir(), PathBuf::from("./snapshots"));
+    assert_eq!(
+        config.get_output_dir().unwrap(),
+        PathBuf::from("./snapshots")
+    );
     assert!(config.get_include_plugins().is_none());
 
     // Should have hooks configuration
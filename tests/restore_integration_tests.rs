@@ -183,7 +183,7 @@ async fn test_restore_manager_nonexistent_plugin_filter() -> Result<()> {
         "Should fail when no selected plugins are found"
     );
     let error_message = result.unwrap_err().to_string();
-    assert!(error_message.contains("None of the selected plugins were found"));
+    assert!(error_message.contains("Plugin selector 'nonexistent_plugin' matches no plugin"));
 
     Ok(())
 }